@@ -0,0 +1,291 @@
+// src/assessment.rs
+
+use crate::audit;
+use crate::constants::*;
+use crate::models::{
+    AssessmentAnswer, AssessmentOutcome, AssessmentResult, AssessmentSkill, PlacementAnswer,
+    PlacementProbe, SkillMasteryDelta, SkillUnlocked,
+};
+use crate::pedagogy;
+use crate::repository::Repository;
+use log::info;
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// The onboarding quiz: for every skill, one problem per `ASSESSMENT_DIFFICULTIES` entry,
+/// skills in seed (prerequisite) order.
+pub fn get_assessment(conn: &Connection) -> Result<Vec<AssessmentSkill>, String> {
+    let repo = Repository::new(conn);
+    let track_id = 1;
+    let names = repo.get_skill_names().map_err(|e| e.to_string())?;
+    let mut skill_ids: Vec<i64> = names.keys().copied().collect();
+    skill_ids.sort_unstable();
+
+    let mut quiz = Vec::new();
+    for skill_id in skill_ids {
+        let problems = repo
+            .find_assessment_problems(track_id, skill_id, &ASSESSMENT_DIFFICULTIES)
+            .map_err(|e| e.to_string())?;
+        if problems.is_empty() {
+            continue;
+        }
+        quiz.push(AssessmentSkill {
+            skill_id,
+            skill: names.get(&skill_id).cloned().unwrap_or_default(),
+            problems,
+        });
+    }
+    Ok(quiz)
+}
+
+/// Seeds mastery from quiz answers. Each answered skill scores the difficulty-weighted
+/// share of its problems solved (struggles count `ASSESSMENT_CREDIT_STRUGGLED`), scaled to
+/// `ASSESSMENT_MASTERY_MAX`. Mastery is only ever raised, so retaking the quiz cannot undo
+/// practice, and skills the answers do not touch are left alone. All or nothing.
+pub fn complete_assessment(
+    conn: &Connection,
+    answers: &[AssessmentAnswer],
+    now: i64,
+) -> Result<AssessmentResult, String> {
+    // skill_id -> (earned weight, total weight)
+    let mut scores: BTreeMap<i64, (f64, f64)> = BTreeMap::new();
+    for answer in answers {
+        let (difficulty, skills) = Repository::new(conn)
+            .get_problem_metadata(answer.problem_id)
+            .map_err(|_| {
+                format!(
+                    "Problem {} is not part of the assessment",
+                    answer.problem_id
+                )
+            })?;
+        let weight = pedagogy::difficulty_multiplier(difficulty);
+        let credit = match answer.outcome {
+            AssessmentOutcome::Solved => 1.0,
+            AssessmentOutcome::Struggled => ASSESSMENT_CREDIT_STRUGGLED,
+            AssessmentOutcome::Unsolved => 0.0,
+        };
+        for (skill_id, skill_weight) in skills {
+            let score = scores.entry(skill_id).or_insert((0.0, 0.0));
+            score.0 += weight * skill_weight * credit;
+            score.1 += weight * skill_weight;
+        }
+    }
+
+    let assessed: Vec<(i64, f64)> = scores
+        .into_iter()
+        .map(|(skill_id, (earned, total))| (skill_id, ASSESSMENT_MASTERY_MAX * earned / total))
+        .collect();
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let result = audit::with_cause(&tx, AUDIT_CAUSE_ASSESSMENT, None, || {
+        seed_mastery(&tx, &assessed, "assessment", now)
+    })?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(result)
+}
+
+/// Skip-ahead for topics the user already knows: brings each skill to consolidation-level
+/// mastery and attempts in one transaction, so their dependents unlock immediately.
+pub fn mark_skills_mastered(
+    conn: &Connection,
+    skill_ids: &[i64],
+    now: i64,
+) -> Result<AssessmentResult, String> {
+    let policy = pedagogy::get_unlock_policy(&Repository::new(conn))?;
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let repo = Repository::new(&tx);
+    let result = audit::with_cause(&tx, AUDIT_CAUSE_MANUAL, None, || {
+        for &skill_id in skill_ids {
+            if !repo.skill_exists(skill_id).map_err(|e| e.to_string())? {
+                return Err(format!("Skill {} does not exist", skill_id));
+            }
+            let mut state = repo.get_skill_state(skill_id).map_err(|e| e.to_string())?;
+            if (state.attempts as i64) < policy.consolidation_attempts {
+                state.attempts = policy.consolidation_attempts as i32;
+                repo.update_skill_state(&state).map_err(|e| e.to_string())?;
+            }
+        }
+        let targets: Vec<(i64, f64)> = skill_ids
+            .iter()
+            .map(|&skill_id| (skill_id, policy.consolidation_threshold))
+            .collect();
+        seed_mastery(&tx, &targets, "marked mastered", now)
+    })?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(result)
+}
+
+/// Raises each skill to its given mastery (never lowers it), logging changes under
+/// `reason`, and records the skills that became unlocked. Callers own the transaction.
+fn seed_mastery(
+    conn: &Connection,
+    targets: &[(i64, f64)],
+    reason: &str,
+    now: i64,
+) -> Result<AssessmentResult, String> {
+    let repo = Repository::new(conn);
+    let unlocked_before = pedagogy::get_unlocked_skills(&repo)?;
+    let names = repo.get_skill_names().map_err(|e| e.to_string())?;
+    let mut mastery = Vec::new();
+    for &(skill_id, target) in targets {
+        let mut state = repo.get_skill_state(skill_id).map_err(|e| e.to_string())?;
+        if target <= state.mastery {
+            continue;
+        }
+        let before = state.mastery;
+        state.mastery = target;
+        repo.update_skill_state(&state).map_err(|e| e.to_string())?;
+        repo.log_mastery_change(skill_id, None, before, target, reason, now)
+            .map_err(|e| e.to_string())?;
+        mastery.push(SkillMasteryDelta {
+            skill_id,
+            skill: names.get(&skill_id).cloned().unwrap_or_default(),
+            before,
+            after: target,
+        });
+    }
+
+    let mut unlocked_skills = Vec::new();
+    for skill_id in pedagogy::get_unlocked_skills(&repo)? {
+        if unlocked_before.contains(&skill_id) {
+            continue;
+        }
+        repo.log_skill_unlock(skill_id, None, now)
+            .map_err(|e| e.to_string())?;
+        unlocked_skills.push(SkillUnlocked {
+            skill_id,
+            name: names.get(&skill_id).cloned().unwrap_or_default(),
+        });
+    }
+    info!(
+        "Seeded {} skills from {} and unlocked {}",
+        mastery.len(),
+        reason,
+        unlocked_skills.len()
+    );
+    Ok(AssessmentResult {
+        mastery,
+        unlocked_skills,
+    })
+}
+
+/// Prerequisite depth of every skill: 0 for roots, otherwise one more than its deepest
+/// prerequisite.
+pub fn skill_depths(skill_ids: &[i64], prereqs: &[(i64, i64)]) -> HashMap<i64, i64> {
+    let mut depths: HashMap<i64, i64> = HashMap::new();
+    // Relax until stable; the DAG is small and acyclic, so this settles within its height
+    for _ in 0..=skill_ids.len() {
+        let mut changed = false;
+        for &sid in skill_ids {
+            let depth = prereqs
+                .iter()
+                .filter(|(skill_id, _)| *skill_id == sid)
+                .map(|(_, prereq_id)| depths.get(prereq_id).copied().unwrap_or(0) + 1)
+                .max()
+                .unwrap_or(0);
+            if depths.insert(sid, depth) != Some(depth) {
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    depths
+}
+
+/// Replays the answers as a binary search over skill depth: a solved probe proves its
+/// level and everything below it, a failed one rules out its level and everything above.
+/// Returns the remaining `(low, high)` bounds; placement is settled once `low > high`.
+fn placement_bounds(
+    conn: &Connection,
+    answers: &[PlacementAnswer],
+    depths: &HashMap<i64, i64>,
+) -> Result<(i64, i64), String> {
+    let mut low = 0;
+    let mut high = depths.values().copied().max().unwrap_or(0);
+    for answer in answers {
+        let (_, skills) = Repository::new(conn)
+            .get_problem_metadata(answer.problem_id)
+            .map_err(|_| format!("Problem {} is not part of placement", answer.problem_id))?;
+        let depth = skills
+            .iter()
+            .filter_map(|(sid, _)| depths.get(sid))
+            .copied()
+            .max()
+            .unwrap_or(0);
+        if answer.solved {
+            low = low.max(depth + 1);
+        } else {
+            high = high.min(depth - 1);
+        }
+    }
+    Ok((low, high))
+}
+
+/// The next placement problem given the answers so far: a problem from a skill halfway
+/// between what is known and what is ruled out, preferring skills not probed yet.
+pub fn get_placement_problem(
+    conn: &Connection,
+    answers: &[PlacementAnswer],
+) -> Result<PlacementProbe, String> {
+    let repo = Repository::new(conn);
+    let track_id = 1;
+    let states = repo.get_all_skill_states().map_err(|e| e.to_string())?;
+    let skill_ids: Vec<i64> = states.iter().map(|s| s.skill_id).collect();
+    let prereqs = repo.get_skill_prereqs().map_err(|e| e.to_string())?;
+    let depths = skill_depths(&skill_ids, &prereqs);
+    let max_level = depths.values().copied().max().unwrap_or(0);
+    let (low, high) = placement_bounds(conn, answers, &depths)?;
+
+    let mut probe = PlacementProbe {
+        problem: None,
+        level: low,
+        max_level,
+    };
+    if low > high || answers.len() >= PLACEMENT_MAX_PROBLEMS {
+        return Ok(probe);
+    }
+    let mid = (low + high) / 2;
+    let answered: HashSet<i64> = answers.iter().map(|a| a.problem_id).collect();
+    for skill_id in skill_ids.iter().filter(|sid| depths.get(sid) == Some(&mid)) {
+        let problems = repo
+            .find_assessment_problems(track_id, *skill_id, &PLACEMENT_DIFFICULTIES)
+            .map_err(|e| e.to_string())?;
+        if let Some(p) = problems.into_iter().find(|p| !answered.contains(&p.id)) {
+            probe.problem = Some(p);
+            break;
+        }
+    }
+    Ok(probe)
+}
+
+/// Settles placement: every skill below the placed level gets `ASSESSMENT_MASTERY_MAX`
+/// (raised only, as in `complete_assessment`), which unlocks the level itself. All or
+/// nothing.
+pub fn complete_placement(
+    conn: &Connection,
+    answers: &[PlacementAnswer],
+    now: i64,
+) -> Result<AssessmentResult, String> {
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let repo = Repository::new(&tx);
+    let states = repo.get_all_skill_states().map_err(|e| e.to_string())?;
+    let skill_ids: Vec<i64> = states.iter().map(|s| s.skill_id).collect();
+    let prereqs = repo.get_skill_prereqs().map_err(|e| e.to_string())?;
+    let depths = skill_depths(&skill_ids, &prereqs);
+    let (level, _) = placement_bounds(&tx, answers, &depths)?;
+    let placed: Vec<(i64, f64)> = skill_ids
+        .into_iter()
+        .filter(|sid| depths.get(sid).is_some_and(|&d| d < level))
+        .map(|sid| (sid, ASSESSMENT_MASTERY_MAX))
+        .collect();
+    info!("Placement settled at level {}", level);
+    let result = audit::with_cause(&tx, AUDIT_CAUSE_ASSESSMENT, None, || {
+        seed_mastery(&tx, &placed, "placement", now)
+    })?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(result)
+}
@@ -0,0 +1,105 @@
+// src/attempt_timer.rs
+
+use crate::constants::MIN_ACTIVE_ATTEMPT_MINUTES;
+use crate::models::{AttemptLog, AttemptTimer, StoredTimer};
+use crate::repository::Repository;
+use rusqlite::Connection;
+
+/// Starts timing a problem, restarting its timer if one was running.
+pub fn start(conn: &Connection, problem_id: i64, now: i64) -> Result<AttemptTimer, String> {
+    let repo = Repository::new(conn);
+    if !repo.problem_exists(problem_id).map_err(|e| e.to_string())? {
+        return Err(format!("Problem {} does not exist", problem_id));
+    }
+    let timer = StoredTimer {
+        started_at: now,
+        paused_at: None,
+        pause_count: 0,
+        paused_seconds: 0,
+    };
+    repo.save_attempt_timer(problem_id, &timer)
+        .map_err(|e| e.to_string())?;
+    Ok(reading(problem_id, timer, now))
+}
+
+/// Pauses the problem's timer. Pausing a paused timer changes nothing.
+pub fn pause(conn: &Connection, problem_id: i64, now: i64) -> Result<AttemptTimer, String> {
+    let repo = Repository::new(conn);
+    let mut timer = stored(&repo, problem_id)?;
+    if timer.paused_at.is_none() {
+        timer.paused_at = Some(now.max(timer.started_at));
+        timer.pause_count += 1;
+        repo.save_attempt_timer(problem_id, &timer)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(reading(problem_id, timer, now))
+}
+
+/// Pauses every running timer as of `at`, e.g. when the machine went to sleep then.
+/// Returns how many were paused.
+pub fn pause_all(conn: &Connection, at: i64) -> Result<usize, String> {
+    let running = Repository::new(conn)
+        .get_running_attempt_timers()
+        .map_err(|e| e.to_string())?;
+    for &problem_id in &running {
+        pause(conn, problem_id, at)?;
+    }
+    Ok(running.len())
+}
+
+/// Resumes the problem's timer, adding the pause to its paused time.
+pub fn resume(conn: &Connection, problem_id: i64, now: i64) -> Result<AttemptTimer, String> {
+    let repo = Repository::new(conn);
+    let mut timer = stored(&repo, problem_id)?;
+    if let Some(paused_at) = timer.paused_at.take() {
+        timer.paused_seconds += (now - paused_at).max(0);
+        repo.save_attempt_timer(problem_id, &timer)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(reading(problem_id, timer, now))
+}
+
+/// The problem's timer as of `now`, if one is running.
+pub fn get(conn: &Connection, problem_id: i64, now: i64) -> Result<Option<AttemptTimer>, String> {
+    let timer = Repository::new(conn)
+        .get_attempt_timer(problem_id)
+        .map_err(|e| e.to_string())?;
+    Ok(timer.map(|t| reading(problem_id, t, now)))
+}
+
+/// Copies the pauses timed for the attempt's problem into `log`, unless the client reported
+/// its own. The timer itself is removed when the attempt is processed.
+pub fn pin_pauses(conn: &Connection, log: &mut AttemptLog, now: i64) -> Result<(), String> {
+    if log.pause_count > 0 || log.paused_minutes > 0.0 {
+        return Ok(());
+    }
+    if let Some(timer) = get(conn, log.problem_id, now)? {
+        log.pause_count = timer.pause_count;
+        // The client's time can stop short of the timer's, and pauses must fit inside it
+        // with some active time left, or a real solve would read as a zero-time one
+        log.paused_minutes = timer
+            .paused_minutes
+            .min(log.time_minutes - MIN_ACTIVE_ATTEMPT_MINUTES)
+            .max(0.0);
+    }
+    Ok(())
+}
+
+fn stored(repo: &Repository, problem_id: i64) -> Result<StoredTimer, String> {
+    repo.get_attempt_timer(problem_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No timer is running for problem {}", problem_id))
+}
+
+fn reading(problem_id: i64, timer: StoredTimer, now: i64) -> AttemptTimer {
+    let elapsed = (now - timer.started_at).max(0);
+    let paused = timer.paused_seconds + timer.paused_at.map_or(0, |at| (now - at).max(0));
+    AttemptTimer {
+        problem_id,
+        started_at: timer.started_at,
+        paused_at: timer.paused_at,
+        pause_count: timer.pause_count,
+        paused_minutes: paused.min(elapsed) as f64 / 60.0,
+        elapsed_minutes: elapsed as f64 / 60.0,
+    }
+}
@@ -0,0 +1,177 @@
+// src/audit.rs
+
+use crate::constants::{AUDIT_CAUSE_RESTORE, AUDIT_ENTITY_PROBLEM, AUDIT_ENTITY_SKILL};
+use crate::database;
+use crate::models::{
+    AuditEntry, Page, Pagination, ProblemRepetitionState, RestoreReport, SkillMasteryState,
+};
+use crate::plan;
+use crate::repository::Repository;
+use log::info;
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+use serde::Deserialize;
+use std::path::Path;
+
+/// Runs `op` with every problem_state / skill_state change it makes logged as caused by
+/// `cause` (and `attempt_id`). The previous cause is put back afterwards, so calls nest:
+/// an import that replays attempts still credits each change to its attempt.
+pub fn with_cause<T>(
+    conn: &Connection,
+    cause: &str,
+    attempt_id: Option<i64>,
+    op: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    let repo = Repository::new(conn);
+    let previous = repo.get_audit_cause().map_err(|e| e.to_string())?;
+    repo.set_audit_cause(Some((cause, attempt_id)))
+        .map_err(|e| e.to_string())?;
+    let result = op();
+    repo.set_audit_cause(previous.as_ref().map(|(cause, id)| (cause.as_str(), *id)))
+        .map_err(|e| e.to_string())?;
+    result
+}
+
+/// A page of state changes, newest first. `entity` narrows to "problem" or "skill" rows,
+/// `entity_id` to one problem or skill.
+pub fn get_audit_log(
+    conn: &Connection,
+    entity: Option<&str>,
+    entity_id: Option<i64>,
+    page: Pagination,
+) -> Result<Page<AuditEntry>, String> {
+    let repo = Repository::new(conn);
+    if let Some(entity) = entity {
+        if entity != AUDIT_ENTITY_PROBLEM && entity != AUDIT_ENTITY_SKILL {
+            return Err(format!(
+                "Unknown audit entity '{}', expected '{}' or '{}'",
+                entity, AUDIT_ENTITY_PROBLEM, AUDIT_ENTITY_SKILL
+            ));
+        }
+    }
+    let entries = repo
+        .get_audit_log(entity, entity_id, page)
+        .map_err(|e| e.to_string())?;
+    let total = repo
+        .count_audit_log(entity, entity_id)
+        .map_err(|e| e.to_string())?;
+    Ok(Page::new(entries, page, total))
+}
+
+/// Columns of a `problem_state` row as the audit triggers record them.
+#[derive(Deserialize)]
+struct ProblemValues {
+    ease_factor: f64,
+    interval_days: f64,
+    next_review_ts: i64,
+}
+
+/// Columns of a `skill_state` row as the audit triggers record them.
+#[derive(Deserialize)]
+struct SkillValues {
+    mastery: f64,
+    attempts: i32,
+}
+
+/// Rolls every problem and skill state back to what it was at `timestamp`, e.g. to undo a
+/// bad import. Attempts, notes and everything else are left alone. A backup is written to
+/// `backup_dir` first, and the rollback is itself audited, so it can be undone the same way.
+pub fn restore_to_date(
+    conn: &Connection,
+    timestamp: i64,
+    backup_dir: &Path,
+) -> Result<RestoreReport, String> {
+    let repo = Repository::new(conn);
+    let start = repo
+        .get_audit_log_start()
+        .map_err(|e| e.to_string())?
+        .ok_or("No state changes have been recorded yet")?;
+    if timestamp < start {
+        return Err(format!(
+            "State history only goes back to {}",
+            plan::local_date(start)
+        ));
+    }
+    let values = repo
+        .get_audit_values_at(timestamp)
+        .map_err(|e| e.to_string())?;
+    let mut report = RestoreReport {
+        timestamp,
+        problems_restored: 0,
+        skills_restored: 0,
+        skipped: 0,
+        backup_path: None,
+    };
+    if values.is_empty() {
+        return Ok(report);
+    }
+    let backup = database::backup(conn, backup_dir, "pre-restore")?;
+    report.backup_path = Some(backup.display().to_string());
+
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let repo = Repository::new(&tx);
+    with_cause(&tx, AUDIT_CAUSE_RESTORE, None, || {
+        for (entity, entity_id, value) in &values {
+            let parse_error = |e: serde_json::Error| {
+                format!("Unreadable audit value for {} {}: {}", entity, entity_id, e)
+            };
+            match entity.as_str() {
+                AUDIT_ENTITY_PROBLEM => {
+                    if !repo
+                        .is_catalog_problem(*entity_id)
+                        .map_err(|e| e.to_string())?
+                    {
+                        report.skipped += 1;
+                        continue;
+                    }
+                    match value {
+                        Some(value) => {
+                            let v: ProblemValues =
+                                serde_json::from_str(value).map_err(parse_error)?;
+                            let state = ProblemRepetitionState {
+                                problem_id: *entity_id,
+                                ease_factor: v.ease_factor,
+                                interval_days: v.interval_days,
+                                next_review_ts: v.next_review_ts,
+                            };
+                            repo.save_problem_repetition_state(&state)
+                        }
+                        None => repo.delete_problem_repetition_state(*entity_id),
+                    }
+                    .map_err(|e| e.to_string())?;
+                    report.problems_restored += 1;
+                }
+                AUDIT_ENTITY_SKILL => {
+                    if !repo.skill_exists(*entity_id).map_err(|e| e.to_string())? {
+                        report.skipped += 1;
+                        continue;
+                    }
+                    // Skills always have a state row; one created later goes back to the defaults
+                    let v = match value {
+                        Some(value) => serde_json::from_str(value).map_err(parse_error)?,
+                        None => SkillValues {
+                            mastery: 0.0,
+                            attempts: 0,
+                        },
+                    };
+                    let state = SkillMasteryState {
+                        skill_id: *entity_id,
+                        mastery: v.mastery,
+                        attempts: v.attempts,
+                    };
+                    repo.update_skill_state(&state).map_err(|e| e.to_string())?;
+                    report.skills_restored += 1;
+                }
+                _ => report.skipped += 1,
+            }
+        }
+        Ok(())
+    })?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    info!(
+        "Restored state as of {}: {} problems, {} skills ({} skipped)",
+        timestamp, report.problems_restored, report.skills_restored, report.skipped
+    );
+    Ok(report)
+}
@@ -0,0 +1,212 @@
+// src/bridge.rs
+
+use crate::constants::*;
+use crate::events;
+use crate::journal;
+use crate::models::{AppState, AttemptLog, AttemptResult};
+use crate::repository::Repository;
+use chrono::Utc;
+use log::{error, info, warn};
+use rand::distributions::{Alphanumeric, DistString};
+use rusqlite::Connection;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::Path;
+use std::thread;
+use tauri::{AppHandle, Manager};
+use tiny_http::{Header, Method, Response, Server};
+
+/// What the companion browser extension reports while the user works on leetcode.com.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BridgeEvent {
+    /// A problem page was opened; starts the clock for that slug.
+    Opened { slug: String },
+    /// LeetCode showed "Accepted" for a submission on that slug.
+    Accepted { slug: String },
+}
+
+/// Problem pages opened but not yet solved, as slug -> open timestamp.
+pub type OpenPages = HashMap<String, i64>;
+
+/// Starts the localhost bridge if `bridge_enabled` is set. Changing the setting takes
+/// effect on the next launch.
+pub fn start_if_enabled(app: AppHandle) {
+    let config = app.state::<AppState>().with_db(|conn| {
+        let repo = Repository::new(conn);
+        let enabled = repo
+            .get_setting_or(SETTING_BRIDGE_ENABLED, false)
+            .unwrap_or(false);
+        if !enabled {
+            return Ok(None);
+        }
+        let port = repo
+            .get_setting_or(SETTING_BRIDGE_PORT, BRIDGE_DEFAULT_PORT)
+            .unwrap_or(BRIDGE_DEFAULT_PORT);
+        get_or_create_token(conn).map(|token| Some((port, token)))
+    });
+    let (port, token) = match config {
+        Ok(Some(config)) => config,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Bridge disabled: {}", e);
+            return;
+        }
+    };
+
+    // Loopback only: the extension runs on this machine, nothing else should reach us
+    let server = match Server::http(("127.0.0.1", port)) {
+        Ok(s) => s,
+        Err(e) => {
+            error!("Bridge failed to bind port {}: {}", port, e);
+            return;
+        }
+    };
+    info!("Browser bridge listening on 127.0.0.1:{}", port);
+
+    thread::spawn(move || {
+        let mut open_pages = OpenPages::new();
+        for mut request in server.incoming_requests() {
+            let cors = Header::from_bytes("Access-Control-Allow-Origin", "*").unwrap();
+            if *request.method() == Method::Options {
+                let allow = Header::from_bytes(
+                    "Access-Control-Allow-Headers",
+                    "Authorization, Content-Type",
+                )
+                .unwrap();
+                let _ = request.respond(Response::empty(204).with_header(cors).with_header(allow));
+                continue;
+            }
+
+            let expected = format!("Bearer {}", token);
+            let authorized = request.headers().iter().any(|h| {
+                h.field.equiv("Authorization") && tokens_match(h.value.as_str(), &expected)
+            });
+            let (status, body) = if !authorized {
+                (401, json!({ "error": "Invalid or missing token" }))
+            } else if *request.method() != Method::Post || request.url() != "/event" {
+                (404, json!({ "error": "Unknown endpoint" }))
+            } else {
+                let mut raw = String::new();
+                let _ = request.as_reader().read_to_string(&mut raw);
+                match serde_json::from_str::<BridgeEvent>(&raw) {
+                    Err(e) => (400, json!({ "error": e.to_string() })),
+                    Ok(event) => {
+                        let now = Utc::now().timestamp();
+                        let state = app.state::<AppState>();
+                        let journal_path = state.journal_path();
+                        let result = state.with_db(|conn| {
+                            events::emitting_changes(&app, conn, || {
+                                handle_event(
+                                    conn,
+                                    &journal_path,
+                                    &mut open_pages,
+                                    event.clone(),
+                                    now,
+                                )
+                            })
+                        });
+                        if matches!(result, Ok(Some(_))) {
+                            state.next_problem.clear();
+                        }
+                        match result {
+                            Ok(attempt) => (200, json!({ "ok": true, "attempt": attempt })),
+                            Err(e) => {
+                                warn!("Bridge event rejected: {}", e);
+                                (409, json!({ "error": e }))
+                            }
+                        }
+                    }
+                }
+            };
+            let response = Response::from_string(body.to_string())
+                .with_status_code(status)
+                .with_header(cors)
+                .with_header(Header::from_bytes("Content-Type", "application/json").unwrap());
+            let _ = request.respond(response);
+        }
+    });
+}
+
+/// Applies one extension event. An `Accepted` records a clean solve timed from the matching
+/// `Opened`; without one there is no honest time to log, so it is rejected. Solves are
+/// journaled to `journal_path` like the ones logged in the app.
+pub fn handle_event(
+    conn: &Connection,
+    journal_path: &Path,
+    open_pages: &mut OpenPages,
+    event: BridgeEvent,
+    now: i64,
+) -> Result<Option<AttemptResult>, String> {
+    let repo = Repository::new(conn);
+    match event {
+        BridgeEvent::Opened { slug } => {
+            repo.find_problem_by_slug(&slug)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Unknown problem '{}'", slug))?;
+            // Re-opening a page keeps the original start time
+            open_pages.entry(slug).or_insert(now);
+            Ok(None)
+        }
+        BridgeEvent::Accepted { slug } => {
+            let problem_id = repo
+                .find_problem_by_slug(&slug)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Unknown problem '{}'", slug))?;
+            let opened_at = open_pages
+                .remove(&slug)
+                .ok_or_else(|| format!("No open page recorded for '{}'", slug))?;
+            let minutes = (now - opened_at) as f64 / 60.0;
+            if minutes > BRIDGE_MAX_TRACKED_MINUTES {
+                return Err(format!(
+                    "'{}' was opened {:.0} minutes ago; log this attempt manually",
+                    slug, minutes
+                ));
+            }
+            let log = AttemptLog {
+                problem_id,
+                time_minutes: minutes,
+                solved: true,
+                read_solution: false,
+                revealed_skills: false,
+                mistakes: Vec::new(),
+                explanation: None,
+                attempt_key: None,
+                timestamp: None,
+                pause_count: 0,
+                paused_minutes: 0.0,
+            };
+            // The extension saw LeetCode accept it, so strict mode has nothing to verify
+            journal::record_attempt(conn, journal_path, &log).map(Some)
+        }
+    }
+}
+
+/// Compares in time that depends only on the lengths, so the check doesn't reveal how
+/// much of a guessed token was right.
+fn tokens_match(given: &str, expected: &str) -> bool {
+    let (given, expected) = (given.as_bytes(), expected.as_bytes());
+    let diff = given
+        .iter()
+        .zip(expected)
+        .fold(given.len() ^ expected.len(), |acc, (a, b)| {
+            acc | usize::from(a ^ b)
+        });
+    diff == 0
+}
+
+/// The shared secret the extension must send, generated on first use.
+pub fn get_or_create_token(conn: &Connection) -> Result<String, String> {
+    let repo = Repository::new(conn);
+    if let Some(token) = repo
+        .get_setting(SETTING_BRIDGE_TOKEN)
+        .map_err(|e| e.to_string())?
+    {
+        return Ok(token);
+    }
+    let token = Alphanumeric.sample_string(&mut rand::thread_rng(), BRIDGE_TOKEN_LENGTH);
+    repo.set_setting(SETTING_BRIDGE_TOKEN, &token)
+        .map_err(|e| e.to_string())?;
+    Ok(token)
+}
@@ -0,0 +1,222 @@
+// src/catalog.rs
+
+use crate::constants::CUSTOM_PROBLEM_ID_BASE;
+use crate::models::{
+    Board, BoardColumn, Page, Pagination, ProblemPage, ProblemSort, ProblemStatus, SkillCoverage,
+    SkillProblem, SortDirection, SortSpec, TrackComparison,
+};
+use crate::repository::Repository;
+use chrono::Local;
+use log::info;
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+use std::fs;
+
+/// Board columns, left to right. Suspended problems are left off the board.
+const BOARD_COLUMNS: [(ProblemStatus, &str); 4] = [
+    (ProblemStatus::New, "To Learn"),
+    (ProblemStatus::Learning, "Learning"),
+    (ProblemStatus::Review, "Reviewing"),
+    (ProblemStatus::Mastered, "Mastered"),
+];
+
+/// A page of a track's problems with their learning status, optionally only those in
+/// `status`, plus how many problems of the track are in each status.
+pub fn list_problems(
+    conn: &Connection,
+    track_id: i64,
+    status: Option<ProblemStatus>,
+    sort: SortSpec<ProblemSort>,
+    page: Pagination,
+    now: i64,
+) -> Result<ProblemPage, String> {
+    let repo = Repository::new(conn);
+    let counts = repo
+        .count_problems_by_status(track_id, now)
+        .map_err(|e| e.to_string())?;
+    let total = match status {
+        Some(status) => counts.get(status),
+        None => counts.total(),
+    };
+    let problems = repo
+        .list_problems(track_id, status, sort, Some(page), now)
+        .map_err(|e| e.to_string())?;
+    Ok(ProblemPage {
+        problems: Page::new(problems, page, total),
+        counts,
+    })
+}
+
+/// Writes a track's problems grouped into status columns to `path` as JSON.
+pub fn export_board(
+    conn: &Connection,
+    track_id: i64,
+    path: &str,
+    now: i64,
+) -> Result<Board, String> {
+    let sort = SortSpec {
+        field: ProblemSort::Title,
+        direction: SortDirection::Asc,
+    };
+    let columns = BOARD_COLUMNS
+        .iter()
+        .map(|&(status, title)| {
+            let problems = Repository::new(conn)
+                .list_problems(track_id, Some(status), sort, None, now)
+                .map_err(|e| e.to_string())?;
+            Ok(BoardColumn {
+                title: title.to_string(),
+                status,
+                problems,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let board = Board {
+        generated_on: Local::now().date_naive().to_string(),
+        track_id,
+        columns,
+    };
+    let json = serde_json::to_string_pretty(&board).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(board)
+}
+
+/// The problems that feed a skill and where each stands, optionally only those in `status`.
+pub fn get_problems_for_skill(
+    conn: &Connection,
+    skill_id: i64,
+    status: Option<ProblemStatus>,
+    now: i64,
+) -> Result<Vec<SkillProblem>, String> {
+    let repo = Repository::new(conn);
+    if !repo.skill_exists(skill_id).map_err(|e| e.to_string())? {
+        return Err(format!("Skill {} not found", skill_id));
+    }
+    repo.get_problems_for_skill(skill_id, status, now)
+        .map_err(|e| e.to_string())
+}
+
+/// How two tracks overlap and how many problems each gives every skill, e.g. to see what
+/// switching from Blind 75 to NeetCode 150 would add.
+pub fn compare_tracks(conn: &Connection, a: i64, b: i64) -> Result<TrackComparison, String> {
+    let repo = Repository::new(conn);
+    let name = |track_id| {
+        repo.get_track_name(track_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Track {} not found", track_id))
+    };
+    let (track_a, track_b) = (name(a)?, name(b)?);
+    let (only_a, only_b, shared) = repo.count_track_overlap(a, b).map_err(|e| e.to_string())?;
+    let mut skills: Vec<SkillCoverage> = repo
+        .get_track_skill_coverage(a, b)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(skill_id, skill, problems_a, problems_b)| SkillCoverage {
+            skill_id,
+            skill,
+            problems_a,
+            problems_b,
+        })
+        .collect();
+    skills.sort_by_key(|s| std::cmp::Reverse((s.problems_b - s.problems_a).abs()));
+    Ok(TrackComparison {
+        track_a,
+        track_b,
+        only_a,
+        only_b,
+        shared,
+        skills,
+    })
+}
+
+/// Soft-deletes a custom problem: it drops out of the queue, listings and search, but its
+/// attempts keep counting toward skill history until `purge_deleted`. Seeded problems
+/// can be suspended instead.
+pub fn delete_problem(conn: &Connection, problem_id: i64, now: i64) -> Result<(), String> {
+    let repo = Repository::new(conn);
+    if !repo
+        .is_catalog_problem(problem_id)
+        .map_err(|e| e.to_string())?
+    {
+        return Err(format!("Problem {} not found", problem_id));
+    }
+    if problem_id < CUSTOM_PROBLEM_ID_BASE {
+        return Err(format!(
+            "Problem {} is part of a seeded track; suspend it instead",
+            problem_id
+        ));
+    }
+    if !repo
+        .soft_delete_problem(problem_id, now)
+        .map_err(|e| e.to_string())?
+    {
+        return Err(format!("Problem {} is already deleted", problem_id));
+    }
+    info!("Deleted problem {}", problem_id);
+    Ok(())
+}
+
+/// Permanently removes soft-deleted problems and their history. Returns how many went.
+pub fn purge_deleted(conn: &Connection) -> Result<usize, String> {
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let purged = Repository::new(&tx)
+        .purge_deleted_problems()
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    info!("Purged {} deleted problems", purged);
+    Ok(purged)
+}
+
+/// Folds `duplicate_id` into `keep_id`: attempts, schedule, notes, tags and track links
+/// move over and the duplicate is deleted. Where both have a schedule (or tag, star...),
+/// the kept problem's wins; notes are joined. Returns how many attempts moved.
+pub fn merge_problems(
+    conn: &Connection,
+    keep_id: i64,
+    duplicate_id: i64,
+    now: i64,
+) -> Result<usize, String> {
+    if keep_id == duplicate_id {
+        return Err("Cannot merge a problem into itself".to_string());
+    }
+    for problem_id in [keep_id, duplicate_id] {
+        if !Repository::new(conn)
+            .is_catalog_problem(problem_id)
+            .map_err(|e| e.to_string())?
+        {
+            return Err(format!("Problem {} not found", problem_id));
+        }
+    }
+
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    if let Some(duplicate_note) = Repository::new(&tx)
+        .get_note(duplicate_id)
+        .map_err(|e| e.to_string())?
+    {
+        let note = match Repository::new(&tx)
+            .get_note(keep_id)
+            .map_err(|e| e.to_string())?
+        {
+            Some(kept) => format!("{}\n\n{}", kept, duplicate_note),
+            None => duplicate_note,
+        };
+        Repository::new(&tx)
+            .save_note(keep_id, &note, now)
+            .map_err(|e| e.to_string())?;
+        Repository::new(&tx)
+            .save_note(duplicate_id, "", now)
+            .map_err(|e| e.to_string())?;
+    }
+    let attempts = Repository::new(&tx)
+        .merge_problem_into(keep_id, duplicate_id)
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    info!(
+        "Merged problem {} into {} ({} attempts moved)",
+        duplicate_id, keep_id, attempts
+    );
+    Ok(attempts)
+}
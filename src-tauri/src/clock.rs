@@ -0,0 +1,26 @@
+// src/clock.rs
+
+use crate::repository::Repository;
+use chrono::Utc;
+use log::warn;
+use rusqlite::Connection;
+
+/// The current time for scheduling, never earlier than the latest attempt, mastery change
+/// or interval already recorded. If the system clock moves backwards, time stands still
+/// there until the clock catches up, so intervals, decay and due dates never run negative.
+pub fn now(conn: &Connection) -> Result<i64, String> {
+    let wall = Utc::now().timestamp();
+    let latest = Repository::new(conn)
+        .get_latest_event_ts()
+        .map_err(|e| e.to_string())?;
+    match latest {
+        Some(latest) if latest > wall => {
+            warn!(
+                "System clock is {}s behind the latest recorded event; holding time there",
+                latest - wall
+            );
+            Ok(latest)
+        }
+        _ => Ok(wall),
+    }
+}
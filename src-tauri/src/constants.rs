@@ -2,12 +2,18 @@
 
 // --- Time Constants ---
 pub const DAY_SECONDS: i64 = 86400;
+pub const SESSION_GAP_SECONDS: i64 = 2 * 3600; // Idle gap that ends a practice session
 pub const EXPECTED_TIME_EASY: f64 = 10.0; // Minutes
 pub const EXPECTED_TIME_MEDIUM: f64 = 25.0; // Minutes
 pub const EXPECTED_TIME_HARD: f64 = 45.0; // Minutes
 
 // --- Spaced Repetition (SM-2) Parameters ---
-pub const ALPHA: f64 = 0.15; // Mastery gain per solve
+// Mastery learning rate: ALPHA_INITIAL for a skill's first solve, shrinking by
+// ALPHA_ATTEMPT_DECAY per past attempt down to ALPHA_MIN, and scaled by the remaining
+// headroom (1 - mastery) so gains taper off near 1.0
+pub const ALPHA_INITIAL: f64 = 0.25;
+pub const ALPHA_ATTEMPT_DECAY: f64 = 0.1;
+pub const ALPHA_MIN: f64 = 0.02;
 pub const INTERVAL_MIN: f64 = 1.0; // Days
 pub const INTERVAL_MAX: f64 = 180.0; // Days
 
@@ -22,16 +28,78 @@ pub const EASE_FACTOR_INCREMENT_CLEAN: f64 = 0.15;
 pub const EASE_FACTOR_INCREMENT_SPEED: f64 = 0.15;
 pub const EASE_FACTOR_NEUTRAL_GRIT: f64 = 0.05;
 
+// Time ratio above which a solve counts as "grit" (slow but solved)
+pub const TIME_RATIO_GRIT: f64 = 1.5;
+// Time ratio below which a review counts as fast
+pub const TIME_RATIO_FAST: f64 = 0.6;
+// Time ratio above which a review counts as a struggle
+pub const TIME_RATIO_STRUGGLE: f64 = 2.0;
+
 // Interval Multipliers
 pub const INTERVAL_NEW_GRIT: f64 = 2.0;
 pub const INTERVAL_NEW_CLEAN: f64 = 4.0;
 pub const INTERVAL_MULTIPLIER_STRUGGLE: f64 = 0.7;
 pub const INTERVAL_MULTIPLIER_SPEED: f64 = 1.2;
 
+// --- Retention Targeting ---
+// Target recall probability at review time, e.g. "0.9". Unset keeps the SM-2 multipliers
+pub const SETTING_TARGET_RETENTION: &str = "target_retention";
+pub const RETENTION_TARGET_MIN: f64 = 0.7;
+pub const RETENTION_TARGET_MAX: f64 = 0.97;
+pub const RETENTION_SM2_BASELINE: f64 = 0.9; // Recall SM-2 intervals are assumed to aim for
+pub const RETENTION_PRIOR_SIGMA: f64 = 1.0; // Log-space spread of the SM-2 stability prior
+pub const RETENTION_MIN_ELAPSED_DAYS: f64 = 0.5; // Shorter gaps do not test memory
+pub const RETENTION_GRID_STEPS: usize = 200;
+
+// --- History Replay ---
+// FSRS-4.5 default weights and forgetting curve, for comparing against SM-2 offline
+pub const FSRS_DEFAULT_WEIGHTS: [f64; 17] = [
+    0.4872, 1.4003, 3.7145, 13.8206, 5.1618, 1.2298, 0.8975, 0.031, 1.6474, 0.1367, 1.0461, 2.1072,
+    0.0793, 0.3246, 1.587, 0.2272, 2.8755,
+];
+pub const FSRS_DECAY: f64 = -0.5;
+pub const FSRS_FACTOR: f64 = 19.0 / 81.0; // Makes R(S) = 0.9
+
+// --- Learner Simulation ---
+pub const SIMULATION_MAX_DAYS: i64 = 730;
+pub const SIMULATION_MAX_RUNS: usize = 50; // Learners per parameter set
+pub const SIMULATION_MAX_DAILY_PROBLEMS: usize = 50;
+// A simulated lapse keeps this share of the concept's memory stability
+pub const SIMULATION_LAPSE_STABILITY: f64 = 0.5;
+// Chance of solving a new problem, relative to an Easy one
+pub const SIMULATION_SOLVE_SCALE_MEDIUM: f64 = 0.8;
+pub const SIMULATION_SOLVE_SCALE_HARD: f64 = 0.6;
+
 // --- Skill Tree / Mastery ---
 pub const MASTERY_UNLOCK_THRESHOLD: f64 = 0.7;
 pub const MASTERY_CONSOLIDATION_THRESHOLD: f64 = 0.9;
 pub const ATTEMPTS_CONSOLIDATION_THRESHOLD: i64 = 2;
+// Mastery is read as a Beta belief worth `attempts` plus this many observations, so its
+// spread narrows with practice (see `SkillMasteryState::uncertainty`)
+pub const MASTERY_PRIOR_ATTEMPTS: f64 = 2.0;
+// The spread is taken with mastery at least this far from 0 and 1, where the Beta collapses
+// to certainty whatever the attempt count
+pub const MASTERY_UNCERTAINTY_EPSILON: f64 = 0.05;
+// A prerequisite only counts once its mastery is pinned down to this standard deviation:
+// 0.7 after 2 lucky solves (about 0.2) does not, 0.7 after 3 attempts does
+pub const MASTERY_UNLOCK_MAX_UNCERTAINTY: f64 = 0.19;
+// Overrides for the thresholds above, and the rule combining prerequisites:
+// "all" (default), "any" or "weighted" (average prerequisite mastery)
+pub const SETTING_UNLOCK_POLICY: &str = "unlock_policy";
+pub const SETTING_UNLOCK_THRESHOLD: &str = "unlock_threshold";
+pub const SETTING_CONSOLIDATION_THRESHOLD: &str = "consolidation_threshold";
+pub const SETTING_CONSOLIDATION_ATTEMPTS: &str = "consolidation_attempts";
+pub const SETTING_UNLOCK_MAX_UNCERTAINTY: &str = "unlock_max_uncertainty";
+// "true" to down-weight locked skills' problems in discovery instead of hiding them
+pub const SETTING_SOFT_PREREQUISITES: &str = "soft_prerequisites";
+// Floor, so a locked skill's problem in the pool always has some chance of being drawn
+pub const SOFT_PREREQ_MIN_WEIGHT: f64 = 0.05;
+// Untouched problems drawn from per selection, easiest first; later ones wait their turn
+pub const SOFT_PREREQ_POOL: usize = 50;
+
+// Share of mastery credit a problem gives each of its skills
+pub const SKILL_WEIGHT_PRIMARY: f64 = 1.0;
+pub const SKILL_WEIGHT_SECONDARY: f64 = 0.3;
 
 pub const DIFFICULTY_MULTIPLIER_EASY: f64 = 0.8;
 pub const DIFFICULTY_MULTIPLIER_MEDIUM: f64 = 1.2;
@@ -41,3 +109,320 @@ pub const PERFORMANCE_MULTIPLIER_FAIL: f64 = 0.0;
 pub const PERFORMANCE_MULTIPLIER_NEW_GRIT: f64 = 1.2;
 pub const PERFORMANCE_MULTIPLIER_NEW_CLEAN: f64 = 1.0;
 pub const PERFORMANCE_MULTIPLIER_REVIEW: f64 = 0.3;
+
+// --- Ability Estimation (IRT) ---
+// Rasch item difficulties on the logit scale; an ability of 0 solves a Medium half the time
+pub const IRT_DIFFICULTY_EASY: f64 = -1.0;
+pub const IRT_DIFFICULTY_MEDIUM: f64 = 0.0;
+pub const IRT_DIFFICULTY_HARD: f64 = 1.0;
+pub const IRT_PRIOR_VARIANCE: f64 = 1.0; // N(0, σ²) prior keeps sparse skills near 0
+pub const IRT_NEWTON_ITERATIONS: usize = 20;
+pub const IRT_TARGET_SUCCESS: f64 = 0.75; // Discovery aims for 70-80% predicted success
+pub const IRT_CANDIDATE_POOL: usize = 50; // Untouched problems ranked per selection
+
+// --- Attempt Validation ---
+pub const MAX_ATTEMPT_MINUTES: f64 = 8.0 * 60.0; // Longer times are clamped and flagged
+pub const ATTEMPT_CLOCK_SKEW_SECONDS: i64 = 5 * 60; // Tolerated lead of client timestamps
+pub const MAX_BATCH_ATTEMPTS: usize = 500;
+// Timed pauses never leave an attempt with less active time than this
+pub const MIN_ACTIVE_ATTEMPT_MINUTES: f64 = 1.0;
+// Longest notes or code an attempt draft keeps
+pub const ATTEMPT_DRAFT_MAX_CHARS: usize = 100_000;
+// quick_log estimates the time as the median of this many of the concept's latest attempts
+pub const QUICK_LOG_HISTORY_ATTEMPTS: usize = 5;
+
+// --- Problem Status ---
+// A concept whose review interval reaches this is reported as mastered
+pub const STATUS_MASTERED_INTERVAL: f64 = 21.0; // Days
+pub const SUSPEND_REASON_MANUAL: &str = "manual"; // Set through a status change, e.g. from Notion
+
+// --- Paging ---
+// List and search commands return PAGE_SIZE_DEFAULT results per page unless asked otherwise
+pub const PAGE_SIZE_DEFAULT: usize = 50;
+pub const PAGE_SIZE_MAX: usize = 500;
+
+// --- Imports ---
+// User-created problems are numbered from here, clear of seeded problem and alternative IDs
+pub const CUSTOM_PROBLEM_ID_BASE: i64 = 1_000_000;
+// Folder in the app data dir scanned for extra JSON problem packs at startup
+pub const PACKS_DIR_NAME: &str = "packs";
+
+// --- Integrations ---
+pub const SETTING_NOTION_TOKEN: &str = "notion_token";
+pub const SETTING_NOTION_DATABASE: &str = "notion_database_id";
+// "remote" (default) or "local": which side wins when both changed since the last sync
+pub const SETTING_NOTION_CONFLICT_POLICY: &str = "notion_conflict_policy";
+pub const NOTION_API_URL: &str = "https://api.notion.com/v1";
+pub const NOTION_API_VERSION: &str = "2022-06-28";
+// Characters per rich-text object
+pub const NOTION_RICH_TEXT_LIMIT: usize = 2000;
+// "true" to require a matching LeetCode submission before crediting a clean solve
+pub const SETTING_STRICT_MODE: &str = "strict_mode";
+pub const SETTING_LEETCODE_USERNAME: &str = "leetcode_username";
+pub const LEETCODE_GRAPHQL_URL: &str = "https://leetcode.com/graphql";
+pub const LEETCODE_RECENT_AC_LIMIT: i64 = 20; // Most LeetCode returns per request
+pub const LEETCODE_TIMEOUT_SECONDS: u64 = 5;
+// How far back an accepted submission may be and still vouch for a solve
+pub const STRICT_MODE_WINDOW_SECONDS: i64 = DAY_SECONDS;
+
+// --- Browser Bridge ---
+pub const SETTING_BRIDGE_ENABLED: &str = "bridge_enabled";
+pub const SETTING_BRIDGE_PORT: &str = "bridge_port";
+pub const SETTING_BRIDGE_TOKEN: &str = "bridge_token";
+pub const BRIDGE_DEFAULT_PORT: u16 = 27121;
+pub const BRIDGE_TOKEN_LENGTH: usize = 32;
+// Longer gaps between "opened" and "accepted" are a forgotten tab, not solving time
+pub const BRIDGE_MAX_TRACKED_MINUTES: f64 = 180.0;
+
+// --- Database Maintenance ---
+pub const BACKUP_DIR_NAME: &str = "backups";
+pub const BACKUP_FILE_PREFIX: &str = "neetcode_trainer-";
+pub const BACKUP_KEEP_COUNT: usize = 10; // Older backups are pruned by maintain_db
+
+// "false" leaves migrations to an explicit `run_migrations` call
+pub const SETTING_AUTO_MIGRATE: &str = "auto_migrate";
+
+// --- SQL Console ---
+pub const QUERY_ALLOWED_KEYWORDS: [&str; 4] = ["SELECT", "WITH", "EXPLAIN", "VALUES"];
+pub const QUERY_MAX_ROWS: usize = 1000;
+
+// --- Logging ---
+pub const LOG_FILE_NAME: &str = "leetgraph.log";
+pub const LOG_MAX_BYTES: u64 = 1024 * 1024; // Rotate once the file passes 1 MB
+pub const LOG_KEEP_FILES: usize = 3; // Rotated files kept besides the live one
+pub const LOG_MAX_LINES: usize = 5000; // Cap for get_recent_logs
+
+// --- Concurrency ---
+pub const DB_BUSY_TIMEOUT_MS: u64 = 5000; // SQLite waits this long for another writer
+pub const DB_BUSY_POLL_MS: u64 = 10; // How often a waiting statement checks the lock again
+pub const DB_BUSY_RETRIES: u32 = 3; // Whole-command retries once the timeout is exceeded
+pub const DB_STATEMENT_CACHE_CAPACITY: usize = 256; // Room for every repository statement
+pub const DB_BUSY_BACKOFF_MS: u64 = 100; // Doubled on each retry
+pub const DB_QUEUE_MAX_PENDING: usize = 16; // Commands allowed to wait for the connection
+
+// --- Command Rate Limits ---
+pub const SUBMIT_ATTEMPT_MIN_INTERVAL_MS: u64 = 1000; // Swallows double-clicked submits
+pub const NEXT_PROBLEM_DEBOUNCE_MS: u64 = 500; // Repeat calls reuse the last selection
+
+// --- Queue Preview ---
+pub const QUEUE_PREVIEW_MAX: usize = 50; // Cap for get_next_problems
+pub const RESERVATION_TTL_SECONDS: i64 = 10 * 60; // How long a served problem stays claimed
+pub const RESERVATION_LOOKAHEAD: usize = 5; // Candidates checked to step past reserved ones
+
+// --- Serve History ---
+pub const SERVED_LOG_RETENTION_DAYS: i64 = 90;
+
+// --- Skips ---
+// A skipped concept is held back from serving this long
+pub const SKIP_COOLDOWN_SECONDS: i64 = 4 * 60 * 60;
+// "false" to log too-hard skips without touching mastery or the schedule
+pub const SETTING_SKIP_NUDGE: &str = "skip_too_hard_nudge";
+pub const SKIP_TOO_HARD_MASTERY_NUDGE: f64 = 0.02; // Per unit of skill weight
+pub const SKIP_TOO_HARD_INTERVAL_FACTOR: f64 = 0.8;
+
+// --- Attempt Journal ---
+// Appended to the database's file name: each database keeps its journal beside it
+pub const JOURNAL_FILE_SUFFIX: &str = ".attempts.jsonl";
+// Where the default database's journal lived before, in the app data dir
+pub const LEGACY_JOURNAL_FILE_NAME: &str = "attempt_journal.jsonl";
+// Appended to the journal's path for entries a replay could not apply
+pub const JOURNAL_POISONED_SUFFIX: &str = ".poisoned";
+pub const JOURNAL_APPLIED_RETENTION_DAYS: i64 = 30; // Applied-entry markers kept this long
+
+// --- Assessment ---
+// One problem per difficulty for each skill in the onboarding quiz
+pub const ASSESSMENT_DIFFICULTIES: [&str; 2] = ["Easy", "Medium"];
+// Mastery for a skill whose every quiz problem was solved: enough to count as a met
+// prerequisite (MASTERY_CONSOLIDATION_THRESHOLD) and open the skills after it
+pub const ASSESSMENT_MASTERY_MAX: f64 = 0.9;
+pub const ASSESSMENT_CREDIT_STRUGGLED: f64 = 0.5; // Share of full credit for a shaky solve
+pub const PLACEMENT_MAX_PROBLEMS: usize = 5;
+// Probe difficulty, in order of preference
+pub const PLACEMENT_DIFFICULTIES: [&str; 2] = ["Medium", "Easy"];
+
+// --- Practice Mode ---
+// "starred" to cycle only starred problems; anything else serves the normal queue
+pub const SETTING_PRACTICE_MODE: &str = "practice_mode";
+pub const PRACTICE_MODE_STARRED: &str = "starred";
+
+// --- Cram ---
+// "weighted" to spread cram over the weakest few skills; anything else grinds the weakest
+pub const SETTING_CRAM_MODE: &str = "cram_mode";
+pub const CRAM_MODE_WEIGHTED: &str = "weighted";
+pub const SETTING_CRAM_SKILLS: &str = "cram_weighted_skills";
+pub const CRAM_WEIGHTED_SKILLS: usize = 3; // Weakest skills sampled from in weighted mode
+pub const CRAM_MIN_MASTERY_GAP: f64 = 0.05; // Floor, so a mastered skill still comes up
+
+// --- Review Order ---
+// "due" (default), "blocked" or "interleaved" by primary skill; see `ReviewOrder`
+pub const SETTING_REVIEW_ORDER: &str = "review_order";
+
+// --- Study Plan ---
+pub const SETTING_DAILY_NEW_PROBLEMS: &str = "daily_new_problems";
+pub const SETTING_DAILY_REVIEWS: &str = "daily_reviews"; // Also the review budget per day
+pub const DAILY_NEW_PROBLEMS_DEFAULT: i64 = 3;
+pub const DAILY_REVIEWS_DEFAULT: i64 = 10;
+pub const PLAN_MAX_DAYS: i64 = 365;
+// Interview deadline mode: intervals are capped so every review lands before the date,
+// and in the final stretch no new Hard problems are introduced
+pub const SETTING_INTERVIEW_DATE: &str = "interview_date"; // YYYY-MM-DD
+pub const INTERVIEW_FINAL_DAYS: i64 = 7;
+
+// --- Review Load Balancing ---
+// A review landing on a day already at the daily budget moves to the nearest day with
+// room, by at most this many days and this fraction of its interval
+pub const REVIEW_LOAD_MAX_SHIFT_DAYS: i64 = 3;
+pub const REVIEW_LOAD_MAX_SHIFT_FRACTION: f64 = 0.2;
+
+// --- Backlog Recovery ---
+pub const POSTPONE_SPREAD_DAYS: i64 = 7; // postpone_backlog spreads overdue reviews over this
+
+// --- Backlog Triage ---
+// Past the threshold of overdue reviews, triage keeps the `daily_reviews` weakest due and
+// suspends the rest; with auto triage on, the background scheduler runs it
+pub const SETTING_TRIAGE_THRESHOLD: &str = "triage_overdue_threshold";
+pub const SETTING_TRIAGE_AUTO: &str = "triage_auto";
+pub const TRIAGE_THRESHOLD_DEFAULT: i64 = 50;
+pub const SUSPEND_REASON_TRIAGE: &str = "triage";
+
+// --- Retirement ---
+// A concept whose last N reviews were all fast and clean is retired (suspended for good);
+// 0 turns retirement off
+pub const SETTING_RETIRE_AFTER: &str = "retire_after_clean_reviews";
+pub const RETIRE_AFTER_DEFAULT: i64 = 4;
+pub const SUSPEND_REASON_RETIRED: &str = "retired";
+// When decay takes a skill below this mastery (default MASTERY_UNLOCK_THRESHOLD), up to
+// REACTIVATE_PER_SKILL of its retired problems come back due
+pub const SETTING_REACTIVATE_BELOW: &str = "reactivate_below_mastery";
+pub const REACTIVATE_PER_SKILL: usize = 2;
+
+// --- Exam Cram ---
+pub const EXAM_CRAM_MAX_DAYS: i64 = 14;
+
+// --- Tags ---
+pub const TAG_MAX_LEN: usize = 40; // Characters
+
+// --- Search ---
+pub const SEARCH_SNIPPET_TOKENS: i64 = 12; // Words of context around each match
+
+// --- Audit Log ---
+// Why a problem_state / skill_state row changed; writes made outside `audit::with_cause`
+// are logged as "system"
+pub const AUDIT_CAUSE_ATTEMPT: &str = "attempt";
+pub const AUDIT_CAUSE_MANUAL: &str = "manual";
+pub const AUDIT_CAUSE_IMPORT: &str = "import";
+pub const AUDIT_CAUSE_DECAY: &str = "decay";
+pub const AUDIT_CAUSE_ASSESSMENT: &str = "assessment";
+pub const AUDIT_CAUSE_RESTORE: &str = "restore";
+pub const AUDIT_CAUSE_SKIP: &str = "skip";
+pub const AUDIT_ENTITY_PROBLEM: &str = "problem";
+pub const AUDIT_ENTITY_SKILL: &str = "skill";
+
+// --- Usage Telemetry ---
+// Local only and off by default; nothing is ever sent anywhere. Statement timings for
+// get_db_info are kept whether or not it is on
+pub const SETTING_TELEMETRY_ENABLED: &str = "telemetry_enabled";
+pub const TELEMETRY_RETENTION_DAYS: i64 = 90;
+pub const TELEMETRY_DEFAULT_DAYS: i64 = 30; // Window for get_usage_stats
+pub const TELEMETRY_APP_START: &str = "app_start";
+pub const QUERY_STATS_SLOW_COUNT: usize = 10; // Slowest statements listed by get_db_info
+
+// --- Background Scheduler ---
+pub const SCHEDULER_INTERVAL_SECONDS: u64 = 5 * 60;
+// The loop waits in steps of this, ticking early when the wall clock drifts more than
+// SCHEDULER_CLOCK_JUMP_SECONDS from the monotonic one (a resume from sleep or a clock change)
+pub const SCHEDULER_WAKE_CHECK_SECONDS: u64 = 30;
+pub const SCHEDULER_CLOCK_JUMP_SECONDS: i64 = 90;
+pub const SETTING_NOTIFICATIONS_ENABLED: &str = "notifications_enabled";
+// The daily "reviews waiting" notification goes out at the start of the user's usual
+// study window, learned from when they made attempts recently
+pub const STUDY_WINDOW_HISTORY_DAYS: i64 = 60;
+pub const STUDY_WINDOW_HOURS: u32 = 3;
+pub const STUDY_WINDOW_MIN_ATTEMPTS: usize = 10; // Fewer and the default hour is used
+pub const NOTIFICATION_DEFAULT_HOUR: u32 = 18; // Local time
+pub const SETTING_NOTIFICATION_HOUR: &str = "notification_hour"; // 0-23, overrides the learned hour
+pub const SETTING_REVIEW_NOTIFIED_ON: &str = "review_notified_on"; // Local date of the last one
+
+// --- Mastery Decay ---
+pub const DECAY_GRACE_DAYS: i64 = 14; // Idle days before a skill starts to fade
+pub const DECAY_RATE_PER_DAY: f64 = 0.01; // Fraction of mastery lost per idle day after that
+
+// --- Frontend Events ---
+pub const EVENT_REVIEW_DUE_COUNT_CHANGED: &str = "review-due-count-changed";
+pub const EVENT_SKILL_UNLOCKED: &str = "skill-unlocked";
+pub const EVENT_STREAK_UPDATED: &str = "streak-updated";
+
+// --- Reports ---
+pub const WEEKLY_REPORT_WEAKEST_COUNT: usize = 3;
+pub const REPORT_DEFAULT_RANGE_DAYS: i64 = 30;
+pub const ANON_STATS_FORMAT_VERSION: u32 = 1;
+pub const PROFILE_TOP_SKILLS: usize = 5;
+// Upper bounds (days) of interval histogram buckets; the last bucket is open-ended
+pub const INTERVAL_BUCKET_BOUNDS: [f64; 8] = [1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0, 128.0];
+
+// --- Mistake Taxonomy ---
+pub const DEFAULT_MISTAKE_TYPES: [&str; 4] = [
+    "Off-by-one",
+    "Wrong data structure",
+    "Missed edge case",
+    "Time limit exceeded",
+];
+
+// --- Retrospective Prompts ---
+pub const RETRO_PROMPT_FAIL: &str = "What was the key insight you missed?";
+pub const RETRO_PROMPT_READ_SOLUTION: &str =
+    "Which step of the solution would you not have come up with on your own?";
+pub const RETRO_PROMPT_GRIT: &str =
+    "What slowed you down the most, and how would you spot it sooner?";
+
+// --- Bundled Problem Pack ---
+pub const BUNDLED_TRACK_NAME: &str = "NeetCode 150";
+// Version of the bundled pack the database was last seeded or upgraded with
+pub const SETTING_SEED_PACK_VERSION: &str = "seed_pack_version";
+
+// --- Localization ---
+// Locale for problem titles and skill names, e.g. "es" or "pt-BR"
+pub const SETTING_LOCALE: &str = "locale";
+// The stored catalog text is English, so this locale needs no translations
+pub const DEFAULT_LOCALE: &str = "en";
+pub const TRANSLATION_KIND_PROBLEM: &str = "problem";
+// Alternatives number apart from problems, so their IDs can collide
+pub const TRANSLATION_KIND_ALTERNATIVE: &str = "alternative";
+pub const TRANSLATION_KIND_SKILL: &str = "skill";
+
+// --- Track Bundles ---
+// Version of the .lgtrack format export_track writes; newer bundles are refused
+pub const TRACK_BUNDLE_FORMAT: i64 = 1;
+// Appended to the bundled track's name when an export of it is imported again
+pub const IMPORTED_TRACK_SUFFIX: &str = " (imported)";
+
+// --- Remote Pack ---
+// Where a newer version of the bundled pack is downloaded from
+pub const SETTING_REMOTE_PACK_URL: &str = "remote_pack_url";
+// The detached signature sits next to the pack: base64 ed25519 over the pack's bytes
+pub const REMOTE_PACK_SIGNATURE_SUFFIX: &str = ".sig";
+// Base64 ed25519 public key packs must be signed with; a build can swap in its own
+// through LEETGRAPH_PACK_PUBLIC_KEY
+pub const REMOTE_PACK_PUBLIC_KEY: &str = match option_env!("LEETGRAPH_PACK_PUBLIC_KEY") {
+    Some(key) => key,
+    None => "CChlaaEg22WjAnj3XZ4PZ2gjIg2f7m7Y/Lj3gfEhCi0=",
+};
+pub const REMOTE_PACK_TIMEOUT_SECONDS: u64 = 30;
+pub const REMOTE_PACK_MAX_BYTES: u64 = 8 * 1024 * 1024;
+
+// --- Learned Difficulty Multipliers ---
+// Refit at most this often
+pub const DIFFICULTY_FIT_INTERVAL_SECONDS: i64 = DAY_SECONDS;
+// Pseudo-attempts at the default multiplier, so a few attempts barely move the fit
+pub const DIFFICULTY_FIT_PRIOR_ATTEMPTS: f64 = 20.0;
+pub const DIFFICULTY_MULTIPLIER_MIN: f64 = 0.4;
+pub const DIFFICULTY_MULTIPLIER_MAX: f64 = 2.5;
+
+// --- Time to Mastery ---
+// Recent history the projection learns the per-attempt gain and practice rate from
+pub const TIME_TO_MASTERY_WINDOW_DAYS: i64 = 28;
+// Gain assumed without recent attempts: a clean first solve of a Medium problem
+pub const TIME_TO_MASTERY_DEFAULT_GAIN: f64 =
+    PERFORMANCE_MULTIPLIER_NEW_CLEAN * DIFFICULTY_MULTIPLIER_MEDIUM;
+// Projections needing more attempts than this are reported as out of reach
+pub const TIME_TO_MASTERY_MAX_ATTEMPTS: i64 = 500;
@@ -1,7 +1,90 @@
 // src/database.rs
 
-use crate::models::JsonProblem;
-use rusqlite::{params, Connection, Result};
+use crate::constants::{
+    BACKUP_FILE_PREFIX, BACKUP_KEEP_COUNT, BUNDLED_TRACK_NAME, DB_BUSY_BACKOFF_MS, DB_BUSY_POLL_MS,
+    DB_BUSY_RETRIES, DB_BUSY_TIMEOUT_MS, DB_STATEMENT_CACHE_CAPACITY, DEFAULT_MISTAKE_TYPES,
+    QUERY_ALLOWED_KEYWORDS, QUERY_MAX_ROWS, QUERY_STATS_SLOW_COUNT, SETTING_SEED_PACK_VERSION,
+    SKILL_WEIGHT_PRIMARY, TRANSLATION_KIND_ALTERNATIVE, TRANSLATION_KIND_PROBLEM,
+    TRANSLATION_KIND_SKILL,
+};
+use crate::models::{
+    DbHealth, DbInfo, Difficulty, HealthIssue, JsonPack, JsonProblem, MaintenanceReport,
+    MigrationReport, MigrationStep, QueryResult, TableCount,
+};
+use crate::repository::Repository;
+use crate::selection_rng;
+use chrono::Local;
+use log::{debug, info, warn};
+use rusqlite::types::ValueRef;
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+/// Per-connection setup. WAL lets another window or process read while we write, and the
+/// busy handler makes SQLite wait for a competing writer instead of failing at once.
+/// Foreign keys are off by default in SQLite and must be enabled per connection, as must
+/// the `selection_random()` function the selection queries shuffle with.
+pub fn configure_connection(conn: &Connection, read_only: bool) -> Result<()> {
+    conn.busy_handler(Some(wait_for_lock))?;
+    conn.pragma_update(None, "foreign_keys", true)?;
+    conn.set_prepared_statement_cache_capacity(DB_STATEMENT_CACHE_CAPACITY);
+    selection_rng::register(conn)?;
+    if !read_only {
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+    }
+    Ok(())
+}
+
+thread_local! {
+    /// Set when `wait_for_lock` gives up on this thread, failing a statement with `SQLITE_BUSY`.
+    static BUSY: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Busy handler: polls for up to `DB_BUSY_TIMEOUT_MS`, then lets the statement fail with
+/// `SQLITE_BUSY` and records that for `retry_busy`.
+fn wait_for_lock(waits: i32) -> bool {
+    if waits.max(0) as u64 * DB_BUSY_POLL_MS >= DB_BUSY_TIMEOUT_MS {
+        BUSY.with(|busy| busy.set(true));
+        return false;
+    }
+    thread::sleep(Duration::from_millis(DB_BUSY_POLL_MS));
+    true
+}
+
+/// Runs `op`, retrying with exponential backoff while it fails on another connection's
+/// lock. A run that already changed rows is never repeated, so retries can't double-apply
+/// writes; writers should take the lock up front (`BEGIN IMMEDIATE`) to stay retryable.
+/// Errors reach us as text, so a busy failure is told apart by `wait_for_lock` having
+/// given up during the run, never by the message.
+pub fn retry_busy<T>(
+    conn: &Connection,
+    mut op: impl FnMut(&Connection) -> std::result::Result<T, String>,
+) -> std::result::Result<T, String> {
+    let mut retries = 0;
+    loop {
+        let changes_before = total_changes(conn).map_err(|e| e.to_string())?;
+        BUSY.with(|busy| busy.set(false));
+        match op(conn) {
+            Err(e) if BUSY.with(Cell::get) && retries < DB_BUSY_RETRIES => {
+                if total_changes(conn).map_err(|e| e.to_string())? != changes_before {
+                    return Err(e);
+                }
+                retries += 1;
+                warn!("Database busy, retry {}/{}", retries, DB_BUSY_RETRIES);
+                thread::sleep(Duration::from_millis(DB_BUSY_BACKOFF_MS << retries));
+            }
+            result => return result,
+        }
+    }
+}
+
+fn total_changes(conn: &Connection) -> Result<i64> {
+    conn.query_row("SELECT total_changes()", [], |row| row.get(0))
+}
 
 pub fn init_db(conn: &Connection) -> Result<()> {
     println!("[DEBUG] init_db: Checking database schema...");
@@ -37,6 +120,12 @@ pub fn init_db(conn: &Connection) -> Result<()> {
             skill_id INTEGER,
             PRIMARY KEY (problem_id, skill_id)
         );
+        -- Primary skill links made by the bundled pack, so an upgrade only retracts its own
+        CREATE TABLE IF NOT EXISTS bundled_skill_links (
+            problem_id INTEGER NOT NULL,
+            skill_id INTEGER NOT NULL,
+            PRIMARY KEY (problem_id, skill_id)
+        );
         CREATE TABLE IF NOT EXISTS tracks (
             id INTEGER PRIMARY KEY,
             name TEXT UNIQUE NOT NULL
@@ -65,9 +154,270 @@ pub fn init_db(conn: &Connection) -> Result<()> {
             interval_days REAL NOT NULL DEFAULT 1.0,
             next_review_ts INTEGER NOT NULL
         );
+        CREATE TABLE IF NOT EXISTS mistake_types (
+            id INTEGER PRIMARY KEY,
+            name TEXT UNIQUE NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS attempt_mistakes (
+            attempt_id INTEGER,
+            mistake_type_id INTEGER,
+            PRIMARY KEY (attempt_id, mistake_type_id)
+        );
+        CREATE TABLE IF NOT EXISTS retrospectives (
+            id INTEGER PRIMARY KEY,
+            attempt_id INTEGER NOT NULL,
+            problem_id INTEGER NOT NULL,
+            prompt TEXT NOT NULL,
+            response TEXT,
+            created_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS skill_mastery_log (
+            id INTEGER PRIMARY KEY,
+            skill_id INTEGER NOT NULL,
+            attempt_id INTEGER,
+            old_mastery REAL NOT NULL,
+            new_mastery REAL NOT NULL,
+            reason TEXT NOT NULL,
+            timestamp INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS skill_unlocks (
+            id INTEGER PRIMARY KEY,
+            skill_id INTEGER NOT NULL,
+            attempt_id INTEGER,
+            unlocked_at INTEGER NOT NULL
+        );
+        -- Every interval the scheduler set for a concept (keyed by parent problem)
+        CREATE TABLE IF NOT EXISTS problem_interval_log (
+            id INTEGER PRIMARY KEY,
+            problem_id INTEGER NOT NULL,
+            interval_days REAL NOT NULL,
+            ease_factor REAL NOT NULL,
+            timestamp INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+        -- One free-form note per concept (keyed by parent problem)
+        CREATE TABLE IF NOT EXISTS notes (
+            problem_id INTEGER PRIMARY KEY,
+            body TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        );
+        -- Free-form user tags per concept (keyed by parent problem), matched case-insensitively
+        CREATE TABLE IF NOT EXISTS problem_tags (
+            problem_id INTEGER NOT NULL,
+            tag TEXT NOT NULL COLLATE NOCASE,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (problem_id, tag)
+        );
+        -- Concepts starred for favorites practice (keyed by parent problem)
+        CREATE TABLE IF NOT EXISTS starred_problems (
+            problem_id INTEGER PRIMARY KEY,
+            starred_at INTEGER NOT NULL
+        );
+        -- Per-problem user overrides of catalog data (keyed by the problem itself)
+        CREATE TABLE IF NOT EXISTS problem_overrides (
+            problem_id INTEGER PRIMARY KEY,
+            difficulty TEXT CHECK (difficulty IN ('Easy','Medium','Hard'))
+        );
+        -- The current goal-dated study plan; generating a new one replaces it
+        CREATE TABLE IF NOT EXISTS study_plan (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            deadline TEXT NOT NULL,
+            new_problems INTEGER NOT NULL,
+            feasible INTEGER NOT NULL,
+            warnings TEXT NOT NULL DEFAULT '[]',
+            created_at INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS study_plan_skills (
+            skill_id INTEGER PRIMARY KEY
+        );
+        CREATE TABLE IF NOT EXISTS study_plan_days (
+            day TEXT PRIMARY KEY,
+            new_problems INTEGER NOT NULL,
+            reviews INTEGER NOT NULL
+        );
+        -- Concepts kept out of the due queue (keyed by parent problem); `reason` says why
+        CREATE TABLE IF NOT EXISTS suspended_problems (
+            problem_id INTEGER PRIMARY KEY,
+            reason TEXT NOT NULL,
+            suspended_at INTEGER NOT NULL
+        );
+        -- Active exam cram; while the row exists the queue goes for weak-skill coverage
+        CREATE TABLE IF NOT EXISTS exam_cram (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            until TEXT NOT NULL,
+            started_at INTEGER NOT NULL
+        );
+        -- Repetition state when the cram started, restored when it ends
+        CREATE TABLE IF NOT EXISTS exam_cram_snapshot (
+            problem_id INTEGER PRIMARY KEY,
+            ease_factor REAL NOT NULL,
+            interval_days REAL NOT NULL,
+            next_review_ts INTEGER NOT NULL
+        );
+        -- Last state pushed to / agreed with Notion, used to tell which side changed
+        CREATE TABLE IF NOT EXISTS notion_sync (
+            problem_id INTEGER PRIMARY KEY,
+            page_id TEXT NOT NULL,
+            synced_status TEXT,
+            synced_note TEXT,
+            synced_at INTEGER NOT NULL
+        );
+        -- Attempt journal entries already committed, so startup replay skips them
+        CREATE TABLE IF NOT EXISTS journal_applied (
+            entry_id TEXT PRIMARY KEY,
+            applied_at INTEGER NOT NULL
+        );
+        -- Idempotency keys sent with submissions, and the result first returned for each
+        CREATE TABLE IF NOT EXISTS attempt_keys (
+            key TEXT PRIMARY KEY,
+            result TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        );
+        -- Concepts recently served, skipped by get_next_problem until they expire
+        CREATE TABLE IF NOT EXISTS reservations (
+            problem_id INTEGER PRIMARY KEY,
+            expires_at INTEGER NOT NULL
+        );
+        -- Opt-in local telemetry: one row per command call
+        CREATE TABLE IF NOT EXISTS usage_events (
+            id INTEGER PRIMARY KEY,
+            command TEXT NOT NULL,
+            duration_ms INTEGER NOT NULL,
+            ok INTEGER NOT NULL,
+            timestamp INTEGER NOT NULL
+        );
+        -- Every change to problem_state and skill_state, written by the audit triggers
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY,
+            timestamp INTEGER NOT NULL,
+            entity TEXT NOT NULL,
+            entity_id INTEGER NOT NULL,
+            old_value TEXT,
+            new_value TEXT,
+            cause TEXT NOT NULL,
+            attempt_id INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_audit_log_entity ON audit_log(entity, entity_id);
+        -- Cause the audit triggers record, set around a change by audit::with_cause
+        CREATE TABLE IF NOT EXISTS audit_context (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            cause TEXT NOT NULL,
+            attempt_id INTEGER
+        );
+        -- Opt-in local telemetry: latency of every distinct SQL statement the app runs
+        CREATE TABLE IF NOT EXISTS query_stats (
+            sql TEXT PRIMARY KEY,
+            calls INTEGER NOT NULL,
+            total_us INTEGER NOT NULL,
+            max_us INTEGER NOT NULL,
+            last_seen INTEGER NOT NULL
+        );
+        -- Mastery multipliers fitted to the user's own history, one row per difficulty
+        CREATE TABLE IF NOT EXISTS difficulty_fits (
+            difficulty TEXT PRIMARY KEY,
+            default_multiplier REAL NOT NULL,
+            multiplier REAL NOT NULL,
+            attempts INTEGER NOT NULL,
+            fail_rate REAL,
+            mean_minutes REAL,
+            fitted_at INTEGER NOT NULL
+        );
+        -- Autosaved attempt in progress, one per problem, deleted when the attempt is logged
+        CREATE TABLE IF NOT EXISTS attempt_drafts (
+            problem_id INTEGER PRIMARY KEY,
+            elapsed_minutes REAL NOT NULL,
+            notes TEXT,
+            code TEXT,
+            updated_at INTEGER NOT NULL
+        );
+        -- The backend timer of a problem in progress, deleted when the attempt is logged
+        CREATE TABLE IF NOT EXISTS attempt_timers (
+            problem_id INTEGER PRIMARY KEY,
+            started_at INTEGER NOT NULL,
+            paused_at INTEGER,
+            pause_count INTEGER NOT NULL DEFAULT 0,
+            -- Finished pauses only; the current one runs from paused_at
+            paused_seconds INTEGER NOT NULL DEFAULT 0
+        );
+        -- Every problem get_next_problem served, with the attempt that followed if any.
+        -- queue is the serialized QueueKind; concept_id is the parent for alternatives,
+        -- and problem_id is an alternative's ID when is_alternative is set.
+        CREATE TABLE IF NOT EXISTS served_log (
+            id INTEGER PRIMARY KEY,
+            problem_id INTEGER NOT NULL,
+            is_alternative INTEGER NOT NULL DEFAULT 0,
+            concept_id INTEGER NOT NULL,
+            queue TEXT NOT NULL,
+            served_at INTEGER NOT NULL,
+            attempt_id INTEGER
+        );
+        CREATE TABLE IF NOT EXISTS problem_skips (
+            id INTEGER PRIMARY KEY,
+            problem_id INTEGER NOT NULL,
+            reason TEXT NOT NULL,
+            skipped_at INTEGER NOT NULL
+        );
+        -- Localized catalog text, keyed by the ID of the problem, alternative or skill.
+        -- Anything missing here shows the English stored with the problem or skill.
+        CREATE TABLE IF NOT EXISTS translations (
+            kind TEXT NOT NULL CHECK (kind IN ('problem','alternative','skill')),
+            ref_id INTEGER NOT NULL,
+            locale TEXT NOT NULL,
+            text TEXT NOT NULL,
+            PRIMARY KEY (kind, ref_id, locale)
+        );
+        -- Skills credited by each attempt. Alternatives without their own
+        -- mappings fall back to the parent's skills (mirrors process_attempt).
+        CREATE VIEW IF NOT EXISTS attempt_skills AS
+            SELECT a.id AS attempt_id, ps.skill_id
+            FROM attempts a
+            JOIN problem_skills ps ON ps.problem_id = CASE
+                WHEN EXISTS (SELECT 1 FROM problem_skills x WHERE x.problem_id = a.problem_id)
+                THEN a.problem_id
+                ELSE (SELECT parent_id FROM alternatives WHERE id = a.problem_id)
+            END;
         ",
     )?;
 
+    // Columns added after the initial schema shipped. Their migrations add them too, but
+    // the app queries them whether or not migrations have run (see `SETTING_AUTO_MIGRATE`)
+    add_column_if_missing(conn, "attempts", "explanation", "TEXT")?;
+    add_column_if_missing(
+        conn,
+        "attempts",
+        "time_clamped",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_column_if_missing(conn, "problem_overrides", "expected_minutes", "REAL")?;
+    add_column_if_missing(
+        conn,
+        "problem_skills",
+        "weight",
+        "REAL NOT NULL DEFAULT 1.0",
+    )?;
+    add_column_if_missing(
+        conn,
+        "attempts",
+        "pause_count",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_column_if_missing(
+        conn,
+        "attempts",
+        "paused_minutes",
+        "REAL NOT NULL DEFAULT 0",
+    )?;
+    add_column_if_missing(conn, "problems", "deleted_at", "INTEGER")?;
+
+    // Default taxonomy; users can extend it at runtime.
+    let mut stmt = conn.prepare("INSERT OR IGNORE INTO mistake_types (name) VALUES (?)")?;
+    for m in DEFAULT_MISTAKE_TYPES {
+        stmt.execute([m])?;
+    }
+
     let count: i64 = conn.query_row("SELECT count(*) FROM problems", [], |row| row.get(0))?;
     if count == 0 {
         println!("[DEBUG] init_db: Table empty. Seeding data...");
@@ -77,6 +427,1095 @@ pub fn init_db(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+// --- Migrations ---
+// Schema changes after the initial release. `PRAGMA user_version` records the last one
+// applied; every step must also be safe on databases that already have the change.
+
+struct Migration {
+    version: i64,
+    description: &'static str,
+    apply: fn(&Connection) -> Result<()>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Add attempts.explanation for explain-your-solution notes",
+        apply: migrate_attempt_explanation,
+    },
+    Migration {
+        version: 2,
+        description: "Add attempts.time_clamped to flag capped attempt times",
+        apply: migrate_attempt_time_clamped,
+    },
+    Migration {
+        version: 3,
+        description: "Add a full-text search index over titles, notes and explanations",
+        apply: migrate_search_index,
+    },
+    Migration {
+        version: 4,
+        description: "Add problem_overrides.expected_minutes for per-problem time targets",
+        apply: migrate_expected_minutes_override,
+    },
+    Migration {
+        version: 5,
+        description: "Add problem_skills.weight so secondary skills get partial credit",
+        apply: migrate_problem_skill_weight,
+    },
+    Migration {
+        version: 6,
+        description: "Map seeded problems to their secondary skills",
+        apply: migrate_secondary_skills,
+    },
+    Migration {
+        version: 7,
+        description: "Declare foreign keys so deleted problems, skills and attempts cascade",
+        apply: migrate_foreign_keys,
+    },
+    Migration {
+        version: 8,
+        description: "Add problems.deleted_at so removed custom problems keep their history",
+        apply: migrate_problem_deleted_at,
+    },
+    Migration {
+        version: 9,
+        description: "Log every problem_state and skill_state change to audit_log",
+        apply: migrate_audit_triggers,
+    },
+    Migration {
+        version: 10,
+        description: "Add attempts.pause_count and paused_minutes for interrupted attempts",
+        apply: migrate_attempt_pauses,
+    },
+];
+
+fn migrate_attempt_explanation(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "attempts", "explanation", "TEXT")
+}
+
+fn migrate_attempt_time_clamped(conn: &Connection) -> Result<()> {
+    add_column_if_missing(
+        conn,
+        "attempts",
+        "time_clamped",
+        "INTEGER NOT NULL DEFAULT 0",
+    )
+}
+
+fn migrate_attempt_pauses(conn: &Connection) -> Result<()> {
+    add_column_if_missing(
+        conn,
+        "attempts",
+        "pause_count",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_column_if_missing(
+        conn,
+        "attempts",
+        "paused_minutes",
+        "REAL NOT NULL DEFAULT 0",
+    )
+}
+
+fn migrate_expected_minutes_override(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "problem_overrides", "expected_minutes", "REAL")
+}
+
+fn migrate_problem_skill_weight(conn: &Connection) -> Result<()> {
+    add_column_if_missing(
+        conn,
+        "problem_skills",
+        "weight",
+        "REAL NOT NULL DEFAULT 1.0",
+    )
+}
+
+/// Runs after `migrate_problem_skill_weight` rather than in `seed_data`, so databases seeded
+/// before weights existed pick up the secondary mappings too.
+fn migrate_secondary_skills(conn: &Connection) -> Result<()> {
+    let problems = bundled_pack().problems;
+    let ids: Vec<i64> = problems.iter().map(|p| p.id).collect();
+    link_secondary_skills(conn, &problems, &ids)
+}
+
+/// `ids` are the problem ids the pack's problems are stored under (see `seed_pack`).
+pub fn link_secondary_skills(
+    conn: &Connection,
+    problems: &[JsonProblem],
+    ids: &[i64],
+) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "INSERT OR IGNORE INTO problem_skills (problem_id, skill_id, weight)
+         SELECT ?1, id, ?2 FROM skills
+         WHERE name = ?3 AND EXISTS (SELECT 1 FROM problems WHERE id = ?1)",
+    )?;
+    for (p, id) in problems.iter().zip(ids) {
+        for secondary in &p.secondary_categories {
+            stmt.execute(params![id, secondary.weight, secondary.category.trim()])?;
+        }
+    }
+    Ok(())
+}
+
+fn migrate_problem_deleted_at(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "problems", "deleted_at", "INTEGER")
+}
+
+/// Old and new values go in as JSON so one table covers both state tables. Seeding and the
+/// exam cram use INSERT OR REPLACE, which skips delete triggers, so problem_state inserts
+/// are logged before they happen, while the row being replaced can still be read.
+fn migrate_audit_triggers(conn: &Connection) -> Result<()> {
+    let cause = "COALESCE((SELECT cause FROM audit_context), 'system'),
+                (SELECT attempt_id FROM audit_context)";
+    let problem = |row: &str| {
+        format!(
+            "json_object('ease_factor', {0}.ease_factor, 'interval_days', {0}.interval_days,
+                'next_review_ts', {0}.next_review_ts)",
+            row
+        )
+    };
+    let skill = |row: &str| {
+        format!(
+            "json_object('mastery', {0}.mastery, 'attempts', {0}.attempts)",
+            row
+        )
+    };
+    let log = "INSERT INTO audit_log
+            (timestamp, entity, entity_id, old_value, new_value, cause, attempt_id)";
+    let now = "CAST(strftime('%s', 'now') AS INTEGER)";
+    conn.execute_batch(&format!(
+        "
+        CREATE TRIGGER IF NOT EXISTS audit_problem_state_insert BEFORE INSERT ON problem_state
+        WHEN NOT EXISTS (
+            SELECT 1 FROM problem_state WHERE problem_id = new.problem_id
+            AND ease_factor IS new.ease_factor AND interval_days IS new.interval_days
+            AND next_review_ts IS new.next_review_ts
+        ) BEGIN
+            {log} SELECT {now}, 'problem', new.problem_id,
+                (SELECT {current} FROM problem_state cur WHERE cur.problem_id = new.problem_id),
+                {new_problem}, {cause};
+        END;
+        CREATE TRIGGER IF NOT EXISTS audit_problem_state_update AFTER UPDATE ON problem_state
+        WHEN old.ease_factor IS NOT new.ease_factor OR old.interval_days IS NOT new.interval_days
+            OR old.next_review_ts IS NOT new.next_review_ts BEGIN
+            {log} SELECT {now}, 'problem', new.problem_id, {old_problem}, {new_problem}, {cause};
+        END;
+        CREATE TRIGGER IF NOT EXISTS audit_problem_state_delete AFTER DELETE ON problem_state BEGIN
+            {log} SELECT {now}, 'problem', old.problem_id, {old_problem}, NULL, {cause};
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS audit_skill_state_insert AFTER INSERT ON skill_state BEGIN
+            {log} SELECT {now}, 'skill', new.skill_id, NULL, {new_skill}, {cause};
+        END;
+        CREATE TRIGGER IF NOT EXISTS audit_skill_state_update AFTER UPDATE ON skill_state
+        WHEN old.mastery IS NOT new.mastery OR old.attempts IS NOT new.attempts BEGIN
+            {log} SELECT {now}, 'skill', new.skill_id, {old_skill}, {new_skill}, {cause};
+        END;
+        CREATE TRIGGER IF NOT EXISTS audit_skill_state_delete AFTER DELETE ON skill_state BEGIN
+            {log} SELECT {now}, 'skill', old.skill_id, {old_skill}, NULL, {cause};
+        END;
+        ",
+        log = log,
+        now = now,
+        cause = cause,
+        current = problem("cur"),
+        old_problem = problem("old"),
+        new_problem = problem("new"),
+        old_skill = skill("old"),
+        new_skill = skill("new"),
+    ))
+}
+
+/// Tables `migrate_foreign_keys` rebuilds, with their full definitions. Attempts, skill
+/// mappings and overrides may point at an alternative as well as a problem, so their
+/// `problem_id` stays unconstrained; attempt history is never deleted implicitly.
+const FOREIGN_KEY_TABLES: &[(&str, &str)] = &[
+    (
+        "skill_prereqs",
+        "skill_id INTEGER REFERENCES skills(id) ON DELETE CASCADE,
+         prereq_id INTEGER REFERENCES skills(id) ON DELETE CASCADE,
+         PRIMARY KEY (skill_id, prereq_id)",
+    ),
+    (
+        "alternatives",
+        "id INTEGER PRIMARY KEY,
+         parent_id INTEGER NOT NULL REFERENCES problems(id) ON DELETE CASCADE,
+         title TEXT NOT NULL,
+         url TEXT,
+         difficulty TEXT",
+    ),
+    (
+        "problem_skills",
+        "problem_id INTEGER,
+         skill_id INTEGER REFERENCES skills(id) ON DELETE CASCADE,
+         weight REAL NOT NULL DEFAULT 1.0,
+         PRIMARY KEY (problem_id, skill_id)",
+    ),
+    (
+        "track_problems",
+        "track_id INTEGER REFERENCES tracks(id) ON DELETE CASCADE,
+         problem_id INTEGER REFERENCES problems(id) ON DELETE CASCADE,
+         PRIMARY KEY (track_id, problem_id)",
+    ),
+    (
+        "skill_state",
+        "skill_id INTEGER PRIMARY KEY REFERENCES skills(id) ON DELETE CASCADE,
+         mastery REAL NOT NULL DEFAULT 0.0,
+         attempts INTEGER NOT NULL DEFAULT 0",
+    ),
+    (
+        "problem_state",
+        "problem_id INTEGER PRIMARY KEY REFERENCES problems(id) ON DELETE CASCADE,
+         ease_factor REAL NOT NULL DEFAULT 2.5,
+         interval_days REAL NOT NULL DEFAULT 1.0,
+         next_review_ts INTEGER NOT NULL",
+    ),
+    (
+        "attempt_mistakes",
+        "attempt_id INTEGER REFERENCES attempts(id) ON DELETE CASCADE,
+         mistake_type_id INTEGER REFERENCES mistake_types(id) ON DELETE CASCADE,
+         PRIMARY KEY (attempt_id, mistake_type_id)",
+    ),
+    (
+        "retrospectives",
+        "id INTEGER PRIMARY KEY,
+         attempt_id INTEGER NOT NULL REFERENCES attempts(id) ON DELETE CASCADE,
+         problem_id INTEGER NOT NULL,
+         prompt TEXT NOT NULL,
+         response TEXT,
+         created_at INTEGER NOT NULL",
+    ),
+    (
+        "skill_mastery_log",
+        "id INTEGER PRIMARY KEY,
+         skill_id INTEGER NOT NULL REFERENCES skills(id) ON DELETE CASCADE,
+         attempt_id INTEGER REFERENCES attempts(id) ON DELETE SET NULL,
+         old_mastery REAL NOT NULL,
+         new_mastery REAL NOT NULL,
+         reason TEXT NOT NULL,
+         timestamp INTEGER NOT NULL",
+    ),
+    (
+        "skill_unlocks",
+        "id INTEGER PRIMARY KEY,
+         skill_id INTEGER NOT NULL REFERENCES skills(id) ON DELETE CASCADE,
+         attempt_id INTEGER REFERENCES attempts(id) ON DELETE SET NULL,
+         unlocked_at INTEGER NOT NULL",
+    ),
+    (
+        "problem_interval_log",
+        "id INTEGER PRIMARY KEY,
+         problem_id INTEGER NOT NULL REFERENCES problems(id) ON DELETE CASCADE,
+         interval_days REAL NOT NULL,
+         ease_factor REAL NOT NULL,
+         timestamp INTEGER NOT NULL",
+    ),
+    (
+        "notes",
+        "problem_id INTEGER PRIMARY KEY REFERENCES problems(id) ON DELETE CASCADE,
+         body TEXT NOT NULL,
+         updated_at INTEGER NOT NULL",
+    ),
+    (
+        "problem_tags",
+        "problem_id INTEGER NOT NULL REFERENCES problems(id) ON DELETE CASCADE,
+         tag TEXT NOT NULL COLLATE NOCASE,
+         created_at INTEGER NOT NULL,
+         PRIMARY KEY (problem_id, tag)",
+    ),
+    (
+        "starred_problems",
+        "problem_id INTEGER PRIMARY KEY REFERENCES problems(id) ON DELETE CASCADE,
+         starred_at INTEGER NOT NULL",
+    ),
+    (
+        "study_plan_skills",
+        "skill_id INTEGER PRIMARY KEY REFERENCES skills(id) ON DELETE CASCADE",
+    ),
+    (
+        "suspended_problems",
+        "problem_id INTEGER PRIMARY KEY REFERENCES problems(id) ON DELETE CASCADE,
+         reason TEXT NOT NULL,
+         suspended_at INTEGER NOT NULL",
+    ),
+    (
+        "exam_cram_snapshot",
+        "problem_id INTEGER PRIMARY KEY REFERENCES problems(id) ON DELETE CASCADE,
+         ease_factor REAL NOT NULL,
+         interval_days REAL NOT NULL,
+         next_review_ts INTEGER NOT NULL",
+    ),
+    (
+        "notion_sync",
+        "problem_id INTEGER PRIMARY KEY REFERENCES problems(id) ON DELETE CASCADE,
+         page_id TEXT NOT NULL,
+         synced_status TEXT,
+         synced_note TEXT,
+         synced_at INTEGER NOT NULL",
+    ),
+    (
+        "reservations",
+        "problem_id INTEGER PRIMARY KEY REFERENCES problems(id) ON DELETE CASCADE,
+         expires_at INTEGER NOT NULL",
+    ),
+];
+
+/// SQLite cannot add constraints to a table, so each one is rebuilt and its rows copied
+/// over. Rows that already point at something missing would break the new constraints:
+/// optional attempt links are cleared, anything else is dropped. Dropping a table takes its
+/// triggers with it, so the search triggers are recreated at the end.
+fn migrate_foreign_keys(conn: &Connection) -> Result<()> {
+    // Don't have RENAME check views (e.g. attempt_skills) while their tables are swapped
+    conn.pragma_update(None, "legacy_alter_table", true)?;
+    for (table, definition) in FOREIGN_KEY_TABLES {
+        let columns = conn
+            .prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>>>()?
+            .join(", ");
+        conn.execute_batch(&format!(
+            "CREATE TABLE {0}_new ({1});
+             INSERT INTO {0}_new ({2}) SELECT {2} FROM {0};
+             DROP TABLE {0};
+             ALTER TABLE {0}_new RENAME TO {0};",
+            table, definition, columns
+        ))?;
+    }
+    conn.pragma_update(None, "legacy_alter_table", false)?;
+
+    for (table, rowid, parent) in foreign_key_violations(conn)? {
+        if parent == "attempts" && matches!(table.as_str(), "skill_mastery_log" | "skill_unlocks") {
+            conn.execute(
+                &format!("UPDATE {} SET attempt_id = NULL WHERE rowid = ?", table),
+                [rowid],
+            )?;
+        } else {
+            conn.execute(&format!("DELETE FROM {} WHERE rowid = ?", table), [rowid])?;
+        }
+    }
+    migrate_search_index(conn)
+}
+
+/// (table, rowid, parent table) for every row whose foreign key points at nothing.
+fn foreign_key_violations(conn: &Connection) -> Result<Vec<(String, i64, String)>> {
+    let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+    let violations = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(violations)
+}
+
+/// One FTS5 table for everything searchable, kept current by triggers. `kind` and
+/// `ref_id` point back at the source row; `problem_id` is what a hit opens. Seeding uses
+/// INSERT OR REPLACE, which skips delete triggers, so insert triggers clear old rows first.
+fn migrate_search_index(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+            title, body, kind UNINDEXED, ref_id UNINDEXED, problem_id UNINDEXED
+        );
+
+        CREATE TRIGGER IF NOT EXISTS search_problems_insert AFTER INSERT ON problems BEGIN
+            DELETE FROM search_index WHERE kind = 'problem' AND ref_id = new.id;
+            INSERT INTO search_index (title, body, kind, ref_id, problem_id)
+            VALUES (new.title, '', 'problem', new.id, new.id);
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_problems_update AFTER UPDATE OF title ON problems BEGIN
+            UPDATE search_index SET title = new.title WHERE kind = 'problem' AND ref_id = new.id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_problems_delete AFTER DELETE ON problems BEGIN
+            DELETE FROM search_index WHERE kind = 'problem' AND ref_id = old.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS search_alternatives_insert AFTER INSERT ON alternatives BEGIN
+            DELETE FROM search_index WHERE kind = 'alternative' AND ref_id = new.id;
+            INSERT INTO search_index (title, body, kind, ref_id, problem_id)
+            VALUES (new.title, '', 'alternative', new.id, new.id);
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_alternatives_update AFTER UPDATE OF title ON alternatives BEGIN
+            UPDATE search_index SET title = new.title WHERE kind = 'alternative' AND ref_id = new.id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_alternatives_delete AFTER DELETE ON alternatives BEGIN
+            DELETE FROM search_index WHERE kind = 'alternative' AND ref_id = old.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS search_notes_insert AFTER INSERT ON notes BEGIN
+            DELETE FROM search_index WHERE kind = 'note' AND ref_id = new.problem_id;
+            INSERT INTO search_index (title, body, kind, ref_id, problem_id)
+            VALUES ('', new.body, 'note', new.problem_id, new.problem_id);
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_notes_update AFTER UPDATE OF body ON notes BEGIN
+            UPDATE search_index SET body = new.body WHERE kind = 'note' AND ref_id = new.problem_id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_notes_delete AFTER DELETE ON notes BEGIN
+            DELETE FROM search_index WHERE kind = 'note' AND ref_id = old.problem_id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS search_explanations_insert AFTER INSERT ON attempts
+        WHEN new.explanation IS NOT NULL BEGIN
+            INSERT INTO search_index (title, body, kind, ref_id, problem_id)
+            VALUES ('', new.explanation, 'explanation', new.id, new.problem_id);
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_explanations_update AFTER UPDATE OF explanation ON attempts BEGIN
+            DELETE FROM search_index WHERE kind = 'explanation' AND ref_id = new.id;
+            INSERT INTO search_index (title, body, kind, ref_id, problem_id)
+            SELECT '', new.explanation, 'explanation', new.id, new.problem_id
+            WHERE new.explanation IS NOT NULL;
+        END;
+        CREATE TRIGGER IF NOT EXISTS search_explanations_delete AFTER DELETE ON attempts
+        WHEN old.explanation IS NOT NULL BEGIN
+            DELETE FROM search_index WHERE kind = 'explanation' AND ref_id = old.id;
+        END;
+
+        -- (Re)build from the source tables
+        DELETE FROM search_index;
+        INSERT INTO search_index (title, body, kind, ref_id, problem_id)
+            SELECT title, '', 'problem', id, id FROM problems;
+        INSERT INTO search_index (title, body, kind, ref_id, problem_id)
+            SELECT title, '', 'alternative', id, id FROM alternatives;
+        INSERT INTO search_index (title, body, kind, ref_id, problem_id)
+            SELECT '', body, 'note', problem_id, problem_id FROM notes;
+        INSERT INTO search_index (title, body, kind, ref_id, problem_id)
+            SELECT '', explanation, 'explanation', id, problem_id FROM attempts
+            WHERE explanation IS NOT NULL;
+        ",
+    )
+}
+
+pub fn schema_version(conn: &Connection) -> Result<i64> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Reports pending migrations and, unless `dry_run`, applies them after writing a backup
+/// to `backup_dir` (skipped for databases with no attempts yet, which have nothing to lose).
+pub fn run_migrations(
+    conn: &Connection,
+    dry_run: bool,
+    backup_dir: &Path,
+) -> std::result::Result<MigrationReport, String> {
+    let current_version = schema_version(conn).map_err(|e| e.to_string())?;
+    let pending: Vec<MigrationStep> = MIGRATIONS
+        .iter()
+        .filter(|m| m.version > current_version)
+        .map(|m| MigrationStep {
+            version: m.version,
+            description: m.description.to_string(),
+        })
+        .collect();
+    let mut report = MigrationReport {
+        current_version,
+        target_version: MIGRATIONS.last().map_or(current_version, |m| m.version),
+        pending,
+        applied: false,
+        backup_path: None,
+    };
+    if dry_run || report.pending.is_empty() {
+        return Ok(report);
+    }
+
+    let attempts: i64 = conn
+        .query_row("SELECT count(*) FROM attempts", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if attempts > 0 {
+        let label = format!("pre-migration-v{}", current_version);
+        report.backup_path = Some(backup(conn, backup_dir, &label)?.display().to_string());
+    }
+    // Rebuilding a table must not cascade into its children; the pragma can only change
+    // outside a transaction, so enforcement is off for the whole run and checked per step
+    let foreign_keys: bool = conn
+        .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    conn.pragma_update(None, "foreign_keys", false)
+        .map_err(|e| e.to_string())?;
+    let result = apply_migrations(conn, current_version);
+    conn.pragma_update(None, "foreign_keys", foreign_keys)
+        .map_err(|e| e.to_string())?;
+    result?;
+    report.applied = true;
+    Ok(report)
+}
+
+fn apply_migrations(conn: &Connection, current_version: i64) -> std::result::Result<(), String> {
+    for m in MIGRATIONS.iter().filter(|m| m.version > current_version) {
+        debug!("migrate: v{} {}", m.version, m.description);
+        // Each step lands together with its version bump, or not at all
+        let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+        (m.apply)(&tx).map_err(|e| format!("Migration v{} failed: {}", m.version, e))?;
+        let violations = foreign_key_violations(&tx).map_err(|e| e.to_string())?;
+        if let Some((table, _, parent)) = violations.first() {
+            return Err(format!(
+                "Migration v{} failed: {} rows break foreign keys (first: {} -> {})",
+                m.version,
+                violations.len(),
+                table,
+                parent
+            ));
+        }
+        tx.execute_batch(&format!("PRAGMA user_version = {}", m.version))
+            .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Writes a compacted copy of the database to `dir` via `VACUUM INTO`.
+pub fn backup(conn: &Connection, dir: &Path, label: &str) -> std::result::Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    let stamp = Local::now().format("%Y%m%d-%H%M%S");
+    let path = dir.join(format!("{}{}-{}.db", BACKUP_FILE_PREFIX, stamp, label));
+    conn.execute("VACUUM INTO ?", [path.to_string_lossy()])
+        .map_err(|e| format!("Backup failed: {}", e))?;
+    Ok(path)
+}
+
+// --- Maintenance ---
+
+/// Compacts the file, refreshes query-planner statistics and prunes old backups.
+pub fn maintain(
+    conn: &Connection,
+    backup_dir: &Path,
+) -> std::result::Result<MaintenanceReport, String> {
+    let size_before_bytes = database_size(conn).map_err(|e| e.to_string())?;
+    conn.execute_batch("VACUUM; ANALYZE;")
+        .map_err(|e| format!("Maintenance failed: {}", e))?;
+    let size_after_bytes = database_size(conn).map_err(|e| e.to_string())?;
+    let backups_removed = prune_backups(backup_dir, BACKUP_KEEP_COUNT)?;
+    Ok(MaintenanceReport {
+        size_before_bytes,
+        size_after_bytes,
+        backups_removed,
+    })
+}
+
+pub fn database_size(conn: &Connection) -> Result<i64> {
+    conn.query_row(
+        "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Backups in `dir`, newest first. Names embed a sortable timestamp.
+pub fn list_backups(dir: &Path) -> Vec<PathBuf> {
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with(BACKUP_FILE_PREFIX) && n.ends_with(".db"))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    backups.sort();
+    backups.reverse();
+    backups
+}
+
+/// Deletes all but the newest `keep` backups, returning the removed file names.
+fn prune_backups(dir: &Path, keep: usize) -> std::result::Result<Vec<String>, String> {
+    let mut removed = Vec::new();
+    for path in list_backups(dir).into_iter().skip(keep) {
+        std::fs::remove_file(&path).map_err(|e| format!("Failed to remove {:?}: {}", path, e))?;
+        removed.push(
+            path.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+        );
+    }
+    Ok(removed)
+}
+
+// --- Diagnostics ---
+
+pub fn get_db_info(conn: &Connection, backup_dir: &Path) -> Result<DbInfo> {
+    let path = conn.path().filter(|p| !p.is_empty()).map(str::to_string);
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    )?;
+    let names = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+    // Read-only sessions may open a database created before query timing existed
+    let slow_queries = if names.iter().any(|n| n == "query_stats") {
+        Repository::new(conn).get_slow_queries(QUERY_STATS_SLOW_COUNT)?
+    } else {
+        Vec::new()
+    };
+    let mut tables = Vec::new();
+    for name in names {
+        let rows = conn.query_row(&format!("SELECT count(*) FROM \"{}\"", name), [], |row| {
+            row.get(0)
+        })?;
+        tables.push(TableCount { name, rows });
+    }
+
+    let last_backup = list_backups(backup_dir).into_iter().next();
+    let last_backup_at = last_backup
+        .as_ref()
+        .and_then(|p| std::fs::metadata(p).ok()?.modified().ok())
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64);
+
+    Ok(DbInfo {
+        size_bytes: database_size(conn)?,
+        path,
+        schema_version: schema_version(conn)?,
+        journal_mode: conn.query_row("PRAGMA journal_mode", [], |row| row.get(0))?,
+        tables,
+        last_backup_path: last_backup.map(|p| p.display().to_string()),
+        last_backup_at,
+        slow_queries,
+    })
+}
+
+/// Runs one ad-hoc statement for the SQL console. Only statements SQLite itself reports
+/// as read-only are executed; ATTACH/DETACH and transaction control are refused too since
+/// they count as read-only but change the connection. Anything after the first statement
+/// is ignored.
+pub fn run_readonly_query(
+    conn: &Connection,
+    sql: &str,
+) -> std::result::Result<QueryResult, String> {
+    let first_word = sql
+        .trim_start()
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_ascii_uppercase();
+    if !QUERY_ALLOWED_KEYWORDS.contains(&first_word.as_str()) {
+        return Err(format!(
+            "Only {} statements are allowed",
+            QUERY_ALLOWED_KEYWORDS.join("/")
+        ));
+    }
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    if !stmt.readonly() {
+        return Err("Only read-only statements are allowed".to_string());
+    }
+
+    let columns: Vec<String> = stmt
+        .column_names()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    let mut rows = Vec::new();
+    let mut truncated = false;
+    let mut result = stmt.query([]).map_err(|e| e.to_string())?;
+    while let Some(row) = result.next().map_err(|e| e.to_string())? {
+        if rows.len() >= QUERY_MAX_ROWS {
+            truncated = true;
+            break;
+        }
+        let values = (0..columns.len())
+            .map(|i| match row.get_ref(i) {
+                Ok(ValueRef::Null) | Err(_) => serde_json::Value::Null,
+                Ok(ValueRef::Integer(n)) => n.into(),
+                Ok(ValueRef::Real(f)) => f.into(),
+                Ok(ValueRef::Text(t)) => String::from_utf8_lossy(t).into_owned().into(),
+                Ok(ValueRef::Blob(b)) => format!("<blob, {} bytes>", b.len()).into(),
+            })
+            .collect();
+        rows.push(values);
+    }
+    Ok(QueryResult {
+        columns,
+        rows,
+        truncated,
+    })
+}
+
+// --- Health Checks ---
+
+struct OrphanCheck {
+    name: &'static str,
+    count_sql: &'static str,
+    /// `None` for findings we only report, such as attempt history nobody should lose silently.
+    repair_sql: Option<&'static str>,
+}
+
+const ORPHAN_CHECKS: &[OrphanCheck] = &[
+    OrphanCheck {
+        name: "Attempts for unknown problems",
+        count_sql: "SELECT count(*) FROM attempts WHERE problem_id NOT IN (SELECT id FROM problems)
+                    AND problem_id NOT IN (SELECT id FROM alternatives)",
+        repair_sql: None,
+    },
+    OrphanCheck {
+        name: "Skill state for deleted skills",
+        count_sql: "SELECT count(*) FROM skill_state WHERE skill_id NOT IN (SELECT id FROM skills)",
+        repair_sql: Some("DELETE FROM skill_state WHERE skill_id NOT IN (SELECT id FROM skills)"),
+    },
+    OrphanCheck {
+        name: "Skills without state",
+        count_sql: "SELECT count(*) FROM skills WHERE id NOT IN (SELECT skill_id FROM skill_state)",
+        repair_sql: Some("INSERT OR IGNORE INTO skill_state (skill_id) SELECT id FROM skills"),
+    },
+    OrphanCheck {
+        name: "Review state for unknown problems",
+        count_sql: "SELECT count(*) FROM problem_state WHERE problem_id NOT IN (SELECT id FROM problems)",
+        repair_sql: Some("DELETE FROM problem_state WHERE problem_id NOT IN (SELECT id FROM problems)"),
+    },
+    OrphanCheck {
+        name: "Skill mappings for missing problems or skills",
+        count_sql: "SELECT count(*) FROM problem_skills
+                    WHERE problem_id NOT IN (SELECT id FROM problems UNION SELECT id FROM alternatives)
+                    OR skill_id NOT IN (SELECT id FROM skills)",
+        repair_sql: Some(
+            "DELETE FROM problem_skills
+             WHERE problem_id NOT IN (SELECT id FROM problems UNION SELECT id FROM alternatives)
+             OR skill_id NOT IN (SELECT id FROM skills)",
+        ),
+    },
+    OrphanCheck {
+        name: "Prerequisites referencing missing skills",
+        count_sql: "SELECT count(*) FROM skill_prereqs
+                    WHERE skill_id NOT IN (SELECT id FROM skills) OR prereq_id NOT IN (SELECT id FROM skills)",
+        repair_sql: Some(
+            "DELETE FROM skill_prereqs
+             WHERE skill_id NOT IN (SELECT id FROM skills) OR prereq_id NOT IN (SELECT id FROM skills)",
+        ),
+    },
+    OrphanCheck {
+        name: "Track entries for missing tracks or problems",
+        count_sql: "SELECT count(*) FROM track_problems
+                    WHERE track_id NOT IN (SELECT id FROM tracks) OR problem_id NOT IN (SELECT id FROM problems)",
+        repair_sql: Some(
+            "DELETE FROM track_problems
+             WHERE track_id NOT IN (SELECT id FROM tracks) OR problem_id NOT IN (SELECT id FROM problems)",
+        ),
+    },
+    OrphanCheck {
+        name: "Alternatives whose parent is missing",
+        count_sql: "SELECT count(*) FROM alternatives WHERE parent_id NOT IN (SELECT id FROM problems)",
+        repair_sql: None,
+    },
+    OrphanCheck {
+        name: "Mistake tags for missing attempts",
+        count_sql: "SELECT count(*) FROM attempt_mistakes WHERE attempt_id NOT IN (SELECT id FROM attempts)
+                    OR mistake_type_id NOT IN (SELECT id FROM mistake_types)",
+        repair_sql: Some(
+            "DELETE FROM attempt_mistakes WHERE attempt_id NOT IN (SELECT id FROM attempts)
+             OR mistake_type_id NOT IN (SELECT id FROM mistake_types)",
+        ),
+    },
+    OrphanCheck {
+        name: "Retrospectives for missing attempts",
+        count_sql: "SELECT count(*) FROM retrospectives WHERE attempt_id NOT IN (SELECT id FROM attempts)",
+        repair_sql: Some("DELETE FROM retrospectives WHERE attempt_id NOT IN (SELECT id FROM attempts)"),
+    },
+];
+
+/// Runs `PRAGMA integrity_check` and looks for rows pointing at things that no longer exist.
+/// With `repair`, fixable findings are cleaned up; nothing is repaired on a corrupt file.
+pub fn check_health(conn: &Connection, repair: bool) -> Result<DbHealth> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let integrity_messages = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+    let integrity_ok = integrity_messages == ["ok"];
+
+    let mut issues = Vec::new();
+    for check in ORPHAN_CHECKS {
+        let count: i64 = conn.query_row(check.count_sql, [], |row| row.get(0))?;
+        if count == 0 {
+            continue;
+        }
+        let repaired = match check.repair_sql {
+            Some(sql) if repair && integrity_ok => {
+                conn.execute_batch(sql)?;
+                true
+            }
+            _ => false,
+        };
+        issues.push(HealthIssue {
+            check: check.name.to_string(),
+            count,
+            repaired,
+        });
+    }
+
+    Ok(DbHealth {
+        integrity_ok,
+        integrity_messages: if integrity_ok {
+            Vec::new()
+        } else {
+            integrity_messages
+        },
+        issues,
+    })
+}
+
+/// `ALTER TABLE ... ADD COLUMN` for databases created before the column existed.
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<()> {
+    let exists: bool = conn.query_row(
+        &format!(
+            "SELECT count(*) > 0 FROM pragma_table_info('{}') WHERE name = ?",
+            table
+        ),
+        [column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        debug!("init_db: Adding column {}.{}", table, column);
+        conn.execute_batch(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table, column, definition
+        ))?;
+    }
+    Ok(())
+}
+
+fn bundled_pack() -> JsonPack {
+    let data = include_str!("data/neetcode_150_alts.json");
+    serde_json::from_str(data).expect("Error parsing problems JSON")
+}
+
+/// Brings the bundled track up to the bundled pack's version if the database was seeded
+/// from an older one. Returns whether an upgrade ran.
+pub fn upgrade_bundled_pack(conn: &Connection) -> Result<bool> {
+    upgrade_bundled_track(conn, &bundled_pack())
+}
+
+/// Brings the bundled track up to `pack` if its version is newer than the one recorded:
+/// new problems and alternatives are added, and titles, difficulties, URLs and skills are
+/// corrected. Problems the pack dropped leave the track unless they were attempted. No
+/// problem, alternative or schedule is deleted, and a moved problem only loses a primary
+/// skill the bundled pack itself linked (see `bundled_skill_links`; databases seeded before
+/// it existed keep their old links). Returns whether an upgrade ran.
+pub fn upgrade_bundled_track(conn: &Connection, pack: &JsonPack) -> Result<bool> {
+    let repo = Repository::new(conn);
+    let recorded = repo.get_setting_or(SETTING_SEED_PACK_VERSION, 0i64)?;
+    if pack.version <= recorded {
+        return Ok(false);
+    }
+    // Secondary skills need problem_skills.weight
+    let latest_schema = MIGRATIONS.last().map_or(0, |m| m.version);
+    if schema_version(conn)? < latest_schema {
+        warn!(
+            "Pack v{} not applied to the bundled track: schema migrations are pending",
+            pack.version
+        );
+        return Ok(false);
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let ids: Vec<i64> = seed_pack(&tx, BUNDLED_TRACK_NAME, &pack.problems, true)?
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+
+    // A problem that moved to another category loses the old primary skill the pack linked.
+    // This runs before secondaries are linked, in case the old primary became one.
+    let stale = "problem_id = ?1
+         AND skill_id IN (SELECT skill_id FROM bundled_skill_links WHERE problem_id = ?1)
+         AND skill_id != (SELECT id FROM skills WHERE name = ?2)
+         AND EXISTS (SELECT 1 FROM skills WHERE name = ?2)";
+    let mut stale_primary = tx.prepare(&format!(
+        "DELETE FROM problem_skills WHERE {} AND weight >= ?3",
+        stale
+    ))?;
+    let mut stale_link = tx.prepare(&format!("DELETE FROM bundled_skill_links WHERE {}", stale))?;
+    for (p, id) in pack.problems.iter().zip(&ids) {
+        let category = p.category.trim();
+        stale_primary.execute(params![id, category, SKILL_WEIGHT_PRIMARY])?;
+        stale_link.execute(params![id, category])?;
+    }
+    drop((stale_primary, stale_link));
+    link_secondary_skills(&tx, &pack.problems, &ids)?;
+    record_bundled_links(&tx, &pack.problems, &ids)?;
+    seed_skill_names(&tx, &pack.skill_names)?;
+
+    let keep: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+    let unlinked = tx.execute(
+        &format!(
+            "DELETE FROM track_problems
+             WHERE track_id = (SELECT id FROM tracks WHERE name = ?1)
+             AND problem_id NOT IN ({})
+             AND NOT EXISTS (
+                SELECT 1 FROM attempts a
+                WHERE a.problem_id = track_problems.problem_id
+                OR a.problem_id IN (
+                    SELECT id FROM alternatives WHERE parent_id = track_problems.problem_id
+                )
+             )",
+            keep.join(",")
+        ),
+        [BUNDLED_TRACK_NAME],
+    )?;
+    Repository::new(&tx).set_setting(SETTING_SEED_PACK_VERSION, &pack.version.to_string())?;
+    tx.commit()?;
+    info!(
+        "Bundled track upgraded from v{} to v{}, {} dropped problems unlinked",
+        recorded, pack.version, unlinked
+    );
+    Ok(true)
+}
+
+/// Records which primary skill links the bundled pack made, after `seed_pack`.
+fn record_bundled_links(conn: &Connection, problems: &[JsonProblem], ids: &[i64]) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "INSERT OR IGNORE INTO bundled_skill_links (problem_id, skill_id)
+         SELECT ps.problem_id, ps.skill_id FROM problem_skills ps
+         JOIN skills s ON s.id = ps.skill_id
+         WHERE ps.problem_id = ?1 AND s.name = ?2",
+    )?;
+    for (p, id) in problems.iter().zip(ids) {
+        stmt.execute(params![id, p.category.trim()])?;
+    }
+    Ok(())
+}
+
+/// Stores a pack's translated skill names. Skills the database doesn't have are skipped.
+pub fn seed_skill_names(
+    conn: &Connection,
+    names: &BTreeMap<String, BTreeMap<String, String>>,
+) -> Result<()> {
+    let repo = Repository::new(conn);
+    for (skill, translations) in names {
+        let Some(skill_id) = repo.get_skill_id(skill.trim())? else {
+            continue;
+        };
+        for (locale, name) in translations {
+            repo.save_translation(TRANSLATION_KIND_SKILL, skill_id, locale, name, true)?;
+        }
+    }
+    Ok(())
+}
+
+/// Loads a pack of problems into the track `track_name`, creating it if needed. A problem
+/// whose slug is already known, e.g. from another pack, keeps its single `problems` row
+/// (and so its schedule and history) and is linked into this track as well; a pack id
+/// taken by a different problem gets a fresh one. Only the `bundled` pack may change rows
+/// that already exist (titles, URLs, skills, alternatives, translations); any other pack
+/// just adds. Returns the id each problem is stored under, in pack order, and whether this
+/// call created it.
+pub fn seed_pack(
+    conn: &Connection,
+    track_name: &str,
+    problems: &[JsonProblem],
+    bundled: bool,
+) -> Result<Vec<(i64, bool)>> {
+    conn.execute(
+        "INSERT OR IGNORE INTO tracks (name) VALUES (?)",
+        [track_name],
+    )?;
+    let track_id: i64 = conn.query_row(
+        "SELECT id FROM tracks WHERE name = ?",
+        [track_name],
+        |row| row.get(0),
+    )?;
+
+    let mut slug_stmt = conn.prepare("SELECT id FROM problems WHERE slug = ?")?;
+    let mut id_stmt = conn.prepare("SELECT EXISTS(SELECT 1 FROM problems WHERE id = ?)")?;
+    // An upsert rather than INSERT OR REPLACE: replacing deletes the row, which would
+    // cascade to its schedule and notes
+    let mut p_stmt = conn.prepare(
+        "INSERT INTO problems (id, slug, title, difficulty, url) VALUES (?, ?, ?, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET
+            slug = excluded.slug, title = excluded.title,
+            difficulty = excluded.difficulty, url = excluded.url",
+    )?;
+    let mut insert_stmt = conn.prepare(
+        "INSERT INTO problems (id, slug, title, difficulty, url) VALUES (?, ?, ?, ?, ?)",
+    )?;
+    let mut ps_stmt = conn.prepare("INSERT OR IGNORE INTO problem_skills (problem_id, skill_id) SELECT ?, id FROM skills WHERE name = ?")?;
+    let mut tp_stmt =
+        conn.prepare("INSERT OR IGNORE INTO track_problems (track_id, problem_id) VALUES (?, ?)")?;
+
+    // Statement for alternatives; an upsert for the same reason as problems
+    let mut alt_stmt = conn.prepare(
+        "INSERT INTO alternatives (id, parent_id, title, difficulty, url) VALUES (?, ?, ?, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET
+            parent_id = excluded.parent_id, title = excluded.title,
+            difficulty = excluded.difficulty, url = excluded.url",
+    )?;
+    let mut alt_insert_stmt = conn.prepare(
+        "INSERT OR IGNORE INTO alternatives (id, parent_id, title, difficulty, url)
+         VALUES (?, ?, ?, ?, ?)",
+    )?;
+
+    let repo = Repository::new(conn);
+    let mut shared = 0;
+    let mut skipped_alternatives = 0;
+    let mut ids = Vec::with_capacity(problems.len());
+    for p in problems {
+        let slug = p.title.to_lowercase().replace(" ", "-");
+        let known: Option<i64> = slug_stmt.query_row([&slug], |row| row.get(0)).optional()?;
+        let (problem_id, created) = match known {
+            Some(id) if id != p.id || !bundled => {
+                shared += 1;
+                (id, false)
+            }
+            None if id_stmt.query_row([p.id], |row| row.get(0))? => {
+                let difficulty = Difficulty::from_str(&p.difficulty).unwrap_or(Difficulty::Medium);
+                let id = repo.insert_custom_problem(&slug, &p.title, difficulty, Some(&p.url))?;
+                (id, true)
+            }
+            Some(_) => {
+                p_stmt.execute(params![p.id, slug, p.title, p.difficulty, p.url])?;
+                (p.id, false)
+            }
+            None => {
+                insert_stmt.execute(params![p.id, slug, p.title, p.difficulty, p.url])?;
+                (p.id, true)
+            }
+        };
+        tp_stmt.execute(params![track_id, problem_id])?;
+        ids.push((problem_id, created));
+        // Other packs may translate a known problem, but not retranslate it
+        for (locale, title) in &p.titles {
+            repo.save_translation(TRANSLATION_KIND_PROBLEM, problem_id, locale, title, bundled)?;
+        }
+        if !bundled && !created {
+            continue;
+        }
+        ps_stmt.execute(params![problem_id, p.category.trim()])?;
+
+        // Insert Alternatives. Other packs never take over an id already in use.
+        for alt in &p.alternatives {
+            let alt_params = params![alt.id, problem_id, alt.title, alt.difficulty, alt.url];
+            if bundled {
+                alt_stmt.execute(alt_params)?;
+            } else if alt_insert_stmt.execute(alt_params)? == 0 {
+                skipped_alternatives += 1;
+                continue;
+            }
+            for (locale, title) in &alt.titles {
+                repo.save_translation(
+                    TRANSLATION_KIND_ALTERNATIVE,
+                    alt.id,
+                    locale,
+                    title,
+                    bundled,
+                )?;
+            }
+        }
+    }
+    if shared > 0 {
+        debug!(
+            "seed_pack: {} problems of '{}' already known, linked into the track",
+            shared, track_name
+        );
+    }
+    if skipped_alternatives > 0 {
+        warn!(
+            "seed_pack: {} alternatives of '{}' skipped, their ids are taken",
+            skipped_alternatives, track_name
+        );
+    }
+    Ok(ids)
+}
+
 fn seed_data(conn: &Connection) -> Result<()> {
     // 1. Skills
     let skills = vec![
@@ -134,41 +1573,17 @@ fn seed_data(conn: &Connection) -> Result<()> {
         stmt.execute(params![child, parent])?;
     }
 
-    // 3. Tracks
-    conn.execute(
-        "INSERT OR IGNORE INTO tracks (name) VALUES ('NeetCode 150')",
-        [],
-    )?;
-
-    // 4. Problems & Alternatives
-    let data = include_str!("data/neetcode_150_alts.json");
-    let problems: Vec<JsonProblem> =
-        serde_json::from_str(data).expect("Error parsing problems JSON");
-
-    let mut p_stmt = conn.prepare(
-        "INSERT OR REPLACE INTO problems (id, slug, title, difficulty, url) VALUES (?, ?, ?, ?, ?)",
-    )?;
-    let mut ps_stmt = conn.prepare("INSERT OR REPLACE INTO problem_skills (problem_id, skill_id) SELECT ?, id FROM skills WHERE name = ?")?;
-    let mut tp_stmt =
-        conn.prepare("INSERT OR REPLACE INTO track_problems (track_id, problem_id) VALUES (1, ?)")?;
+    // 3. Tracks, Problems & Alternatives
+    let pack = bundled_pack();
+    let ids: Vec<i64> = seed_pack(conn, BUNDLED_TRACK_NAME, &pack.problems, true)?
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    record_bundled_links(conn, &pack.problems, &ids)?;
+    seed_skill_names(conn, &pack.skill_names)?;
+    Repository::new(conn).set_setting(SETTING_SEED_PACK_VERSION, &pack.version.to_string())?;
 
-    // Statement for alternatives
-    let mut alt_stmt = conn.prepare(
-        "INSERT OR REPLACE INTO alternatives (id, parent_id, title, difficulty, url) VALUES (?, ?, ?, ?, ?)"
-    )?;
-
-    for p in problems {
-        let slug = p.title.to_lowercase().replace(" ", "-");
-        p_stmt.execute(params![p.id, slug, p.title, p.difficulty, p.url])?;
-        ps_stmt.execute(params![p.id, p.category])?;
-        tp_stmt.execute(params![p.id])?;
-
-        // Insert Alternatives
-        for alt in p.alternatives {
-            alt_stmt.execute(params![alt.id, p.id, alt.title, alt.difficulty, alt.url])?;
-        }
-    }
-    // 5. Init Skill State
+    // 4. Init Skill State
     conn.execute(
         "INSERT OR IGNORE INTO skill_state (skill_id) SELECT id FROM skills",
         [],
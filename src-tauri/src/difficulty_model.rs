@@ -0,0 +1,128 @@
+// src/difficulty_model.rs
+
+use crate::constants::*;
+use crate::models::{Difficulty, DifficultyFit, DifficultyModel};
+use crate::pedagogy;
+use crate::repository::Repository;
+use log::info;
+use rusqlite::Connection;
+
+const DIFFICULTIES: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+/// Refits the multipliers if the last fit is older than `DIFFICULTY_FIT_INTERVAL_SECONDS`.
+/// Returns whether it refit.
+pub fn refit_if_stale(conn: &Connection, now: i64) -> Result<bool, String> {
+    let (_, fitted_at) = Repository::new(conn)
+        .get_difficulty_fits()
+        .map_err(|e| e.to_string())?;
+    if fitted_at.is_some_and(|ts| now - ts < DIFFICULTY_FIT_INTERVAL_SECONDS) {
+        return Ok(false);
+    }
+    fit(conn, now)?;
+    Ok(true)
+}
+
+/// Fits each difficulty's mastery multiplier to the minutes the user spends per clean
+/// solve: the harder a difficulty is for them, the more credit solving it earns. The
+/// multipliers keep the defaults' average, so only the split between difficulties is
+/// learned, and each is pulled toward its default by `DIFFICULTY_FIT_PRIOR_ATTEMPTS`.
+pub fn fit(conn: &Connection, now: i64) -> Result<DifficultyModel, String> {
+    let repo = Repository::new(conn);
+    let inputs = repo
+        .get_difficulty_fit_inputs()
+        .map_err(|e| e.to_string())?;
+
+    // Minutes per clean solve, with the solve rate smoothed so all-failures stays finite
+    let effort = |attempts: i64, failures: i64, minutes: f64| {
+        let solve_rate = (attempts - failures + 1) as f64 / (attempts + 2) as f64;
+        minutes / attempts as f64 / solve_rate
+    };
+    let fitted = inputs.len() as f64;
+    let mean_default = inputs
+        .iter()
+        .map(|i| pedagogy::difficulty_multiplier(i.0))
+        .sum::<f64>()
+        / fitted;
+    let mean_effort = inputs
+        .iter()
+        .map(|&(_, attempts, failures, minutes)| effort(attempts, failures, minutes))
+        .sum::<f64>()
+        / fitted;
+    // Attempts logged with no time leave nothing to compare; each difficulty then counts
+    // as average effort rather than dividing by zero
+    let relative_effort = |effort: f64| {
+        if mean_effort.is_finite() && mean_effort > 0.0 {
+            effort / mean_effort
+        } else {
+            1.0
+        }
+    };
+
+    let fits: Vec<DifficultyFit> = DIFFICULTIES
+        .iter()
+        .map(|&difficulty| {
+            let Some(&(_, attempts, failures, minutes)) = inputs.iter().find(|i| i.0 == difficulty)
+            else {
+                return unfitted(difficulty);
+            };
+            let default_multiplier = pedagogy::difficulty_multiplier(difficulty);
+            let learned = relative_effort(effort(attempts, failures, minutes)) * mean_default;
+            let n = attempts as f64;
+            let multiplier = (n * learned + DIFFICULTY_FIT_PRIOR_ATTEMPTS * default_multiplier)
+                / (n + DIFFICULTY_FIT_PRIOR_ATTEMPTS);
+            DifficultyFit {
+                difficulty,
+                default_multiplier,
+                multiplier: multiplier.clamp(DIFFICULTY_MULTIPLIER_MIN, DIFFICULTY_MULTIPLIER_MAX),
+                attempts,
+                fail_rate: Some(failures as f64 / n),
+                mean_minutes: Some(minutes / n),
+            }
+        })
+        .collect();
+
+    repo.save_difficulty_fits(&fits, now)
+        .map_err(|e| e.to_string())?;
+    info!(
+        "Difficulty multipliers refit: {}",
+        fits.iter()
+            .map(|f| format!("{} {:.2}", f.difficulty.as_str(), f.multiplier))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    Ok(DifficultyModel {
+        fitted_at: Some(now),
+        fits,
+    })
+}
+
+/// The multipliers in use. Before the first fit every difficulty shows its default.
+pub fn get_model(conn: &Connection) -> Result<DifficultyModel, String> {
+    let (stored, fitted_at) = Repository::new(conn)
+        .get_difficulty_fits()
+        .map_err(|e| e.to_string())?;
+    let fits = DIFFICULTIES
+        .iter()
+        .map(|&difficulty| {
+            stored
+                .iter()
+                .find(|f| f.difficulty == difficulty)
+                .cloned()
+                .unwrap_or_else(|| unfitted(difficulty))
+        })
+        .collect();
+    Ok(DifficultyModel { fitted_at, fits })
+}
+
+/// A difficulty with no attempts to fit: it keeps its default.
+fn unfitted(difficulty: Difficulty) -> DifficultyFit {
+    let default_multiplier = pedagogy::difficulty_multiplier(difficulty);
+    DifficultyFit {
+        difficulty,
+        default_multiplier,
+        multiplier: default_multiplier,
+        attempts: 0,
+        fail_rate: None,
+        mean_minutes: None,
+    }
+}
@@ -0,0 +1,84 @@
+// src/events.rs
+
+use crate::constants::*;
+use crate::models::{SkillUnlocked, Streak};
+use crate::pedagogy;
+use crate::reports;
+use crate::repository::Repository;
+use chrono::Utc;
+use log::warn;
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashSet;
+use tauri::{AppHandle, Emitter};
+
+/// The slice of state the UI mirrors live.
+pub struct Snapshot {
+    pub reviews_due: i64,
+    pub unlocked: HashSet<i64>,
+    pub streak: Streak,
+}
+
+impl Snapshot {
+    pub fn capture(conn: &Connection) -> Result<Self, String> {
+        let now = Utc::now().timestamp();
+        Ok(Snapshot {
+            reviews_due: Repository::new(conn)
+                .count_reviews_due_by(now)
+                .map_err(|e| e.to_string())?,
+            unlocked: pedagogy::get_unlocked_skills(&Repository::new(conn))?
+                .into_iter()
+                .collect(),
+            streak: reports::get_streak(conn)?,
+        })
+    }
+}
+
+/// Runs a state-changing operation and emits an event for everything it changed, so the
+/// frontend can update without polling. Event failures are logged, never returned.
+pub fn emitting_changes<T>(
+    app: &AppHandle,
+    conn: &Connection,
+    op: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    let before = Snapshot::capture(conn)?;
+    let result = op()?;
+    match Snapshot::capture(conn) {
+        Ok(after) => emit_diff(app, conn, &before, &after),
+        Err(e) => warn!("Could not compute change events: {}", e),
+    }
+    Ok(result)
+}
+
+pub fn emit_diff(app: &AppHandle, conn: &Connection, before: &Snapshot, after: &Snapshot) {
+    if before.reviews_due != after.reviews_due {
+        emit(
+            app,
+            EVENT_REVIEW_DUE_COUNT_CHANGED,
+            json!({ "count": after.reviews_due }),
+        );
+    }
+    if before.streak != after.streak {
+        emit(app, EVENT_STREAK_UPDATED, after.streak);
+    }
+    let mut newly_unlocked: Vec<i64> = after
+        .unlocked
+        .difference(&before.unlocked)
+        .copied()
+        .collect();
+    if !newly_unlocked.is_empty() {
+        newly_unlocked.sort();
+        let names = Repository::new(conn).get_skill_names().unwrap_or_default();
+        for skill_id in newly_unlocked {
+            let name = names.get(&skill_id).cloned().unwrap_or_default();
+            emit(app, EVENT_SKILL_UNLOCKED, SkillUnlocked { skill_id, name });
+        }
+    }
+}
+
+fn emit<S: Serialize + Clone>(app: &AppHandle, event: &str, payload: S) {
+    if let Err(e) = app.emit(event, payload) {
+        warn!("Failed to emit {}: {}", event, e);
+    }
+}
@@ -0,0 +1,150 @@
+// src/exam_cram.rs
+
+use crate::constants::*;
+use crate::models::{ExamCram, ProblemView};
+use crate::pedagogy;
+use crate::plan;
+use crate::repository::Repository;
+use chrono::NaiveDate;
+use log::info;
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+use std::collections::HashSet;
+
+/// Switches the queue to exam cram until the end of `until`: weak skills are covered
+/// round-robin and due dates are ignored. Repetition state is snapshotted so that
+/// `stop_exam_cram` can undo what cramming does to the long-term schedule.
+pub fn start_exam_cram(conn: &Connection, until: &str, now: i64) -> Result<ExamCram, String> {
+    let until_date = NaiveDate::parse_from_str(until.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("Invalid date '{}': {}", until, e))?;
+    let days_left = (until_date - plan::local_date(now)).num_days();
+    if days_left < 0 {
+        return Err(format!("Cram end date {} is in the past", until_date));
+    }
+    if days_left >= EXAM_CRAM_MAX_DAYS {
+        return Err(format!(
+            "An exam cram can last at most {} days",
+            EXAM_CRAM_MAX_DAYS
+        ));
+    }
+    expire_exam_cram(conn, now)?;
+    if Repository::new(conn)
+        .get_exam_cram()
+        .map_err(|e| e.to_string())?
+        .is_some()
+    {
+        return Err("An exam cram is already running".to_string());
+    }
+
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    Repository::new(&tx)
+        .start_exam_cram(&until_date.to_string(), now)
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    info!("Exam cram started until {}", until_date);
+    Ok(ExamCram {
+        until: until_date.to_string(),
+        started_at: now,
+        days_left,
+    })
+}
+
+/// Ends the cram and restores the repetition state of every problem tracked before it.
+/// Mastery and attempt history keep what was practiced.
+pub fn stop_exam_cram(conn: &Connection) -> Result<(), String> {
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    Repository::new(&tx)
+        .end_exam_cram()
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    info!("Exam cram ended; repetition state restored");
+    Ok(())
+}
+
+/// Stops a cram whose last day has passed, for the scheduler tick. Returns whether one was
+/// stopped.
+pub fn expire_exam_cram(conn: &Connection, now: i64) -> Result<bool, String> {
+    let Some((until, _)) = Repository::new(conn)
+        .get_exam_cram()
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(false);
+    };
+    if days_left(&until, now) >= 0 {
+        return Ok(false);
+    }
+    stop_exam_cram(conn)?;
+    Ok(true)
+}
+
+/// The running cram. One whose last day has passed reads as none; `expire_exam_cram`
+/// ends it.
+pub fn get_exam_cram(conn: &Connection, now: i64) -> Result<Option<ExamCram>, String> {
+    let Some((until, started_at)) = Repository::new(conn)
+        .get_exam_cram()
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(None);
+    };
+    let days_left = days_left(&until, now);
+    if days_left < 0 {
+        return Ok(None);
+    }
+    Ok(Some(ExamCram {
+        until,
+        started_at,
+        days_left,
+    }))
+}
+
+/// Days from today to the cram's last day; unreadable dates count as past.
+fn days_left(until: &str, now: i64) -> i64 {
+    NaiveDate::parse_from_str(until, "%Y-%m-%d")
+        .map(|d| (d - plan::local_date(now)).num_days())
+        .unwrap_or(-1)
+}
+
+/// Up to `n` problems spread over the unlocked skills, weakest first: one per skill per
+/// round, skipping concepts already practiced during the cram.
+pub fn next_problems(
+    conn: &Connection,
+    cram: &ExamCram,
+    n: usize,
+) -> Result<Vec<ProblemView>, String> {
+    let repo = Repository::new(conn);
+    let track_id = 1;
+    let unlocked: HashSet<i64> = pedagogy::get_unlocked_skills(&repo)?.into_iter().collect();
+    let mut skills = repo.get_all_skill_states().map_err(|e| e.to_string())?;
+    skills.retain(|s| unlocked.contains(&s.skill_id));
+    skills.sort_by(|a, b| a.mastery.total_cmp(&b.mastery));
+
+    let mut per_skill = Vec::new();
+    for skill in &skills {
+        let problems = repo
+            .find_exam_cram_problems(track_id, skill.skill_id, cram.started_at, n)
+            .map_err(|e| e.to_string())?;
+        per_skill.push(problems.into_iter());
+    }
+
+    let mut queue = Vec::new();
+    let mut seen = HashSet::new();
+    let mut progressed = true;
+    while queue.len() < n && progressed {
+        progressed = false;
+        for problems in per_skill.iter_mut() {
+            if queue.len() >= n {
+                break;
+            }
+            // Problems shared between skills count once; take the skill's next one instead
+            for p in problems.by_ref() {
+                if seen.insert(p.id) {
+                    queue.push(p);
+                    progressed = true;
+                    break;
+                }
+            }
+        }
+    }
+    Ok(queue)
+}
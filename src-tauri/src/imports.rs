@@ -0,0 +1,448 @@
+// src/imports.rs
+
+use crate::audit;
+use crate::constants::*;
+use crate::database;
+use crate::models::{
+    AnkiImportSummary, CsvImportSummary, Difficulty, JsonPackFile, JsonProblem, PackLoad,
+    ProblemRepetitionState,
+};
+use crate::repository::Repository;
+use chrono::Utc;
+use log::{info, warn};
+use rusqlite::{Connection, OpenFlags};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+// --- Anki ---
+
+/// Imports scheduling state from an exported Anki deck (`.apkg`). Cards are matched to
+/// problems by LeetCode URL, slug or title; problems that already have LeetGraph history
+/// are left alone.
+pub fn import_anki(conn: &Connection, path: &str) -> Result<AnkiImportSummary, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Not an .apkg file: {}", e))?;
+
+    // Newer Anki versions also ship a zstd-compressed `collection.anki21b`, but keep a
+    // legacy copy alongside it unless "support older Anki versions" was unticked.
+    let entry = ["collection.anki21", "collection.anki2"]
+        .into_iter()
+        .find(|name| archive.by_name(name).is_ok())
+        .ok_or("Deck has no legacy collection; re-export it with 'Support older Anki versions' enabled")?;
+
+    let tmp_path = std::env::temp_dir().join(format!("leetgraph-anki-{}.db", std::process::id()));
+    {
+        let mut src = archive.by_name(entry).map_err(|e| e.to_string())?;
+        let mut dst = File::create(&tmp_path).map_err(|e| e.to_string())?;
+        io::copy(&mut src, &mut dst).map_err(|e| format!("Failed to extract deck: {}", e))?;
+    }
+    let cards = read_anki_cards(&tmp_path);
+    let _ = fs::remove_file(&tmp_path);
+    let (created_at, cards) =
+        cards.map_err(|e| format!("Failed to read Anki collection: {}", e))?;
+
+    let lookup = build_title_lookup(conn)?;
+    let now = Utc::now().timestamp();
+
+    // Several cards can point at one problem; the longest interval wins
+    let mut best: HashMap<i64, ProblemRepetitionState> = HashMap::new();
+    let mut summary = AnkiImportSummary::default();
+    for card in cards {
+        let Some(problem_id) = match_note(&card.fields, &lookup) else {
+            summary
+                .unmatched
+                .push(strip_html(card.fields.first().map_or("", |f| f.as_str())));
+            continue;
+        };
+        let interval_days = if card.ivl < 0 {
+            // Negative intervals are learning steps in seconds
+            -card.ivl as f64 / DAY_SECONDS as f64
+        } else {
+            card.ivl as f64
+        }
+        .clamp(INTERVAL_MIN, INTERVAL_MAX);
+        let ease_factor = if card.factor > 0 {
+            (card.factor as f64 / 1000.0).clamp(EASE_FACTOR_MIN, EASE_FACTOR_MAX)
+        } else {
+            EASE_FACTOR_DEFAULT
+        };
+        // Review cards store `due` as a day number relative to collection creation
+        let next_review_ts = if card.card_type == 2 {
+            created_at + card.due * DAY_SECONDS
+        } else {
+            now + (interval_days * DAY_SECONDS as f64) as i64
+        };
+
+        let state = ProblemRepetitionState {
+            problem_id,
+            ease_factor,
+            interval_days,
+            next_review_ts,
+        };
+        match best.get(&problem_id) {
+            Some(existing) if existing.interval_days >= interval_days => {}
+            _ => {
+                best.insert(problem_id, state);
+            }
+        }
+    }
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    audit::with_cause(&tx, AUDIT_CAUSE_IMPORT, None, || {
+        for state in best.values() {
+            if Repository::new(&tx)
+                .has_problem_state(state.problem_id)
+                .map_err(|e| e.to_string())?
+            {
+                summary.skipped_existing += 1;
+                continue;
+            }
+            Repository::new(&tx)
+                .save_problem_repetition_state(state)
+                .map_err(|e| e.to_string())?;
+            summary.imported += 1;
+        }
+        Ok(())
+    })?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    info!(
+        "Anki import: {} imported, {} skipped, {} unmatched",
+        summary.imported,
+        summary.skipped_existing,
+        summary.unmatched.len()
+    );
+    Ok(summary)
+}
+
+// --- CSV Problem Lists ---
+
+/// Creates a new track from a CSV of `title,url,difficulty,category` rows (header required,
+/// any column order). Rows matching a known problem reuse it; the rest become custom
+/// problems, and unknown categories become new skills. `category` may list several skills
+/// separated by `;`: the first is the primary skill, the rest are secondary.
+pub fn import_problems_csv(
+    conn: &Connection,
+    path: &str,
+    track_name: &str,
+) -> Result<CsvImportSummary, String> {
+    let track_name = track_name.trim();
+    if track_name.is_empty() {
+        return Err("Track name cannot be empty".to_string());
+    }
+    if Repository::new(conn)
+        .get_track_id(track_name)
+        .map_err(|e| e.to_string())?
+        .is_some()
+    {
+        return Err(format!("Track '{}' already exists", track_name));
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let title_col = column("title").ok_or("CSV needs a 'title' column")?;
+    let (url_col, difficulty_col, category_col) =
+        (column("url"), column("difficulty"), column("category"));
+
+    let mut lookup = build_title_lookup(conn)?;
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let track_id = Repository::new(&tx)
+        .create_track(track_name)
+        .map_err(|e| e.to_string())?;
+    let mut summary = CsvImportSummary {
+        track_id,
+        ..Default::default()
+    };
+
+    for (i, record) in reader.records().enumerate() {
+        // Line numbers as a spreadsheet shows them (header is line 1)
+        let record = record.map_err(|e| format!("Line {}: {}", i + 2, e))?;
+        let get = |col: Option<usize>| col.and_then(|c| record.get(c)).filter(|s| !s.is_empty());
+        let Some(title) = get(Some(title_col)) else {
+            continue;
+        };
+        let url = get(url_col);
+        let slug = url
+            .and_then(slug_from_url)
+            .unwrap_or_else(|| slugify(title));
+
+        let existing = [slug.as_str(), title]
+            .iter()
+            .find_map(|k| lookup.get(&normalize_title(k)).copied());
+        let problem_id = match existing {
+            Some(id) => {
+                summary.reused_problems += 1;
+                id
+            }
+            None => {
+                let difficulty = match get(difficulty_col).map(str::to_ascii_lowercase).as_deref() {
+                    Some("easy") => Difficulty::Easy,
+                    Some("hard") => Difficulty::Hard,
+                    _ => Difficulty::Medium,
+                };
+                let id = Repository::new(&tx)
+                    .insert_custom_problem(&slug, title, difficulty, url)
+                    .map_err(|e| format!("Line {}: {}", i + 2, e))?;
+                let categories = get(category_col).unwrap_or_default().split(';');
+                for (n, category) in categories
+                    .map(str::trim)
+                    .filter(|c| !c.is_empty())
+                    .enumerate()
+                {
+                    let (skill_id, created) = Repository::new(&tx)
+                        .get_or_create_skill(category)
+                        .map_err(|e| e.to_string())?;
+                    if created {
+                        summary.created_skills.push(category.to_string());
+                    }
+                    let weight = if n == 0 {
+                        SKILL_WEIGHT_PRIMARY
+                    } else {
+                        SKILL_WEIGHT_SECONDARY
+                    };
+                    Repository::new(&tx)
+                        .add_problem_skill(id, skill_id, weight)
+                        .map_err(|e| e.to_string())?;
+                }
+                // Later rows in the same file may repeat this problem
+                lookup.insert(normalize_title(&slug), id);
+                lookup.insert(normalize_title(title), id);
+                summary.created_problems += 1;
+                id
+            }
+        };
+        Repository::new(&tx)
+            .add_track_problem(track_id, problem_id)
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    info!(
+        "CSV import into '{}': {} new problems, {} reused, {} new skills",
+        track_name,
+        summary.created_problems,
+        summary.reused_problems,
+        summary.created_skills.len()
+    );
+    Ok(summary)
+}
+
+struct AnkiCard {
+    fields: Vec<String>,
+    ivl: i64,
+    factor: i64,
+    due: i64,
+    card_type: i64,
+}
+
+/// Returns the collection creation time and every card that has left the "new" queue.
+fn read_anki_cards(path: &std::path::Path) -> rusqlite::Result<(i64, Vec<AnkiCard>)> {
+    let anki = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let created_at: i64 = anki.query_row("SELECT crt FROM col", [], |row| row.get(0))?;
+    let mut stmt = anki.prepare(
+        "SELECT n.flds, c.ivl, c.factor, c.due, c.type
+         FROM cards c JOIN notes n ON n.id = c.nid
+         WHERE c.type > 0",
+    )?;
+    let cards = stmt
+        .query_map([], |row| {
+            let flds: String = row.get(0)?;
+            Ok(AnkiCard {
+                fields: flds.split('\u{1f}').map(str::to_string).collect(),
+                ivl: row.get(1)?,
+                factor: row.get(2)?,
+                due: row.get(3)?,
+                card_type: row.get(4)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok((created_at, cards))
+}
+
+/// A URL anywhere in the note is the strongest signal; otherwise any field may be the title.
+fn match_note(fields: &[String], lookup: &HashMap<String, i64>) -> Option<i64> {
+    fields
+        .iter()
+        .filter_map(|f| slug_from_url(f))
+        .chain(fields.iter().map(|f| strip_html(f)))
+        .find_map(|key| lookup.get(&normalize_title(&key)).copied())
+}
+
+// --- Problem Packs ---
+
+/// Loads every `*.json` file in `dir` into a track named after the file, in file name
+/// order. Files use the bundled pack's `JsonProblem` schema, with or without the versioned
+/// wrapper, and unknown categories become new skills. Loading only adds, so it runs at
+/// every start: problems already in the catalog, from the bundled pack or an earlier load,
+/// are linked into the track but never changed, and new ones are added. A file that fails
+/// is rolled back and reported without stopping the others.
+pub fn load_packs(conn: &Connection, dir: &Path) -> Result<Vec<PackLoad>, String> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+
+    let mut loads = Vec::new();
+    for path in files {
+        let track = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().trim().to_string())
+            .unwrap_or_default();
+        let mut load = PackLoad {
+            file: path
+                .file_name()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            track: track.clone(),
+            problems: 0,
+            created_skills: Vec::new(),
+            error: None,
+        };
+        if let Err(e) = load_pack(conn, &path, &mut load) {
+            warn!("Pack {} not loaded: {}", load.file, e);
+            load.problems = 0;
+            load.created_skills.clear();
+            load.error = Some(e);
+        } else {
+            info!(
+                "Pack {} loaded into '{}': {} problems",
+                load.file, track, load.problems
+            );
+        }
+        loads.push(load);
+    }
+    Ok(loads)
+}
+
+fn load_pack(conn: &Connection, path: &Path, load: &mut PackLoad) -> Result<(), String> {
+    if load.track.is_empty() || load.track == BUNDLED_TRACK_NAME {
+        return Err(format!("'{}' cannot be used as a track name", load.track));
+    }
+    let data = fs::read_to_string(path).map_err(|e| format!("Failed to read: {}", e))?;
+    let (problems, skill_names) =
+        match serde_json::from_str(&data).map_err(|e| format!("Invalid pack: {}", e))? {
+            JsonPackFile::Versioned(pack) => (pack.problems, pack.skill_names),
+            JsonPackFile::Problems(problems) => (problems, BTreeMap::new()),
+        };
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let ids = store_pack(&tx, &load.track, &problems, &mut load.created_skills)?;
+    database::seed_skill_names(&tx, &skill_names).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    load.problems = ids.len();
+    Ok(())
+}
+
+/// Stores `problems` into `track` like the bundled pack, first creating any skill they
+/// name that does not exist yet (added to `created_skills`). Returns the id each problem
+/// is stored under, in order. Callers own the transaction.
+pub fn store_pack(
+    conn: &Connection,
+    track: &str,
+    problems: &[JsonProblem],
+    created_skills: &mut Vec<String>,
+) -> Result<Vec<i64>, String> {
+    let repo = Repository::new(conn);
+    for p in problems {
+        let categories =
+            std::iter::once(&p.category).chain(p.secondary_categories.iter().map(|s| &s.category));
+        for category in categories.filter(|c| !c.trim().is_empty()) {
+            let (_, created) = repo
+                .get_or_create_skill(category.trim())
+                .map_err(|e| e.to_string())?;
+            if created {
+                created_skills.push(category.trim().to_string());
+            }
+        }
+    }
+    let stored = database::seed_pack(conn, track, problems, false).map_err(|e| e.to_string())?;
+    // Problems the catalog already had keep their skills
+    for (p, &(id, created)) in problems.iter().zip(&stored) {
+        if created {
+            database::link_secondary_skills(conn, std::slice::from_ref(p), &[id])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(stored.into_iter().map(|(id, _)| id).collect())
+}
+
+// --- Matching Helpers ---
+
+/// Maps normalized titles and slugs to the concept (parent problem) they belong to.
+fn build_title_lookup(conn: &Connection) -> Result<HashMap<String, i64>, String> {
+    let rows = Repository::new(conn)
+        .get_problem_lookup()
+        .map_err(|e| e.to_string())?;
+    let mut lookup = HashMap::new();
+    for (id, title, slug_or_url) in rows {
+        let slug = slug_or_url.map(|s| slug_from_url(&s).unwrap_or(s));
+        for key in std::iter::once(title).chain(slug) {
+            lookup.entry(normalize_title(&key)).or_insert(id);
+        }
+    }
+    lookup.remove("");
+    Ok(lookup)
+}
+
+/// "Two Sum", "two-sum" and "1. Two Sum" all normalize to "twosum".
+pub fn normalize_title(s: &str) -> String {
+    let s = s.trim();
+    let s = match s.split_once(". ") {
+        Some((num, rest)) if num.chars().all(|c| c.is_ascii_digit()) => rest,
+        _ => s,
+    };
+    s.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Extracts `two-sum` from `https://leetcode.com/problems/two-sum/description/`.
+pub fn slug_from_url(s: &str) -> Option<String> {
+    let rest = &s[s.find("/problems/")? + "/problems/".len()..];
+    let slug: String = rest
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    (!slug.is_empty()).then_some(slug)
+}
+
+/// "Two Sum" -> "two-sum", matching how seeded slugs are derived.
+fn slugify(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn strip_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut in_tag = false;
+    for c in s.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
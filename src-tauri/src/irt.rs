@@ -0,0 +1,87 @@
+// src/irt.rs
+
+use crate::constants::*;
+use crate::models::{Difficulty, ProblemView};
+use crate::repository::Repository;
+use rusqlite::Connection;
+use std::collections::HashMap;
+
+/// Rasch (1PL) item difficulty for a problem.
+pub fn item_difficulty(difficulty: Difficulty) -> f64 {
+    match difficulty {
+        Difficulty::Easy => IRT_DIFFICULTY_EASY,
+        Difficulty::Medium => IRT_DIFFICULTY_MEDIUM,
+        Difficulty::Hard => IRT_DIFFICULTY_HARD,
+    }
+}
+
+/// Chance that someone of `ability` cleanly solves an item of difficulty `b`.
+pub fn success_probability(ability: f64, b: f64) -> f64 {
+    1.0 / (1.0 + (b - ability).exp())
+}
+
+/// MAP ability estimate from `(item difficulty, solved)` outcomes under a N(0, σ²) prior,
+/// by Newton's method. No outcomes gives the prior mean, 0.
+pub fn estimate_ability(outcomes: &[(f64, bool)]) -> f64 {
+    let mut theta = 0.0;
+    for _ in 0..IRT_NEWTON_ITERATIONS {
+        let mut gradient = -theta / IRT_PRIOR_VARIANCE;
+        let mut curvature = -1.0 / IRT_PRIOR_VARIANCE;
+        for &(b, solved) in outcomes {
+            let p = success_probability(theta, b);
+            gradient += if solved { 1.0 } else { 0.0 } - p;
+            curvature -= p * (1.0 - p);
+        }
+        let step = gradient / curvature;
+        theta -= step;
+        if step.abs() < 1e-6 {
+            break;
+        }
+    }
+    theta
+}
+
+/// Ability per skill from every recorded attempt. Skills never attempted are absent;
+/// treat them as 0.
+pub fn skill_abilities(conn: &Connection) -> Result<HashMap<i64, f64>, String> {
+    let mut by_skill: HashMap<i64, Vec<(f64, bool)>> = HashMap::new();
+    for (skill_id, difficulty, solved) in Repository::new(conn)
+        .get_skill_outcomes()
+        .map_err(|e| e.to_string())?
+    {
+        by_skill
+            .entry(skill_id)
+            .or_default()
+            .push((item_difficulty(difficulty), solved));
+    }
+    Ok(by_skill
+        .into_iter()
+        .map(|(skill_id, outcomes)| (skill_id, estimate_ability(&outcomes)))
+        .collect())
+}
+
+/// Predicted clean-solve chance for a problem: its skills' average ability against its
+/// difficulty.
+pub fn predicted_success(abilities: &HashMap<i64, f64>, problem: &ProblemView) -> f64 {
+    let ability = if problem.skill_ids.is_empty() {
+        0.0
+    } else {
+        problem
+            .skill_ids
+            .iter()
+            .map(|sid| abilities.get(sid).copied().unwrap_or(0.0))
+            .sum::<f64>()
+            / problem.skill_ids.len() as f64
+    };
+    success_probability(ability, item_difficulty(problem.difficulty))
+}
+
+/// Orders problems by how close their predicted success is to `IRT_TARGET_SUCCESS`.
+/// The sort is stable, so ties keep their incoming order.
+pub fn rank_by_target(abilities: &HashMap<i64, f64>, problems: &mut [ProblemView]) {
+    problems.sort_by(|a, b| {
+        let da = (predicted_success(abilities, a) - IRT_TARGET_SUCCESS).abs();
+        let db = (predicted_success(abilities, b) - IRT_TARGET_SUCCESS).abs();
+        da.total_cmp(&db)
+    });
+}
@@ -0,0 +1,258 @@
+// src/journal.rs
+
+use crate::attempt_timer;
+use crate::clock;
+use crate::constants::*;
+use crate::models::{AttemptLog, AttemptResult};
+use crate::pedagogy;
+use crate::repository::Repository;
+use chrono::Utc;
+use log::{error, info, warn};
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// One line of the journal: a submitted attempt, or a marker that it was committed.
+#[derive(Serialize, Deserialize)]
+struct JournalEntry {
+    id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    log: Option<AttemptLog>,
+    #[serde(default)]
+    done: bool,
+    /// Why a replay could not apply the entry; set on lines kept in the poisoned file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    poisoned: Option<String>,
+}
+
+/// The journal of the database at `db_path`, next to it, so each database only ever
+/// replays its own attempts.
+pub fn path_for(db_path: &Path) -> PathBuf {
+    let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+    name.push(JOURNAL_FILE_SUFFIX);
+    db_path.with_file_name(name)
+}
+
+fn append(path: &Path, entries: &[JournalEntry]) -> Result<(), String> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Could not open attempt journal: {}", e))?;
+    for entry in entries {
+        let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+    }
+    // The entries must be on disk before the attempts are processed
+    file.sync_data().map_err(|e| e.to_string())
+}
+
+/// Journals the attempt, then processes it in one transaction that also records the
+/// journal entry as applied, so a replay can never double-count it.
+pub fn record_attempt(
+    conn: &Connection,
+    path: &Path,
+    log: &AttemptLog,
+) -> Result<AttemptResult, String> {
+    let mut results = record_attempts(conn, path, std::slice::from_ref(log))?;
+    Ok(results.remove(0))
+}
+
+/// Batch form of `record_attempt`: all attempts commit together or not at all. They are
+/// applied oldest first (attempts without a timestamp count as now) so scheduling sees
+/// them in the order they happened; results come back in input order.
+pub fn record_attempts(
+    conn: &Connection,
+    path: &Path,
+    logs: &[AttemptLog],
+) -> Result<Vec<AttemptResult>, String> {
+    // Held at the latest recorded event if the system clock went backwards
+    let now = clock::now(conn)?;
+    let mut order: Vec<usize> = (0..logs.len()).collect();
+    order.sort_by_key(|&i| logs[i].timestamp.unwrap_or(now));
+
+    // Journaled in applied order, which is also the order replay uses
+    let stamp = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    let entries: Vec<JournalEntry> = order
+        .iter()
+        .map(|&i| {
+            // Pin the time so a replay after a crash doesn't record it as the restart time,
+            // and the timer's pauses of an attempt that just ended for the same reason
+            let mut log = logs[i].clone();
+            if log.timestamp.is_none() {
+                attempt_timer::pin_pauses(conn, &mut log, now)?;
+                log.timestamp = Some(now);
+            }
+            Ok(JournalEntry {
+                id: format!("{}-{}-{}", stamp, i, log.problem_id),
+                log: Some(log),
+                done: false,
+                poisoned: None,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+    append(path, &entries)?;
+
+    let result = apply(conn, &entries, now);
+    // Resolved either way: a failed attempt was reported to the user, not lost. Losing
+    // these markers is harmless since replay also checks journal_applied.
+    let markers: Vec<JournalEntry> = entries
+        .into_iter()
+        .map(|e| JournalEntry {
+            id: e.id,
+            log: None,
+            done: true,
+            poisoned: None,
+        })
+        .collect();
+    if let Err(e) = append(path, &markers) {
+        warn!("Could not mark journal entries done: {}", e);
+    }
+
+    let mut applied = result?.into_iter();
+    let mut results: Vec<Option<AttemptResult>> = logs.iter().map(|_| None).collect();
+    for &i in &order {
+        results[i] = applied.next();
+    }
+    Ok(results.into_iter().flatten().collect())
+}
+
+/// Processes journaled attempts in order, in a single transaction.
+fn apply(
+    conn: &Connection,
+    entries: &[JournalEntry],
+    now: i64,
+) -> Result<Vec<AttemptResult>, String> {
+    // IMMEDIATE takes the write lock before any change, keeping busy failures retryable
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let mut results = Vec::new();
+    for entry in entries {
+        let Some(log) = &entry.log else {
+            continue;
+        };
+        let result = pedagogy::process_attempt(&tx, log).map_err(|e| {
+            if entries.len() > 1 {
+                format!("Attempt on problem {}: {}", log.problem_id, e)
+            } else {
+                e
+            }
+        })?;
+        Repository::new(&tx)
+            .mark_journal_applied(&entry.id, now)
+            .map_err(|e| e.to_string())?;
+        results.push(result);
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(results)
+}
+
+/// Startup recovery: processes journaled attempts that never committed, then empties the
+/// journal. Unreadable lines (e.g. a write torn by the crash) are skipped. An attempt that
+/// fails to apply is poisoned: it moves to the journal's `JOURNAL_POISONED_SUFFIX` file
+/// with the error, so it neither blocks the others nor fails every later launch.
+/// Returns how many attempts were replayed.
+pub fn replay(conn: &Connection, path: &Path) -> Result<usize, String> {
+    let repo = Repository::new(conn);
+    let Ok(text) = fs::read_to_string(path) else {
+        return Ok(0);
+    };
+    let entries: Vec<JournalEntry> = text
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let mut replayed = 0;
+    let mut poisoned = Vec::new();
+    for entry in &entries {
+        let Some(log) = &entry.log else {
+            continue;
+        };
+        let marked_done = entries.iter().any(|e| e.done && e.id == entry.id);
+        if marked_done
+            || repo
+                .is_journal_applied(&entry.id)
+                .map_err(|e| e.to_string())?
+        {
+            continue;
+        }
+        info!("Replaying journaled attempt {}", entry.id);
+        let pending = JournalEntry {
+            id: entry.id.clone(),
+            log: Some(log.clone()),
+            done: false,
+            poisoned: None,
+        };
+        if let Err(e) = apply(conn, std::slice::from_ref(&pending), Utc::now().timestamp()) {
+            error!("Journaled attempt {} could not be applied: {}", entry.id, e);
+            poisoned.push(JournalEntry {
+                poisoned: Some(e),
+                ..pending
+            });
+            continue;
+        }
+        replayed += 1;
+    }
+    if !poisoned.is_empty() {
+        let mut poisoned_path = path.as_os_str().to_os_string();
+        poisoned_path.push(JOURNAL_POISONED_SUFFIX);
+        append(Path::new(&poisoned_path), &poisoned)?;
+    }
+    fs::write(path, "").map_err(|e| format!("Could not clear attempt journal: {}", e))?;
+    repo.prune_journal_applied(
+        Utc::now().timestamp() - JOURNAL_APPLIED_RETENTION_DAYS * DAY_SECONDS,
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(replayed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database;
+
+    fn attempt(problem_id: i64, timestamp: Option<i64>) -> AttemptLog {
+        AttemptLog {
+            problem_id,
+            time_minutes: 20.0,
+            solved: true,
+            read_solution: false,
+            revealed_skills: false,
+            mistakes: Vec::new(),
+            explanation: None,
+            attempt_key: None,
+            timestamp,
+            pause_count: 0,
+            paused_minutes: 0.0,
+        }
+    }
+
+    #[test]
+    fn live_attempt_is_not_dated_before_recorded_ones() {
+        let conn = Connection::open_in_memory().unwrap();
+        database::configure_connection(&conn, false).unwrap();
+        database::init_db(&conn).unwrap();
+        let path = std::env::temp_dir().join(format!("journal-clock-{}.jsonl", std::process::id()));
+        let problem_id: i64 = conn
+            .query_row("SELECT id FROM problems ORDER BY id LIMIT 1", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+
+        // As if the system clock had since gone back two minutes
+        let ahead = Utc::now().timestamp() + 120;
+        record_attempt(&conn, &path, &attempt(problem_id, Some(ahead))).unwrap();
+        record_attempt(&conn, &path, &attempt(problem_id, None)).unwrap();
+        fs::remove_file(&path).ok();
+
+        let latest: i64 = conn
+            .query_row(
+                "SELECT timestamp FROM attempts ORDER BY id DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(latest >= ahead);
+    }
+}
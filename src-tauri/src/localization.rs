@@ -0,0 +1,90 @@
+// src/localization.rs
+
+use crate::constants::*;
+use crate::models::{
+    ProblemListItem, ProblemView, QueueKind, ServedProblem, SkillNode, UnlockRequirements,
+};
+use crate::repository::Repository;
+use rusqlite::Connection;
+use std::collections::HashMap;
+
+/// Problem titles and skill names in the user's `locale` setting. A regional locale such as
+/// `pt-BR` falls back to plain `pt`, and anything untranslated keeps its English text.
+#[derive(Default)]
+pub struct Translations {
+    problems: HashMap<i64, String>,
+    alternatives: HashMap<i64, String>,
+    skills: HashMap<i64, String>,
+}
+
+impl Translations {
+    pub fn load(conn: &Connection) -> Result<Self, String> {
+        let repo = Repository::new(conn);
+        let locale = repo
+            .get_setting(SETTING_LOCALE)
+            .map_err(|e| e.to_string())?
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+            .unwrap_or_else(|| DEFAULT_LOCALE.to_string());
+        let language = locale.split(['-', '_']).next().unwrap_or_default();
+        if language.eq_ignore_ascii_case(DEFAULT_LOCALE)
+            || !repo.has_translations().map_err(|e| e.to_string())?
+        {
+            return Ok(Self::default());
+        }
+
+        let mut translations = Self::default();
+        for (kind, ref_id, text) in repo
+            .get_translations(&locale, language)
+            .map_err(|e| e.to_string())?
+        {
+            let map = match kind.as_str() {
+                TRANSLATION_KIND_SKILL => &mut translations.skills,
+                TRANSLATION_KIND_ALTERNATIVE => &mut translations.alternatives,
+                _ => &mut translations.problems,
+            };
+            map.insert(ref_id, text);
+        }
+        Ok(translations)
+    }
+
+    pub fn view(&self, p: &mut ProblemView) {
+        localize(self.titles(&p.queue), p.id, &mut p.title);
+        for (name, id) in p.skills.iter_mut().zip(&p.skill_ids) {
+            localize(&self.skills, *id, name);
+        }
+    }
+
+    pub fn item(&self, p: &mut ProblemListItem) {
+        localize(&self.problems, p.id, &mut p.title);
+    }
+
+    pub fn served(&self, s: &mut ServedProblem) {
+        localize(self.titles(&s.queue), s.problem_id, &mut s.title);
+    }
+
+    pub fn skill(&self, s: &mut SkillNode) {
+        localize(&self.skills, s.skill_id, &mut s.name);
+    }
+
+    pub fn requirements(&self, r: &mut UnlockRequirements) {
+        localize(&self.skills, r.skill_id, &mut r.skill);
+        for p in &mut r.prerequisites {
+            localize(&self.skills, p.skill_id, &mut p.skill);
+        }
+    }
+
+    /// Titles of whatever `queue` serves: alternatives or catalog problems.
+    fn titles(&self, queue: &QueueKind) -> &HashMap<i64, String> {
+        match queue {
+            QueueKind::AlternativeFor { .. } => &self.alternatives,
+            _ => &self.problems,
+        }
+    }
+}
+
+fn localize(map: &HashMap<i64, String>, id: i64, text: &mut String) {
+    if let Some(t) = map.get(&id) {
+        text.clone_from(t);
+    }
+}
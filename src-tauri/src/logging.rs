@@ -0,0 +1,114 @@
+// src/logging.rs
+
+use crate::constants::*;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static LOG_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Sends `env_logger` output to stderr and to a size-rotated file in `log_dir`.
+/// Falls back to stderr only if the file cannot be opened.
+pub fn init(log_dir: &Path) {
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug"));
+    let path = log_dir.join(LOG_FILE_NAME);
+    match fs::create_dir_all(log_dir).and_then(|_| RotatingFile::open(path.clone())) {
+        Ok(file) => {
+            builder.target(env_logger::Target::Pipe(Box::new(Tee(file))));
+            let _ = LOG_FILE.set(path);
+        }
+        Err(e) => eprintln!("File logging disabled ({:?}): {}", path, e),
+    }
+    builder.init();
+}
+
+/// The last `lines` lines logged, oldest first, reaching into the rotated file if needed.
+pub fn recent_lines(lines: usize) -> Result<Vec<String>, String> {
+    let path = LOG_FILE.get().ok_or("File logging is not active")?;
+    let mut collected: Vec<String> = Vec::new();
+    for file in [path.clone(), rotated_path(path, 1)] {
+        if collected.len() >= lines {
+            break;
+        }
+        let Ok(text) = fs::read_to_string(&file) else {
+            continue;
+        };
+        let needed = lines - collected.len();
+        let mut chunk: Vec<String> = text
+            .lines()
+            .rev()
+            .take(needed)
+            .map(str::to_string)
+            .collect();
+        chunk.reverse();
+        chunk.extend(collected);
+        collected = chunk;
+    }
+    Ok(collected)
+}
+
+/// `leetgraph.log` -> `leetgraph.log.1`
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(RotatingFile { path, file, size })
+    }
+
+    /// Shifts `.1` -> `.2` ... and starts a fresh file, dropping the oldest.
+    fn rotate(&mut self) -> io::Result<()> {
+        for n in (1..LOG_KEEP_FILES).rev() {
+            let from = rotated_path(&self.path, n);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.path, n + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        *self = RotatingFile::open(self.path.clone())?;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size + buf.len() as u64 > LOG_MAX_BYTES {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.size += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Keeps terminal output for `cargo tauri dev` while also writing the file.
+struct Tee(RotatingFile);
+
+impl Write for Tee {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let _ = io::stderr().write_all(buf);
+        self.0.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
@@ -2,18 +2,73 @@
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod assessment;
+mod attempt_timer;
+mod audit;
+mod bridge;
+mod catalog;
+mod clock;
 mod constants;
 mod database;
+mod difficulty_model;
+mod events;
+mod exam_cram;
+mod imports;
+mod irt;
+mod journal;
+mod localization;
+mod logging;
+#[cfg(test)]
+mod mock_repository;
 mod models;
+mod pdf_report;
 mod pedagogy;
+mod plan;
+mod remote_pack;
+mod replay;
+mod reports;
 mod repository;
+mod retention;
+mod scheduler;
+mod search;
+mod selection_rng;
+mod serve_log;
+mod simulation;
+mod study_window;
+mod sync;
+mod tags;
+mod telemetry;
+mod throttle;
+mod track_bundle;
+mod triage;
 
-use crate::models::{AppState, AttemptLog, ProblemView};
-use rusqlite::Connection;
+use crate::constants::{
+    BACKUP_DIR_NAME, LEGACY_JOURNAL_FILE_NAME, LOG_MAX_LINES, MAX_ATTEMPT_MINUTES,
+    MAX_BATCH_ATTEMPTS, PACKS_DIR_NAME, QUEUE_PREVIEW_MAX, SETTING_AUTO_MIGRATE,
+    SETTING_REMOTE_PACK_URL, SUBMIT_ATTEMPT_MIN_INTERVAL_MS, TELEMETRY_DEFAULT_DAYS,
+};
+use crate::localization::Translations;
+use crate::models::{
+    AnkiImportSummary, AppState, AssessmentAnswer, AssessmentResult, AssessmentSkill, AttemptDraft,
+    AttemptLog, AttemptResult, AttemptTimer, AuditEntry, BacklogPostpone, Board, CsvImportSummary,
+    DateRange, DbHealth, DbInfo, Difficulty, DifficultyModel, ExamCram, LastAttempt,
+    MaintenanceReport, MasteryEta, MigrationReport, MistakeStat, MistakeType, NotificationSchedule,
+    PackLoad, Page, Pagination, PlacementAnswer, PlacementProbe, ProblemPage, ProblemSort,
+    ProblemStats, ProblemStatus, ProblemView, ProfileCard, QueryResult, QuickLogOutcome,
+    RemotePackRefresh, ReplayReport, RestoreReport, ReviewQualityStats, SchedulerConfig, SearchHit,
+    ServeHistory, SessionSummary, SimulationParams, SimulationResult, SkillCooccurrence, SkillNode,
+    SkillProblem, SkipReason, SortSpec, StudyPlan, SuspendedProblem, SyncReport, TagCount,
+    TrackBundle, TrackComparison, TrackImport, TriageReport, UnlockRequirements, UsageStats,
+};
+use crate::repository::Repository;
+use rusqlite::{Connection, OpenFlags};
+use std::collections::HashMap;
 use std::fs;
-use tauri::{Manager, State};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
 
-use log::info;
+use log::{error, info, warn};
 
 #[tauri::command]
 fn open_external_url(url: String) -> Result<(), String> {
@@ -22,23 +77,1182 @@ fn open_external_url(url: String) -> Result<(), String> {
 
 #[tauri::command]
 fn get_next_problem(state: State<AppState>) -> Result<Option<ProblemView>, String> {
-    let conn = state.db.lock().unwrap();
-    pedagogy::get_next_problem(&conn)
+    telemetry::timed(&state, "get_next_problem", || {
+        state.next_problem.get_or_compute(|| {
+            state.with_db(|conn| {
+                let mut problem = pedagogy::get_next_problem(conn)?;
+                let translations = Translations::load(conn)?;
+                problem.iter_mut().for_each(|p| translations.view(p));
+                Ok(problem)
+            })
+        })
+    })
 }
 
 #[tauri::command]
-fn submit_attempt(state: State<AppState>, log: AttemptLog) -> Result<(), String> {
-    let conn = state.db.lock().unwrap();
-    pedagogy::process_attempt(&conn, &log)
+fn get_serve_history(
+    state: State<AppState>,
+    page: Option<Pagination>,
+) -> Result<ServeHistory, String> {
+    telemetry::timed(&state, "get_serve_history", || {
+        let now = chrono::Utc::now().timestamp();
+        state.with_db(|conn| {
+            let mut history = serve_log::get_history(conn, page.unwrap_or_default(), now)?;
+            let translations = Translations::load(conn)?;
+            history
+                .serves
+                .items
+                .iter_mut()
+                .for_each(|s| translations.served(s));
+            Ok(history)
+        })
+    })
 }
 
-fn main() {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("debug")).init();
+#[tauri::command]
+fn get_next_problems(state: State<AppState>, n: usize) -> Result<Vec<ProblemView>, String> {
+    telemetry::timed(&state, "get_next_problems", || {
+        state.with_db(|conn| {
+            let mut problems = pedagogy::get_next_problems(conn, n.min(QUEUE_PREVIEW_MAX))?;
+            let translations = Translations::load(conn)?;
+            problems.iter_mut().for_each(|p| translations.view(p));
+            Ok(problems)
+        })
+    })
+}
 
-    info!("Starting NeetCode Trainer Backend...");
+#[tauri::command]
+fn submit_attempt(
+    app: AppHandle,
+    state: State<AppState>,
+    mut log: AttemptLog,
+) -> Result<AttemptResult, String> {
+    telemetry::timed(&state, "submit_attempt", || {
+        state.ensure_writable()?;
+        // Keyed submissions are deduplicated by key, so retries must get through
+        if log.attempt_key.is_none() {
+            state.throttle.check(
+                "submit_attempt",
+                Duration::from_millis(SUBMIT_ATTEMPT_MIN_INTERVAL_MS),
+            )?;
+        }
+        let mut strict_warnings =
+            sync::verify_claimed_solves(&state, std::slice::from_mut(&mut log))?;
+        let result = state.with_db(|conn| {
+            events::emitting_changes(&app, conn, || {
+                journal::record_attempt(conn, &state.journal_path(), &log)
+            })
+        });
+        // The attempt moved the schedule, so the next selection must be fresh
+        state.next_problem.clear();
+        let mut result = result?;
+        result.warnings.append(&mut strict_warnings[0]);
+        Ok(result)
+    })
+}
+
+#[tauri::command]
+fn submit_attempts(
+    app: AppHandle,
+    state: State<AppState>,
+    mut logs: Vec<AttemptLog>,
+) -> Result<Vec<AttemptResult>, String> {
+    telemetry::timed(&state, "submit_attempts", || {
+        state.ensure_writable()?;
+        if logs.len() > MAX_BATCH_ATTEMPTS {
+            return Err(format!(
+                "At most {} attempts can be submitted at once",
+                MAX_BATCH_ATTEMPTS
+            ));
+        }
+        let strict_warnings = sync::verify_claimed_solves(&state, &mut logs)?;
+        let result = state.with_db(|conn| {
+            events::emitting_changes(&app, conn, || {
+                journal::record_attempts(conn, &state.journal_path(), &logs)
+            })
+        });
+        state.next_problem.clear();
+        let mut results = result?;
+        for (result, mut warnings) in results.iter_mut().zip(strict_warnings) {
+            result.warnings.append(&mut warnings);
+        }
+        Ok(results)
+    })
+}
+
+#[tauri::command]
+fn quick_log(
+    app: AppHandle,
+    state: State<AppState>,
+    problem_id: i64,
+    outcome: QuickLogOutcome,
+) -> Result<AttemptResult, String> {
+    telemetry::timed(&state, "quick_log", || {
+        state.ensure_writable()?;
+        state.throttle.check(
+            "quick_log",
+            Duration::from_millis(SUBMIT_ATTEMPT_MIN_INTERVAL_MS),
+        )?;
+        let mut log =
+            state.with_db(|conn| pedagogy::quick_log_attempt(conn, problem_id, outcome))?;
+        let mut strict_warnings =
+            sync::verify_claimed_solves(&state, std::slice::from_mut(&mut log))?;
+        let result = state.with_db(|conn| {
+            events::emitting_changes(&app, conn, || {
+                journal::record_attempt(conn, &state.journal_path(), &log)
+            })
+        });
+        state.next_problem.clear();
+        let mut result = result?;
+        result.warnings.append(&mut strict_warnings[0]);
+        Ok(result)
+    })
+}
+
+#[tauri::command]
+fn save_attempt_draft(state: State<AppState>, draft: AttemptDraft) -> Result<(), String> {
+    telemetry::timed(&state, "save_attempt_draft", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        state.with_db(|conn| pedagogy::save_attempt_draft(conn, &draft, now))
+    })
+}
+
+#[tauri::command]
+fn get_attempt_draft(
+    state: State<AppState>,
+    problem_id: i64,
+) -> Result<Option<AttemptDraft>, String> {
+    telemetry::timed(&state, "get_attempt_draft", || {
+        state.with_db(|conn| {
+            Repository::new(conn)
+                .get_attempt_draft(problem_id)
+                .map_err(|e| e.to_string())
+        })
+    })
+}
+
+#[tauri::command]
+fn start_attempt_timer(state: State<AppState>, problem_id: i64) -> Result<AttemptTimer, String> {
+    telemetry::timed(&state, "start_attempt_timer", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        state.with_db(|conn| attempt_timer::start(conn, problem_id, now))
+    })
+}
+
+#[tauri::command]
+fn pause_attempt_timer(state: State<AppState>, problem_id: i64) -> Result<AttemptTimer, String> {
+    telemetry::timed(&state, "pause_attempt_timer", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        state.with_db(|conn| attempt_timer::pause(conn, problem_id, now))
+    })
+}
+
+#[tauri::command]
+fn resume_attempt_timer(state: State<AppState>, problem_id: i64) -> Result<AttemptTimer, String> {
+    telemetry::timed(&state, "resume_attempt_timer", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        state.with_db(|conn| attempt_timer::resume(conn, problem_id, now))
+    })
+}
+
+#[tauri::command]
+fn get_attempt_timer(
+    state: State<AppState>,
+    problem_id: i64,
+) -> Result<Option<AttemptTimer>, String> {
+    telemetry::timed(&state, "get_attempt_timer", || {
+        let now = chrono::Utc::now().timestamp();
+        state.with_db(|conn| attempt_timer::get(conn, problem_id, now))
+    })
+}
+
+#[tauri::command]
+fn get_mistake_types(state: State<AppState>) -> Result<Vec<MistakeType>, String> {
+    telemetry::timed(&state, "get_mistake_types", || {
+        state.with_db(|conn| {
+            Repository::new(conn)
+                .get_mistake_types()
+                .map_err(|e| e.to_string())
+        })
+    })
+}
+
+#[tauri::command]
+fn add_mistake_type(state: State<AppState>, name: String) -> Result<(), String> {
+    telemetry::timed(&state, "add_mistake_type", || {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Mistake type name cannot be empty".to_string());
+        }
+        state.ensure_writable()?;
+        state.with_db(|conn| {
+            Repository::new(conn)
+                .add_mistake_type(name)
+                .map_err(|e| e.to_string())
+        })
+    })
+}
+
+#[tauri::command]
+fn get_mistake_stats(state: State<AppState>) -> Result<Vec<MistakeStat>, String> {
+    telemetry::timed(&state, "get_mistake_stats", || {
+        state.with_db(|conn| {
+            Repository::new(conn)
+                .get_mistake_stats()
+                .map_err(|e| e.to_string())
+        })
+    })
+}
+
+#[tauri::command]
+fn get_skill_cooccurrence(state: State<AppState>) -> Result<Vec<SkillCooccurrence>, String> {
+    telemetry::timed(&state, "get_skill_cooccurrence", || {
+        state.with_db(|conn| {
+            Repository::new(conn)
+                .get_skill_cooccurrence()
+                .map_err(|e| e.to_string())
+        })
+    })
+}
+
+#[tauri::command]
+fn answer_retrospective(
+    state: State<AppState>,
+    retrospective_id: i64,
+    response: String,
+) -> Result<(), String> {
+    telemetry::timed(&state, "answer_retrospective", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| {
+            match Repository::new(conn).answer_retrospective(retrospective_id, response.trim()) {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(format!("Retrospective {} not found", retrospective_id)),
+                Err(e) => Err(e.to_string()),
+            }
+        })
+    })
+}
+
+#[tauri::command]
+fn get_session_summary(
+    state: State<AppState>,
+    session_id: Option<i64>,
+) -> Result<Option<SessionSummary>, String> {
+    telemetry::timed(&state, "get_session_summary", || {
+        state.with_db(|conn| reports::get_session_summary(conn, session_id))
+    })
+}
+
+#[tauri::command]
+fn generate_weekly_report(
+    state: State<AppState>,
+    week: Option<String>,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    telemetry::timed(&state, "generate_weekly_report", || {
+        state.with_db(|conn| {
+            reports::generate_weekly_report(conn, week.as_deref(), output_path.as_deref())
+        })
+    })
+}
+
+#[tauri::command]
+fn export_report_pdf(
+    state: State<AppState>,
+    path: String,
+    range: Option<DateRange>,
+) -> Result<(), String> {
+    telemetry::timed(&state, "export_report_pdf", || {
+        state.with_db(|conn| pdf_report::export_report_pdf(conn, &path, range.as_ref()))
+    })
+}
+
+#[tauri::command]
+fn export_anonymous_stats(state: State<AppState>, path: String) -> Result<(), String> {
+    telemetry::timed(&state, "export_anonymous_stats", || {
+        state.with_db(|conn| reports::export_anonymous_stats(conn, &path))
+    })
+}
+
+#[tauri::command]
+fn export_profile_card(state: State<AppState>, path: String) -> Result<ProfileCard, String> {
+    telemetry::timed(&state, "export_profile_card", || {
+        state.with_db(|conn| reports::export_profile_card(conn, &path))
+    })
+}
+
+#[tauri::command]
+fn import_anki(state: State<AppState>, path: String) -> Result<AnkiImportSummary, String> {
+    telemetry::timed(&state, "import_anki", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| imports::import_anki(conn, &path))
+    })
+}
+
+#[tauri::command]
+fn import_problems_csv(
+    state: State<AppState>,
+    path: String,
+    track_name: String,
+) -> Result<CsvImportSummary, String> {
+    telemetry::timed(&state, "import_problems_csv", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| imports::import_problems_csv(conn, &path, &track_name))
+    })
+}
+
+#[tauri::command]
+fn reload_packs(state: State<AppState>) -> Result<Vec<PackLoad>, String> {
+    telemetry::timed(&state, "reload_packs", || {
+        state.ensure_writable()?;
+        let loads = state.with_db(|conn| imports::load_packs(conn, &state.packs_dir()))?;
+        state.next_problem.clear();
+        Ok(loads)
+    })
+}
+
+#[tauri::command]
+fn export_track(
+    state: State<AppState>,
+    track_id: i64,
+    path: String,
+    include_notes: bool,
+) -> Result<TrackBundle, String> {
+    telemetry::timed(&state, "export_track", || {
+        state.with_db(|conn| track_bundle::export_track(conn, track_id, &path, include_notes))
+    })
+}
+
+#[tauri::command]
+fn import_track(state: State<AppState>, path: String) -> Result<TrackImport, String> {
+    telemetry::timed(&state, "import_track", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        let import = state.with_db(|conn| track_bundle::import_track(conn, &path, now))?;
+        state.next_problem.clear();
+        Ok(import)
+    })
+}
+
+#[tauri::command]
+fn refresh_remote_pack(state: State<AppState>) -> Result<RemotePackRefresh, String> {
+    telemetry::timed(&state, "refresh_remote_pack", || {
+        state.ensure_writable()?;
+        let url = state
+            .with_db(|conn| {
+                Repository::new(conn)
+                    .get_setting(SETTING_REMOTE_PACK_URL)
+                    .map_err(|e| e.to_string())
+            })?
+            .ok_or_else(|| format!("Set '{}' to download packs", SETTING_REMOTE_PACK_URL))?;
+        // Download and verify before taking the database
+        let pack = remote_pack::fetch(url.trim())?;
+        let refresh = state.with_db(|conn| remote_pack::apply(conn, &pack))?;
+        if refresh.applied {
+            state.next_problem.clear();
+        }
+        Ok(refresh)
+    })
+}
+
+#[tauri::command]
+fn get_settings(state: State<AppState>) -> Result<HashMap<String, String>, String> {
+    telemetry::timed(&state, "get_settings", || {
+        state.with_db(|conn| {
+            Repository::new(conn)
+                .get_all_settings()
+                .map_err(|e| e.to_string())
+        })
+    })
+}
+
+#[tauri::command]
+fn set_setting(state: State<AppState>, key: String, value: String) -> Result<(), String> {
+    telemetry::timed(&state, "set_setting", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| {
+            Repository::new(conn)
+                .set_setting(key.trim(), value.trim())
+                .map_err(|e| e.to_string())
+        })?;
+        // Settings such as the practice mode change what is served next
+        state.next_problem.clear();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn get_note(state: State<AppState>, problem_id: i64) -> Result<Option<String>, String> {
+    telemetry::timed(&state, "get_note", || {
+        state.with_db(|conn| {
+            let repo = Repository::new(conn);
+            let (parent_id, _) = repo
+                .resolve_parent_id(problem_id)
+                .map_err(|e| e.to_string())?;
+            repo.get_note(parent_id).map_err(|e| e.to_string())
+        })
+    })
+}
+
+#[tauri::command]
+fn save_note(state: State<AppState>, problem_id: i64, body: String) -> Result<(), String> {
+    telemetry::timed(&state, "save_note", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| {
+            let repo = Repository::new(conn);
+            let (parent_id, _) = repo
+                .resolve_parent_id(problem_id)
+                .map_err(|e| e.to_string())?;
+            let now = chrono::Utc::now().timestamp();
+            repo.save_note(parent_id, body.trim(), now)
+                .map_err(|e| e.to_string())
+        })
+    })
+}
+
+#[tauri::command]
+fn sync_notion(state: State<AppState>, pull: bool) -> Result<SyncReport, String> {
+    telemetry::timed(&state, "sync_notion", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| sync::sync_notion(conn, pull))
+    })
+}
+
+#[tauri::command]
+fn get_bridge_token(state: State<AppState>) -> Result<String, String> {
+    telemetry::timed(&state, "get_bridge_token", || {
+        // The token is created on first use, and the bridge never runs read-only anyway
+        state.ensure_writable()?;
+        state.with_db(bridge::get_or_create_token)
+    })
+}
+
+#[tauri::command]
+fn get_db_health(state: State<AppState>, repair: bool) -> Result<DbHealth, String> {
+    telemetry::timed(&state, "get_db_health", || {
+        if repair {
+            state.ensure_writable()?;
+        }
+        state.with_db(|conn| database::check_health(conn, repair).map_err(|e| e.to_string()))
+    })
+}
+
+#[tauri::command]
+fn run_migrations(state: State<AppState>, dry_run: bool) -> Result<MigrationReport, String> {
+    telemetry::timed(&state, "run_migrations", || {
+        if !dry_run {
+            state.ensure_writable()?;
+        }
+        state.with_db(|conn| database::run_migrations(conn, dry_run, &state.backup_dir()))
+    })
+}
+
+#[tauri::command]
+fn maintain_db(state: State<AppState>) -> Result<MaintenanceReport, String> {
+    telemetry::timed(&state, "maintain_db", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| database::maintain(conn, &state.backup_dir()))
+    })
+}
+
+#[tauri::command]
+fn get_db_info(state: State<AppState>) -> Result<DbInfo, String> {
+    telemetry::timed(&state, "get_db_info", || {
+        state.with_db(|conn| {
+            database::get_db_info(conn, &state.backup_dir()).map_err(|e| e.to_string())
+        })
+    })
+}
+
+#[tauri::command]
+fn run_query(state: State<AppState>, sql: String) -> Result<QueryResult, String> {
+    telemetry::timed(&state, "run_query", || {
+        state.with_db(|conn| database::run_readonly_query(conn, &sql))
+    })
+}
+
+#[tauri::command]
+fn get_recent_logs(lines: usize) -> Result<Vec<String>, String> {
+    logging::recent_lines(lines.min(LOG_MAX_LINES))
+}
+
+#[tauri::command]
+fn get_usage_stats(state: State<AppState>, days: Option<i64>) -> Result<UsageStats, String> {
+    state.with_db(|conn| telemetry::get_usage_stats(conn, days.unwrap_or(TELEMETRY_DEFAULT_DAYS)))
+}
+
+#[tauri::command]
+fn search_everything(
+    state: State<AppState>,
+    query: String,
+    page: Option<Pagination>,
+) -> Result<Page<SearchHit>, String> {
+    telemetry::timed(&state, "search_everything", || {
+        state.with_db(|conn| search::search_everything(conn, &query, page.unwrap_or_default()))
+    })
+}
+
+#[tauri::command]
+fn search_notes(
+    state: State<AppState>,
+    query: String,
+    page: Option<Pagination>,
+) -> Result<Page<SearchHit>, String> {
+    telemetry::timed(&state, "search_notes", || {
+        state.with_db(|conn| search::search_notes(conn, &query, page.unwrap_or_default()))
+    })
+}
+
+#[tauri::command]
+fn add_problem_tag(state: State<AppState>, problem_id: i64, tag: String) -> Result<(), String> {
+    telemetry::timed(&state, "add_problem_tag", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| tags::add_tag(conn, problem_id, &tag))
+    })
+}
+
+#[tauri::command]
+fn remove_problem_tag(state: State<AppState>, problem_id: i64, tag: String) -> Result<(), String> {
+    telemetry::timed(&state, "remove_problem_tag", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| tags::remove_tag(conn, problem_id, &tag))
+    })
+}
+
+#[tauri::command]
+fn get_problem_tags(state: State<AppState>, problem_id: i64) -> Result<Vec<String>, String> {
+    telemetry::timed(&state, "get_problem_tags", || {
+        state.with_db(|conn| tags::get_tags(conn, problem_id))
+    })
+}
+
+#[tauri::command]
+fn list_tags(state: State<AppState>) -> Result<Vec<TagCount>, String> {
+    telemetry::timed(&state, "list_tags", || state.with_db(tags::list_tags))
+}
+
+#[tauri::command]
+fn rename_tag(state: State<AppState>, old_tag: String, new_tag: String) -> Result<usize, String> {
+    telemetry::timed(&state, "rename_tag", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| tags::rename_tag(conn, &old_tag, &new_tag))
+    })
+}
+
+#[tauri::command]
+fn delete_tag(state: State<AppState>, tag: String) -> Result<usize, String> {
+    telemetry::timed(&state, "delete_tag", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| tags::delete_tag(conn, &tag))
+    })
+}
+
+#[tauri::command]
+fn get_next_problem_with_tag(
+    state: State<AppState>,
+    tag: String,
+) -> Result<Option<ProblemView>, String> {
+    telemetry::timed(&state, "get_next_problem_with_tag", || {
+        state.with_db(|conn| {
+            let mut problem = pedagogy::get_next_problem_with_tag(conn, tag.trim())?;
+            let translations = Translations::load(conn)?;
+            problem.iter_mut().for_each(|p| translations.view(p));
+            Ok(problem)
+        })
+    })
+}
+
+#[tauri::command]
+fn star_problem(state: State<AppState>, problem_id: i64) -> Result<(), String> {
+    telemetry::timed(&state, "star_problem", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| tags::star(conn, problem_id))?;
+        state.next_problem.clear();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn unstar_problem(state: State<AppState>, problem_id: i64) -> Result<(), String> {
+    telemetry::timed(&state, "unstar_problem", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| tags::unstar(conn, problem_id))?;
+        state.next_problem.clear();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn add_to_review_queue(
+    state: State<AppState>,
+    problem_id: i64,
+    when: Option<i64>,
+) -> Result<(), String> {
+    telemetry::timed(&state, "add_to_review_queue", || {
+        state.ensure_writable()?;
+        let when = when.unwrap_or_else(|| chrono::Utc::now().timestamp());
+        state.with_db(|conn| pedagogy::add_to_review_queue(conn, problem_id, when))?;
+        state.next_problem.clear();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn skip_problem(state: State<AppState>, problem_id: i64, reason: SkipReason) -> Result<(), String> {
+    telemetry::timed(&state, "skip_problem", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        state.with_db(|conn| pedagogy::skip_problem(conn, problem_id, reason, now))?;
+        state.next_problem.clear();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn postpone_backlog(state: State<AppState>, postpone: BacklogPostpone) -> Result<usize, String> {
+    telemetry::timed(&state, "postpone_backlog", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        let moved = state.with_db(|conn| pedagogy::postpone_backlog(conn, postpone, now))?;
+        state.next_problem.clear();
+        Ok(moved)
+    })
+}
+
+#[tauri::command]
+fn triage_backlog(state: State<AppState>) -> Result<TriageReport, String> {
+    telemetry::timed(&state, "triage_backlog", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        let report = state.with_db(|conn| triage::triage_backlog(conn, now))?;
+        state.next_problem.clear();
+        Ok(report)
+    })
+}
+
+#[tauri::command]
+fn unsuspend_problem(state: State<AppState>, problem_id: i64) -> Result<(), String> {
+    telemetry::timed(&state, "unsuspend_problem", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| triage::unsuspend_problem(conn, problem_id))?;
+        state.next_problem.clear();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn get_suspended_problems(state: State<AppState>) -> Result<Vec<SuspendedProblem>, String> {
+    telemetry::timed(&state, "get_suspended_problems", || {
+        state.with_db(triage::get_suspended_problems)
+    })
+}
+
+#[tauri::command]
+fn get_retired_problems(state: State<AppState>) -> Result<Vec<SuspendedProblem>, String> {
+    telemetry::timed(&state, "get_retired_problems", || {
+        state.with_db(triage::get_retired_problems)
+    })
+}
+
+#[tauri::command]
+fn get_problem_stats(state: State<AppState>, problem_id: i64) -> Result<ProblemStats, String> {
+    telemetry::timed(&state, "get_problem_stats", || {
+        state.with_db(|conn| reports::get_problem_stats(conn, problem_id))
+    })
+}
+
+#[tauri::command]
+fn get_last_attempts(
+    state: State<AppState>,
+    problem_ids: Vec<i64>,
+) -> Result<Vec<LastAttempt>, String> {
+    telemetry::timed(&state, "get_last_attempts", || {
+        state.with_db(|conn| {
+            Repository::new(conn)
+                .get_last_attempts(&problem_ids)
+                .map_err(|e| e.to_string())
+        })
+    })
+}
+
+#[tauri::command]
+fn list_problems(
+    state: State<AppState>,
+    track_id: i64,
+    status_filter: Option<ProblemStatus>,
+    sort: Option<SortSpec<ProblemSort>>,
+    page: Option<Pagination>,
+) -> Result<ProblemPage, String> {
+    telemetry::timed(&state, "list_problems", || {
+        let now = chrono::Utc::now().timestamp();
+        state.with_db(|conn| {
+            let mut page = catalog::list_problems(
+                conn,
+                track_id,
+                status_filter,
+                sort.unwrap_or_default(),
+                page.unwrap_or_default(),
+                now,
+            )?;
+            let translations = Translations::load(conn)?;
+            page.problems
+                .items
+                .iter_mut()
+                .for_each(|p| translations.item(p));
+            Ok(page)
+        })
+    })
+}
+
+#[tauri::command]
+fn export_board(state: State<AppState>, track_id: i64, path: String) -> Result<Board, String> {
+    telemetry::timed(&state, "export_board", || {
+        let now = chrono::Utc::now().timestamp();
+        state.with_db(|conn| catalog::export_board(conn, track_id, &path, now))
+    })
+}
+
+#[tauri::command]
+fn get_problems_for_skill(
+    state: State<AppState>,
+    skill_id: i64,
+    status: Option<ProblemStatus>,
+) -> Result<Vec<SkillProblem>, String> {
+    telemetry::timed(&state, "get_problems_for_skill", || {
+        let now = chrono::Utc::now().timestamp();
+        state.with_db(|conn| {
+            let mut problems = catalog::get_problems_for_skill(conn, skill_id, status, now)?;
+            let translations = Translations::load(conn)?;
+            problems
+                .iter_mut()
+                .for_each(|p| translations.item(&mut p.problem));
+            Ok(problems)
+        })
+    })
+}
+
+#[tauri::command]
+fn compare_tracks(state: State<AppState>, a: i64, b: i64) -> Result<TrackComparison, String> {
+    telemetry::timed(&state, "compare_tracks", || {
+        state.with_db(|conn| catalog::compare_tracks(conn, a, b))
+    })
+}
+
+#[tauri::command]
+fn merge_problems(
+    state: State<AppState>,
+    keep_id: i64,
+    duplicate_id: i64,
+) -> Result<usize, String> {
+    telemetry::timed(&state, "merge_problems", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        let moved =
+            state.with_db(|conn| catalog::merge_problems(conn, keep_id, duplicate_id, now))?;
+        state.next_problem.clear();
+        Ok(moved)
+    })
+}
+
+#[tauri::command]
+fn delete_problem(state: State<AppState>, problem_id: i64) -> Result<(), String> {
+    telemetry::timed(&state, "delete_problem", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        state.with_db(|conn| catalog::delete_problem(conn, problem_id, now))?;
+        state.next_problem.clear();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn purge_deleted(state: State<AppState>) -> Result<usize, String> {
+    telemetry::timed(&state, "purge_deleted", || {
+        state.ensure_writable()?;
+        let purged = state.with_db(catalog::purge_deleted)?;
+        state.next_problem.clear();
+        Ok(purged)
+    })
+}
+
+#[tauri::command]
+fn get_audit_log(
+    state: State<AppState>,
+    entity: Option<String>,
+    entity_id: Option<i64>,
+    page: Option<Pagination>,
+) -> Result<Page<AuditEntry>, String> {
+    telemetry::timed(&state, "get_audit_log", || {
+        state.with_db(|conn| {
+            audit::get_audit_log(conn, entity.as_deref(), entity_id, page.unwrap_or_default())
+        })
+    })
+}
+
+#[tauri::command]
+fn restore_to_date(state: State<AppState>, timestamp: i64) -> Result<RestoreReport, String> {
+    telemetry::timed(&state, "restore_to_date", || {
+        state.ensure_writable()?;
+        let report =
+            state.with_db(|conn| audit::restore_to_date(conn, timestamp, &state.backup_dir()))?;
+        state.next_problem.clear();
+        Ok(report)
+    })
+}
+
+#[tauri::command]
+fn replay_history(
+    state: State<AppState>,
+    scheduler_config: SchedulerConfig,
+) -> Result<ReplayReport, String> {
+    telemetry::timed(&state, "replay_history", || {
+        let now = chrono::Utc::now().timestamp();
+        state.with_db(|conn| replay::replay_history(conn, scheduler_config, now))
+    })
+}
+
+#[tauri::command]
+fn simulate_learners(
+    state: State<AppState>,
+    params: Vec<SimulationParams>,
+) -> Result<Vec<SimulationResult>, String> {
+    telemetry::timed(&state, "simulate_learners", || {
+        if !cfg!(debug_assertions) {
+            return Err(
+                "The learner simulator is only available in development builds".to_string(),
+            );
+        }
+        simulation::simulate_learners(&params)
+    })
+}
+
+#[tauri::command]
+fn set_selection_seed(state: State<AppState>, seed: Option<u64>) -> Result<(), String> {
+    telemetry::timed(&state, "set_selection_seed", || {
+        if !cfg!(debug_assertions) {
+            return Err("Selection seeding is only available in development builds".to_string());
+        }
+        selection_rng::set_seed(seed);
+        state.next_problem.clear();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn get_difficulty_model(state: State<AppState>) -> Result<DifficultyModel, String> {
+    telemetry::timed(&state, "get_difficulty_model", || {
+        state.with_db(difficulty_model::get_model)
+    })
+}
+
+#[tauri::command]
+fn get_skill_tree(state: State<AppState>) -> Result<Vec<SkillNode>, String> {
+    telemetry::timed(&state, "get_skill_tree", || {
+        state.with_db(|conn| {
+            let mut nodes = pedagogy::get_skill_tree(conn)?;
+            let translations = Translations::load(conn)?;
+            nodes.iter_mut().for_each(|s| translations.skill(s));
+            Ok(nodes)
+        })
+    })
+}
+
+#[tauri::command]
+fn get_unlock_requirements(
+    state: State<AppState>,
+    skill_id: i64,
+) -> Result<UnlockRequirements, String> {
+    telemetry::timed(&state, "get_unlock_requirements", || {
+        let now = chrono::Utc::now().timestamp();
+        state.with_db(|conn| {
+            let mut requirements = pedagogy::get_unlock_requirements(conn, skill_id, now)?;
+            Translations::load(conn)?.requirements(&mut requirements);
+            Ok(requirements)
+        })
+    })
+}
+
+#[tauri::command]
+fn get_review_quality_stats(
+    state: State<AppState>,
+    range: Option<DateRange>,
+) -> Result<ReviewQualityStats, String> {
+    telemetry::timed(&state, "get_review_quality_stats", || {
+        state.with_db(|conn| reports::get_review_quality_stats(conn, range.as_ref()))
+    })
+}
+
+#[tauri::command]
+fn estimate_time_to_mastery(state: State<AppState>, skill_id: i64) -> Result<MasteryEta, String> {
+    telemetry::timed(&state, "estimate_time_to_mastery", || {
+        state.with_db(|conn| {
+            pedagogy::estimate_time_to_mastery(conn, skill_id, chrono::Utc::now().timestamp())
+        })
+    })
+}
+
+#[tauri::command]
+fn get_notification_schedule(state: State<AppState>) -> Result<NotificationSchedule, String> {
+    telemetry::timed(&state, "get_notification_schedule", || {
+        state.with_db(|conn| study_window::get_schedule(conn, chrono::Utc::now().timestamp()))
+    })
+}
+
+#[tauri::command]
+fn set_difficulty_override(
+    state: State<AppState>,
+    problem_id: i64,
+    difficulty: Option<Difficulty>,
+) -> Result<(), String> {
+    telemetry::timed(&state, "set_difficulty_override", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| {
+            let repo = Repository::new(conn);
+            if !repo.problem_exists(problem_id).map_err(|e| e.to_string())? {
+                return Err(format!("Problem {} does not exist", problem_id));
+            }
+            repo.set_difficulty_override(problem_id, difficulty)
+                .map_err(|e| e.to_string())
+        })
+    })
+}
+
+#[tauri::command]
+fn set_expected_time_override(
+    state: State<AppState>,
+    problem_id: i64,
+    minutes: Option<f64>,
+) -> Result<(), String> {
+    telemetry::timed(&state, "set_expected_time_override", || {
+        if let Some(m) = minutes {
+            if !m.is_finite() || m <= 0.0 || m > MAX_ATTEMPT_MINUTES {
+                return Err(format!(
+                    "Expected time must be between 0 and {} minutes",
+                    MAX_ATTEMPT_MINUTES
+                ));
+            }
+        }
+        state.ensure_writable()?;
+        state.with_db(|conn| {
+            let repo = Repository::new(conn);
+            if !repo.problem_exists(problem_id).map_err(|e| e.to_string())? {
+                return Err(format!("Problem {} does not exist", problem_id));
+            }
+            repo.set_expected_minutes_override(problem_id, minutes)
+                .map_err(|e| e.to_string())
+        })
+    })
+}
+
+#[tauri::command]
+fn set_skill_mastery(
+    state: State<AppState>,
+    skill_id: i64,
+    value: f64,
+    reason: String,
+) -> Result<(), String> {
+    telemetry::timed(&state, "set_skill_mastery", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        state.with_db(|conn| pedagogy::set_skill_mastery(conn, skill_id, value, &reason, now))?;
+        state.next_problem.clear();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn get_assessment(state: State<AppState>) -> Result<Vec<AssessmentSkill>, String> {
+    telemetry::timed(&state, "get_assessment", || {
+        state.with_db(assessment::get_assessment)
+    })
+}
+
+#[tauri::command]
+fn complete_assessment(
+    state: State<AppState>,
+    answers: Vec<AssessmentAnswer>,
+) -> Result<AssessmentResult, String> {
+    telemetry::timed(&state, "complete_assessment", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        let result = state.with_db(|conn| assessment::complete_assessment(conn, &answers, now))?;
+        state.next_problem.clear();
+        Ok(result)
+    })
+}
+
+#[tauri::command]
+fn get_placement_problem(
+    state: State<AppState>,
+    answers: Vec<PlacementAnswer>,
+) -> Result<PlacementProbe, String> {
+    telemetry::timed(&state, "get_placement_problem", || {
+        state.with_db(|conn| assessment::get_placement_problem(conn, &answers))
+    })
+}
+
+#[tauri::command]
+fn complete_placement(
+    state: State<AppState>,
+    answers: Vec<PlacementAnswer>,
+) -> Result<AssessmentResult, String> {
+    telemetry::timed(&state, "complete_placement", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        let result = state.with_db(|conn| assessment::complete_placement(conn, &answers, now))?;
+        state.next_problem.clear();
+        Ok(result)
+    })
+}
+
+#[tauri::command]
+fn mark_skills_mastered(
+    state: State<AppState>,
+    skill_ids: Vec<i64>,
+) -> Result<AssessmentResult, String> {
+    telemetry::timed(&state, "mark_skills_mastered", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        let result =
+            state.with_db(|conn| assessment::mark_skills_mastered(conn, &skill_ids, now))?;
+        state.next_problem.clear();
+        Ok(result)
+    })
+}
+
+#[tauri::command]
+fn generate_plan(
+    state: State<AppState>,
+    target_skills: Vec<i64>,
+    deadline: String,
+) -> Result<StudyPlan, String> {
+    telemetry::timed(&state, "generate_plan", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        let plan =
+            state.with_db(|conn| plan::generate_plan(conn, &target_skills, &deadline, now))?;
+        state.next_problem.clear();
+        Ok(plan)
+    })
+}
+
+#[tauri::command]
+fn get_study_plan(state: State<AppState>) -> Result<Option<StudyPlan>, String> {
+    telemetry::timed(&state, "get_study_plan", || {
+        state.with_db(|conn| {
+            Repository::new(conn)
+                .get_study_plan()
+                .map_err(|e| e.to_string())
+        })
+    })
+}
+
+#[tauri::command]
+fn clear_study_plan(state: State<AppState>) -> Result<(), String> {
+    telemetry::timed(&state, "clear_study_plan", || {
+        state.ensure_writable()?;
+        state.with_db(|conn| {
+            Repository::new(conn)
+                .clear_study_plan()
+                .map_err(|e| e.to_string())
+        })?;
+        state.next_problem.clear();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn start_exam_cram(state: State<AppState>, until: String) -> Result<ExamCram, String> {
+    telemetry::timed(&state, "start_exam_cram", || {
+        state.ensure_writable()?;
+        let now = chrono::Utc::now().timestamp();
+        let cram = state.with_db(|conn| exam_cram::start_exam_cram(conn, &until, now))?;
+        state.next_problem.clear();
+        Ok(cram)
+    })
+}
+
+#[tauri::command]
+fn stop_exam_cram(state: State<AppState>) -> Result<(), String> {
+    telemetry::timed(&state, "stop_exam_cram", || {
+        state.ensure_writable()?;
+        state.with_db(exam_cram::stop_exam_cram)?;
+        state.next_problem.clear();
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn get_exam_cram(state: State<AppState>) -> Result<Option<ExamCram>, String> {
+    telemetry::timed(&state, "get_exam_cram", || {
+        let now = chrono::Utc::now().timestamp();
+        state.with_db(|conn| exam_cram::get_exam_cram(conn, now))
+    })
+}
+
+#[tauri::command]
+fn get_starred_problems(state: State<AppState>) -> Result<Vec<ProblemView>, String> {
+    telemetry::timed(&state, "get_starred_problems", || {
+        state.with_db(|conn| {
+            let mut problems = Repository::new(conn)
+                .find_starred_problems(usize::MAX)
+                .map_err(|e| e.to_string())?;
+            let translations = Translations::load(conn)?;
+            problems.iter_mut().for_each(|p| translations.view(p));
+            Ok(problems)
+        })
+    })
+}
+
+#[tauri::command]
+fn is_read_only(state: State<AppState>) -> bool {
+    state.read_only
+}
+
+/// Command-line switches: `--read-only` opens the database without write access (also
+/// enabled by `LEETGRAPH_READ_ONLY=1`), and `--db <path>` opens another file, e.g. a backup.
+struct LaunchFlags {
+    read_only: bool,
+    db_path: Option<PathBuf>,
+}
+
+impl LaunchFlags {
+    fn parse() -> Self {
+        let mut flags = LaunchFlags {
+            read_only: std::env::var("LEETGRAPH_READ_ONLY").is_ok_and(|v| v == "1"),
+            db_path: None,
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--read-only" => flags.read_only = true,
+                "--db" => flags.db_path = args.next().map(PathBuf::from),
+                _ => {}
+            }
+        }
+        flags
+    }
+}
+
+fn main() {
+    let flags = LaunchFlags::parse();
     tauri::Builder::default()
-        .setup(|app| {
+        .plugin(tauri_plugin_notification::init())
+        .setup(move |app| {
             let app_handle = app.handle();
+            // Logging starts here because the log directory comes from the app handle
+            logging::init(
+                &app_handle
+                    .path()
+                    .app_log_dir()
+                    .expect("failed to get log dir"),
+            );
+            info!("Starting NeetCode Trainer Backend...");
+
             let app_data_dir = app_handle
                 .path()
                 .app_data_dir()
@@ -48,20 +1262,196 @@ fn main() {
                 fs::create_dir_all(&app_data_dir).expect("failed to create app data dir");
             }
 
-            let db_path = app_data_dir.join("neetcode_trainer.db");
+            let db_path = flags
+                .db_path
+                .clone()
+                .unwrap_or_else(|| app_data_dir.join("neetcode_trainer.db"));
             info!("Database path: {:?}", db_path);
-            let conn = Connection::open(db_path).expect("Failed to open DB");
 
-            // Init Database (Schema + Seeds)
-            database::init_db(&conn).expect("Failed to init DB");
+            let mut read_only = flags.read_only;
+            let mut conn = if flags.read_only {
+                // Inspection only: no schema setup, seeding, migrations or repairs
+                info!("Read-only mode enabled");
+                let conn = Connection::open_with_flags(&db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                    .expect("Failed to open DB");
+                database::configure_connection(&conn, true).expect("Failed to configure DB");
+                conn
+            } else {
+                let conn = Connection::open(&db_path).expect("Failed to open DB");
+                database::configure_connection(&conn, false).expect("Failed to configure DB");
 
-            app.manage(AppState::new(conn));
+                // Init Database (Schema + Seeds)
+                database::init_db(&conn).expect("Failed to init DB");
+                let auto_migrate = Repository::new(&conn)
+                    .get_setting_or(SETTING_AUTO_MIGRATE, true)
+                    .unwrap_or(true);
+                if auto_migrate {
+                    let backup_dir = app_data_dir.join(BACKUP_DIR_NAME);
+                    match database::run_migrations(&conn, false, &backup_dir) {
+                        Ok(report) if report.applied => info!(
+                            "Migrated DB v{} -> v{} (backup: {:?})",
+                            report.current_version, report.target_version, report.backup_path
+                        ),
+                        Ok(_) => {}
+                        // Keep the data viewable rather than refusing to launch
+                        Err(e) => {
+                            error!("Migration failed, continuing read-only: {}", e);
+                            read_only = true;
+                        }
+                    }
+                }
+                conn
+            };
+            if !read_only {
+                match database::upgrade_bundled_pack(&conn) {
+                    Ok(true) => info!("Bundled problem pack upgraded"),
+                    Ok(false) => {}
+                    Err(e) => error!("Bundled problem pack upgrade failed: {}", e),
+                }
+                if let Err(e) = imports::load_packs(&conn, &app_data_dir.join(PACKS_DIR_NAME)) {
+                    error!("Loading problem packs failed: {}", e);
+                }
+                let mut journals = vec![journal::path_for(&db_path)];
+                if flags.db_path.is_none() {
+                    journals.insert(0, app_data_dir.join(LEGACY_JOURNAL_FILE_NAME));
+                }
+                for journal_path in &journals {
+                    match journal::replay(&conn, journal_path) {
+                        Ok(0) => {}
+                        Ok(n) => info!("Recovered {} attempt(s) from the journal", n),
+                        Err(e) => error!("Attempt journal replay failed: {}", e),
+                    }
+                }
+                if let Err(e) = telemetry::on_app_start(&conn) {
+                    warn!("Telemetry startup failed: {}", e);
+                }
+            }
+            if !flags.read_only {
+                // Time what the app runs from here on; setup above would dominate
+                conn.profile(Some(telemetry::record_query));
+            }
+            match database::check_health(&conn, !read_only) {
+                Ok(health) if !health.integrity_ok => {
+                    error!(
+                        "Database integrity check failed: {:?}",
+                        health.integrity_messages
+                    )
+                }
+                Ok(health) => {
+                    for issue in &health.issues {
+                        warn!(
+                            "DB health: {} ({} rows, repaired: {})",
+                            issue.check, issue.count, issue.repaired
+                        );
+                    }
+                }
+                Err(e) => error!("Database health check failed: {}", e),
+            }
+
+            app.manage(AppState::new(conn, app_data_dir, db_path, read_only));
+            if !read_only {
+                bridge::start_if_enabled(app_handle.clone());
+                scheduler::start(app_handle.clone());
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             get_next_problem,
             submit_attempt,
-            open_external_url
+            open_external_url,
+            get_mistake_types,
+            add_mistake_type,
+            get_mistake_stats,
+            answer_retrospective,
+            get_session_summary,
+            generate_weekly_report,
+            export_report_pdf,
+            export_anonymous_stats,
+            export_profile_card,
+            import_anki,
+            import_problems_csv,
+            get_settings,
+            set_setting,
+            get_note,
+            save_note,
+            sync_notion,
+            get_bridge_token,
+            get_db_health,
+            run_migrations,
+            maintain_db,
+            get_db_info,
+            is_read_only,
+            run_query,
+            get_recent_logs,
+            get_usage_stats,
+            submit_attempts,
+            get_next_problems,
+            search_everything,
+            search_notes,
+            add_problem_tag,
+            remove_problem_tag,
+            get_problem_tags,
+            list_tags,
+            rename_tag,
+            delete_tag,
+            get_next_problem_with_tag,
+            star_problem,
+            unstar_problem,
+            get_starred_problems,
+            add_to_review_queue,
+            set_difficulty_override,
+            set_expected_time_override,
+            set_skill_mastery,
+            get_assessment,
+            complete_assessment,
+            get_placement_problem,
+            complete_placement,
+            mark_skills_mastered,
+            get_skill_cooccurrence,
+            generate_plan,
+            get_study_plan,
+            clear_study_plan,
+            start_exam_cram,
+            stop_exam_cram,
+            get_exam_cram,
+            postpone_backlog,
+            triage_backlog,
+            unsuspend_problem,
+            get_suspended_problems,
+            get_retired_problems,
+            get_problem_stats,
+            get_last_attempts,
+            list_problems,
+            export_board,
+            get_problems_for_skill,
+            compare_tracks,
+            merge_problems,
+            delete_problem,
+            purge_deleted,
+            get_audit_log,
+            restore_to_date,
+            replay_history,
+            simulate_learners,
+            set_selection_seed,
+            get_difficulty_model,
+            get_skill_tree,
+            get_review_quality_stats,
+            estimate_time_to_mastery,
+            get_notification_schedule,
+            reload_packs,
+            refresh_remote_pack,
+            export_track,
+            import_track,
+            quick_log,
+            save_attempt_draft,
+            get_attempt_draft,
+            start_attempt_timer,
+            pause_attempt_timer,
+            resume_attempt_timer,
+            get_attempt_timer,
+            get_unlock_requirements,
+            get_serve_history,
+            skip_problem
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,237 @@
+// src/mock_repository.rs
+
+use crate::constants::EASE_FACTOR_DEFAULT;
+use crate::models::{Difficulty, ProblemRepetitionState, SkillMasteryState, TimedOutcome};
+use crate::repository::ProblemRepository;
+use rusqlite::Result;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+
+/// A change recorded by `log_mastery_change`.
+#[derive(Clone)]
+pub struct MockMasteryChange {
+    pub skill_id: i64,
+    pub attempt_id: Option<i64>,
+    pub old_mastery: f64,
+    pub new_mastery: f64,
+    pub reason: String,
+    pub timestamp: i64,
+}
+
+/// In-memory stand-in for the database, for driving the scheduling core in `pedagogy`
+/// without SQLite. Fill in the fields it should start from; writes go to the same fields,
+/// so they can be inspected afterwards.
+#[derive(Default)]
+pub struct MockRepository {
+    pub settings: RefCell<HashMap<String, String>>,
+    /// Every skill, including ones never practiced.
+    pub skills: RefCell<BTreeMap<i64, SkillMasteryState>>,
+    /// (skill_id, prereq_id) edges.
+    pub prereqs: Vec<(i64, i64)>,
+    /// Skill ids of each problem.
+    pub problem_skills: HashMap<i64, Vec<i64>>,
+    pub attempt_counts: RefCell<HashMap<i64, i64>>,
+    /// Attempts of each concept, oldest first.
+    pub concept_attempts: RefCell<HashMap<i64, Vec<TimedOutcome>>>,
+    /// problem_id -> (reason, suspended_at).
+    pub suspended: RefCell<HashMap<i64, (String, i64)>>,
+    /// problem_id -> next_review_ts of every tracked concept.
+    pub next_reviews: RefCell<HashMap<i64, i64>>,
+    /// SM-2 state of each concept that has one.
+    pub repetition: RefCell<HashMap<i64, ProblemRepetitionState>>,
+    /// Every saved interval, with its timestamp.
+    pub interval_log: RefCell<Vec<(ProblemRepetitionState, i64)>>,
+    /// (timestamp, recalled) of each concept's attempts, oldest first.
+    pub concept_outcomes: HashMap<i64, Vec<(i64, bool)>>,
+    pub mastery_log: RefCell<Vec<MockMasteryChange>>,
+    /// Learned multipliers; difficulties missing here use the defaults.
+    pub fitted_multipliers: BTreeMap<Difficulty, f64>,
+}
+
+impl ProblemRepository for MockRepository {
+    fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.settings.borrow().get(key).cloned())
+    }
+
+    fn get_skill_state(&self, skill_id: i64) -> Result<SkillMasteryState> {
+        Ok(self
+            .skills
+            .borrow()
+            .get(&skill_id)
+            .cloned()
+            .unwrap_or(SkillMasteryState {
+                skill_id,
+                mastery: 0.0,
+                attempts: 0,
+            }))
+    }
+
+    fn update_skill_state(&self, state: &SkillMasteryState) -> Result<()> {
+        if let Some(s) = self.skills.borrow_mut().get_mut(&state.skill_id) {
+            *s = state.clone();
+        }
+        Ok(())
+    }
+
+    fn get_all_skill_states(&self) -> Result<Vec<SkillMasteryState>> {
+        Ok(self.skills.borrow().values().cloned().collect())
+    }
+
+    fn get_skill_prereqs(&self) -> Result<Vec<(i64, i64)>> {
+        Ok(self.prereqs.clone())
+    }
+
+    fn log_mastery_change(
+        &self,
+        skill_id: i64,
+        attempt_id: Option<i64>,
+        old_mastery: f64,
+        new_mastery: f64,
+        reason: &str,
+        timestamp: i64,
+    ) -> Result<()> {
+        self.mastery_log.borrow_mut().push(MockMasteryChange {
+            skill_id,
+            attempt_id,
+            old_mastery,
+            new_mastery,
+            reason: reason.to_string(),
+            timestamp,
+        });
+        Ok(())
+    }
+
+    fn get_attempt_count(&self, problem_id: i64) -> Result<i64> {
+        Ok(self
+            .attempt_counts
+            .borrow()
+            .get(&problem_id)
+            .copied()
+            .unwrap_or(0))
+    }
+
+    fn get_recent_concept_attempts(
+        &self,
+        parent_id: i64,
+        limit: usize,
+    ) -> Result<Vec<TimedOutcome>> {
+        Ok(self
+            .concept_attempts
+            .borrow()
+            .get(&parent_id)
+            .map(|a| a.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default())
+    }
+
+    fn get_suspension_reason(&self, problem_id: i64) -> Result<Option<String>> {
+        Ok(self
+            .suspended
+            .borrow()
+            .get(&problem_id)
+            .map(|(reason, _)| reason.clone()))
+    }
+
+    fn suspend_problem(&self, problem_id: i64, reason: &str, now: i64) -> Result<()> {
+        self.suspended
+            .borrow_mut()
+            .entry(problem_id)
+            .or_insert_with(|| (reason.to_string(), now));
+        Ok(())
+    }
+
+    fn unsuspend_problem(&self, problem_id: i64) -> Result<bool> {
+        Ok(self.suspended.borrow_mut().remove(&problem_id).is_some())
+    }
+
+    fn find_suspended_for_skill(
+        &self,
+        skill_id: i64,
+        reason: &str,
+        limit: usize,
+    ) -> Result<Vec<i64>> {
+        let mut found: Vec<(i64, i64)> = self
+            .suspended
+            .borrow()
+            .iter()
+            .filter(|(id, (r, _))| {
+                r == reason
+                    && self
+                        .problem_skills
+                        .get(id)
+                        .is_some_and(|skills| skills.contains(&skill_id))
+            })
+            .map(|(&id, &(_, since))| (since, id))
+            .collect();
+        found.sort();
+        Ok(found.into_iter().take(limit).map(|(_, id)| id).collect())
+    }
+
+    fn set_next_review_ts(&self, problem_id: i64, ts: i64) -> Result<()> {
+        if let Some(next) = self.next_reviews.borrow_mut().get_mut(&problem_id) {
+            *next = ts;
+        }
+        Ok(())
+    }
+
+    fn get_problem_repetition_state(&self, problem_id: i64) -> Result<ProblemRepetitionState> {
+        Ok(self
+            .repetition
+            .borrow()
+            .get(&problem_id)
+            .cloned()
+            .unwrap_or(ProblemRepetitionState {
+                problem_id,
+                ease_factor: EASE_FACTOR_DEFAULT,
+                interval_days: 0.0,
+                next_review_ts: 0,
+            }))
+    }
+
+    fn save_problem_repetition_state(&self, state: &ProblemRepetitionState) -> Result<()> {
+        self.repetition
+            .borrow_mut()
+            .insert(state.problem_id, state.clone());
+        self.next_reviews
+            .borrow_mut()
+            .insert(state.problem_id, state.next_review_ts);
+        Ok(())
+    }
+
+    fn log_interval(&self, state: &ProblemRepetitionState, timestamp: i64) -> Result<()> {
+        self.interval_log
+            .borrow_mut()
+            .push((state.clone(), timestamp));
+        Ok(())
+    }
+
+    fn get_concept_outcomes(&self, parent_id: i64) -> Result<Vec<(i64, bool)>> {
+        Ok(self
+            .concept_outcomes
+            .get(&parent_id)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn get_fitted_multiplier(&self, difficulty: Difficulty) -> Result<Option<f64>> {
+        Ok(self.fitted_multipliers.get(&difficulty).copied())
+    }
+
+    fn count_reviews_between(
+        &self,
+        from_ts: i64,
+        to_ts: i64,
+        exclude_problem_id: i64,
+    ) -> Result<i64> {
+        let suspended = self.suspended.borrow();
+        Ok(self
+            .next_reviews
+            .borrow()
+            .iter()
+            .filter(|(id, ts)| {
+                **id != exclude_problem_id
+                    && !suspended.contains_key(id)
+                    && (from_ts..to_ts).contains(*ts)
+            })
+            .count() as i64)
+    }
+}
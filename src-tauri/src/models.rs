@@ -1,27 +1,104 @@
 // src/models.rs
 
+use crate::constants::{
+    ATTEMPTS_CONSOLIDATION_THRESHOLD, BACKUP_DIR_NAME, DB_QUEUE_MAX_PENDING,
+    MASTERY_CONSOLIDATION_THRESHOLD, MASTERY_PRIOR_ATTEMPTS, MASTERY_UNCERTAINTY_EPSILON,
+    MASTERY_UNLOCK_MAX_UNCERTAINTY, MASTERY_UNLOCK_THRESHOLD, NEXT_PROBLEM_DEBOUNCE_MS,
+    PACKS_DIR_NAME, PAGE_SIZE_DEFAULT, PAGE_SIZE_MAX, SKILL_WEIGHT_SECONDARY,
+    STATUS_MASTERED_INTERVAL,
+};
+use crate::database;
+use crate::journal;
+use crate::throttle::{CommandThrottle, Debounce};
+use chrono::NaiveDate;
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::str::FromStr;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
 
 // --- App State ---
 
 pub struct AppState {
     pub db: Mutex<Connection>,
+    pub data_dir: PathBuf,
+    pub db_path: PathBuf,
+    /// Set by `--read-only`: the DB was opened read-only and mutating commands refuse to run.
+    pub read_only: bool,
+    /// Commands currently waiting for or holding `db`.
+    pending: AtomicUsize,
+    pub throttle: CommandThrottle,
+    /// Recent `get_next_problem` result, so repeated calls don't rerun selection.
+    pub next_problem: Debounce<Option<ProblemView>>,
+}
+
+/// Releases a queue slot even if the command panics.
+struct QueueSlot<'a>(&'a AtomicUsize);
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 impl AppState {
-    pub fn new(conn: Connection) -> Self {
+    pub fn new(conn: Connection, data_dir: PathBuf, db_path: PathBuf, read_only: bool) -> Self {
         AppState {
             db: Mutex::new(conn),
+            data_dir,
+            db_path,
+            read_only,
+            pending: AtomicUsize::new(0),
+            throttle: CommandThrottle::default(),
+            next_problem: Debounce::new(Duration::from_millis(NEXT_PROBLEM_DEBOUNCE_MS)),
+        }
+    }
+
+    /// Runs `op` on the shared connection, one command at a time. Once
+    /// `DB_QUEUE_MAX_PENDING` commands are queued, new ones fail fast instead of piling up,
+    /// and ops blocked by another process are retried (see `database::retry_busy`).
+    pub fn with_db<T>(
+        &self,
+        op: impl FnMut(&Connection) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let queued = self.pending.fetch_add(1, Ordering::SeqCst);
+        let _slot = QueueSlot(&self.pending);
+        if queued >= DB_QUEUE_MAX_PENDING {
+            return Err("The database is busy, please try again".to_string());
         }
+        // One panicked command shouldn't lock every later command out of the database
+        let conn = self.db.lock().unwrap_or_else(PoisonError::into_inner);
+        database::retry_busy(&conn, op)
+    }
+
+    /// Guard for commands that change data.
+    pub fn ensure_writable(&self) -> Result<(), String> {
+        if self.read_only {
+            Err("Read-only mode: this action is disabled".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn backup_dir(&self) -> PathBuf {
+        self.data_dir.join(BACKUP_DIR_NAME)
+    }
+
+    pub fn journal_path(&self) -> PathBuf {
+        journal::path_for(&self.db_path)
+    }
+
+    pub fn packs_dir(&self) -> PathBuf {
+        self.data_dir.join(PACKS_DIR_NAME)
     }
 }
 
 // --- Data Models ---
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Difficulty {
     Easy = 1,
     Medium = 2,
@@ -56,17 +133,177 @@ impl ToString for Difficulty {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Where a concept stands in the learning cycle, derived from its repetition state.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProblemStatus {
+    New,
+    Learning,
+    Review,
+    Mastered,
+    /// Kept out of the review queue (triage, retirement or by hand), whatever its schedule.
+    Suspended,
+}
+
+impl ProblemStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProblemStatus::New => "New",
+            ProblemStatus::Learning => "Learning",
+            ProblemStatus::Review => "Review",
+            ProblemStatus::Mastered => "Mastered",
+            ProblemStatus::Suspended => "Suspended",
+        }
+    }
+
+    /// `state` is (interval_days, next_review_ts); `None` means never attempted.
+    pub fn derive(state: Option<(f64, i64)>, suspended: bool, now: i64) -> Self {
+        match state {
+            _ if suspended => ProblemStatus::Suspended,
+            None => ProblemStatus::New,
+            Some((_, next_review_ts)) if next_review_ts <= now => ProblemStatus::Review,
+            Some((interval, _)) if interval >= STATUS_MASTERED_INTERVAL => ProblemStatus::Mastered,
+            Some(_) => ProblemStatus::Learning,
+        }
+    }
+}
+
+impl FromStr for ProblemStatus {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "New" => Ok(ProblemStatus::New),
+            "Learning" => Ok(ProblemStatus::Learning),
+            "Review" => Ok(ProblemStatus::Review),
+            "Mastered" => Ok(ProblemStatus::Mastered),
+            "Suspended" => Ok(ProblemStatus::Suspended),
+            _ => Err(format!("Unknown status '{}'", s)),
+        }
+    }
+}
+
+/// Which queue a served problem came from. Serialized internally tagged, e.g.
+/// `{"kind": "review"}` or `{"kind": "alternative_for", "parent_id": 128}`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QueueKind {
+    Review,
+    Discovery,
+    Cram,
+    /// Favorites practice: starred concepts, least recently attempted first.
+    Starred,
+    /// Onboarding quiz problems, used to seed mastery.
+    Assessment,
+    /// A variation served in place of a due review of `parent_id`.
+    AlternativeFor {
+        parent_id: i64,
+    },
+}
+
+/// Default English label and icon for a queue; frontends may localize from `QueueKind`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QueueDisplay {
+    pub icon: String,
+    pub label: String,
+}
+
+impl QueueKind {
+    pub fn display(&self) -> QueueDisplay {
+        let (icon, label) = match self {
+            QueueKind::Review => ("🧠", "Spaced Review"),
+            QueueKind::Discovery => ("✨", "New Discovery"),
+            QueueKind::Cram => ("🔥", "Cram Mode"),
+            QueueKind::Starred => ("⭐", "Starred Practice"),
+            QueueKind::Assessment => ("📝", "Assessment"),
+            QueueKind::AlternativeFor { .. } => ("🔀", "Concept Variation"),
+        };
+        QueueDisplay {
+            icon: icon.to_string(),
+            label: label.to_string(),
+        }
+    }
+}
+
+/// How due reviews are ordered within a session; the `review_order` setting.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewOrder {
+    /// Most overdue first.
+    #[default]
+    Due,
+    /// Reviews of the same skill back to back, skills taken by their most overdue review.
+    Blocked,
+    /// One review per skill in turn, so no two in a row share a skill while others remain.
+    Interleaved,
+}
+
+impl FromStr for ReviewOrder {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "due" => Ok(ReviewOrder::Due),
+            "blocked" => Ok(ReviewOrder::Blocked),
+            "interleaved" => Ok(ReviewOrder::Interleaved),
+            _ => Err(format!("Unknown review order: {}", s)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ProblemView {
     pub id: i64,
     pub title: String,
     pub url: String,
-    pub difficulty: String,
-    pub track_name: String,
+    pub difficulty: Difficulty,
+    pub queue: QueueKind,
+    #[serde(default)]
+    pub queue_display: Option<QueueDisplay>,
     pub skills: Vec<String>,
+    /// Same order as `skills`.
+    pub skill_ids: Vec<i64>,
+    /// Attempts on the concept, counting its alternatives.
+    pub attempt_count: i64,
+    pub last_attempt_ts: Option<i64>,
+    pub next_review_ts: Option<i64>,
+    #[serde(default)]
+    pub retrospectives: Vec<Retrospective>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Retrospective {
+    pub id: i64,
+    pub attempt_id: i64,
+    pub prompt: String,
+    pub response: Option<String>,
+    pub created_at: i64,
+}
+
+/// A problem in progress, autosaved so closing the app doesn't lose it. Logging an
+/// attempt at the problem consumes the draft.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AttemptDraft {
+    pub problem_id: i64,
+    pub elapsed_minutes: f64,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub code: Option<String>,
+    /// Set on save.
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+/// How practice done away from the app went, for `quick_log`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuickLogOutcome {
+    Solved,
+    /// Solved after peeking at the skills involved.
+    Hinted,
+    ReadSolution,
+    Unsolved,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AttemptLog {
     pub problem_id: i64,
     pub time_minutes: f64,
@@ -74,30 +311,1183 @@ pub struct AttemptLog {
     pub read_solution: bool,
     #[serde(default)]
     pub revealed_skills: bool,
+    #[serde(default)]
+    pub mistakes: Vec<String>,
+    #[serde(default)]
+    pub explanation: Option<String>,
+    /// Client-generated UUID. Resubmitting the same key returns the first result instead
+    /// of logging the attempt again.
+    #[serde(default)]
+    pub attempt_key: Option<String>,
+    /// When the attempt happened, for attempts logged after the fact. Defaults to now.
+    #[serde(default)]
+    pub timestamp: Option<i64>,
+    /// Breaks taken during the attempt. `time_minutes` includes `paused_minutes`, which is
+    /// taken off before the attempt is scored. Filled from the backend timer when unset.
+    #[serde(default)]
+    pub pause_count: i64,
+    #[serde(default)]
+    pub paused_minutes: f64,
+}
+
+/// The backend's clock for a problem in progress. Pauses are kept apart so a break does
+/// not count as time spent on the problem.
+#[derive(Serialize, Debug, Clone)]
+pub struct AttemptTimer {
+    pub problem_id: i64,
+    pub started_at: i64,
+    /// Set while paused.
+    pub paused_at: Option<i64>,
+    pub pause_count: i64,
+    /// Including the current pause.
+    pub paused_minutes: f64,
+    /// Since the start, pauses included: the attempt's `time_minutes`.
+    pub elapsed_minutes: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SkillMasteryDelta {
+    pub skill_id: i64,
+    pub skill: String,
+    pub before: f64,
+    pub after: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SessionSummary {
+    /// ID of the first attempt in the session.
+    pub session_id: i64,
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub attempts: i64,
+    pub problems_done: i64,
+    pub time_spent_minutes: f64,
+    pub solve_rate: f64,
+    pub mastery_gains: Vec<SkillMasteryDelta>,
+    pub new_unlocks: Vec<String>,
+    pub reviews_due_tomorrow: i64,
+}
+
+/// Inclusive `YYYY-MM-DD` bounds; missing ends fall back to command defaults.
+#[derive(Deserialize, Debug, Default)]
+pub struct DateRange {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+// --- Anonymized Export ---
+// Deliberately free of titles, URLs, problem IDs, notes and absolute dates.
+
+#[derive(Serialize, Debug)]
+pub struct AnonymousStats {
+    pub format_version: u32,
+    pub skills: Vec<AnonSkillCurve>,
+    pub interval_distribution: Vec<IntervalBucket>,
+    pub outcome_sequences: Vec<Vec<AnonOutcome>>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AnonSkillCurve {
+    pub skill: String,
+    /// (days since first activity, mastery after the change)
+    pub mastery_curve: Vec<(i64, f64)>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct IntervalBucket {
+    pub min_days: f64,
+    pub max_days: Option<f64>,
+    pub count: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AnonOutcome {
+    pub day: i64,
+    pub difficulty: Difficulty,
+    pub solved: bool,
+    pub read_solution: bool,
+    pub time_ratio: f64,
+}
+
+// --- Problem Stats ---
+
+#[derive(Serialize, Debug)]
+pub struct IntervalPoint {
+    pub timestamp: i64,
+    pub interval_days: f64,
+    pub ease_factor: f64,
+}
+
+/// Detail-screen stats for a concept (the problem and its alternatives).
+#[derive(Serialize, Debug)]
+pub struct ProblemStats {
+    pub problem_id: i64,
+    pub attempts: i64,
+    /// Consecutive clean solves up to the latest attempt.
+    pub streak: i64,
+    /// Over clean solves only.
+    pub best_minutes: Option<f64>,
+    pub worst_minutes: Option<f64>,
+    pub average_minutes: Option<f64>,
+    /// Intervals set by past attempts, oldest first.
+    pub intervals: Vec<IntervalPoint>,
+}
+
+/// The latest attempt on a concept, for list views.
+#[derive(Serialize, Debug)]
+pub struct LastAttempt {
+    /// As requested.
+    pub problem_id: i64,
+    /// The problem actually attempted: `problem_id` or one of its alternatives.
+    pub attempted_id: i64,
+    pub timestamp: i64,
+    pub time_minutes: f64,
+    pub solved: bool,
+    pub read_solution: bool,
+}
+
+// --- Paging ---
+
+/// Which slice of a list command's results to return.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Pagination {
+    /// Zero-based.
+    #[serde(default)]
+    pub page: usize,
+    #[serde(default = "default_page_size")]
+    pub page_size: usize,
+}
+
+fn default_page_size() -> usize {
+    PAGE_SIZE_DEFAULT
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Pagination {
+            page: 0,
+            page_size: PAGE_SIZE_DEFAULT,
+        }
+    }
+}
+
+impl Pagination {
+    /// `page_size`, kept between 1 and `PAGE_SIZE_MAX`.
+    pub fn limit(&self) -> usize {
+        self.page_size.clamp(1, PAGE_SIZE_MAX)
+    }
+
+    pub fn offset(&self) -> usize {
+        self.page.saturating_mul(self.limit())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Sort order for a list command whose sortable fields are `F`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct SortSpec<F> {
+    pub field: F,
+    #[serde(default)]
+    pub direction: SortDirection,
+}
+
+/// One page of a list command's results.
+#[derive(Serialize, Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Zero-based.
+    pub page: usize,
+    pub page_size: usize,
+    /// Results across all pages.
+    pub total: i64,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, pagination: Pagination, total: i64) -> Self {
+        Page {
+            items,
+            page: pagination.page,
+            page_size: pagination.limit(),
+            total,
+        }
+    }
+}
+
+// --- Problem Browser ---
+
+/// Sortable fields of `list_problems`. Problems without a value sort last either way.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSort {
+    #[default]
+    Id,
+    Title,
+    Difficulty,
+    NextReview,
+    LastAttempt,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProblemListItem {
+    pub id: i64,
+    pub title: String,
+    pub url: Option<String>,
+    pub difficulty: Difficulty,
+    pub status: ProblemStatus,
+    pub next_review_ts: Option<i64>,
+    /// Counting the problem's alternatives.
+    pub last_attempt_ts: Option<i64>,
+}
+
+/// A problem feeding a skill, with how much of the skill it trains.
+#[derive(Serialize, Debug)]
+pub struct SkillProblem {
+    #[serde(flatten)]
+    pub problem: ProblemListItem,
+    pub weight: f64,
+}
+
+/// Problems of a track per status, regardless of the status filter.
+#[derive(Serialize, Debug, Default)]
+pub struct StatusCounts {
+    pub new: i64,
+    pub learning: i64,
+    pub review: i64,
+    pub mastered: i64,
+    pub suspended: i64,
+}
+
+impl StatusCounts {
+    pub fn get(&self, status: ProblemStatus) -> i64 {
+        match status {
+            ProblemStatus::New => self.new,
+            ProblemStatus::Learning => self.learning,
+            ProblemStatus::Review => self.review,
+            ProblemStatus::Mastered => self.mastered,
+            ProblemStatus::Suspended => self.suspended,
+        }
+    }
+
+    pub fn total(&self) -> i64 {
+        self.new + self.learning + self.review + self.mastered + self.suspended
+    }
+}
+
+/// `total` counts the problems matching the status filter.
+#[derive(Serialize, Debug)]
+pub struct ProblemPage {
+    #[serde(flatten)]
+    pub problems: Page<ProblemListItem>,
+    pub counts: StatusCounts,
+}
+
+// --- Track Comparison ---
+
+#[derive(Serialize, Debug)]
+pub struct SkillCoverage {
+    pub skill_id: i64,
+    pub skill: String,
+    pub problems_a: i64,
+    pub problems_b: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TrackComparison {
+    pub track_a: String,
+    pub track_b: String,
+    pub only_a: i64,
+    pub only_b: i64,
+    pub shared: i64,
+    /// Skills covered by either track, largest coverage difference first.
+    pub skills: Vec<SkillCoverage>,
+}
+
+// --- Status Board ---
+
+#[derive(Serialize, Debug)]
+pub struct BoardColumn {
+    pub title: String,
+    pub status: ProblemStatus,
+    pub problems: Vec<ProblemListItem>,
+}
+
+/// A track laid out as a kanban board, for mirroring into Trello or Notion.
+#[derive(Serialize, Debug)]
+pub struct Board {
+    pub generated_on: String,
+    pub track_id: i64,
+    pub columns: Vec<BoardColumn>,
+}
+
+// --- Profile Card ---
+
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Streak {
+    /// Consecutive active days ending today (or yesterday, if today has no attempts yet).
+    pub current: i64,
+    pub longest: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProfileCard {
+    pub generated_on: String,
+    pub streak: Streak,
+    pub total_solved: i64,
+    pub solved_by_difficulty: Vec<DifficultyCount>,
+    pub top_skills: Vec<SkillLevel>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DifficultyCount {
+    pub difficulty: String,
+    pub count: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SkillLevel {
+    pub skill: String,
+    pub mastery: f64,
+}
+
+// --- Imports ---
+
+#[derive(Serialize, Debug, Default)]
+pub struct AnkiImportSummary {
+    pub imported: i64,
+    /// Matched problems that already had LeetGraph scheduling state.
+    pub skipped_existing: i64,
+    /// First field of every card that matched no known problem.
+    pub unmatched: Vec<String>,
+}
+
+/// One file from the packs folder. On failure nothing from the file is kept.
+#[derive(Serialize, Debug)]
+pub struct PackLoad {
+    pub file: String,
+    /// Named after the file.
+    pub track: String,
+    pub problems: usize,
+    pub created_skills: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// A track shared as a single `.lgtrack` file: its problems in the bundled pack's format,
+/// custom ones included, plus the exporter's notes by problem id if they chose to share them.
+#[derive(Serialize, Deserialize)]
+pub struct TrackBundle {
+    pub format: i64,
+    pub track: String,
+    pub problems: Vec<JsonProblem>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub notes: BTreeMap<i64, String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TrackImport {
+    pub track_id: i64,
+    pub track: String,
+    pub problems: usize,
+    pub created_skills: Vec<String>,
+    /// Notes from the bundle that were kept; problems that already had one keep theirs.
+    pub notes: usize,
+}
+
+/// Outcome of downloading the bundled track's pack from `remote_pack_url`.
+#[derive(Serialize, Debug)]
+pub struct RemotePackRefresh {
+    pub previous_version: i64,
+    pub version: i64,
+    /// False when the download was not newer than what the database has.
+    pub applied: bool,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct CsvImportSummary {
+    pub track_id: i64,
+    /// Rows added as brand-new problems.
+    pub created_problems: i64,
+    /// Rows that matched a problem we already had.
+    pub reused_problems: i64,
+    pub created_skills: Vec<String>,
+}
+
+// --- Integrations ---
+
+#[derive(Serialize, Debug, Default)]
+pub struct SyncReport {
+    pub created: i64,
+    pub updated: i64,
+    /// Manual status changes taken from Notion and applied locally.
+    pub pulled: i64,
+    /// Problems changed on both sides since the last sync, with the side that won.
+    pub conflicts: Vec<String>,
+}
+
+// --- Events ---
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SkillUnlocked {
+    pub skill_id: i64,
+    pub name: String,
+}
+
+// --- Database Health ---
+
+#[derive(Serialize, Debug)]
+pub struct DbHealth {
+    pub integrity_ok: bool,
+    /// Problems reported by `PRAGMA integrity_check`; empty when the file is sound.
+    pub integrity_messages: Vec<String>,
+    pub issues: Vec<HealthIssue>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct HealthIssue {
+    pub check: String,
+    pub count: i64,
+    pub repaired: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MigrationReport {
+    pub current_version: i64,
+    pub target_version: i64,
+    pub pending: Vec<MigrationStep>,
+    pub applied: bool,
+    pub backup_path: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MigrationStep {
+    pub version: i64,
+    pub description: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MaintenanceReport {
+    pub size_before_bytes: i64,
+    pub size_after_bytes: i64,
+    pub backups_removed: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DbInfo {
+    /// `None` for in-memory databases.
+    pub path: Option<String>,
+    pub size_bytes: i64,
+    pub schema_version: i64,
+    pub journal_mode: String,
+    pub tables: Vec<TableCount>,
+    pub last_backup_path: Option<String>,
+    pub last_backup_at: Option<i64>,
+    /// Statements with the highest mean latency, when telemetry is enabled.
+    pub slow_queries: Vec<QueryStat>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct QueryStat {
+    pub sql: String,
+    pub calls: i64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TableCount {
+    pub name: String,
+    pub rows: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// More rows existed than `QUERY_MAX_ROWS`.
+    pub truncated: bool,
+}
+
+/// Why an attempt was rejected. Displays as `CODE: message` so the frontend can branch on
+/// the code and show the message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttemptError {
+    InvalidTime(f64),
+    FutureTimestamp(i64),
+    ZeroTimeSolve,
+    UnknownProblem(i64),
+    InvalidPause(f64),
+}
+
+impl AttemptError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AttemptError::InvalidTime(_) => "INVALID_TIME",
+            AttemptError::FutureTimestamp(_) => "FUTURE_TIMESTAMP",
+            AttemptError::ZeroTimeSolve => "ZERO_TIME_SOLVE",
+            AttemptError::UnknownProblem(_) => "UNKNOWN_PROBLEM",
+            AttemptError::InvalidPause(_) => "INVALID_PAUSE",
+        }
+    }
+}
+
+impl std::fmt::Display for AttemptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let message = match self {
+            AttemptError::InvalidTime(t) => format!("Time must be zero or more minutes, got {}", t),
+            AttemptError::FutureTimestamp(ts) => format!("Timestamp {} is in the future", ts),
+            AttemptError::ZeroTimeSolve => "A solve needs a time above zero minutes".to_string(),
+            AttemptError::UnknownProblem(id) => format!("Problem {} does not exist", id),
+            AttemptError::InvalidPause(m) => format!(
+                "Paused time must be between zero and the attempt's time, got {}",
+                m
+            ),
+        };
+        write!(f, "{}: {}", self.code(), message)
+    }
+}
+
+/// Returned by `submit_attempt`.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct AttemptResult {
+    /// The explanation written on the previous attempt of this concept, revealed only
+    /// after the new attempt is in so the user can compare against their past self.
+    pub previous_explanation: Option<String>,
+    /// Non-fatal notes about how the attempt was recorded (e.g. a downgraded strict-mode claim).
+    pub warnings: Vec<String>,
+    /// Skills whose prerequisites were completed by this attempt.
+    pub unlocked_skills: Vec<SkillUnlocked>,
+    /// The reported time was implausible and was capped at `MAX_ATTEMPT_MINUTES`.
+    pub time_clamped: bool,
+    /// The concept has had enough fast, clean reviews and left the review rotation.
+    #[serde(default)] // absent from results stored under an attempt key before retirement
+    pub retired: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MistakeType {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MistakeStat {
+    pub skill: String,
+    pub mistake: String,
+    pub count: i64,
+}
+
+/// Two skills practiced by the same problems. `failures` counts failed attempts that
+/// involved both.
+#[derive(Serialize, Debug)]
+pub struct SkillCooccurrence {
+    pub skill_a: String,
+    pub skill_b: String,
+    pub problems: i64,
+    pub failures: i64,
+}
+
+// --- Assessment ---
+
+/// Quiz problems for one skill, easiest first.
+#[derive(Serialize, Debug)]
+pub struct AssessmentSkill {
+    pub skill_id: i64,
+    pub skill: String,
+    pub problems: Vec<ProblemView>,
+}
+
+/// How a quiz problem went: the user's own judgement, nothing is timed.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AssessmentOutcome {
+    Solved,
+    Struggled,
+    Unsolved,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AssessmentAnswer {
+    pub problem_id: i64,
+    pub outcome: AssessmentOutcome,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PlacementAnswer {
+    pub problem_id: i64,
+    pub solved: bool,
+}
+
+/// Where a placement run stands. Levels are prerequisite depths in the skill DAG: skills
+/// below `level` count as known.
+#[derive(Serialize, Debug)]
+pub struct PlacementProbe {
+    /// The next problem to try, or `None` once placement is settled.
+    pub problem: Option<ProblemView>,
+    pub level: i64,
+    pub max_level: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AssessmentResult {
+    /// Skills whose mastery was raised, in skill order.
+    pub mastery: Vec<SkillMasteryDelta>,
+    pub unlocked_skills: Vec<SkillUnlocked>,
+}
+
+// --- Study Plan ---
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PlanDay {
+    pub date: String, // YYYY-MM-DD, local time
+    pub new_problems: i64,
+    /// Projected reviews: ones already scheduled plus the follow-ups of planned problems.
+    pub reviews: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct StudyPlan {
+    pub deadline: String,
+    /// The requested skills plus any prerequisites still to be met.
+    pub skill_ids: Vec<i64>,
+    /// Never-attempted problems in those skills.
+    pub new_problems: i64,
+    /// Whether every new problem fits before the deadline at the daily limit.
+    pub feasible: bool,
+    pub warnings: Vec<String>,
+    pub days: Vec<PlanDay>,
+}
+
+/// How `postpone_backlog` reschedules overdue reviews: all to `days` from now, or spread
+/// evenly over the next `POSTPONE_SPREAD_DAYS` days.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum BacklogPostpone {
+    Days(i64),
+    Spread,
+}
+
+// --- Suspension & Triage ---
+
+#[derive(Serialize, Debug)]
+pub struct SuspendedProblem {
+    pub problem_id: i64,
+    pub title: String,
+    pub reason: String,
+    pub suspended_at: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TriagedProblem {
+    pub problem_id: i64,
+    pub title: String,
+    /// Weighted mean mastery of the problem's skills.
+    pub mastery: f64,
+}
+
+/// What `triage_backlog` did. Nothing is suspended unless `overdue > threshold`.
+#[derive(Serialize, Debug)]
+pub struct TriageReport {
+    pub overdue: i64,
+    pub threshold: i64,
+    pub kept: Vec<TriagedProblem>,
+    pub suspended: Vec<TriagedProblem>,
+}
+
+// --- Exam Cram ---
+
+#[derive(Serialize, Debug)]
+pub struct ExamCram {
+    pub until: String, // YYYY-MM-DD, last cram day
+    pub started_at: i64,
+    pub days_left: i64,
+}
+
+// --- Tags ---
+
+#[derive(Serialize, Debug)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: i64,
+}
+
+// --- Search ---
+
+#[derive(Serialize, Debug)]
+pub struct SearchHit {
+    /// "problem", "alternative", "note" or "explanation".
+    pub kind: String,
+    pub problem_id: i64,
+    pub title: String,
+    /// Matching excerpt with terms wrapped in `<mark>` tags. Everything else is raw user
+    /// text, so escape it before rendering as HTML.
+    pub snippet: String,
+}
+
+// --- History Replay ---
+
+/// A scheduler for `replay_history` to run the attempt log through.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[serde(tag = "scheduler", rename_all = "snake_case")]
+pub enum SchedulerConfig {
+    /// The built-in scheduler. With `target_retention` set, intervals come from each
+    /// concept's forgetting curve, as with the `target_retention` setting.
+    Sm2 { target_retention: Option<f64> },
+    /// FSRS-4.5 with its default weights, aiming for `desired_retention` at each review.
+    Fsrs { desired_retention: f64 },
+}
+
+#[derive(Serialize, Debug)]
+pub struct ReplayReport {
+    pub scheduler: SchedulerConfig,
+    pub concepts: usize,
+    /// Reviews (attempts after a concept's first) actually done, and the share recalled.
+    pub actual_reviews: usize,
+    pub actual_retention: Option<f64>,
+    /// Reviews the scheduler would have asked for up to now, and the mean predicted
+    /// recall at those reviews.
+    pub simulated_reviews: usize,
+    pub simulated_retention: Option<f64>,
+}
+
+// --- Learner Simulation ---
+
+/// A synthetic learner for `simulate_learners`.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+pub struct LearnerProfile {
+    /// Chance (0-1) of solving a new Easy problem unaided; Medium and Hard are harder.
+    pub ability: f64,
+    /// Days until recall of a freshly learned concept falls to 1/e.
+    pub initial_stability_days: f64,
+    /// Factor each successful review multiplies the memory's stability by.
+    pub stability_growth: f64,
+}
+
+/// One parameter set to simulate: who learns, how much they practice, and the scheduler
+/// settings to run them under.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+pub struct SimulationParams {
+    pub learner: LearnerProfile,
+    pub days: i64,
+    /// Problems attempted per day; due reviews come first, as in the app.
+    pub daily_problems: usize,
+    /// Learners to average over.
+    pub runs: usize,
+    /// Value for the `target_retention` setting; `None` keeps plain SM-2.
+    pub target_retention: Option<f64>,
+    /// Makes the run reproducible: the same seed and parameters give the same result for
+    /// the same end date. `None` seeds from entropy.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// The local day the run ends at the start of; `None` is today.
+    #[serde(default)]
+    pub end_date: Option<NaiveDate>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SimulationResult {
+    pub params: SimulationParams,
+    pub reviews_per_day: f64,
+    pub new_per_day: f64,
+    /// Share of reviews the learner recalled.
+    pub review_recall_rate: Option<f64>,
+    /// Mean recall probability over every concept seen, at the end of the run.
+    pub retention_at_end: f64,
+    pub mean_mastery: f64,
+    /// Reviews still due at the end: work the daily budget could not keep up with.
+    pub backlog_at_end: f64,
+}
+
+// --- Serve History ---
+
+/// What became of a served problem. A serve is abandoned once something else is served or
+/// its reservation lapses without an attempt.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ServeOutcome {
+    Attempted,
+    Abandoned,
+    Pending,
+}
+
+impl FromStr for ServeOutcome {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "attempted" => Ok(ServeOutcome::Attempted),
+            "abandoned" => Ok(ServeOutcome::Abandoned),
+            "pending" => Ok(ServeOutcome::Pending),
+            _ => Err(format!("Unknown serve outcome '{}'", s)),
+        }
+    }
+}
+
+/// One problem `get_next_problem` served, and why.
+#[derive(Serialize, Debug)]
+pub struct ServedProblem {
+    pub id: i64,
+    pub problem_id: i64,
+    pub title: String,
+    pub queue: QueueKind,
+    pub served_at: i64,
+    pub outcome: ServeOutcome,
+    pub attempt_id: Option<i64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ServeHistory {
+    /// Newest first.
+    pub serves: Page<ServedProblem>,
+    /// Over the whole history, not just this page.
+    pub attempted: i64,
+    pub abandoned: i64,
+    pub pending: i64,
+}
+
+// --- Skips ---
+
+/// Why a served problem was declined. Only `TooHard` feeds back into scheduling.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    TooHard,
+    NotNow,
+    NotInterested,
+}
+
+impl SkipReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipReason::TooHard => "too_hard",
+            SkipReason::NotNow => "not_now",
+            SkipReason::NotInterested => "not_interested",
+        }
+    }
+}
+
+// --- Audit Log ---
+
+#[derive(Serialize, Debug)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub timestamp: i64,
+    /// "problem" (a `problem_state` row) or "skill" (a `skill_state` row).
+    pub entity: String,
+    pub entity_id: i64,
+    /// Column values before the change; `None` when the row was created.
+    pub old_value: Option<serde_json::Value>,
+    /// Column values after the change; `None` when the row was deleted.
+    pub new_value: Option<serde_json::Value>,
+    pub cause: String,
+    pub attempt_id: Option<i64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RestoreReport {
+    pub timestamp: i64,
+    pub problems_restored: usize,
+    pub skills_restored: usize,
+    /// Rows whose problem or skill has since been deleted.
+    pub skipped: usize,
+    /// Copy of the database taken before anything was rolled back.
+    pub backup_path: Option<String>,
+}
+
+// --- Usage Telemetry ---
+
+#[derive(Serialize, Debug)]
+pub struct CommandUsage {
+    pub command: String,
+    pub calls: i64,
+    pub errors: i64,
+    pub avg_ms: f64,
+    pub max_ms: i64,
+    /// Calls divided by the days in the window.
+    pub per_day: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct UsageStats {
+    pub enabled: bool,
+    pub days: i64,
+    /// Includes `app_start`, recorded once per launch. Most used first.
+    pub commands: Vec<CommandUsage>,
+}
+
+// --- Review Notifications ---
+
+#[derive(Serialize, Debug)]
+pub struct NotificationSchedule {
+    /// Local hour the daily notification goes out.
+    pub hour: u32,
+    /// Start of the usual study window; None until there are enough recent attempts.
+    pub learned_hour: Option<u32>,
+    /// The `notification_hour` setting, when valid.
+    pub override_hour: Option<u32>,
+    pub attempts_considered: usize,
+    pub notified_today: bool,
+}
+
+// --- Learned Difficulty Multipliers ---
+
+/// How much mastery credit problems of one difficulty earn, fitted to the user's history.
+#[derive(Serialize, Debug, Clone)]
+pub struct DifficultyFit {
+    pub difficulty: Difficulty,
+    pub default_multiplier: f64,
+    /// What `update_mastery_logic` uses; the default until there are attempts to fit.
+    pub multiplier: f64,
+    pub attempts: i64,
+    /// Share of attempts failed or solved only after reading the solution.
+    pub fail_rate: Option<f64>,
+    pub mean_minutes: Option<f64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DifficultyModel {
+    /// None until the first fit.
+    pub fitted_at: Option<i64>,
+    /// Easy to Hard.
+    pub fits: Vec<DifficultyFit>,
+}
+
+// --- Skill Tree ---
+
+/// One skill with its mastery belief. `alpha` and `beta` parameterize a Beta distribution
+/// over the true mastery, so "0.7 from 20 attempts" and "0.7 from 2 lucky solves" differ
+/// in `uncertainty` rather than in `mastery`.
+#[derive(Serialize, Debug)]
+pub struct SkillNode {
+    pub skill_id: i64,
+    pub name: String,
+    pub mastery: f64,
+    pub attempts: i32,
+    pub alpha: f64,
+    pub beta: f64,
+    /// Standard deviation of the belief.
+    pub uncertainty: f64,
+    pub prereq_ids: Vec<i64>,
+    pub unlocked: bool,
+}
+
+/// What stands between a skill and unlocking: where each prerequisite is against the
+/// thresholds of the unlock policy, and how many more attempts it should take to meet them.
+#[derive(Serialize, Debug)]
+pub struct UnlockRequirements {
+    pub skill_id: i64,
+    pub skill: String,
+    pub rule: UnlockRule,
+    pub unlocked: bool,
+    /// Set under `UnlockRule::Weighted`, where the average decides rather than any single
+    /// prerequisite.
+    pub weighted: Option<WeightedUnlockProgress>,
+    pub prerequisites: Vec<PrereqProgress>,
+}
+
+/// Average prerequisite mastery against the unlock threshold, with the uncertainty of that
+/// average against `max_uncertainty`.
+#[derive(Serialize, Debug)]
+pub struct WeightedUnlockProgress {
+    pub met: bool,
+    pub average_mastery: f64,
+    pub unlock_threshold: f64,
+    pub uncertainty: f64,
+    pub max_uncertainty: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PrereqProgress {
+    pub skill_id: i64,
+    pub skill: String,
+    /// Under `UnlockRule::Weighted`, whether the average is met.
+    pub met: bool,
+    pub mastery: f64,
+    pub unlock_threshold: f64,
+    pub attempts: i32,
+    pub consolidation_threshold: f64,
+    /// Below `consolidation_threshold` mastery, the prerequisite needs this many attempts.
+    pub consolidation_attempts: i64,
+    pub uncertainty: f64,
+    pub max_uncertainty: f64,
+    /// Projected from recent gains as in `estimate_time_to_mastery`; `None` when out of reach,
+    /// or under `UnlockRule::Weighted` while the average is unmet.
+    pub attempts_remaining: Option<i64>,
+    pub days_remaining: Option<f64>,
+}
+
+// --- Time to Mastery ---
+
+/// How many more problems, and days at the recent practice rate, a skill needs to reach
+/// the unlock and consolidation thresholds. `None` means out of reach: no recent practice
+/// (days only) or more than `TIME_TO_MASTERY_MAX_ATTEMPTS` problems. Decay is ignored.
+#[derive(Serialize, Debug)]
+pub struct MasteryEta {
+    pub skill_id: i64,
+    pub skill: String,
+    pub mastery: f64,
+    pub attempts: i32,
+    /// Average learning-rate multiplier of recent attempts, failures included.
+    pub gain_per_attempt: f64,
+    pub attempts_per_day: f64,
+    pub unlock_threshold: f64,
+    pub problems_to_unlock: Option<i64>,
+    pub days_to_unlock: Option<f64>,
+    pub consolidation_threshold: f64,
+    pub problems_to_consolidate: Option<i64>,
+    pub days_to_consolidate: Option<f64>,
+}
+
+// --- Review Quality ---
+
+/// The SM-2 branch a review took (see `pedagogy::review_branch`).
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewBranch {
+    /// Unsolved or solution read: ease drops and the interval restarts.
+    Fail,
+    /// Solved, but slowly: ease drops and the interval shrinks.
+    Struggle,
+    /// The interval grows by the ease factor.
+    Normal,
+    /// Fast: ease rises and the interval grows extra.
+    Speed,
+}
+
+/// How many reviews took each branch.
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct ReviewBranchCounts {
+    pub fail: i64,
+    pub struggle: i64,
+    pub normal: i64,
+    pub speed: i64,
+}
+
+impl ReviewBranchCounts {
+    pub fn add(&mut self, branch: ReviewBranch) {
+        match branch {
+            ReviewBranch::Fail => self.fail += 1,
+            ReviewBranch::Struggle => self.struggle += 1,
+            ReviewBranch::Normal => self.normal += 1,
+            ReviewBranch::Speed => self.speed += 1,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ReviewQualityWeek {
+    /// Monday, `YYYY-MM-DD`.
+    pub week_start: String,
+    pub counts: ReviewBranchCounts,
+    /// Average ease factor set by that week's attempts; None without any.
+    pub mean_ease: Option<f64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SkillReviewQuality {
+    pub skill_id: i64,
+    pub skill: String,
+    pub counts: ReviewBranchCounts,
+}
+
+/// Review outcomes over a date range: healthy ease factors show mostly normal and speed
+/// reviews, while a growing fail or struggle share means intervals are stretching too far.
+#[derive(Serialize, Debug)]
+pub struct ReviewQualityStats {
+    pub from: String,
+    pub to: String,
+    pub total: ReviewBranchCounts,
+    /// Oldest first; weeks without reviews are included.
+    pub weeks: Vec<ReviewQualityWeek>,
+    /// Skills with at least one review, most reviewed first.
+    pub skills: Vec<SkillReviewQuality>,
 }
 
 // Used for seeding
+
+/// The bundled problem pack. `version` goes up whenever the problems change, so existing
+/// databases pick the changes up on their next start.
 #[derive(Deserialize)]
+pub struct JsonPack {
+    pub version: i64,
+    pub problems: Vec<JsonProblem>,
+    /// Translated skill names: skill -> locale -> name.
+    #[serde(default)]
+    pub skill_names: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// A pack file in the packs folder: the bundled pack's format, or just its problem list.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum JsonPackFile {
+    Versioned(JsonPack),
+    Problems(Vec<JsonProblem>),
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct JsonProblem {
     pub id: i64,
     pub title: String,
     pub difficulty: String,
     pub category: String,
+    /// Further skills the problem practices, credited at `weight` instead of in full.
+    #[serde(default)]
+    pub secondary_categories: Vec<JsonSecondaryCategory>,
     pub url: String,
     #[serde(default)]
     pub alternatives: Vec<JsonAlternative>,
+    /// Translated titles by locale.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub titles: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct JsonSecondaryCategory {
+    pub category: String,
+    #[serde(default = "default_secondary_weight")]
+    pub weight: f64,
+}
+
+fn default_secondary_weight() -> f64 {
+    SKILL_WEIGHT_SECONDARY
 }
 
 //  struct for the nested data
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct JsonAlternative {
     pub id: i64,
     pub title: String,
     pub difficulty: String,
     pub url: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub titles: BTreeMap<String, String>,
 }
 
 // Internal State Models
+
+/// An `attempt_timers` row. `paused_seconds` counts finished pauses only.
+#[derive(Clone, Copy)]
+pub struct StoredTimer {
+    pub started_at: i64,
+    pub paused_at: Option<i64>,
+    pub pause_count: i64,
+    pub paused_seconds: i64,
+}
+
+/// One attempt of a concept, as needed to judge whether it was a fast, clean solve.
+#[derive(Clone)]
+pub struct TimedOutcome {
+    pub time_minutes: f64,
+    pub clean: bool,
+    /// The user's override if set.
+    pub difficulty: Difficulty,
+    pub expected_minutes_override: Option<f64>,
+}
+#[derive(Clone)]
 pub struct ProblemRepetitionState {
     pub problem_id: i64,
     pub ease_factor: f64,
@@ -105,8 +1495,173 @@ pub struct ProblemRepetitionState {
     pub next_review_ts: i64,
 }
 
+#[derive(Clone)]
 pub struct SkillMasteryState {
     pub skill_id: i64,
     pub mastery: f64,
     pub attempts: i32,
 }
+
+impl SkillMasteryState {
+    /// Beta(alpha, beta) belief about the skill's true mastery: centered on `mastery` and
+    /// worth `attempts` plus `MASTERY_PRIOR_ATTEMPTS` observations.
+    pub fn beta(&self) -> (f64, f64) {
+        let n = self.attempts.max(0) as f64 + MASTERY_PRIOR_ATTEMPTS;
+        let mean = self.mastery.clamp(0.0, 1.0);
+        (mean * n, (1.0 - mean) * n)
+    }
+
+    /// Standard deviation of `beta()`: how far the true mastery may be from the estimate.
+    /// Mastery is clamped by `MASTERY_UNCERTAINTY_EPSILON` first, so 0 or 1 is never certain.
+    pub fn uncertainty(&self) -> f64 {
+        let mean = self.mastery.clamp(
+            MASTERY_UNCERTAINTY_EPSILON,
+            1.0 - MASTERY_UNCERTAINTY_EPSILON,
+        );
+        let n = self.attempts.max(0) as f64 + MASTERY_PRIOR_ATTEMPTS;
+        (mean * (1.0 - mean) / (n + 1.0)).sqrt()
+    }
+}
+
+/// How prerequisites gate a skill; the `unlock_policy` setting.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UnlockRule {
+    /// Every prerequisite must be met.
+    #[default]
+    All,
+    /// One met prerequisite is enough.
+    Any,
+    /// Average prerequisite mastery must reach the unlock threshold, so a strong
+    /// prerequisite can carry a weaker one.
+    Weighted,
+}
+
+impl FromStr for UnlockRule {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(UnlockRule::All),
+            "any" => Ok(UnlockRule::Any),
+            "weighted" => Ok(UnlockRule::Weighted),
+            _ => Err(format!("Unknown unlock policy: {}", s)),
+        }
+    }
+}
+
+/// Unlock rule and thresholds, from settings with the constants as defaults.
+#[derive(Debug, Clone)]
+pub struct UnlockPolicy {
+    pub rule: UnlockRule,
+    pub unlock_threshold: f64,
+    pub consolidation_threshold: f64,
+    pub consolidation_attempts: i64,
+    pub max_uncertainty: f64,
+}
+
+impl Default for UnlockPolicy {
+    fn default() -> Self {
+        UnlockPolicy {
+            rule: UnlockRule::All,
+            unlock_threshold: MASTERY_UNLOCK_THRESHOLD,
+            consolidation_threshold: MASTERY_CONSOLIDATION_THRESHOLD,
+            consolidation_attempts: ATTEMPTS_CONSOLIDATION_THRESHOLD,
+            max_uncertainty: MASTERY_UNLOCK_MAX_UNCERTAINTY,
+        }
+    }
+}
+
+impl UnlockPolicy {
+    /// Whether a skill is strong enough, and known well enough, to count as a met
+    /// prerequisite.
+    pub fn meets_prereq(&self, state: &SkillMasteryState) -> bool {
+        state.mastery >= self.unlock_threshold
+            && (state.mastery >= self.consolidation_threshold
+                || state.attempts as i64 >= self.consolidation_attempts)
+            && state.uncertainty() <= self.max_uncertainty
+    }
+
+    /// Whether a skill with these prerequisite states is unlocked. `None` is a prerequisite
+    /// without a state row, which is never met.
+    pub fn is_unlocked(&self, prereqs: &[Option<&SkillMasteryState>]) -> bool {
+        if prereqs.is_empty() {
+            return true;
+        }
+        let met = |p: &Option<&SkillMasteryState>| p.is_some_and(|s| self.meets_prereq(s));
+        match self.rule {
+            UnlockRule::All => prereqs.iter().all(met),
+            UnlockRule::Any => prereqs.iter().any(met),
+            UnlockRule::Weighted => {
+                let (average, uncertainty) = Self::weighted_average(prereqs);
+                average >= self.unlock_threshold && uncertainty <= self.max_uncertainty
+            }
+        }
+    }
+
+    /// Average mastery of non-empty `prereqs` and the standard deviation of that average,
+    /// as `UnlockRule::Weighted` judges them.
+    pub fn weighted_average(prereqs: &[Option<&SkillMasteryState>]) -> (f64, f64) {
+        let n = prereqs.len() as f64;
+        let total: f64 = prereqs.iter().map(|p| p.map_or(0.0, |s| s.mastery)).sum();
+        // Standard deviation of the average, treating the estimates as independent
+        let variance: f64 = prereqs
+            .iter()
+            .map(|p| p.map_or(0.25, |s| s.uncertainty().powi(2)))
+            .sum();
+        (total / n, variance.sqrt() / n)
+    }
+}
+
+pub struct AttemptRecord {
+    pub problem_id: i64,
+    pub time_minutes: f64,
+    pub solved: bool,
+    pub read_solution: bool,
+    pub timestamp: i64,
+    /// Whether an earlier attempt exists for the same concept (parent or alternative).
+    pub is_review: bool,
+    /// Parent problem ID for alternatives, otherwise the problem itself.
+    pub concept_id: i64,
+    pub difficulty: Difficulty,
+}
+
+/// A review as `get_review_quality_stats` needs it to tell which SM-2 branch it took.
+pub struct ReviewAttempt {
+    pub attempt_id: i64,
+    pub timestamp: i64,
+    pub time_minutes: f64,
+    pub clean: bool,
+    /// The user's override if set.
+    pub difficulty: Difficulty,
+    pub expected_minutes_override: Option<f64>,
+}
+
+pub struct MasteryChange {
+    pub skill_id: i64,
+    pub attempt_id: Option<i64>,
+    pub old_mastery: f64,
+    pub new_mastery: f64,
+    pub timestamp: i64,
+}
+
+/// A track problem joined with everything the Notion sync compares.
+pub struct SyncRow {
+    pub problem_id: i64,
+    pub title: String,
+    pub url: Option<String>,
+    pub difficulty: String,
+    /// (interval_days, next_review_ts) if the problem has been attempted.
+    pub repetition: Option<(f64, i64)>,
+    pub suspended: bool,
+    pub note: Option<String>,
+    pub synced_status: Option<String>,
+    pub synced_note: Option<String>,
+}
+
+pub struct SkillActivity {
+    pub skill_id: i64,
+    /// Latest attempt credited to the skill.
+    pub last_attempt_ts: Option<i64>,
+    /// Latest decay step applied to it.
+    pub last_decay_ts: Option<i64>,
+}
@@ -0,0 +1,289 @@
+// src/pdf_report.rs
+
+use crate::models::{DateRange, SkillMasteryState};
+use crate::reports;
+use crate::repository::Repository;
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone};
+use printpdf::{
+    BuiltinFont, Color, IndirectFontRef, Line, Mm, PdfDocument, PdfLayerReference, Point, Polygon,
+    Rect, Rgb,
+};
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::fs::File;
+use std::io::BufWriter;
+
+const PAGE_WIDTH: f32 = 210.0; // A4, mm
+const PAGE_HEIGHT: f32 = 297.0;
+const HEATMAP_MAX_WEEKS: i64 = 26;
+
+/// Writes a one-page progress report (mastery radar, activity heatmap,
+/// difficulty breakdown) for the given date range to `path`.
+pub fn export_report_pdf(
+    conn: &Connection,
+    path: &str,
+    range: Option<&DateRange>,
+) -> Result<(), String> {
+    let repo = Repository::new(conn);
+    let (from, to) = reports::resolve_range(range)?;
+    let start_ts = reports::local_midnight(from);
+    let end_ts = reports::local_midnight(to + Duration::days(1)) - 1;
+
+    let attempts = repo
+        .get_attempts_between(start_ts, end_ts)
+        .map_err(|e| e.to_string())?;
+    let states = repo.get_all_skill_states().map_err(|e| e.to_string())?;
+    let names = repo.get_skill_names().map_err(|e| e.to_string())?;
+    let breakdown = repo
+        .get_difficulty_breakdown(start_ts, end_ts)
+        .map_err(|e| e.to_string())?;
+
+    let (doc, page, layer) = PdfDocument::new(
+        "LeetGraph Progress Report",
+        Mm(PAGE_WIDTH),
+        Mm(PAGE_HEIGHT),
+        "Report",
+    );
+    let layer = doc.get_page(page).get_layer(layer);
+    let font = doc
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(|e| e.to_string())?;
+    let bold = doc
+        .add_builtin_font(BuiltinFont::HelveticaBold)
+        .map_err(|e| e.to_string())?;
+
+    // Header
+    layer.use_text(
+        "LeetGraph Progress Report",
+        20.0,
+        Mm(15.0),
+        Mm(280.0),
+        &bold,
+    );
+    layer.use_text(
+        format!("{} to {}", from, to),
+        10.0,
+        Mm(15.0),
+        Mm(273.0),
+        &font,
+    );
+    let clean = attempts
+        .iter()
+        .filter(|a| a.solved && !a.read_solution)
+        .count();
+    let minutes: f64 = attempts.iter().map(|a| a.time_minutes).sum();
+    layer.use_text(
+        format!(
+            "{} attempts, {} clean solves, {:.1} hours practiced",
+            attempts.len(),
+            clean,
+            minutes / 60.0
+        ),
+        10.0,
+        Mm(15.0),
+        Mm(266.0),
+        &font,
+    );
+
+    // Charts
+    draw_mastery_radar(&layer, &font, &bold, &states, &names);
+    draw_difficulty_breakdown(&layer, &font, &bold, &breakdown);
+
+    let mut per_day: HashMap<NaiveDate, usize> = HashMap::new();
+    for a in &attempts {
+        if let Some(dt) = Local.timestamp_opt(a.timestamp, 0).single() {
+            *per_day.entry(dt.date_naive()).or_insert(0) += 1;
+        }
+    }
+    draw_activity_heatmap(&layer, &font, &bold, from, to, &per_day);
+
+    let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    doc.save(&mut BufWriter::new(file))
+        .map_err(|e| format!("Failed to write PDF: {}", e))
+}
+
+fn draw_mastery_radar(
+    layer: &PdfLayerReference,
+    font: &IndirectFontRef,
+    bold: &IndirectFontRef,
+    states: &[SkillMasteryState],
+    names: &HashMap<i64, String>,
+) {
+    let (cx, cy, radius) = (62.0, 195.0, 40.0);
+    layer.use_text("Skill Mastery", 13.0, Mm(15.0), Mm(252.0), bold);
+    if states.len() < 3 {
+        return;
+    }
+
+    let n = states.len() as f32;
+    let at = |i: usize, r: f32| {
+        let angle = PI / 2.0 - 2.0 * PI * i as f32 / n;
+        (cx + r * angle.cos(), cy + r * angle.sin())
+    };
+
+    // Grid rings and spokes
+    layer.set_outline_color(grey(0.8));
+    layer.set_outline_thickness(0.5);
+    for ring in [0.25, 0.5, 0.75, 1.0] {
+        layer.add_line(Line {
+            points: (0..states.len())
+                .map(|i| {
+                    let (x, y) = at(i, radius * ring);
+                    (point(x, y), false)
+                })
+                .collect(),
+            is_closed: true,
+        });
+    }
+    for i in 0..states.len() {
+        let (x, y) = at(i, radius);
+        layer.add_line(Line {
+            points: vec![(point(cx, cy), false), (point(x, y), false)],
+            is_closed: false,
+        });
+    }
+
+    // Mastery shape
+    layer.set_fill_color(rgb(0.55, 0.7, 0.95));
+    layer.add_polygon(Polygon {
+        rings: vec![states
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let (x, y) = at(i, radius * s.mastery.clamp(0.02, 1.0) as f32);
+                (point(x, y), false)
+            })
+            .collect()],
+        ..Default::default()
+    });
+
+    // Labels
+    layer.set_fill_color(grey(0.1));
+    for (i, s) in states.iter().enumerate() {
+        let (x, y) = at(i, radius + 4.0);
+        let name = names.get(&s.skill_id).map(String::as_str).unwrap_or("?");
+        let label = format!("{} {:.0}%", name, s.mastery * 100.0);
+        // Right-align left-side labels so they don't run into the chart
+        let x = if x < cx {
+            x - label.len() as f32 * 1.2
+        } else {
+            x
+        };
+        layer.use_text(label, 6.0, Mm(x), Mm(y), font);
+    }
+}
+
+fn draw_difficulty_breakdown(
+    layer: &PdfLayerReference,
+    font: &IndirectFontRef,
+    bold: &IndirectFontRef,
+    breakdown: &[(String, i64, i64)],
+) {
+    let (left, bottom, height) = (130.0, 160.0, 75.0);
+    layer.use_text("Difficulty Breakdown", 13.0, Mm(125.0), Mm(252.0), bold);
+
+    let max = breakdown
+        .iter()
+        .map(|(_, solved, failed)| solved + failed)
+        .max()
+        .unwrap_or(0)
+        .max(1) as f32;
+    for (i, (difficulty, solved, failed)) in breakdown.iter().enumerate() {
+        let x = left + i as f32 * 22.0;
+        let solved_h = height * *solved as f32 / max;
+        let failed_h = height * *failed as f32 / max;
+        layer.set_fill_color(rgb(0.3, 0.7, 0.4));
+        layer.add_rect(Rect::new(
+            Mm(x),
+            Mm(bottom),
+            Mm(x + 14.0),
+            Mm(bottom + solved_h),
+        ));
+        layer.set_fill_color(rgb(0.85, 0.35, 0.35));
+        layer.add_rect(Rect::new(
+            Mm(x),
+            Mm(bottom + solved_h),
+            Mm(x + 14.0),
+            Mm(bottom + solved_h + failed_h),
+        ));
+        layer.set_fill_color(grey(0.1));
+        layer.use_text(difficulty.as_str(), 8.0, Mm(x), Mm(bottom - 6.0), font);
+        layer.use_text(
+            format!("{}/{}", solved, solved + failed),
+            7.0,
+            Mm(x),
+            Mm(bottom + solved_h + failed_h + 2.0),
+            font,
+        );
+    }
+    layer.set_fill_color(rgb(0.3, 0.7, 0.4));
+    layer.add_rect(Rect::new(Mm(130.0), Mm(145.0), Mm(133.0), Mm(148.0)));
+    layer.set_fill_color(rgb(0.85, 0.35, 0.35));
+    layer.add_rect(Rect::new(Mm(160.0), Mm(145.0), Mm(163.0), Mm(148.0)));
+    layer.set_fill_color(grey(0.1));
+    layer.use_text("Clean solve", 7.0, Mm(135.0), Mm(145.5), font);
+    layer.use_text("Failed", 7.0, Mm(165.0), Mm(145.5), font);
+}
+
+fn draw_activity_heatmap(
+    layer: &PdfLayerReference,
+    font: &IndirectFontRef,
+    bold: &IndirectFontRef,
+    from: NaiveDate,
+    to: NaiveDate,
+    per_day: &HashMap<NaiveDate, usize>,
+) {
+    let (left, top, cell) = (25.0, 120.0, 5.5);
+    layer.use_text("Activity", 13.0, Mm(15.0), Mm(130.0), bold);
+
+    // Only the most recent weeks fit on the page
+    let first = from.max(to - Duration::weeks(HEATMAP_MAX_WEEKS) + Duration::days(1));
+    let first_monday = first - Duration::days(weekday_index(first));
+    let max = per_day.values().copied().max().unwrap_or(0).max(1) as f32;
+
+    layer.set_fill_color(grey(0.1));
+    for (row, label) in ["Mon", "Wed", "Fri"].iter().enumerate() {
+        let y = top - (row * 2) as f32 * cell - cell + 1.0;
+        layer.use_text(*label, 6.0, Mm(15.0), Mm(y), font);
+    }
+
+    let mut day = first;
+    while day <= to {
+        let col = (day - first_monday).num_days() / 7;
+        let row = weekday_index(day);
+        let x = left + col as f32 * cell;
+        let y = top - (row + 1) as f32 * cell;
+        let count = per_day.get(&day).copied().unwrap_or(0);
+        let color = if count == 0 {
+            grey(0.92)
+        } else {
+            let t = 0.3 + 0.7 * count as f32 / max;
+            rgb(0.9 - 0.7 * t, 0.95 - 0.35 * t, 0.9 - 0.6 * t)
+        };
+        layer.set_fill_color(color);
+        layer.add_rect(Rect::new(
+            Mm(x),
+            Mm(y),
+            Mm(x + cell - 0.8),
+            Mm(y + cell - 0.8),
+        ));
+        day += Duration::days(1);
+    }
+}
+
+fn weekday_index(day: NaiveDate) -> i64 {
+    day.weekday().num_days_from_monday() as i64
+}
+
+fn point(x: f32, y: f32) -> Point {
+    Point::new(Mm(x), Mm(y))
+}
+
+fn rgb(r: f32, g: f32, b: f32) -> Color {
+    Color::Rgb(Rgb::new(r, g, b, None))
+}
+
+fn grey(v: f32) -> Color {
+    rgb(v, v, v)
+}
@@ -1,106 +1,477 @@
 // src/pedagogy.rs
 
+use crate::audit;
+use crate::clock;
 use crate::constants::*;
-use crate::models::{AttemptLog, Difficulty, ProblemView};
-use crate::repository;
-use chrono::Utc;
+use crate::exam_cram;
+use crate::irt;
+use crate::models::{
+    AttemptDraft, AttemptError, AttemptLog, AttemptResult, BacklogPostpone, Difficulty, MasteryEta,
+    PrereqProgress, ProblemRepetitionState, ProblemStatus, ProblemView, QueueKind, QuickLogOutcome,
+    ReviewBranch, ReviewOrder, SkillMasteryState, SkillNode, SkillUnlocked, SkipReason,
+    UnlockPolicy, UnlockRequirements, UnlockRule, WeightedUnlockProgress,
+};
+use crate::plan;
+use crate::reports;
+use crate::repository::{ProblemRepository, Repository};
+use crate::retention;
+use crate::selection_rng;
+use crate::serve_log;
+use chrono::{Duration, NaiveDate, Utc};
 use log::{debug, info, warn};
-use rusqlite::Connection;
+use rand::Rng;
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+use std::collections::{HashMap, HashSet};
 
 // --- Public Interface ---
 
+/// Serves the first upcoming problem (review, then discovery, then cram) and reserves its
+/// concept for `RESERVATION_TTL_SECONDS`, so another window or a refresh moves on to the
+/// next item instead of showing the same one. Reserved items are still served when
+/// nothing else is left. In starred practice mode only starred concepts are served.
 pub fn get_next_problem(conn: &Connection) -> Result<Option<ProblemView>, String> {
+    let repo = Repository::new(conn);
+    debug!("Requesting next problem...");
+    let mode = repo
+        .get_setting(SETTING_PRACTICE_MODE)
+        .map_err(|e| e.to_string())?;
+    let candidates = if let Some(cram) = exam_cram::get_exam_cram(conn, Utc::now().timestamp())? {
+        exam_cram::next_problems(conn, &cram, RESERVATION_LOOKAHEAD)?
+    } else if mode.as_deref() == Some(PRACTICE_MODE_STARRED) {
+        repo.find_starred_problems(RESERVATION_LOOKAHEAD)
+            .map_err(|e| e.to_string())?
+    } else {
+        get_next_problems(conn, RESERVATION_LOOKAHEAD)?
+    };
+    serve_first_unreserved(conn, candidates)
+}
+
+/// Like `get_next_problem`, limited to concepts carrying `tag`: its due reviews, then its
+/// untouched problems, then its other tracked problems, soonest review first.
+pub fn get_next_problem_with_tag(
+    conn: &Connection,
+    tag: &str,
+) -> Result<Option<ProblemView>, String> {
+    let repo = Repository::new(conn);
+    debug!("Requesting next problem tagged '{}'...", tag);
     let now = Utc::now().timestamp();
+    let n = RESERVATION_LOOKAHEAD;
+    let mut candidates: Vec<ProblemView> = repo
+        .find_due_reviews_with_tag(tag, now, n)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|parent| serve_review(conn, parent))
+        .collect();
+    if candidates.len() < n {
+        candidates.extend(
+            repo.find_new_problems_with_tag(tag, n)
+                .map_err(|e| e.to_string())?,
+        );
+    }
+    if candidates.len() < n {
+        candidates.extend(
+            repo.find_upcoming_reviews_with_tag(tag, now, n)
+                .map_err(|e| e.to_string())?,
+        );
+    }
+    serve_first_unreserved(conn, candidates)
+}
+
+/// Serves the first candidate not reserved by an earlier call (or the first one if all
+/// are) and reserves its concept. Concepts skipped within `SKIP_COOLDOWN_SECONDS` are
+/// never served.
+fn serve_first_unreserved(
+    conn: &Connection,
+    mut candidates: Vec<ProblemView>,
+) -> Result<Option<ProblemView>, String> {
+    let repo = Repository::new(conn);
+    let now = Utc::now().timestamp();
+    let skipped = repo
+        .get_skipped_concepts_since(now - SKIP_COOLDOWN_SECONDS)
+        .map_err(|e| e.to_string())?;
+    candidates.retain(|p| !skipped.contains(&concept_id(p)));
+    if candidates.is_empty() {
+        info!("No problems available.");
+        return Ok(None);
+    }
+    let reserved = repo
+        .get_active_reservations(now)
+        .map_err(|e| e.to_string())?;
+    let pick = candidates
+        .iter()
+        .position(|p| !reserved.contains(&concept_id(p)))
+        .unwrap_or(0);
+    let problem = candidates.swap_remove(pick);
+
+    match problem.queue {
+        QueueKind::Review => info!("Serving Due Review: {} (ID: {})", problem.title, problem.id),
+        QueueKind::AlternativeFor { parent_id } => info!(
+            "Serving Review Alternative: {} (ID: {}) for Parent ID: {}",
+            problem.title, problem.id, parent_id
+        ),
+        QueueKind::Discovery => info!("Serving Discovery: {} (ID: {})", problem.title, problem.id),
+        QueueKind::Starred => info!("Serving Starred: {} (ID: {})", problem.title, problem.id),
+        QueueKind::Assessment => {
+            info!("Serving Assessment: {} (ID: {})", problem.title, problem.id)
+        }
+        QueueKind::Cram => warn!(
+            "No new content/reviews available. Entering Cram Mode: {} (ID: {})",
+            problem.title, problem.id
+        ),
+    }
+
+    // Best effort: a read-only database can still serve problems
+    let expires_at = now + RESERVATION_TTL_SECONDS;
+    if let Err(e) = repo.reserve_problem(concept_id(&problem), expires_at) {
+        warn!("Could not reserve problem {}: {}", problem.id, e);
+    }
+    serve_log::record(conn, &problem, now);
+    Ok(Some(problem))
+}
+
+/// The concept a served problem schedules: the parent for alternatives.
+pub fn concept_id(p: &ProblemView) -> i64 {
+    match p.queue {
+        QueueKind::AlternativeFor { parent_id } => parent_id,
+        _ => p.id,
+    }
+}
+
+/// Up to `n` distinct upcoming problems in the order `get_next_problem` would serve them:
+/// due reviews, then discovery, then cram. Each concept appears at most once.
+pub fn get_next_problems(conn: &Connection, n: usize) -> Result<Vec<ProblemView>, String> {
+    get_next_problems_at(conn, n, Utc::now().timestamp())
+}
+
+/// `get_next_problems` as of `now`, for running the queue on a virtual clock.
+pub fn get_next_problems_at(
+    conn: &Connection,
+    n: usize,
+    now: i64,
+) -> Result<Vec<ProblemView>, String> {
+    let repo = Repository::new(conn);
     let track_id = 1;
-    debug!("Requesting next problem...");
+    let mut queue: Vec<ProblemView> = Vec::new();
+    let mut concepts: HashSet<i64> = HashSet::new();
+    let mut push = |queue: &mut Vec<ProblemView>, p: ProblemView| {
+        if queue.len() < n && concepts.insert(concept_id(&p)) {
+            queue.push(p);
+        }
+    };
 
-    // 1. Review
-    if let Ok(Some(parent_problem)) = repository::find_due_review(conn, now) {
-        // ... (Keep existing Review logic regarding alternatives) ...
-        if let Ok(Some(alt_problem)) = repository::get_random_alternative(conn, parent_problem.id) {
-            info!(
-                "Serving Review Alternative: {} (ID: {}) for Parent: {} (ID: {})",
-                alt_problem.title, alt_problem.id, parent_problem.title, parent_problem.id
-            );
-            return Ok(Some(alt_problem));
+    let order = repo
+        .get_setting_or(SETTING_REVIEW_ORDER, ReviewOrder::default())
+        .map_err(|e| e.to_string())?;
+    for parent in repo
+        .find_due_reviews(now, n, order)
+        .map_err(|e| e.to_string())?
+    {
+        push(&mut queue, serve_review(conn, parent));
+    }
+    // Interview deadline mode: in the final days, breadth comes first, so anything that
+    // would not come up again before the interview is reviewed now
+    let interview = plan::interview_deadline(&repo, now)?.filter(|i| i.in_final_days());
+    if let Some(interview) = &interview {
+        if queue.len() < n {
+            for parent in repo
+                .find_reviews_scheduled_after(interview.starts_at, n)
+                .map_err(|e| e.to_string())?
+            {
+                push(&mut queue, serve_review(conn, parent));
+            }
+        }
+    }
+    if queue.len() < n {
+        let unlocked_skills = get_unlocked_skills(&repo)?;
+        debug!("Unlocked Skill IDs: {:?}", unlocked_skills);
+        let soft = repo
+            .get_setting_or(SETTING_SOFT_PREREQUISITES, false)
+            .map_err(|e| e.to_string())?;
+        // A study plan narrows discovery to its skills and to today's new-problem quota
+        let quota = plan::todays_quota(conn, now)?;
+        let discovery_skills: Vec<i64> = match &quota {
+            Some(q) => unlocked_skills
+                .iter()
+                .copied()
+                .filter(|s| q.skill_ids.contains(s))
+                .collect(),
+            None => unlocked_skills.clone(),
+        };
+        let mut discovery = if soft {
+            soft_discovery(conn, track_id, n)?
+        } else {
+            // Rank a wider pool so the best-targeted problems are not cut off by the limit
+            let mut pool = repo
+                .find_new_problems_for_skills(
+                    track_id,
+                    &discovery_skills,
+                    IRT_CANDIDATE_POOL.max(n),
+                )
+                .map_err(|e| e.to_string())?;
+            irt::rank_by_target(&irt::skill_abilities(conn)?, &mut pool);
+            pool.truncate(n);
+            pool
+        };
+        if let Some(q) = &quota {
+            discovery.retain(|p| p.skill_ids.iter().any(|s| q.skill_ids.contains(s)));
+            discovery.truncate(q.new_remaining);
+        }
+        if interview.is_some() {
+            discovery.retain(|p| p.difficulty != Difficulty::Hard);
+        }
+        for p in discovery {
+            push(&mut queue, p);
+        }
+        if queue.len() < n {
+            // Ask for extra: cram may repeat concepts already queued as reviews
+            let mode = repo
+                .get_setting(SETTING_CRAM_MODE)
+                .map_err(|e| e.to_string())?;
+            let cram = if mode.as_deref() == Some(CRAM_MODE_WEIGHTED) {
+                weighted_cram(conn, track_id, &unlocked_skills, n + queue.len())?
+            } else {
+                repo.find_cram_problems(track_id, &unlocked_skills, n + queue.len())
+                    .map_err(|e| e.to_string())?
+            };
+            for p in cram {
+                push(&mut queue, p);
+            }
         }
-        info!(
-            "Serving Due Review: {} (ID: {})",
-            parent_problem.title, parent_problem.id
-        );
-        return Ok(Some(parent_problem));
     }
+    Ok(queue)
+}
+
+/// Discovery across every skill, in a weighted shuffle: problems of unlocked skills weigh 1
+/// and problems of locked skills weigh their prerequisites' readiness (see
+/// `skill_readiness`), so advanced users can still reach them.
+fn soft_discovery(conn: &Connection, track_id: i64, n: usize) -> Result<Vec<ProblemView>, String> {
+    let repo = Repository::new(conn);
+    let states = repo.get_all_skill_states().map_err(|e| e.to_string())?;
+    let prereqs = repo.get_skill_prereqs().map_err(|e| e.to_string())?;
+    let readiness = skill_readiness(&states, &prereqs, &get_unlock_policy(&repo)?);
+    let all_skills: Vec<i64> = states.iter().map(|s| s.skill_id).collect();
+    let pool = repo
+        .find_new_problems_for_skills(track_id, &all_skills, SOFT_PREREQ_POOL)
+        .map_err(|e| e.to_string())?;
+
+    // Weighted sampling without replacement (Efraimidis-Spirakis): sort by u^(1/w)
+    let mut keyed: Vec<(f64, ProblemView)> = selection_rng::with_rng(conn, |rng| {
+        pool.into_iter()
+            .map(|p| {
+                let weight = p
+                    .skill_ids
+                    .iter()
+                    .filter_map(|sid| readiness.get(sid))
+                    .fold(1.0_f64, |w, r| w.min(*r))
+                    .max(SOFT_PREREQ_MIN_WEIGHT);
+                (rng.gen::<f64>().powf(1.0 / weight), p)
+            })
+            .collect()
+    })
+    .map_err(|e| e.to_string())?;
+    keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+    Ok(keyed.into_iter().take(n).map(|(_, p)| p).collect())
+}
 
-    // 2. Discovery
-    let unlocked_skills = repository::get_unlocked_skills(conn).map_err(|e| e.to_string())?;
-    debug!("Unlocked Skill IDs: {:?}", unlocked_skills);
+/// Cram spread over the `SETTING_CRAM_SKILLS` weakest unlocked skills: each pick draws a
+/// skill with probability proportional to its mastery gap (1 - mastery, at least
+/// `CRAM_MIN_MASTERY_GAP`), then takes that skill's next problem.
+fn weighted_cram(
+    conn: &Connection,
+    track_id: i64,
+    unlocked_skills: &[i64],
+    n: usize,
+) -> Result<Vec<ProblemView>, String> {
+    let repo = Repository::new(conn);
+    let k = repo
+        .get_setting_or(SETTING_CRAM_SKILLS, CRAM_WEIGHTED_SKILLS)
+        .map_err(|e| e.to_string())?
+        .max(1);
+    let mut weakest: Vec<SkillMasteryState> = repo
+        .get_all_skill_states()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|s| unlocked_skills.contains(&s.skill_id))
+        .collect();
+    weakest.sort_by(|a, b| a.mastery.total_cmp(&b.mastery));
+    weakest.truncate(k);
 
-    if let Ok(Some(p)) = repository::find_new_problem_for_skills(conn, track_id, &unlocked_skills) {
-        info!("Serving Discovery: {} (ID: {})", p.title, p.id);
-        return Ok(Some(p));
+    let mut pools = Vec::new();
+    for skill in &weakest {
+        let problems = repo
+            .find_cram_problems(track_id, &[skill.skill_id], n)
+            .map_err(|e| e.to_string())?;
+        let gap = (1.0 - skill.mastery).max(CRAM_MIN_MASTERY_GAP);
+        pools.push((gap, problems.into_iter()));
     }
 
-    // 3. Cram (Grind lowest mastery)
-    // PASS THE UNLOCKED SKILLS HERE
-    if let Ok(Some(p)) = repository::find_cram_problem(conn, track_id, &unlocked_skills) {
-        warn!(
-            "No new content/reviews available. Entering Cram Mode: {} (ID: {})",
-            p.title, p.id
-        );
-        return Ok(Some(p));
+    let mut picked = Vec::new();
+    selection_rng::with_rng(conn, |rng| {
+        while picked.len() < n {
+            let total: f64 = pools.iter().map(|(gap, _)| gap).sum();
+            if total <= 0.0 {
+                break;
+            }
+            let mut r = rng.gen::<f64>() * total;
+            let i = pools
+                .iter()
+                .position(|(gap, _)| {
+                    r -= gap;
+                    r < 0.0
+                })
+                .or_else(|| pools.iter().rposition(|(gap, _)| *gap > 0.0))
+                .unwrap_or(0);
+            match pools[i].1.next() {
+                Some(p) => picked.push(p),
+                // An exhausted skill drops out of the draw
+                None => pools[i].0 = 0.0,
+            }
+        }
+    })
+    .map_err(|e| e.to_string())?;
+    Ok(picked)
+}
+
+/// A due review as served: with its past retrospectives, and swapped for a random
+/// alternative when the concept has one.
+fn serve_review(conn: &Connection, mut parent_problem: ProblemView) -> ProblemView {
+    let repo = Repository::new(conn);
+    // Surface past retrospectives so the user re-reads what went wrong last time
+    let retrospectives = repo
+        .get_retrospectives(parent_problem.id)
+        .unwrap_or_default();
+
+    if let Ok(Some(mut alt_problem)) = repo.get_random_alternative(parent_problem.id) {
+        alt_problem.retrospectives = retrospectives;
+        return alt_problem;
     }
+    parent_problem.retrospectives = retrospectives;
+    parent_problem
+}
 
-    info!("No problems available.");
-    Ok(None)
+/// Records an attempt, or replays the stored result if its `attempt_key` was seen before.
+pub fn process_attempt(conn: &Connection, log: &AttemptLog) -> Result<AttemptResult, String> {
+    let repo = Repository::new(conn);
+    let key = log
+        .attempt_key
+        .as_deref()
+        .map(str::trim)
+        .filter(|k| !k.is_empty());
+    let Some(key) = key else {
+        return record_attempt(conn, log);
+    };
+    if let Some(stored) = repo
+        .get_attempt_key_result(key)
+        .map_err(|e| e.to_string())?
+    {
+        info!(
+            "Duplicate attempt key {}, returning the original result",
+            key
+        );
+        return serde_json::from_str(&stored).map_err(|e| e.to_string());
+    }
+    let result = record_attempt(conn, log)?;
+    let stored = serde_json::to_string(&result).map_err(|e| e.to_string())?;
+    repo.save_attempt_key(key, &stored, Utc::now().timestamp())
+        .map_err(|e| e.to_string())?;
+    Ok(result)
 }
 
-pub fn process_attempt(conn: &Connection, log: &AttemptLog) -> Result<(), String> {
-    let now = Utc::now().timestamp();
+fn record_attempt(conn: &Connection, log: &AttemptLog) -> Result<AttemptResult, String> {
+    let repo = Repository::new(conn);
+    let now = match log.timestamp {
+        Some(ts) => ts,
+        None => clock::now(conn)?,
+    };
     info!("Processing attempt for Submitted ID: {}", log.problem_id);
 
+    let mut warnings = Vec::new();
+    let (mut validated, time_clamped) = validate_attempt(conn, log, &mut warnings)?;
+    repo.delete_attempt_timer(validated.problem_id)
+        .map_err(|e| e.to_string())?;
+    // The attempt consumes its draft; the draft's notes stand in for a missing explanation
+    if let Some(draft) = repo
+        .take_attempt_draft(validated.problem_id)
+        .map_err(|e| e.to_string())?
+    {
+        if validated.explanation.is_none() {
+            validated.explanation = draft.notes;
+        }
+    }
+    let log = &validated;
+
     // 1. Resolve Parent (For SM-2 / Memory protection)
     // We still want to schedule the review based on the "Concept" (Parent)
-    let (parent_id, _is_alternative) =
-        repository::resolve_parent_id(conn, log.problem_id).map_err(|e| e.to_string())?;
+    let (parent_id, _is_alternative) = repo
+        .resolve_parent_id(log.problem_id)
+        .map_err(|e| e.to_string())?;
 
     // 2. Get Metadata (FIXED)
     // We try to fetch skills for the SPECIFIC problem you solved (e.g., Two Sum).
     // If the specific problem isn't in the problems table (it's a pure alternative),
     // we fallback to the Parent's skills.
-    let (difficulty, mut skill_ids) = repository::get_problem_metadata(conn, log.problem_id)
+    let (difficulty, mut skills) = repo
+        .get_problem_metadata(log.problem_id)
         .unwrap_or_else(|_| {
             // Fallback: Use parent metadata if specific lookup fails
-            repository::get_problem_metadata(conn, parent_id)
+            repo.get_problem_metadata(parent_id)
                 .unwrap_or((Difficulty::Medium, vec![]))
         });
 
+    // The user's own rating wins over the catalog for time and multiplier lookups
+    let difficulty = repo
+        .get_difficulty_override(log.problem_id)
+        .map_err(|e| e.to_string())?
+        .unwrap_or(difficulty);
+
     // Edge Case: If the specific lookup worked but returned no skills (weird data), try parent
-    if skill_ids.is_empty() {
-        if let Ok((_, parent_skills)) = repository::get_problem_metadata(conn, parent_id) {
-            skill_ids = parent_skills;
+    if skills.is_empty() {
+        if let Ok((_, parent_skills)) = repo.get_problem_metadata(parent_id) {
+            skills = parent_skills;
         }
     }
 
     debug!(
-        "Crediting Skills: {:?} for Problem ID: {}",
-        skill_ids, log.problem_id
+        "Crediting Skills (id, weight): {:?} for Problem ID: {}",
+        skills, log.problem_id
     );
 
+    let unlocked_before = get_unlocked_skills(&repo)?;
+
+    // Read the past explanation before logging, so we never echo back the one just written
+    let previous_explanation = repo
+        .get_latest_explanation(parent_id)
+        .map_err(|e| e.to_string())?;
+
     // 3. Log Attempt
-    repository::log_attempt(
-        conn,
-        log.problem_id,
-        log.time_minutes,
-        log.solved,
-        log.read_solution,
-        now,
-    )
-    .map_err(|e| e.to_string())?;
+    let attempt_id = repo
+        .log_attempt(log, now, time_clamped)
+        .map_err(|e| e.to_string())?;
+    // Served problems are reserved under their own ID, alternatives under the parent's
+    for id in [log.problem_id, parent_id] {
+        repo.clear_reservation(id).map_err(|e| e.to_string())?;
+    }
+    repo.mark_serve_attempted(log.problem_id, parent_id, attempt_id, now)
+        .map_err(|e| e.to_string())?;
+    repo.tag_attempt_mistakes(attempt_id, &log.mistakes)
+        .map_err(|e| e.to_string())?;
+
+    // Failed or grit attempts get a reflection prompt, shown on the next review (keyed by parent)
+    let expected_minutes = repo
+        .get_expected_minutes_override(log.problem_id)
+        .map_err(|e| e.to_string())?
+        .unwrap_or_else(|| expected_time(difficulty));
+    let time_ratio = log.time_minutes / expected_minutes;
+    if let Some(prompt) = retrospective_prompt(log, time_ratio) {
+        repo.add_retrospective(attempt_id, parent_id, prompt, now)
+            .map_err(|e| e.to_string())?;
+    }
 
     // 4. Update Repetition State (SM-2 Logic) -> ON PARENT ID
     // Keep this on Parent so you don't memorize duplicates
-    let prior_attempts_parent =
-        repository::get_attempt_count(conn, parent_id).map_err(|e| e.to_string())?;
+    let prior_attempts_parent = repo
+        .get_attempt_count(parent_id)
+        .map_err(|e| e.to_string())?;
 
     let logic_log = AttemptLog {
         problem_id: parent_id,
@@ -109,52 +480,824 @@ pub fn process_attempt(conn: &Connection, log: &AttemptLog) -> Result<(), String
         read_solution: log.read_solution,
         // preserve whether the user revealed skills on the original attempt
         revealed_skills: log.revealed_skills,
+        mistakes: Vec::new(),
+        explanation: None,
+        attempt_key: None,
+        timestamp: log.timestamp,
+        pause_count: log.pause_count,
+        paused_minutes: log.paused_minutes,
     };
 
-    update_repetition_logic(conn, &logic_log, difficulty, prior_attempts_parent, now)?;
+    let retired = audit::with_cause(conn, AUDIT_CAUSE_ATTEMPT, Some(attempt_id), || {
+        schedule_attempt(
+            &repo,
+            &logic_log,
+            AttemptContext {
+                difficulty,
+                expected_minutes,
+                prior_attempts: prior_attempts_parent,
+                attempt_id,
+                now,
+            },
+            &skills,
+            &mut warnings,
+        )
+    })?;
 
-    // 5. Update Skill Mastery -> ON SPECIFIC SKILLS (FIXED)
-    // Now this will update "Arrays" when you solve "Two Sum"
-    update_mastery_logic(conn, &logic_log, difficulty, &skill_ids)?;
+    // 6. Skills whose prerequisites this attempt completed
+    let names = repo.get_skill_names().map_err(|e| e.to_string())?;
+    let mut unlocked_skills = Vec::new();
+    for skill_id in get_unlocked_skills(&repo)? {
+        if unlocked_before.contains(&skill_id) {
+            continue;
+        }
+        info!("Skill {} unlocked by attempt {}", skill_id, attempt_id);
+        repo.log_skill_unlock(skill_id, Some(attempt_id), now)
+            .map_err(|e| e.to_string())?;
+        unlocked_skills.push(SkillUnlocked {
+            skill_id,
+            name: names.get(&skill_id).cloned().unwrap_or_default(),
+        });
+    }
+
+    Ok(AttemptResult {
+        previous_explanation,
+        warnings,
+        unlocked_skills,
+        time_clamped,
+        retired,
+    })
+}
+
+/// Skills whose prerequisites satisfy the configured unlock policy (see `UnlockPolicy`).
+pub fn get_unlocked_skills(repo: &impl ProblemRepository) -> Result<Vec<i64>, String> {
+    let states = repo.get_all_skill_states().map_err(|e| e.to_string())?;
+    let prereqs = repo.get_skill_prereqs().map_err(|e| e.to_string())?;
+    let policy = get_unlock_policy(repo)?;
+    Ok(unlocked_skill_ids(&states, &prereqs, &policy))
+}
+
+/// The unlock policy from settings. Unparseable values fall back to the defaults and
+/// thresholds are kept within 0..=1.
+pub fn get_unlock_policy(repo: &impl ProblemRepository) -> Result<UnlockPolicy, String> {
+    let defaults = UnlockPolicy::default();
+    let setting = |key, default: f64| {
+        repo.get_setting_or(key, default)
+            .map(|v| v.clamp(0.0, 1.0))
+            .map_err(|e| e.to_string())
+    };
+    Ok(UnlockPolicy {
+        rule: repo
+            .get_setting_or(SETTING_UNLOCK_POLICY, defaults.rule)
+            .map_err(|e| e.to_string())?,
+        unlock_threshold: setting(SETTING_UNLOCK_THRESHOLD, defaults.unlock_threshold)?,
+        consolidation_threshold: setting(
+            SETTING_CONSOLIDATION_THRESHOLD,
+            defaults.consolidation_threshold,
+        )?,
+        consolidation_attempts: repo
+            .get_setting_or(
+                SETTING_CONSOLIDATION_ATTEMPTS,
+                defaults.consolidation_attempts,
+            )
+            .map_err(|e| e.to_string())?
+            .max(0),
+        max_uncertainty: setting(SETTING_UNLOCK_MAX_UNCERTAINTY, defaults.max_uncertainty)?,
+    })
+}
+
+/// How ready each skill is, from 0 to 1: unlocked skills are fully ready, locked ones score
+/// the average of their prerequisites' progress toward the unlock threshold.
+pub fn skill_readiness(
+    states: &[SkillMasteryState],
+    prereqs: &[(i64, i64)],
+    policy: &UnlockPolicy,
+) -> HashMap<i64, f64> {
+    let unlocked: HashSet<i64> = unlocked_skill_ids(states, prereqs, policy)
+        .into_iter()
+        .collect();
+    let mastery: HashMap<i64, f64> = states.iter().map(|s| (s.skill_id, s.mastery)).collect();
+    states
+        .iter()
+        .map(|s| {
+            if unlocked.contains(&s.skill_id) {
+                return (s.skill_id, 1.0);
+            }
+            let progress: Vec<f64> = prereqs
+                .iter()
+                .filter(|(skill_id, _)| *skill_id == s.skill_id)
+                .map(|(_, prereq_id)| {
+                    let m = mastery.get(prereq_id).copied().unwrap_or(0.0);
+                    if policy.unlock_threshold > 0.0 {
+                        (m / policy.unlock_threshold).min(1.0)
+                    } else {
+                        1.0
+                    }
+                })
+                .collect();
+            let readiness = if progress.is_empty() {
+                1.0
+            } else {
+                progress.iter().sum::<f64>() / progress.len() as f64
+            };
+            (s.skill_id, readiness)
+        })
+        .collect()
+}
+
+/// Pure unlock evaluation, so callers can also ask "what was unlocked" for past states.
+pub fn unlocked_skill_ids(
+    states: &[SkillMasteryState],
+    prereqs: &[(i64, i64)],
+    policy: &UnlockPolicy,
+) -> Vec<i64> {
+    let by_id: HashMap<i64, &SkillMasteryState> = states.iter().map(|s| (s.skill_id, s)).collect();
+    states
+        .iter()
+        .map(|s| s.skill_id)
+        .filter(|&sid| {
+            let prereq_states: Vec<Option<&SkillMasteryState>> = prereqs
+                .iter()
+                .filter(|(skill_id, _)| *skill_id == sid)
+                .map(|(_, prereq_id)| by_id.get(prereq_id).copied())
+                .collect();
+            policy.is_unlocked(&prereq_states)
+        })
+        .collect()
+}
+
+/// Every skill with its mastery, how sure that estimate is, its prerequisites and whether
+/// it is unlocked.
+pub fn get_skill_tree(conn: &Connection) -> Result<Vec<SkillNode>, String> {
+    let repo = Repository::new(conn);
+    let states = repo.get_all_skill_states().map_err(|e| e.to_string())?;
+    let prereqs = repo.get_skill_prereqs().map_err(|e| e.to_string())?;
+    let names = repo.get_skill_names().map_err(|e| e.to_string())?;
+    let unlocked: HashSet<i64> = unlocked_skill_ids(&states, &prereqs, &get_unlock_policy(&repo)?)
+        .into_iter()
+        .collect();
+    Ok(states
+        .iter()
+        .map(|s| {
+            let (alpha, beta) = s.beta();
+            SkillNode {
+                skill_id: s.skill_id,
+                name: names.get(&s.skill_id).cloned().unwrap_or_default(),
+                mastery: s.mastery,
+                attempts: s.attempts,
+                alpha,
+                beta,
+                uncertainty: s.uncertainty(),
+                prereq_ids: prereqs
+                    .iter()
+                    .filter(|(skill_id, _)| *skill_id == s.skill_id)
+                    .map(|(_, prereq_id)| *prereq_id)
+                    .collect(),
+                unlocked: unlocked.contains(&s.skill_id),
+            }
+        })
+        .collect())
+}
+
+/// Projects when a skill reaches the unlock and consolidation thresholds if the user keeps
+/// practicing it like over the last `TIME_TO_MASTERY_WINDOW_DAYS`: each further attempt
+/// earns the window's average gain, at the window's pace.
+pub fn estimate_time_to_mastery(
+    conn: &Connection,
+    skill_id: i64,
+    now: i64,
+) -> Result<MasteryEta, String> {
+    let repo = Repository::new(conn);
+    if !repo.skill_exists(skill_id).map_err(|e| e.to_string())? {
+        return Err(format!("Skill {} does not exist", skill_id));
+    }
+    let state = repo.get_skill_state(skill_id).map_err(|e| e.to_string())?;
+    let policy = get_unlock_policy(&repo)?;
+    let changes: Vec<_> = repo
+        .get_mastery_changes_since(now - TIME_TO_MASTERY_WINDOW_DAYS * DAY_SECONDS)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|c| c.skill_id == skill_id && c.attempt_id.is_some())
+        .collect();
+
+    // Undo the learning rate each change was made at, walking back from today's attempts
+    let mut attempts_before = state.attempts;
+    let mut gains = Vec::new();
+    for c in changes.iter().rev() {
+        attempts_before = (attempts_before - 1).max(0);
+        let alpha = effective_alpha(c.old_mastery, attempts_before);
+        if alpha > 0.0 {
+            gains.push((c.new_mastery - c.old_mastery) / alpha);
+        }
+    }
+    let gain = if gains.is_empty() {
+        TIME_TO_MASTERY_DEFAULT_GAIN
+    } else {
+        gains.iter().sum::<f64>() / gains.len() as f64
+    };
+    let attempts_per_day = changes.len() as f64 / TIME_TO_MASTERY_WINDOW_DAYS as f64;
+
+    let mut projected = state.clone();
+    let (mut to_unlock, mut to_consolidate) = (None, None);
+    for n in 0..=TIME_TO_MASTERY_MAX_ATTEMPTS {
+        if to_unlock.is_none() && policy.meets_prereq(&projected) {
+            to_unlock = Some(n);
+        }
+        if to_consolidate.is_none() && projected.mastery >= policy.consolidation_threshold {
+            to_consolidate = Some(n);
+        }
+        if to_unlock.is_some() && to_consolidate.is_some() {
+            break;
+        }
+        let delta = effective_alpha(projected.mastery, projected.attempts) * gain;
+        projected.mastery = (projected.mastery + delta).clamp(0.0, 1.0);
+        projected.attempts += 1;
+    }
+    let days = |problems: Option<i64>| match problems {
+        Some(0) => Some(0.0),
+        Some(n) if attempts_per_day > 0.0 => Some(n as f64 / attempts_per_day),
+        _ => None,
+    };
+
+    Ok(MasteryEta {
+        skill_id,
+        skill: repo
+            .get_skill_names()
+            .map_err(|e| e.to_string())?
+            .remove(&skill_id)
+            .unwrap_or_default(),
+        mastery: state.mastery,
+        attempts: state.attempts,
+        gain_per_attempt: gain,
+        attempts_per_day,
+        unlock_threshold: policy.unlock_threshold,
+        problems_to_unlock: to_unlock,
+        days_to_unlock: days(to_unlock),
+        consolidation_threshold: policy.consolidation_threshold,
+        problems_to_consolidate: to_consolidate,
+        days_to_consolidate: days(to_consolidate),
+    })
+}
 
+/// Progress of each prerequisite of `skill_id` toward the unlock policy's thresholds.
+pub fn get_unlock_requirements(
+    conn: &Connection,
+    skill_id: i64,
+    now: i64,
+) -> Result<UnlockRequirements, String> {
+    let repo = Repository::new(conn);
+    if !repo.skill_exists(skill_id).map_err(|e| e.to_string())? {
+        return Err(format!("Skill {} does not exist", skill_id));
+    }
+    let states = repo.get_all_skill_states().map_err(|e| e.to_string())?;
+    let prereqs = repo.get_skill_prereqs().map_err(|e| e.to_string())?;
+    let names = repo.get_skill_names().map_err(|e| e.to_string())?;
+    let policy = get_unlock_policy(&repo)?;
+    let unlocked = unlocked_skill_ids(&states, &prereqs, &policy).contains(&skill_id);
+    let prereq_ids: Vec<i64> = prereqs
+        .iter()
+        .filter(|(id, _)| *id == skill_id)
+        .map(|&(_, prereq_id)| prereq_id)
+        .collect();
+
+    // The weighted rule judges the average, so no prerequisite is met or unmet on its own
+    let weighted = (policy.rule == UnlockRule::Weighted && !prereq_ids.is_empty()).then(|| {
+        let prereq_states: Vec<Option<&SkillMasteryState>> = prereq_ids
+            .iter()
+            .map(|id| states.iter().find(|s| s.skill_id == *id))
+            .collect();
+        let (average_mastery, uncertainty) = UnlockPolicy::weighted_average(&prereq_states);
+        WeightedUnlockProgress {
+            met: unlocked,
+            average_mastery,
+            unlock_threshold: policy.unlock_threshold,
+            uncertainty,
+            max_uncertainty: policy.max_uncertainty,
+        }
+    });
+
+    let prerequisites = prereq_ids
+        .into_iter()
+        .map(|prereq_id| {
+            let state = repo.get_skill_state(prereq_id).map_err(|e| e.to_string())?;
+            let met = weighted
+                .as_ref()
+                .map_or_else(|| policy.meets_prereq(&state), |w| w.met);
+            let (attempts_remaining, days_remaining) = if met {
+                (Some(0), Some(0.0))
+            } else if weighted.is_some() {
+                (None, None)
+            } else {
+                let eta = estimate_time_to_mastery(conn, prereq_id, now)?;
+                (eta.problems_to_unlock, eta.days_to_unlock)
+            };
+            Ok(PrereqProgress {
+                skill_id: prereq_id,
+                skill: names.get(&prereq_id).cloned().unwrap_or_default(),
+                met,
+                mastery: state.mastery,
+                unlock_threshold: policy.unlock_threshold,
+                attempts: state.attempts,
+                consolidation_threshold: policy.consolidation_threshold,
+                consolidation_attempts: policy.consolidation_attempts,
+                uncertainty: state.uncertainty(),
+                max_uncertainty: policy.max_uncertainty,
+                attempts_remaining,
+                days_remaining,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(UnlockRequirements {
+        skill_id,
+        skill: names.get(&skill_id).cloned().unwrap_or_default(),
+        rule: policy.rule,
+        unlocked,
+        weighted,
+        prerequisites,
+    })
+}
+
+/// Fades mastery of skills left idle for more than `DECAY_GRACE_DAYS`, one step per whole
+/// idle day. Safe to call repeatedly: each decay is logged and the next run resumes from it.
+/// A skill falling below `reactivate_below_mastery` gets some retired problems back.
+/// Returns how many skills changed.
+pub fn apply_decay(conn: &Connection, now: i64) -> Result<usize, String> {
+    let repo = Repository::new(conn);
+    let activity = repo.get_skill_activity().map_err(|e| e.to_string())?;
+    let reactivate_below = repo
+        .get_setting_or(SETTING_REACTIVATE_BELOW, MASTERY_UNLOCK_THRESHOLD)
+        .map_err(|e| e.to_string())?;
+    let mut changed = 0;
+    for a in activity {
+        let Some(last_attempt) = a.last_attempt_ts else {
+            continue;
+        };
+        let skill_id = a.skill_id;
+        let from =
+            (last_attempt + DECAY_GRACE_DAYS * DAY_SECONDS).max(a.last_decay_ts.unwrap_or(0));
+        let days = (now - from) / DAY_SECONDS;
+        if days < 1 {
+            continue;
+        }
+        let mut state = repo.get_skill_state(skill_id).map_err(|e| e.to_string())?;
+        if state.mastery <= 0.0 {
+            continue;
+        }
+        let old_mastery = state.mastery;
+        state.mastery *= (1.0 - DECAY_RATE_PER_DAY).powi(days as i32);
+        audit::with_cause(conn, AUDIT_CAUSE_DECAY, None, || {
+            repo.update_skill_state(&state).map_err(|e| e.to_string())
+        })?;
+        // Stamp the end of the last whole day so partial days carry over to the next run
+        repo.log_mastery_change(
+            skill_id,
+            None,
+            old_mastery,
+            state.mastery,
+            "decay",
+            from + days * DAY_SECONDS,
+        )
+        .map_err(|e| e.to_string())?;
+        debug!(
+            "[Decay] Skill {}: {:.3} -> {:.3} after {} idle days",
+            skill_id, old_mastery, state.mastery, days
+        );
+        if old_mastery >= reactivate_below && state.mastery < reactivate_below {
+            reactivate_retired(&repo, skill_id, now)?;
+        }
+        changed += 1;
+    }
+    Ok(changed)
+}
+
+/// Brings up to `REACTIVATE_PER_SKILL` of the skill's retired problems back into the review
+/// queue, due now, so a fading skill gets practiced again.
+fn reactivate_retired(
+    repo: &impl ProblemRepository,
+    skill_id: i64,
+    now: i64,
+) -> Result<(), String> {
+    let retired = repo
+        .find_suspended_for_skill(skill_id, SUSPEND_REASON_RETIRED, REACTIVATE_PER_SKILL)
+        .map_err(|e| e.to_string())?;
+    for problem_id in retired {
+        repo.unsuspend_problem(problem_id)
+            .map_err(|e| e.to_string())?;
+        repo.set_next_review_ts(problem_id, now)
+            .map_err(|e| e.to_string())?;
+        info!(
+            "Problem {} reactivated: skill {} decayed below threshold",
+            problem_id, skill_id
+        );
+    }
     Ok(())
 }
 
-// --- Internal Algorithm Logic ---
+/// Rewrites a concept's schedule so it reads back as `status`, for statuses set by hand
+/// outside the app (e.g. in Notion). Suspending keeps the schedule; any other status lifts
+/// a suspension.
+pub fn apply_manual_status(
+    conn: &Connection,
+    problem_id: i64,
+    status: ProblemStatus,
+    now: i64,
+) -> Result<(), String> {
+    let repo = Repository::new(conn);
+    audit::with_cause(conn, AUDIT_CAUSE_MANUAL, None, || {
+        if status != ProblemStatus::Suspended {
+            repo.unsuspend_problem(problem_id)
+                .map_err(|e| e.to_string())?;
+        }
+        let mut state = repo
+            .get_problem_repetition_state(problem_id)
+            .map_err(|e| e.to_string())?;
+        let interval = state.interval_days.max(INTERVAL_MIN);
+        let (interval_days, next_review_ts) = match status {
+            ProblemStatus::Suspended => {
+                return repo
+                    .suspend_problem(problem_id, SUSPEND_REASON_MANUAL, now)
+                    .map_err(|e| e.to_string());
+            }
+            ProblemStatus::New => {
+                return repo
+                    .delete_problem_repetition_state(problem_id)
+                    .map_err(|e| e.to_string());
+            }
+            ProblemStatus::Learning => {
+                let interval = interval.min(STATUS_MASTERED_INTERVAL - 1.0);
+                (interval, now + (interval * DAY_SECONDS as f64) as i64)
+            }
+            ProblemStatus::Review => (interval, now),
+            ProblemStatus::Mastered => {
+                let interval = interval.max(STATUS_MASTERED_INTERVAL);
+                (interval, now + (interval * DAY_SECONDS as f64) as i64)
+            }
+        };
+        state.interval_days = interval_days;
+        state.next_review_ts = next_review_ts;
+        repo.save_problem_repetition_state(&state)
+            .map_err(|e| e.to_string())
+    })
+}
 
-fn update_repetition_logic(
+/// Overwrites a skill's mastery by hand, e.g. to fix values after an import. The change is
+/// logged to `skill_mastery_log` as `manual: <reason>`.
+pub fn set_skill_mastery(
     conn: &Connection,
-    log: &AttemptLog,
-    difficulty: Difficulty,
-    prior_attempts: i64,
+    skill_id: i64,
+    value: f64,
+    reason: &str,
     now: i64,
 ) -> Result<(), String> {
-    let mut state = repository::get_problem_repetition_state(conn, log.problem_id)
+    let repo = Repository::new(conn);
+    if !(0.0..=1.0).contains(&value) {
+        return Err("Mastery must be between 0 and 1".to_string());
+    }
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err("A reason is required for manual mastery changes".to_string());
+    }
+    if !repo.skill_exists(skill_id).map_err(|e| e.to_string())? {
+        return Err(format!("Skill {} does not exist", skill_id));
+    }
+    let mut state = repo.get_skill_state(skill_id).map_err(|e| e.to_string())?;
+    let old_mastery = state.mastery;
+    state.mastery = value;
+    audit::with_cause(conn, AUDIT_CAUSE_MANUAL, None, || {
+        repo.update_skill_state(&state).map_err(|e| e.to_string())
+    })?;
+    repo.log_mastery_change(
+        skill_id,
+        None,
+        old_mastery,
+        value,
+        &format!("manual: {}", reason),
+        now,
+    )
+    .map_err(|e| e.to_string())?;
+    info!(
+        "Skill {} mastery set by hand: {:.3} -> {:.3} ({})",
+        skill_id, old_mastery, value, reason
+    );
+    Ok(())
+}
+
+/// Puts the concept behind `problem_id` into the review pipeline, due at `when`. Untracked
+/// problems start with a fresh schedule; tracked ones keep theirs and are only rescheduled.
+pub fn add_to_review_queue(conn: &Connection, problem_id: i64, when: i64) -> Result<(), String> {
+    let repo = Repository::new(conn);
+    if !repo.problem_exists(problem_id).map_err(|e| e.to_string())? {
+        return Err(format!("Problem {} does not exist", problem_id));
+    }
+    let (parent_id, _) = repo
+        .resolve_parent_id(problem_id)
+        .map_err(|e| e.to_string())?;
+    let mut state = repo
+        .get_problem_repetition_state(parent_id)
         .map_err(|e| e.to_string())?;
+    state.interval_days = state.interval_days.max(INTERVAL_MIN);
+    state.next_review_ts = when;
+    info!("Queued Problem {} for review at {}", parent_id, when);
+    audit::with_cause(conn, AUDIT_CAUSE_MANUAL, None, || {
+        repo.save_problem_repetition_state(&state)
+            .map_err(|e| e.to_string())
+    })
+}
 
-    // Snapshot old state for logging
-    let old_ease = state.ease_factor;
-    let old_interval = state.interval_days;
+/// Moves every overdue review into the future, keeping their order: either all to `days`
+/// from now or spread evenly over the next `POSTPONE_SPREAD_DAYS` days, starting tomorrow.
+/// Intervals are left alone. Returns how many reviews moved.
+pub fn postpone_backlog(
+    conn: &Connection,
+    postpone: BacklogPostpone,
+    now: i64,
+) -> Result<usize, String> {
+    if let BacklogPostpone::Days(days) = postpone {
+        if !(1..=INTERVAL_MAX as i64).contains(&days) {
+            return Err(format!(
+                "Postpone by 1 to {} days, not {}",
+                INTERVAL_MAX, days
+            ));
+        }
+    }
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let overdue = Repository::new(&tx)
+        .find_overdue_problem_ids(now)
+        .map_err(|e| e.to_string())?;
+    let tomorrow = plan::local_date(now) + Duration::days(1);
+    let count = overdue.len() as i64;
+    audit::with_cause(&tx, AUDIT_CAUSE_MANUAL, None, || {
+        for (i, problem_id) in (0_i64..).zip(&overdue) {
+            // The +i seconds keeps the original order among reviews moved to the same day
+            let ts = match postpone {
+                BacklogPostpone::Days(days) => now + days * DAY_SECONDS + i,
+                BacklogPostpone::Spread => {
+                    let day = i * POSTPONE_SPREAD_DAYS / count;
+                    reports::local_midnight(tomorrow + Duration::days(day)) + i
+                }
+            };
+            Repository::new(&tx)
+                .set_next_review_ts(*problem_id, ts)
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    })?;
+    tx.commit().map_err(|e| e.to_string())?;
+    info!(
+        "Postponed {} overdue reviews ({:?})",
+        overdue.len(),
+        postpone
+    );
+    Ok(overdue.len())
+}
 
-    let is_new = prior_attempts <= 1; // Since we just logged one, current count is 1+; check is based on *before* this attempt
-    let expected_time = match difficulty {
+/// Declines a served problem: the skip is logged, which holds its concept back from
+/// serving for `SKIP_COOLDOWN_SECONDS`, so the next serve moves on. A `TooHard` skip also nudges the
+/// problem's skills down by `SKIP_TOO_HARD_MASTERY_NUDGE` and shortens a tracked concept's
+/// interval, unless `SETTING_SKIP_NUDGE` is "false".
+pub fn skip_problem(
+    conn: &Connection,
+    problem_id: i64,
+    reason: SkipReason,
+    now: i64,
+) -> Result<(), String> {
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let repo = Repository::new(&tx);
+    let (parent_id, _) = repo
+        .resolve_parent_id(problem_id)
+        .map_err(|e| e.to_string())?;
+    if !repo.problem_exists(parent_id).map_err(|e| e.to_string())? {
+        return Err(format!("Problem {} does not exist", problem_id));
+    }
+    repo.log_skip(problem_id, reason, now)
+        .map_err(|e| e.to_string())?;
+
+    let nudge = repo
+        .get_setting(SETTING_SKIP_NUDGE)
+        .map_err(|e| e.to_string())?
+        .as_deref()
+        != Some("false");
+    if reason == SkipReason::TooHard && nudge {
+        // The skipped problem's own skills, as an attempt would credit them
+        let skills = match repo.get_problem_metadata(problem_id) {
+            Ok((_, skills)) if !skills.is_empty() => skills,
+            _ => {
+                repo.get_problem_metadata(parent_id)
+                    .map_err(|e| e.to_string())?
+                    .1
+            }
+        };
+        audit::with_cause(&tx, AUDIT_CAUSE_SKIP, None, || {
+            for &(skill_id, weight) in &skills {
+                let mut state = repo.get_skill_state(skill_id).map_err(|e| e.to_string())?;
+                let old_mastery = state.mastery;
+                state.mastery = (old_mastery - SKIP_TOO_HARD_MASTERY_NUDGE * weight).max(0.0);
+                repo.update_skill_state(&state).map_err(|e| e.to_string())?;
+                repo.log_mastery_change(
+                    skill_id,
+                    None,
+                    old_mastery,
+                    state.mastery,
+                    "skip: too hard",
+                    now,
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            repo.scale_interval(parent_id, SKIP_TOO_HARD_INTERVAL_FACTOR)
+                .map_err(|e| e.to_string())
+        })?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    info!("Skipped problem {} ({})", problem_id, reason.as_str());
+    Ok(())
+}
+
+// --- Internal Algorithm Logic ---
+
+/// The attempt `quick_log` records: `outcome` as given, no mistakes or explanation, and a
+/// time estimated from history. That is the median of the concept's latest
+/// `QUICK_LOG_HISTORY_ATTEMPTS` attempts, else the user's mean for the difficulty from the
+/// last multiplier fit, else the problem's expected time.
+pub fn quick_log_attempt(
+    conn: &Connection,
+    problem_id: i64,
+    outcome: QuickLogOutcome,
+) -> Result<AttemptLog, String> {
+    let repo = Repository::new(conn);
+    let (parent_id, _) = repo
+        .resolve_parent_id(problem_id)
+        .map_err(|e| e.to_string())?;
+    let mut minutes: Vec<f64> = repo
+        .get_recent_concept_attempts(parent_id, QUICK_LOG_HISTORY_ATTEMPTS)
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|a| a.time_minutes)
+        .collect();
+    minutes.sort_by(f64::total_cmp);
+    let time_minutes = match minutes.len() {
+        0 => {
+            let (difficulty, _) = repo
+                .get_problem_metadata(problem_id)
+                .or_else(|_| repo.get_problem_metadata(parent_id))
+                .map_err(|_| format!("Problem {} not found", problem_id))?;
+            let difficulty = repo
+                .get_difficulty_override(problem_id)
+                .map_err(|e| e.to_string())?
+                .unwrap_or(difficulty);
+            let (fits, _) = repo.get_difficulty_fits().map_err(|e| e.to_string())?;
+            let fitted = fits
+                .iter()
+                .find(|f| f.difficulty == difficulty)
+                .and_then(|f| f.mean_minutes);
+            match fitted {
+                Some(mean) => mean,
+                None => repo
+                    .get_expected_minutes_override(problem_id)
+                    .map_err(|e| e.to_string())?
+                    .unwrap_or_else(|| expected_time(difficulty)),
+            }
+        }
+        n if n % 2 == 0 => (minutes[n / 2 - 1] + minutes[n / 2]) / 2.0,
+        n => minutes[n / 2],
+    };
+
+    Ok(AttemptLog {
+        problem_id,
+        time_minutes,
+        solved: matches!(outcome, QuickLogOutcome::Solved | QuickLogOutcome::Hinted),
+        read_solution: outcome == QuickLogOutcome::ReadSolution,
+        revealed_skills: outcome == QuickLogOutcome::Hinted,
+        mistakes: Vec::new(),
+        explanation: None,
+        attempt_key: None,
+        timestamp: None,
+        pause_count: 0,
+        paused_minutes: 0.0,
+    })
+}
+
+/// Autosaves a problem in progress, replacing its earlier draft. Blank notes or code are
+/// dropped.
+pub fn save_attempt_draft(conn: &Connection, draft: &AttemptDraft, now: i64) -> Result<(), String> {
+    let repo = Repository::new(conn);
+    let mut draft = draft.clone();
+    if !draft.elapsed_minutes.is_finite() || draft.elapsed_minutes < 0.0 {
+        return Err(AttemptError::InvalidTime(draft.elapsed_minutes).to_string());
+    }
+    if !repo
+        .problem_exists(draft.problem_id)
+        .map_err(|e| e.to_string())?
+    {
+        return Err(AttemptError::UnknownProblem(draft.problem_id).to_string());
+    }
+    for text in [&mut draft.notes, &mut draft.code] {
+        if text
+            .as_ref()
+            .is_some_and(|t| t.chars().count() > ATTEMPT_DRAFT_MAX_CHARS)
+        {
+            return Err(format!(
+                "Drafts keep at most {} characters of notes or code",
+                ATTEMPT_DRAFT_MAX_CHARS
+            ));
+        }
+        if text.as_deref().is_some_and(|t| t.trim().is_empty()) {
+            *text = None;
+        }
+    }
+    draft.updated_at = now;
+    repo.save_attempt_draft(&draft).map_err(|e| e.to_string())
+}
+
+pub fn expected_time(difficulty: Difficulty) -> f64 {
+    match difficulty {
         Difficulty::Easy => EXPECTED_TIME_EASY,
         Difficulty::Medium => EXPECTED_TIME_MEDIUM,
         Difficulty::Hard => EXPECTED_TIME_HARD,
-    };
-    let time_ratio = log.time_minutes / expected_time;
-    let is_fail = !log.solved || log.read_solution;
+    }
+}
 
-    debug!(
-        "[SM-2 Input] New: {}, Fail: {}, TimeRatio: {:.2}, Diff: {:?}",
-        is_new, is_fail, time_ratio, difficulty
-    );
+/// How much a problem of this difficulty counts toward skill mastery.
+pub fn difficulty_multiplier(difficulty: Difficulty) -> f64 {
+    match difficulty {
+        Difficulty::Easy => DIFFICULTY_MULTIPLIER_EASY,
+        Difficulty::Medium => DIFFICULTY_MULTIPLIER_MEDIUM,
+        Difficulty::Hard => DIFFICULTY_MULTIPLIER_HARD,
+    }
+}
+
+/// Rejects impossible attempts (see `AttemptError`) and caps times over
+/// `MAX_ATTEMPT_MINUTES`, which are almost always a timer left running. Returns the
+/// attempt to record and whether its time was capped.
+fn validate_attempt(
+    conn: &Connection,
+    log: &AttemptLog,
+    warnings: &mut Vec<String>,
+) -> Result<(AttemptLog, bool), String> {
+    if !log.time_minutes.is_finite() || log.time_minutes < 0.0 {
+        return Err(AttemptError::InvalidTime(log.time_minutes).to_string());
+    }
+    if let Some(ts) = log.timestamp {
+        // Against the held clock, so attempts pinned to it after a backwards jump still pass
+        if ts > clock::now(conn)? + ATTEMPT_CLOCK_SKEW_SECONDS {
+            return Err(AttemptError::FutureTimestamp(ts).to_string());
+        }
+    }
+    if !log.paused_minutes.is_finite()
+        || log.paused_minutes < 0.0
+        || log.paused_minutes > log.time_minutes
+    {
+        return Err(AttemptError::InvalidPause(log.paused_minutes).to_string());
+    }
+    if log.solved && log.time_minutes - log.paused_minutes <= 0.0 {
+        return Err(AttemptError::ZeroTimeSolve.to_string());
+    }
+    if !Repository::new(conn)
+        .problem_exists(log.problem_id)
+        .map_err(|e| e.to_string())?
+    {
+        return Err(AttemptError::UnknownProblem(log.problem_id).to_string());
+    }
+    // Only time spent on the problem is scored and stored
+    let mut log = log.clone();
+    log.time_minutes -= log.paused_minutes;
+    let clamped = log.time_minutes > MAX_ATTEMPT_MINUTES;
+    if clamped {
+        warn!(
+            "Clamping {:.0} minute attempt on {}",
+            log.time_minutes, log.problem_id
+        );
+        warnings.push(format!(
+            "{:.0} minutes looks like a timer left running; recorded as {:.0}",
+            log.time_minutes, MAX_ATTEMPT_MINUTES
+        ));
+        log.time_minutes = MAX_ATTEMPT_MINUTES;
+    }
+    Ok((log, clamped))
+}
 
+fn retrospective_prompt(log: &AttemptLog, time_ratio: f64) -> Option<&'static str> {
+    if !log.solved {
+        Some(RETRO_PROMPT_FAIL)
+    } else if log.read_solution {
+        Some(RETRO_PROMPT_READ_SOLUTION)
+    } else if time_ratio > TIME_RATIO_GRIT {
+        Some(RETRO_PROMPT_GRIT)
+    } else {
+        None
+    }
+}
+
+/// One SM-2 step on `state`'s ease and interval, before retention targets, clamping and
+/// load balancing. Pure, so `replay` can run it over past attempts.
+pub fn sm2_step(state: &mut ProblemRepetitionState, is_new: bool, is_fail: bool, time_ratio: f64) {
     if is_fail {
         state.ease_factor = (state.ease_factor - EASE_FACTOR_DECREMENT_FAIL).max(EASE_FACTOR_MIN);
         state.interval_days = INTERVAL_MIN;
     } else if is_new {
-        if time_ratio > 1.5 {
+        if time_ratio > TIME_RATIO_GRIT {
             // Grit solve (took long)
             debug!("[SM-2 logic] Branch: New Grit");
             state.ease_factor -= EASE_FACTOR_NEUTRAL_GRIT;
@@ -167,55 +1310,278 @@ fn update_repetition_logic(
         }
     } else {
         // Review
-        if time_ratio > 2.0 {
-            // Struggle
-            debug!("[SM-2 logic] Branch: Review Struggle");
-            state.ease_factor -= EASE_FACTOR_DECREMENT_STRUGGLE;
-            state.interval_days *= INTERVAL_MULTIPLIER_STRUGGLE;
-        } else if time_ratio < 0.6 {
-            // Speed
-            debug!("[SM-2 logic] Branch: Review Speed");
-            state.ease_factor += EASE_FACTOR_INCREMENT_SPEED;
-            state.interval_days *= state.ease_factor * INTERVAL_MULTIPLIER_SPEED;
-        } else {
-            // Normal
-            debug!("[SM-2 logic] Branch: Review Normal");
-            state.interval_days *= state.ease_factor;
+        match review_branch(false, time_ratio) {
+            ReviewBranch::Struggle => {
+                debug!("[SM-2 logic] Branch: Review Struggle");
+                state.ease_factor -= EASE_FACTOR_DECREMENT_STRUGGLE;
+                state.interval_days *= INTERVAL_MULTIPLIER_STRUGGLE;
+            }
+            ReviewBranch::Speed => {
+                debug!("[SM-2 logic] Branch: Review Speed");
+                state.ease_factor += EASE_FACTOR_INCREMENT_SPEED;
+                state.interval_days *= state.ease_factor * INTERVAL_MULTIPLIER_SPEED;
+            }
+            _ => {
+                debug!("[SM-2 logic] Branch: Review Normal");
+                state.interval_days *= state.ease_factor;
+            }
+        }
+    }
+}
+
+/// Which branch `sm2_step` takes for a review (an attempt after the concept's first).
+pub fn review_branch(is_fail: bool, time_ratio: f64) -> ReviewBranch {
+    if is_fail {
+        ReviewBranch::Fail
+    } else if time_ratio > TIME_RATIO_STRUGGLE {
+        ReviewBranch::Struggle
+    } else if time_ratio < TIME_RATIO_FAST {
+        ReviewBranch::Speed
+    } else {
+        ReviewBranch::Normal
+    }
+}
+
+/// What `schedule_attempt` needs to know about the attempt beyond its log.
+#[derive(Clone, Copy)]
+struct AttemptContext {
+    difficulty: Difficulty,
+    expected_minutes: f64,
+    /// Attempts of the concept, including this one.
+    prior_attempts: i64,
+    attempt_id: i64,
+    now: i64,
+}
+
+/// The scheduling core of an attempt, after it has been logged: the concept's next review,
+/// its retirement, and the mastery of `skills`. `log` is keyed by the concept (parent).
+/// Returns whether the concept was retired.
+fn schedule_attempt(
+    repo: &impl ProblemRepository,
+    log: &AttemptLog,
+    ctx: AttemptContext,
+    skills: &[(i64, f64)],
+    warnings: &mut Vec<String>,
+) -> Result<bool, String> {
+    update_repetition_logic(
+        repo,
+        log,
+        ctx.difficulty,
+        ctx.expected_minutes,
+        ctx.prior_attempts,
+        ctx.now,
+        warnings,
+    )?;
+
+    let retired = update_retirement(repo, log, ctx.now)?;
+
+    // 5. Update Skill Mastery -> ON SPECIFIC SKILLS (FIXED)
+    // Now this will update "Arrays" when you solve "Two Sum"
+    update_mastery_logic(
+        repo,
+        log,
+        ctx.difficulty,
+        ctx.expected_minutes,
+        skills,
+        ctx.attempt_id,
+        ctx.now,
+    )?;
+    Ok(retired)
+}
+
+fn update_repetition_logic(
+    repo: &impl ProblemRepository,
+    log: &AttemptLog,
+    difficulty: Difficulty,
+    expected_minutes: f64,
+    prior_attempts: i64,
+    now: i64,
+    warnings: &mut Vec<String>,
+) -> Result<(), String> {
+    let mut state = repo
+        .get_problem_repetition_state(log.problem_id)
+        .map_err(|e| e.to_string())?;
+
+    // Snapshot old state for logging
+    let old_ease = state.ease_factor;
+    let old_interval = state.interval_days;
+
+    let is_new = prior_attempts <= 1; // Since we just logged one, current count is 1+; check is based on *before* this attempt
+    let time_ratio = log.time_minutes / expected_minutes;
+    let is_fail = !log.solved || log.read_solution;
+
+    debug!(
+        "[SM-2 Input] New: {}, Fail: {}, TimeRatio: {:.2}, Diff: {:?}",
+        is_new, is_fail, time_ratio, difficulty
+    );
+
+    sm2_step(&mut state, is_new, is_fail, time_ratio);
+
+    // With a retention target, the concept's own forgetting curve sets the interval;
+    // failures still restart at INTERVAL_MIN
+    if !is_fail {
+        if let Some(target) = retention::target_retention(repo)? {
+            let sm2_interval = state.interval_days;
+            state.interval_days =
+                retention::target_interval(repo, log.problem_id, sm2_interval, target)?;
+            debug!(
+                "[Retention] Target {:.2}: interval {:.1}d (SM-2 {:.1}d)",
+                target, state.interval_days, sm2_interval
+            );
         }
     }
 
     // Clamping
     state.ease_factor = state.ease_factor.clamp(EASE_FACTOR_MIN, EASE_FACTOR_MAX);
     state.interval_days = state.interval_days.clamp(INTERVAL_MIN, INTERVAL_MAX);
+    if let Some(interview) = plan::interview_deadline(repo, now)? {
+        state.interval_days = state.interval_days.min(interview.max_interval());
+    }
     state.next_review_ts = now + ((state.interval_days * DAY_SECONDS as f64) as i64);
+    balance_review_load(repo, &mut state, now, warnings)?;
 
     info!(
         "[SM-2 Result] Problem {}: Ease {:.2} -> {:.2}, Interval {:.1}d -> {:.1}d",
         log.problem_id, old_ease, state.ease_factor, old_interval, state.interval_days
     );
 
-    repository::save_problem_repetition_state(conn, &state).map_err(|e| e.to_string())?;
+    repo.save_problem_repetition_state(&state)
+        .map_err(|e| e.to_string())?;
+    repo.log_interval(&state, now).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Retires the concept once its last `retire_after_clean_reviews` reviews (attempts after
+/// the first) were all clean and fast, and brings a retired concept back on a failure.
+/// Returns whether this attempt retired it.
+fn update_retirement(
+    repo: &impl ProblemRepository,
+    log: &AttemptLog,
+    now: i64,
+) -> Result<bool, String> {
+    let reason = repo
+        .get_suspension_reason(log.problem_id)
+        .map_err(|e| e.to_string())?;
+    if !log.solved || log.read_solution {
+        if reason.as_deref() == Some(SUSPEND_REASON_RETIRED) {
+            info!(
+                "Problem {} failed while retired; back in rotation",
+                log.problem_id
+            );
+            repo.unsuspend_problem(log.problem_id)
+                .map_err(|e| e.to_string())?;
+        }
+        return Ok(false);
+    }
+    let needed = repo
+        .get_setting_or(SETTING_RETIRE_AFTER, RETIRE_AFTER_DEFAULT)
+        .map_err(|e| e.to_string())?;
+    if needed <= 0 || reason.is_some() {
+        return Ok(false);
+    }
+
+    // One extra attempt: the first attempt is not a review
+    let recent = repo
+        .get_recent_concept_attempts(log.problem_id, needed as usize + 1)
+        .map_err(|e| e.to_string())?;
+    if recent.len() <= needed as usize {
+        return Ok(false);
+    }
+    let streak = recent[..needed as usize].iter().all(|a| {
+        let expected = a
+            .expected_minutes_override
+            .unwrap_or_else(|| expected_time(a.difficulty));
+        a.clean && a.time_minutes / expected < TIME_RATIO_FAST
+    });
+    if streak {
+        info!(
+            "Problem {} retired after {} fast, clean reviews",
+            log.problem_id, needed
+        );
+        repo.suspend_problem(log.problem_id, SUSPEND_REASON_RETIRED, now)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(streak)
+}
+
+/// Keeps the review budget (`daily_reviews`) from being exceeded: a review landing on a
+/// full day moves to the nearest day with room, earlier days first on ties, never to today
+/// or beyond the shift limits. If none has room the review stays put and a warning says so.
+fn balance_review_load(
+    repo: &impl ProblemRepository,
+    state: &mut ProblemRepetitionState,
+    now: i64,
+    warnings: &mut Vec<String>,
+) -> Result<(), String> {
+    let budget = repo
+        .get_setting_or(SETTING_DAILY_REVIEWS, DAILY_REVIEWS_DEFAULT)
+        .map_err(|e| e.to_string())?;
+    let today = plan::local_date(now);
+    let due_date = plan::local_date(state.next_review_ts);
+    let load = |date: NaiveDate| {
+        repo.count_reviews_between(
+            reports::local_midnight(date),
+            reports::local_midnight(date + Duration::days(1)),
+            state.problem_id,
+        )
+        .map_err(|e| e.to_string())
+    };
+    let due_load = load(due_date)?;
+    if due_load < budget {
+        return Ok(());
+    }
+
+    let max_shift = REVIEW_LOAD_MAX_SHIFT_DAYS
+        .min((state.interval_days * REVIEW_LOAD_MAX_SHIFT_FRACTION).round() as i64);
+    for distance in 1..=max_shift {
+        for offset in [-distance, distance] {
+            let date = due_date + Duration::days(offset);
+            if date <= today || load(date)? >= budget {
+                continue;
+            }
+            state.next_review_ts += offset * DAY_SECONDS;
+            state.interval_days += offset as f64;
+            debug!(
+                "[Load] Review moved {} -> {} ({} due)",
+                due_date, date, due_load
+            );
+            warnings.push(format!(
+                "Next review moved from {} to {} to stay within {} reviews per day",
+                due_date, date, budget
+            ));
+            return Ok(());
+        }
+    }
+    warnings.push(format!(
+        "Next review is on {}, which already has {} reviews (budget {} per day)",
+        due_date, due_load, budget
+    ));
     Ok(())
 }
 
+/// Learning rate for a skill at `mastery` after `attempts`: large early on, small near 1.0,
+/// so a handful of easy solves cannot saturate a skill.
+pub fn effective_alpha(mastery: f64, attempts: i32) -> f64 {
+    let rate =
+        (ALPHA_INITIAL / (1.0 + ALPHA_ATTEMPT_DECAY * attempts.max(0) as f64)).max(ALPHA_MIN);
+    rate * (1.0 - mastery).max(0.0)
+}
+
 fn update_mastery_logic(
-    conn: &Connection,
+    repo: &impl ProblemRepository,
     log: &AttemptLog,
     difficulty: Difficulty,
-    skill_ids: &[i64],
+    expected_minutes: f64,
+    skills: &[(i64, f64)],
+    attempt_id: i64,
+    now: i64,
 ) -> Result<(), String> {
-    let diff_mult = match difficulty {
-        Difficulty::Easy => DIFFICULTY_MULTIPLIER_EASY,
-        Difficulty::Medium => DIFFICULTY_MULTIPLIER_MEDIUM,
-        Difficulty::Hard => DIFFICULTY_MULTIPLIER_HARD,
-    };
+    let diff_mult = repo
+        .get_fitted_multiplier(difficulty)
+        .map_err(|e| e.to_string())?
+        .unwrap_or_else(|| difficulty_multiplier(difficulty));
 
-    let expected_time = match difficulty {
-        Difficulty::Easy => EXPECTED_TIME_EASY,
-        Difficulty::Medium => EXPECTED_TIME_MEDIUM,
-        Difficulty::Hard => EXPECTED_TIME_HARD,
-    };
-    let time_ratio = log.time_minutes / expected_time;
+    let time_ratio = log.time_minutes / expected_minutes;
     let is_fail = !log.solved || log.read_solution;
 
     // We assume it's "New" for performance bonus if it was the first solve,
@@ -224,7 +1590,7 @@ fn update_mastery_logic(
 
     let perf_mult = if is_fail {
         PERFORMANCE_MULTIPLIER_FAIL
-    } else if time_ratio > 1.5 {
+    } else if time_ratio > TIME_RATIO_GRIT {
         // Assume Grit context
         PERFORMANCE_MULTIPLIER_NEW_GRIT
     } else {
@@ -233,8 +1599,9 @@ fn update_mastery_logic(
         // Assuming "New Clean" as baseline for success, and "Review" needs handling:
         // *Refinement*: If it's a review, we should use PERFORMANCE_MULTIPLIER_REVIEW.
         // Let's check attempt count via repository again or pass it down.
-        let attempts =
-            repository::get_attempt_count(conn, log.problem_id).map_err(|e| e.to_string())?;
+        let attempts = repo
+            .get_attempt_count(log.problem_id)
+            .map_err(|e| e.to_string())?;
         if attempts > 1 {
             PERFORMANCE_MULTIPLIER_REVIEW
         } else {
@@ -247,15 +1614,17 @@ fn update_mastery_logic(
     // We reduce the learning alpha by 50% for this attempt.
     let scaffolding_mult = if log.revealed_skills { 0.5 } else { 1.0 };
 
-    let delta = ALPHA * diff_mult * perf_mult * scaffolding_mult;
+    let gain = diff_mult * perf_mult * scaffolding_mult;
     debug!(
-        "[Mastery Input] Delta: {:.4} (Scaffold Penalty: {}) (based on perf_mult: {:.2})",
-        delta, scaffolding_mult, perf_mult
+        "[Mastery Input] Gain: {:.4} (Scaffold Penalty: {}) (based on perf_mult: {:.2})",
+        gain, scaffolding_mult, perf_mult
     );
 
-    for &sid in skill_ids {
-        let mut s_state = repository::get_skill_state(conn, sid).map_err(|e| e.to_string())?;
+    // Secondary skills get their weight's share of the credit
+    for &(sid, weight) in skills {
+        let mut s_state = repo.get_skill_state(sid).map_err(|e| e.to_string())?;
         let old_mastery = s_state.mastery;
+        let delta = effective_alpha(s_state.mastery, s_state.attempts) * gain * weight;
         s_state.mastery = (s_state.mastery + delta).clamp(0.0, 1.0);
         s_state.attempts += 1;
 
@@ -263,8 +1632,192 @@ fn update_mastery_logic(
             "[Mastery Result] Skill {}: {:.3} -> {:.3} (Attempts: {})",
             sid, old_mastery, s_state.mastery, s_state.attempts
         );
-        repository::update_skill_state(conn, &s_state).map_err(|e| e.to_string())?;
+        repo.update_skill_state(&s_state)
+            .map_err(|e| e.to_string())?;
+        repo.log_mastery_change(
+            sid,
+            Some(attempt_id),
+            old_mastery,
+            s_state.mastery,
+            "attempt",
+            now,
+        )
+        .map_err(|e| e.to_string())?;
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_repository::MockRepository;
+    use crate::models::TimedOutcome;
+
+    const NOW: i64 = 1_700_000_000;
+    const SKILL: i64 = 7;
+
+    fn attempt(problem_id: i64, time_minutes: f64, solved: bool) -> AttemptLog {
+        AttemptLog {
+            problem_id,
+            time_minutes,
+            solved,
+            read_solution: false,
+            revealed_skills: false,
+            mistakes: Vec::new(),
+            explanation: None,
+            attempt_key: None,
+            timestamp: Some(NOW),
+            pause_count: 0,
+            paused_minutes: 0.0,
+        }
+    }
+
+    fn context(prior_attempts: i64) -> AttemptContext {
+        AttemptContext {
+            difficulty: Difficulty::Medium,
+            expected_minutes: expected_time(Difficulty::Medium),
+            prior_attempts,
+            attempt_id: 42,
+            now: NOW,
+        }
+    }
+
+    fn repo() -> MockRepository {
+        let repo = MockRepository::default();
+        repo.skills.borrow_mut().insert(
+            SKILL,
+            SkillMasteryState {
+                skill_id: SKILL,
+                mastery: 0.0,
+                attempts: 0,
+            },
+        );
+        repo
+    }
+
+    #[test]
+    fn clean_first_solve_schedules_and_credits_skill() {
+        let repo = repo();
+        let log = attempt(1, expected_time(Difficulty::Medium) * 0.8, true);
+        let mut warnings = Vec::new();
+        let retired =
+            schedule_attempt(&repo, &log, context(1), &[(SKILL, 1.0)], &mut warnings).unwrap();
+
+        assert!(!retired);
+        assert!(warnings.is_empty());
+        let state = repo.get_problem_repetition_state(1).unwrap();
+        assert_eq!(state.interval_days, INTERVAL_NEW_CLEAN);
+        assert_eq!(
+            state.next_review_ts,
+            NOW + (INTERVAL_NEW_CLEAN * DAY_SECONDS as f64) as i64
+        );
+        assert_eq!(repo.interval_log.borrow().len(), 1);
+
+        let skill = repo.get_skill_state(SKILL).unwrap();
+        assert!(skill.mastery > 0.0);
+        assert_eq!(skill.attempts, 1);
+        let changes = repo.mastery_log.borrow();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].skill_id, SKILL);
+        assert_eq!(changes[0].attempt_id, Some(42));
+        assert_eq!(changes[0].old_mastery, 0.0);
+        assert_eq!(changes[0].new_mastery, skill.mastery);
+        assert_eq!(changes[0].reason, "attempt");
+        assert_eq!(changes[0].timestamp, NOW);
+    }
+
+    #[test]
+    fn failed_review_restarts_interval() {
+        let repo = repo();
+        repo.repetition.borrow_mut().insert(
+            1,
+            ProblemRepetitionState {
+                problem_id: 1,
+                ease_factor: EASE_FACTOR_DEFAULT,
+                interval_days: 20.0,
+                next_review_ts: NOW,
+            },
+        );
+        let log = attempt(1, 10.0, false);
+        schedule_attempt(&repo, &log, context(3), &[(SKILL, 1.0)], &mut Vec::new()).unwrap();
+
+        let state = repo.get_problem_repetition_state(1).unwrap();
+        assert_eq!(state.interval_days, INTERVAL_MIN);
+        assert!(state.ease_factor < EASE_FACTOR_DEFAULT);
+        assert_eq!(repo.get_skill_state(SKILL).unwrap().mastery, 0.0);
+    }
+
+    #[test]
+    fn full_day_moves_review_earlier() {
+        let repo = repo();
+        repo.settings
+            .borrow_mut()
+            .insert(SETTING_DAILY_REVIEWS.to_string(), "1".to_string());
+        let due = NOW + (INTERVAL_NEW_CLEAN * DAY_SECONDS as f64) as i64;
+        repo.next_reviews.borrow_mut().insert(2, due);
+        let log = attempt(1, expected_time(Difficulty::Medium) * 0.8, true);
+        let mut warnings = Vec::new();
+        schedule_attempt(&repo, &log, context(1), &[], &mut warnings).unwrap();
+
+        let state = repo.get_problem_repetition_state(1).unwrap();
+        assert_eq!(state.next_review_ts, due - DAY_SECONDS);
+        assert_eq!(state.interval_days, INTERVAL_NEW_CLEAN - 1.0);
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn clean_fast_streak_retires_and_failure_brings_back() {
+        let repo = repo();
+        repo.settings
+            .borrow_mut()
+            .insert(SETTING_RETIRE_AFTER.to_string(), "2".to_string());
+        let fast = TimedOutcome {
+            time_minutes: 1.0,
+            clean: true,
+            difficulty: Difficulty::Medium,
+            expected_minutes_override: None,
+        };
+        repo.concept_attempts
+            .borrow_mut()
+            .insert(1, vec![fast.clone(), fast.clone(), fast]);
+        let log = attempt(1, 1.0, true);
+        let retired =
+            schedule_attempt(&repo, &log, context(3), &[(SKILL, 1.0)], &mut Vec::new()).unwrap();
+        assert!(retired);
+        assert_eq!(
+            repo.get_suspension_reason(1).unwrap().as_deref(),
+            Some(SUSPEND_REASON_RETIRED)
+        );
+
+        let log = attempt(1, 10.0, false);
+        let retired =
+            schedule_attempt(&repo, &log, context(4), &[(SKILL, 1.0)], &mut Vec::new()).unwrap();
+        assert!(!retired);
+        assert_eq!(repo.get_suspension_reason(1).unwrap(), None);
+    }
+
+    #[test]
+    fn target_retention_uses_concept_history() {
+        let plain = repo();
+        let log = attempt(1, expected_time(Difficulty::Medium) * 0.8, true);
+        schedule_attempt(&plain, &log, context(1), &[], &mut Vec::new()).unwrap();
+
+        let mut targeted = repo();
+        targeted
+            .settings
+            .borrow_mut()
+            .insert(SETTING_TARGET_RETENTION.to_string(), "0.95".to_string());
+        targeted.concept_outcomes.insert(1, vec![(NOW, true)]);
+        schedule_attempt(&targeted, &log, context(1), &[], &mut Vec::new()).unwrap();
+
+        // A higher target than the SM-2 baseline asks for an earlier review
+        assert!(
+            targeted
+                .get_problem_repetition_state(1)
+                .unwrap()
+                .interval_days
+                < plain.get_problem_repetition_state(1).unwrap().interval_days
+        );
+    }
+}
@@ -0,0 +1,274 @@
+// src/plan.rs
+
+use crate::assessment;
+use crate::constants::*;
+use crate::models::{PlanDay, StudyPlan};
+use crate::pedagogy;
+use crate::reports;
+use crate::repository::{ProblemRepository, Repository};
+use chrono::{Duration, Local, NaiveDate, TimeZone};
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+use std::collections::HashSet;
+
+/// What the plan asks of today, for `get_next_problem`.
+pub struct PlanQuota {
+    pub skill_ids: HashSet<i64>,
+    /// New problems still to start today.
+    pub new_remaining: usize,
+}
+
+/// Spreads the never-attempted problems of `target_skills` (and of their unmet
+/// prerequisites) over the days from today to `deadline`, within the daily new-problem
+/// limit, and projects the review load this creates. Replaces any previous plan.
+pub fn generate_plan(
+    conn: &Connection,
+    target_skills: &[i64],
+    deadline: &str,
+    now: i64,
+) -> Result<StudyPlan, String> {
+    let repo = Repository::new(conn);
+    let deadline_date = NaiveDate::parse_from_str(deadline.trim(), "%Y-%m-%d")
+        .map_err(|e| format!("Invalid deadline '{}': {}", deadline, e))?;
+    let today = local_date(now);
+    if deadline_date < today {
+        return Err(format!("Deadline {} is in the past", deadline_date));
+    }
+    let day_count = (deadline_date - today).num_days() + 1;
+    if day_count > PLAN_MAX_DAYS {
+        return Err(format!("Deadline is more than {} days away", PLAN_MAX_DAYS));
+    }
+    if target_skills.is_empty() {
+        return Err("Choose at least one skill to plan for".to_string());
+    }
+    for &skill_id in target_skills {
+        if !repo.skill_exists(skill_id).map_err(|e| e.to_string())? {
+            return Err(format!("Skill {} not found", skill_id));
+        }
+    }
+
+    let skill_ids = plan_skills(conn, target_skills)?;
+    let new_problems = repo
+        .count_new_problems_for_skills(1, &skill_ids)
+        .map_err(|e| e.to_string())?;
+    let new_limit = repo
+        .get_setting_or(SETTING_DAILY_NEW_PROBLEMS, DAILY_NEW_PROBLEMS_DEFAULT)
+        .map_err(|e| e.to_string())?
+        .max(0);
+    let review_limit = repo
+        .get_setting_or(SETTING_DAILY_REVIEWS, DAILY_REVIEWS_DEFAULT)
+        .map_err(|e| e.to_string())?
+        .max(0);
+
+    let new_per_day = allocate_new(new_problems, day_count, new_limit);
+    let reviews_per_day = project_reviews(conn, &new_per_day, today, now)?;
+
+    let mut warnings = Vec::new();
+    let planned: i64 = new_per_day.iter().sum();
+    let feasible = planned >= new_problems;
+    if !feasible {
+        warnings.push(format!(
+            "{} new problems do not fit before {} at {} per day ({} fit); raise the daily \
+             new-problem limit or move the deadline",
+            new_problems, deadline_date, new_limit, planned
+        ));
+    }
+    let overloaded: Vec<(usize, i64)> = reviews_per_day
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(_, r)| *r > review_limit)
+        .collect();
+    if let Some(&(peak_day, peak)) = overloaded.iter().max_by_key(|(_, r)| *r) {
+        warnings.push(format!(
+            "{} days exceed the daily review limit of {} (peak {} on {})",
+            overloaded.len(),
+            review_limit,
+            peak,
+            today + Duration::days(peak_day as i64)
+        ));
+    }
+
+    let plan = StudyPlan {
+        deadline: deadline_date.to_string(),
+        skill_ids,
+        new_problems,
+        feasible,
+        warnings,
+        days: new_per_day
+            .iter()
+            .zip(&reviews_per_day)
+            .enumerate()
+            .map(|(i, (&new_problems, &reviews))| PlanDay {
+                date: (today + Duration::days(i as i64)).to_string(),
+                new_problems,
+                reviews,
+            })
+            .collect(),
+    };
+
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    Repository::new(&tx)
+        .save_study_plan(&plan, now)
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(plan)
+}
+
+/// The upcoming interview from the `interview_date` setting.
+pub struct InterviewDeadline {
+    /// Whole days from today; 0 on the day itself.
+    pub days_left: i64,
+    /// Local midnight at the start of the interview day.
+    pub starts_at: i64,
+}
+
+impl InterviewDeadline {
+    pub fn in_final_days(&self) -> bool {
+        self.days_left < INTERVIEW_FINAL_DAYS
+    }
+
+    /// Longest interval that still brings a review back before the interview day.
+    pub fn max_interval(&self) -> f64 {
+        ((self.days_left - 1) as f64).max(INTERVAL_MIN)
+    }
+}
+
+/// `None` when no interview date is set, it does not parse, or it has passed.
+pub fn interview_deadline(
+    repo: &impl ProblemRepository,
+    now: i64,
+) -> Result<Option<InterviewDeadline>, String> {
+    let Some(date) = repo
+        .get_setting(SETTING_INTERVIEW_DATE)
+        .map_err(|e| e.to_string())?
+        .and_then(|s| NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok())
+    else {
+        return Ok(None);
+    };
+    let days_left = (date - local_date(now)).num_days();
+    if days_left < 0 {
+        return Ok(None);
+    }
+    Ok(Some(InterviewDeadline {
+        days_left,
+        starts_at: reports::local_midnight(date),
+    }))
+}
+
+/// Today's share of the stored plan, or `None` when there is no plan or it does not cover
+/// today (e.g. the deadline has passed).
+pub fn todays_quota(conn: &Connection, now: i64) -> Result<Option<PlanQuota>, String> {
+    let repo = Repository::new(conn);
+    let today = local_date(now);
+    let Some(quota) = repo
+        .get_plan_day_quota(&today.to_string())
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(None);
+    };
+    let started = repo
+        .count_concepts_started_since(reports::local_midnight(today))
+        .map_err(|e| e.to_string())?;
+    let skill_ids = repo
+        .get_study_plan()
+        .map_err(|e| e.to_string())?
+        .map(|p| p.skill_ids)
+        .unwrap_or_default();
+    Ok(Some(PlanQuota {
+        skill_ids: skill_ids.into_iter().collect(),
+        new_remaining: (quota - started).max(0) as usize,
+    }))
+}
+
+/// The target skills plus every prerequisite, direct or indirect, that the user has not
+/// met yet, prerequisites first.
+fn plan_skills(conn: &Connection, target_skills: &[i64]) -> Result<Vec<i64>, String> {
+    let repo = Repository::new(conn);
+    let states = repo.get_all_skill_states().map_err(|e| e.to_string())?;
+    let prereqs = repo.get_skill_prereqs().map_err(|e| e.to_string())?;
+    let policy = pedagogy::get_unlock_policy(&repo)?;
+    let met: HashSet<i64> = states
+        .iter()
+        .filter(|s| policy.meets_prereq(s))
+        .map(|s| s.skill_id)
+        .collect();
+
+    let mut skills: HashSet<i64> = target_skills.iter().copied().collect();
+    let mut frontier: Vec<i64> = target_skills.to_vec();
+    while let Some(skill_id) = frontier.pop() {
+        for &(_, prereq_id) in prereqs.iter().filter(|(s, _)| *s == skill_id) {
+            if !met.contains(&prereq_id) && skills.insert(prereq_id) {
+                frontier.push(prereq_id);
+            }
+        }
+    }
+
+    let all: Vec<i64> = states.iter().map(|s| s.skill_id).collect();
+    let depths = assessment::skill_depths(&all, &prereqs);
+    let mut skills: Vec<i64> = skills.into_iter().collect();
+    skills.sort_by_key(|s| (depths.get(s).copied().unwrap_or(0), *s));
+    Ok(skills)
+}
+
+/// New problems per day: an even spread, rounded up so any slack falls on the last days,
+/// never above `daily_limit`.
+fn allocate_new(total: i64, days: i64, daily_limit: i64) -> Vec<i64> {
+    let mut remaining = total;
+    (0..days)
+        .map(|day| {
+            let days_left = days - day;
+            let today = ((remaining + days_left - 1) / days_left).min(daily_limit);
+            remaining -= today;
+            today
+        })
+        .collect()
+}
+
+/// Reviews per plan day: tracked problems follow their stored schedule, planned problems
+/// start at the clean-solve interval, and every review grows the interval by its ease.
+fn project_reviews(
+    conn: &Connection,
+    new_per_day: &[i64],
+    today: NaiveDate,
+    now: i64,
+) -> Result<Vec<i64>, String> {
+    let horizon = new_per_day.len() as f64;
+    let mut reviews = vec![0_i64; new_per_day.len()];
+    let mut follow = |mut day: f64, mut interval: f64, ease: f64| {
+        while day < horizon {
+            reviews[day.max(0.0) as usize] += 1;
+            interval = (interval * ease).clamp(INTERVAL_MIN, INTERVAL_MAX);
+            day += interval;
+        }
+    };
+
+    let start = reports::local_midnight(today);
+    for (next_review_ts, interval, ease) in Repository::new(conn)
+        .get_review_schedule()
+        .map_err(|e| e.to_string())?
+    {
+        // Overdue reviews land on today
+        let day = ((next_review_ts.max(now) - start) / DAY_SECONDS) as f64;
+        follow(day, interval, ease);
+    }
+    for (day, &count) in new_per_day.iter().enumerate() {
+        for _ in 0..count {
+            follow(
+                day as f64 + INTERVAL_NEW_CLEAN,
+                INTERVAL_NEW_CLEAN,
+                EASE_FACTOR_DEFAULT,
+            );
+        }
+    }
+    Ok(reviews)
+}
+
+/// The local calendar date of `ts`.
+pub fn local_date(ts: i64) -> NaiveDate {
+    Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .map(|dt| dt.date_naive())
+        .unwrap_or_else(|| Local::now().date_naive())
+}
@@ -0,0 +1,105 @@
+// src/remote_pack.rs
+
+use crate::constants::*;
+use crate::database;
+use crate::models::{JsonPack, RemotePackRefresh};
+use crate::repository::Repository;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use log::{info, warn};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use rusqlite::Connection;
+use std::io::Read;
+use std::time::Duration;
+
+/// Downloads the pack at `url` and its detached signature at `url` + `.sig`, and parses
+/// the pack only once the signature checks out against the key baked into this build.
+/// Nothing touches the database here, so the download runs without holding it.
+pub fn fetch(url: &str) -> Result<JsonPack, String> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(REMOTE_PACK_TIMEOUT_SECONDS))
+        .build();
+    let data = download(&agent, url)?;
+    let signature = download(&agent, &format!("{}{}", url, REMOTE_PACK_SIGNATURE_SUFFIX))?;
+    if let Err(e) = verify(
+        &data,
+        &String::from_utf8_lossy(&signature),
+        REMOTE_PACK_PUBLIC_KEY,
+    ) {
+        warn!("Rejected pack from {}: {}", url, e);
+        return Err(e);
+    }
+    serde_json::from_slice(&data).map_err(|e| format!("Invalid pack: {}", e))
+}
+
+/// Checks a base64 ed25519 `signature` of `data` against a base64 `public_key`.
+pub fn verify(data: &[u8], signature: &str, public_key: &str) -> Result<(), String> {
+    let key = BASE64
+        .decode(public_key.trim())
+        .map_err(|_| "The pack signing key is not valid base64")?;
+    let signature = BASE64
+        .decode(signature.trim())
+        .map_err(|_| "The pack signature is not valid base64")?;
+    UnparsedPublicKey::new(&ED25519, key)
+        .verify(data, &signature)
+        .map_err(|_| "The pack signature does not match".to_string())
+}
+
+/// Upgrades the bundled track to a verified `pack` if it is newer than the database's.
+pub fn apply(conn: &Connection, pack: &JsonPack) -> Result<RemotePackRefresh, String> {
+    let previous_version = Repository::new(conn)
+        .get_setting_or(SETTING_SEED_PACK_VERSION, 0i64)
+        .map_err(|e| e.to_string())?;
+    let applied = database::upgrade_bundled_track(conn, pack).map_err(|e| e.to_string())?;
+    if applied {
+        info!(
+            "Remote pack v{} applied over v{}",
+            pack.version, previous_version
+        );
+    }
+    Ok(RemotePackRefresh {
+        previous_version,
+        version: pack.version,
+        applied,
+    })
+}
+
+fn download(agent: &ureq::Agent, url: &str) -> Result<Vec<u8>, String> {
+    let resp = agent
+        .get(url)
+        .call()
+        .map_err(|e| format!("Could not download {}: {}", url, e))?;
+    let mut data = Vec::new();
+    resp.into_reader()
+        .take(REMOTE_PACK_MAX_BYTES + 1)
+        .read_to_end(&mut data)
+        .map_err(|e| format!("Could not download {}: {}", url, e))?;
+    if data.len() as u64 > REMOTE_PACK_MAX_BYTES {
+        return Err(format!(
+            "{} is larger than {} bytes",
+            url, REMOTE_PACK_MAX_BYTES
+        ));
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Signed with the private half of `REMOTE_PACK_PUBLIC_KEY`
+    const PACK: &[u8] = br#"{"version":1,"problems":[]}"#;
+    const SIGNATURE: &str =
+        "5l5EKwzfdDnjlgz6ylnlxKspL0iv6Ndp+X/YOXFIMJDjRNDU86DEzeSFPropRBV/v/OEH7FRlo40lICnERfNAg==";
+
+    #[test]
+    fn accepts_a_signed_pack() {
+        assert!(verify(PACK, SIGNATURE, REMOTE_PACK_PUBLIC_KEY).is_ok());
+    }
+
+    #[test]
+    fn rejects_a_tampered_pack() {
+        let tampered = br#"{"version":2,"problems":[]}"#;
+        assert!(verify(tampered, SIGNATURE, REMOTE_PACK_PUBLIC_KEY).is_err());
+    }
+}
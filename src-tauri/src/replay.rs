@@ -0,0 +1,247 @@
+// src/replay.rs
+
+use crate::constants::*;
+use crate::models::{AttemptRecord, ProblemRepetitionState, ReplayReport, SchedulerConfig};
+use crate::pedagogy;
+use crate::repository::Repository;
+use crate::retention;
+use rusqlite::Connection;
+use std::collections::BTreeMap;
+
+/// What an attempt tells a scheduler: whether it was recalled and how long it took
+/// relative to the expected time.
+#[derive(Clone, Copy)]
+struct Outcome {
+    recalled: bool,
+    time_ratio: f64,
+}
+
+/// Reviews past the end of the real history are assumed to go as expected.
+const ASSUMED_OUTCOME: Outcome = Outcome {
+    recalled: true,
+    time_ratio: 1.0,
+};
+
+/// Per-concept scheduler state.
+enum Card {
+    Sm2 {
+        state: ProblemRepetitionState,
+        reviews: Vec<(f64, bool)>,
+    },
+    Fsrs {
+        stability: f64,
+        difficulty: f64,
+    },
+}
+
+impl Card {
+    /// Applies the outcome of the review `n` (0 for the first attempt), taken
+    /// `elapsed_days` after the previous one, and returns the next interval in days.
+    fn review(
+        &mut self,
+        config: SchedulerConfig,
+        n: usize,
+        elapsed_days: f64,
+        outcome: Outcome,
+    ) -> Result<f64, String> {
+        let interval = match (&mut *self, config) {
+            (Card::Sm2 { state, reviews }, SchedulerConfig::Sm2 { target_retention }) => {
+                if n > 0 && elapsed_days >= RETENTION_MIN_ELAPSED_DAYS {
+                    reviews.push((elapsed_days, outcome.recalled));
+                }
+                pedagogy::sm2_step(state, n == 0, !outcome.recalled, outcome.time_ratio);
+                state.ease_factor = state.ease_factor.clamp(EASE_FACTOR_MIN, EASE_FACTOR_MAX);
+                match target_retention {
+                    Some(target) if outcome.recalled => {
+                        let prior = state.interval_days / (1.0 / RETENTION_SM2_BASELINE).ln();
+                        let stability = retention::estimate_stability(reviews, prior);
+                        retention::interval_for(stability, target)
+                    }
+                    _ => state.interval_days,
+                }
+            }
+            (
+                Card::Fsrs {
+                    stability,
+                    difficulty,
+                },
+                SchedulerConfig::Fsrs { desired_retention },
+            ) => {
+                let grade = fsrs_grade(outcome);
+                if n == 0 {
+                    *stability = FSRS_DEFAULT_WEIGHTS[grade - 1].max(0.1);
+                    *difficulty = fsrs_initial_difficulty(grade);
+                } else {
+                    let r = fsrs_retrievability(elapsed_days, *stability);
+                    *stability = fsrs_next_stability(*difficulty, *stability, r, grade);
+                    *difficulty = fsrs_next_difficulty(*difficulty, grade);
+                }
+                *stability / FSRS_FACTOR * (desired_retention.powf(1.0 / FSRS_DECAY) - 1.0)
+            }
+            _ => return Err("Replay card does not match the scheduler".to_string()),
+        };
+        let interval = interval.clamp(INTERVAL_MIN, INTERVAL_MAX);
+        if let Card::Sm2 { state, .. } = self {
+            state.interval_days = interval;
+        }
+        Ok(interval)
+    }
+}
+
+/// FSRS grade: 1 again, 2 hard, 3 good, 4 easy. Slow solves count as hard, fast ones as
+/// easy, using the same thresholds as SM-2.
+fn fsrs_grade(outcome: Outcome) -> usize {
+    if !outcome.recalled {
+        1
+    } else if outcome.time_ratio > TIME_RATIO_GRIT {
+        2
+    } else if outcome.time_ratio < TIME_RATIO_FAST {
+        4
+    } else {
+        3
+    }
+}
+
+fn fsrs_retrievability(elapsed_days: f64, stability: f64) -> f64 {
+    (1.0 + FSRS_FACTOR * elapsed_days / stability).powf(FSRS_DECAY)
+}
+
+fn fsrs_initial_difficulty(grade: usize) -> f64 {
+    let w = &FSRS_DEFAULT_WEIGHTS;
+    (w[4] - (grade as f64 - 3.0) * w[5]).clamp(1.0, 10.0)
+}
+
+fn fsrs_next_difficulty(difficulty: f64, grade: usize) -> f64 {
+    let w = &FSRS_DEFAULT_WEIGHTS;
+    let next = difficulty - w[6] * (grade as f64 - 3.0);
+    // Mean reversion toward the difficulty of a first "good"
+    (w[7] * fsrs_initial_difficulty(3) + (1.0 - w[7]) * next).clamp(1.0, 10.0)
+}
+
+fn fsrs_next_stability(difficulty: f64, stability: f64, r: f64, grade: usize) -> f64 {
+    let w = &FSRS_DEFAULT_WEIGHTS;
+    if grade == 1 {
+        return w[11]
+            * difficulty.powf(-w[12])
+            * ((stability + 1.0).powf(w[13]) - 1.0)
+            * (w[14] * (1.0 - r)).exp();
+    }
+    let hard = if grade == 2 { w[15] } else { 1.0 };
+    let easy = if grade == 4 { w[16] } else { 1.0 };
+    stability
+        * (1.0
+            + w[8].exp()
+                * (11.0 - difficulty)
+                * stability.powf(-w[9])
+                * ((w[10] * (1.0 - r)).exp() - 1.0)
+                * hard
+                * easy)
+}
+
+/// Re-runs the whole attempt log through `config`, in memory, and compares the workload
+/// and recall it would have produced with what actually happened.
+///
+/// Each concept keeps its real sequence of outcomes, but its reviews happen when `config`
+/// would have scheduled them, up to `now`. Recall at a review is predicted from the
+/// concept's forgetting curve as fitted to its actual history, so a scheduler only scores
+/// better by spacing reviews better, not by seeing different outcomes.
+pub fn replay_history(
+    conn: &Connection,
+    config: SchedulerConfig,
+    now: i64,
+) -> Result<ReplayReport, String> {
+    let target = match config {
+        SchedulerConfig::Sm2 { target_retention } => target_retention,
+        SchedulerConfig::Fsrs { desired_retention } => Some(desired_retention),
+    };
+    if let Some(target) = target {
+        if !(RETENTION_TARGET_MIN..=RETENTION_TARGET_MAX).contains(&target) {
+            return Err(format!(
+                "Retention must be between {} and {}",
+                RETENTION_TARGET_MIN, RETENTION_TARGET_MAX
+            ));
+        }
+    }
+
+    let attempts = Repository::new(conn)
+        .get_attempts_between(0, i64::MAX)
+        .map_err(|e| e.to_string())?;
+    let mut concepts: BTreeMap<i64, Vec<&AttemptRecord>> = BTreeMap::new();
+    for attempt in &attempts {
+        concepts
+            .entry(attempt.concept_id)
+            .or_default()
+            .push(attempt);
+    }
+
+    let mut report = ReplayReport {
+        scheduler: config,
+        concepts: concepts.len(),
+        actual_reviews: 0,
+        actual_retention: None,
+        simulated_reviews: 0,
+        simulated_retention: None,
+    };
+    let (mut actual_recalled, mut simulated_recall) = (0, 0.0);
+    for history in concepts.values() {
+        let outcomes: Vec<Outcome> = history
+            .iter()
+            .map(|a| Outcome {
+                recalled: a.solved && !a.read_solution,
+                time_ratio: a.time_minutes / pedagogy::expected_time(a.difficulty),
+            })
+            .collect();
+        report.actual_reviews += outcomes.len() - 1;
+        actual_recalled += outcomes[1..].iter().filter(|o| o.recalled).count();
+
+        let stability = concept_stability(history);
+        let mut card = match config {
+            SchedulerConfig::Sm2 { .. } => Card::Sm2 {
+                state: ProblemRepetitionState {
+                    problem_id: history[0].concept_id,
+                    ease_factor: EASE_FACTOR_DEFAULT,
+                    interval_days: 0.0,
+                    next_review_ts: 0,
+                },
+                reviews: Vec::new(),
+            },
+            SchedulerConfig::Fsrs { .. } => Card::Fsrs {
+                stability: 0.0,
+                difficulty: 0.0,
+            },
+        };
+        let mut ts = history[0].timestamp;
+        let mut interval = card.review(config, 0, 0.0, outcomes[0])?;
+        for n in 1.. {
+            ts += (interval * DAY_SECONDS as f64) as i64;
+            if ts > now {
+                break;
+            }
+            report.simulated_reviews += 1;
+            simulated_recall += retention::recall_probability(interval, stability);
+            let outcome = outcomes.get(n).copied().unwrap_or(ASSUMED_OUTCOME);
+            interval = card.review(config, n, interval, outcome)?;
+        }
+    }
+    if report.actual_reviews > 0 {
+        report.actual_retention = Some(actual_recalled as f64 / report.actual_reviews as f64);
+    }
+    if report.simulated_reviews > 0 {
+        report.simulated_retention = Some(simulated_recall / report.simulated_reviews as f64);
+    }
+    Ok(report)
+}
+
+/// Stability of the concept's forgetting curve, from its real review gaps and outcomes.
+fn concept_stability(history: &[&AttemptRecord]) -> f64 {
+    let reviews: Vec<(f64, bool)> = history
+        .windows(2)
+        .map(|w| {
+            let elapsed = (w[1].timestamp - w[0].timestamp) as f64 / DAY_SECONDS as f64;
+            (elapsed, w[1].solved && !w[1].read_solution)
+        })
+        .filter(|&(elapsed, _)| elapsed >= RETENTION_MIN_ELAPSED_DAYS)
+        .collect();
+    let prior = INTERVAL_NEW_CLEAN / (1.0 / RETENTION_SM2_BASELINE).ln();
+    retention::estimate_stability(&reviews, prior)
+}
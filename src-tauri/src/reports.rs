@@ -0,0 +1,637 @@
+// src/reports.rs
+
+use crate::constants::*;
+use crate::models::{
+    AnonOutcome, AnonSkillCurve, AnonymousStats, DateRange, DifficultyCount, IntervalBucket,
+    ProblemStats, ProfileCard, ReviewBranch, ReviewBranchCounts, ReviewQualityStats,
+    ReviewQualityWeek, SessionSummary, SkillLevel, SkillMasteryDelta, SkillMasteryState,
+    SkillReviewQuality, Streak,
+};
+use crate::pedagogy;
+use crate::plan;
+use crate::repository::Repository;
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone, Utc};
+use rusqlite::Connection;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+use std::fs;
+
+// --- Session Summary ---
+
+/// Summarizes one practice session. A session is a run of attempts with no gap
+/// longer than `SESSION_GAP_SECONDS`. `session_id` is the ID of its first attempt;
+/// `None` summarizes the most recent session.
+pub fn get_session_summary(
+    conn: &Connection,
+    session_id: Option<i64>,
+) -> Result<Option<SessionSummary>, String> {
+    let repo = Repository::new(conn);
+    let timeline = repo.get_attempt_timeline().map_err(|e| e.to_string())?;
+    let Some((start_idx, end_idx)) = find_session(&timeline, session_id) else {
+        return Ok(None);
+    };
+    let (first_id, started_at) = timeline[start_idx];
+    let ended_at = timeline[end_idx].1;
+
+    let attempts = repo
+        .get_attempts_between(started_at, ended_at)
+        .map_err(|e| e.to_string())?;
+    let total = attempts.len() as i64;
+    let clean_solves = attempts
+        .iter()
+        .filter(|a| a.solved && !a.read_solution)
+        .count() as f64;
+    let problems_done = attempts
+        .iter()
+        .map(|a| a.problem_id)
+        .collect::<HashSet<_>>()
+        .len() as i64;
+    let time_spent_minutes = attempts.iter().map(|a| a.time_minutes).sum();
+
+    // Mastery at the start and end of the session
+    let before = skill_states_at(conn, started_at - 1)?;
+    let after = skill_states_at(conn, ended_at)?;
+    let names = repo.get_skill_names().map_err(|e| e.to_string())?;
+
+    let mastery_gains = before
+        .iter()
+        .zip(after.iter())
+        .filter(|(b, a)| (a.mastery - b.mastery).abs() > f64::EPSILON)
+        .map(|(b, a)| SkillMasteryDelta {
+            skill_id: a.skill_id,
+            skill: names.get(&a.skill_id).cloned().unwrap_or_default(),
+            before: b.mastery,
+            after: a.mastery,
+        })
+        .collect();
+
+    let prereqs = repo.get_skill_prereqs().map_err(|e| e.to_string())?;
+    let policy = pedagogy::get_unlock_policy(&repo)?;
+    let unlocked_before: HashSet<i64> = pedagogy::unlocked_skill_ids(&before, &prereqs, &policy)
+        .into_iter()
+        .collect();
+    let new_unlocks = pedagogy::unlocked_skill_ids(&after, &prereqs, &policy)
+        .into_iter()
+        .filter(|sid| !unlocked_before.contains(sid))
+        .filter_map(|sid| names.get(&sid).cloned())
+        .collect();
+
+    Ok(Some(SessionSummary {
+        session_id: first_id,
+        started_at,
+        ended_at,
+        attempts: total,
+        problems_done,
+        time_spent_minutes,
+        solve_rate: if total > 0 {
+            clean_solves / total as f64
+        } else {
+            0.0
+        },
+        mastery_gains,
+        new_unlocks,
+        reviews_due_tomorrow: repo
+            .count_reviews_due_by(end_of_tomorrow())
+            .map_err(|e| e.to_string())?,
+    }))
+}
+
+// --- Weekly Report ---
+
+/// Renders a markdown report for the (Monday-based) week containing `week`
+/// (`YYYY-MM-DD`, defaults to the current week). Optionally also writes it to `output_path`.
+pub fn generate_weekly_report(
+    conn: &Connection,
+    week: Option<&str>,
+    output_path: Option<&str>,
+) -> Result<String, String> {
+    let repo = Repository::new(conn);
+    let day = match week {
+        Some(w) => NaiveDate::parse_from_str(w.trim(), "%Y-%m-%d")
+            .map_err(|e| format!("Invalid week '{}': {}", w, e))?,
+        None => Local::now().date_naive(),
+    };
+    let monday = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+    let start_ts = local_midnight(monday);
+    let end_ts = local_midnight(monday + Duration::days(7)) - 1;
+
+    let attempts = repo
+        .get_attempts_between(start_ts, end_ts)
+        .map_err(|e| e.to_string())?;
+    let names = repo.get_skill_names().map_err(|e| e.to_string())?;
+
+    let mut md = String::new();
+    let _ = writeln!(
+        md,
+        "# Weekly Report: {} to {}\n",
+        monday,
+        monday + Duration::days(6)
+    );
+
+    // Activity
+    let mut per_day = [0usize; 7];
+    for a in &attempts {
+        if let Some(dt) = Local.timestamp_opt(a.timestamp, 0).single() {
+            let idx = (dt.date_naive() - monday).num_days();
+            if (0..7).contains(&idx) {
+                per_day[idx as usize] += 1;
+            }
+        }
+    }
+    let minutes: f64 = attempts.iter().map(|a| a.time_minutes).sum();
+    let problems = attempts
+        .iter()
+        .map(|a| a.problem_id)
+        .collect::<HashSet<_>>()
+        .len();
+    let _ = writeln!(md, "## Activity\n");
+    let _ = writeln!(
+        md,
+        "- **Attempts:** {} across {} problems",
+        attempts.len(),
+        problems
+    );
+    let _ = writeln!(
+        md,
+        "- **Active days:** {}/7",
+        per_day.iter().filter(|&&n| n > 0).count()
+    );
+    let _ = writeln!(
+        md,
+        "- **Time practiced:** {}h {:02}m\n",
+        (minutes / 60.0) as i64,
+        (minutes % 60.0) as i64
+    );
+    let _ = writeln!(md, "| Mon | Tue | Wed | Thu | Fri | Sat | Sun |");
+    let _ = writeln!(md, "|---|---|---|---|---|---|---|");
+    let cells: Vec<String> = per_day.iter().map(|n| n.to_string()).collect();
+    let _ = writeln!(md, "| {} |\n", cells.join(" | "));
+
+    // Retention
+    let reviews: Vec<_> = attempts.iter().filter(|a| a.is_review).collect();
+    let recalled = reviews
+        .iter()
+        .filter(|a| a.solved && !a.read_solution)
+        .count();
+    let _ = writeln!(md, "## Retention\n");
+    let _ = writeln!(
+        md,
+        "- **New problems attempted:** {}",
+        attempts.len() - reviews.len()
+    );
+    if reviews.is_empty() {
+        let _ = writeln!(md, "- **Reviews:** none this week\n");
+    } else {
+        let _ = writeln!(
+            md,
+            "- **Reviews:** {} ({} recalled cleanly, {:.0}%)\n",
+            reviews.len(),
+            recalled,
+            100.0 * recalled as f64 / reviews.len() as f64
+        );
+    }
+
+    // Mastery deltas
+    let before = skill_states_at(conn, start_ts - 1)?;
+    let after = skill_states_at(conn, end_ts)?;
+    let _ = writeln!(md, "## Mastery Changes\n");
+    let deltas: Vec<_> = before
+        .iter()
+        .zip(after.iter())
+        .filter(|(b, a)| (a.mastery - b.mastery).abs() > f64::EPSILON)
+        .collect();
+    if deltas.is_empty() {
+        let _ = writeln!(md, "No mastery changes this week.\n");
+    } else {
+        let _ = writeln!(md, "| Skill | Start | End | Change |");
+        let _ = writeln!(md, "|---|---|---|---|");
+        for (b, a) in deltas {
+            let _ = writeln!(
+                md,
+                "| {} | {:.0}% | {:.0}% | {:+.0}% |",
+                names.get(&a.skill_id).map(String::as_str).unwrap_or("?"),
+                b.mastery * 100.0,
+                a.mastery * 100.0,
+                (a.mastery - b.mastery) * 100.0
+            );
+        }
+        let _ = writeln!(md);
+    }
+
+    // Weakest unlocked skills
+    let prereqs = repo.get_skill_prereqs().map_err(|e| e.to_string())?;
+    let policy = pedagogy::get_unlock_policy(&repo)?;
+    let unlocked: HashSet<i64> = pedagogy::unlocked_skill_ids(&after, &prereqs, &policy)
+        .into_iter()
+        .collect();
+    let mut weakest: Vec<&SkillMasteryState> = after
+        .iter()
+        .filter(|s| unlocked.contains(&s.skill_id))
+        .collect();
+    weakest.sort_by(|a, b| a.mastery.total_cmp(&b.mastery));
+    weakest.truncate(WEEKLY_REPORT_WEAKEST_COUNT);
+    let _ = writeln!(md, "## Weakest Skills\n");
+    for s in &weakest {
+        let _ = writeln!(
+            md,
+            "- {}: {:.0}%",
+            names.get(&s.skill_id).map(String::as_str).unwrap_or("?"),
+            s.mastery * 100.0
+        );
+    }
+    let _ = writeln!(md);
+
+    // Suggested focus
+    let failures = repo
+        .get_failures_by_skill(start_ts, end_ts)
+        .map_err(|e| e.to_string())?;
+    let overdue = repo
+        .count_reviews_due_by(Utc::now().timestamp())
+        .map_err(|e| e.to_string())?;
+    let _ = writeln!(md, "## Suggested Focus\n");
+    if let Some(s) = weakest.first() {
+        let _ = writeln!(
+            md,
+            "- Drill **{}**, your weakest unlocked skill.",
+            names.get(&s.skill_id).map(String::as_str).unwrap_or("?")
+        );
+    }
+    if let Some((skill, n)) = failures.first() {
+        let _ = writeln!(
+            md,
+            "- Revisit **{}**: {} failed attempt(s) this week.",
+            skill, n
+        );
+    }
+    if overdue > 0 {
+        let _ = writeln!(md, "- Clear your {} overdue review(s) first.", overdue);
+    }
+    if attempts.is_empty() {
+        let _ = writeln!(md, "- Aim for at least one problem a day next week.");
+    }
+
+    if let Some(path) = output_path {
+        fs::write(path, &md).map_err(|e| format!("Failed to write report: {}", e))?;
+    }
+    Ok(md)
+}
+
+// --- Anonymized Export ---
+
+/// Exports mastery curves, interval distribution and outcome sequences with every
+/// identifying detail stripped, for sharing with scheduler-tuning efforts.
+pub fn export_anonymous_stats(conn: &Connection, path: &str) -> Result<(), String> {
+    let repo = Repository::new(conn);
+    let attempts = repo
+        .get_attempts_between(0, i64::MAX)
+        .map_err(|e| e.to_string())?;
+    let changes = repo
+        .get_mastery_changes_since(0)
+        .map_err(|e| e.to_string())?;
+    let names = repo.get_skill_names().map_err(|e| e.to_string())?;
+
+    // Everything is expressed relative to the first recorded activity
+    let origin = attempts
+        .iter()
+        .map(|a| a.timestamp)
+        .chain(changes.iter().map(|c| c.timestamp))
+        .min()
+        .unwrap_or(0);
+    let day = |ts: i64| (ts - origin) / DAY_SECONDS;
+
+    let mut curves: BTreeMap<i64, Vec<(i64, f64)>> = BTreeMap::new();
+    for c in &changes {
+        curves
+            .entry(c.skill_id)
+            .or_default()
+            .push((day(c.timestamp), c.new_mastery));
+    }
+    let skills = curves
+        .into_iter()
+        .map(|(sid, mastery_curve)| AnonSkillCurve {
+            skill: names.get(&sid).cloned().unwrap_or_default(),
+            mastery_curve,
+        })
+        .collect();
+
+    let intervals = repo.get_all_intervals().map_err(|e| e.to_string())?;
+    let mut interval_distribution = Vec::new();
+    let mut lower = 0.0;
+    for upper in INTERVAL_BUCKET_BOUNDS
+        .iter()
+        .copied()
+        .map(Some)
+        .chain(std::iter::once(None))
+    {
+        let count = intervals
+            .iter()
+            .filter(|&&d| d > lower && upper.is_none_or(|u| d <= u))
+            .count() as i64;
+        interval_distribution.push(IntervalBucket {
+            min_days: lower,
+            max_days: upper,
+            count,
+        });
+        lower = upper.unwrap_or(lower);
+    }
+
+    // One sequence per concept, in first-seen order; concept IDs themselves are dropped
+    let mut sequences: Vec<Vec<AnonOutcome>> = Vec::new();
+    let mut index: BTreeMap<i64, usize> = BTreeMap::new();
+    for a in &attempts {
+        let i = *index.entry(a.concept_id).or_insert_with(|| {
+            sequences.push(Vec::new());
+            sequences.len() - 1
+        });
+        sequences[i].push(AnonOutcome {
+            day: day(a.timestamp),
+            difficulty: a.difficulty,
+            solved: a.solved,
+            read_solution: a.read_solution,
+            time_ratio: a.time_minutes / pedagogy::expected_time(a.difficulty),
+        });
+    }
+
+    let stats = AnonymousStats {
+        format_version: ANON_STATS_FORMAT_VERSION,
+        skills,
+        interval_distribution,
+        outcome_sequences: sequences,
+    };
+    let json = serde_json::to_string_pretty(&stats).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+// --- Profile Card ---
+
+/// Writes a small, shareable JSON summary: streak, solved counts and strongest skills.
+pub fn export_profile_card(conn: &Connection, path: &str) -> Result<ProfileCard, String> {
+    let repo = Repository::new(conn);
+    let solved_by_difficulty: Vec<DifficultyCount> = repo
+        .get_solved_by_difficulty()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(difficulty, count)| DifficultyCount { difficulty, count })
+        .collect();
+
+    let names = repo.get_skill_names().map_err(|e| e.to_string())?;
+    let mut states = repo.get_all_skill_states().map_err(|e| e.to_string())?;
+    states.retain(|s| s.attempts > 0);
+    states.sort_by(|a, b| b.mastery.total_cmp(&a.mastery));
+    let top_skills = states
+        .iter()
+        .take(PROFILE_TOP_SKILLS)
+        .map(|s| SkillLevel {
+            skill: names.get(&s.skill_id).cloned().unwrap_or_default(),
+            mastery: s.mastery,
+        })
+        .collect();
+
+    let card = ProfileCard {
+        generated_on: Local::now().date_naive().to_string(),
+        streak: get_streak(conn)?,
+        total_solved: solved_by_difficulty.iter().map(|d| d.count).sum(),
+        solved_by_difficulty,
+        top_skills,
+    };
+    let json = serde_json::to_string_pretty(&card).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(card)
+}
+
+/// Counts consecutive local days with at least one attempt.
+pub fn get_streak(conn: &Connection) -> Result<Streak, String> {
+    let timeline = Repository::new(conn)
+        .get_attempt_timeline()
+        .map_err(|e| e.to_string())?;
+    let days: BTreeSet<NaiveDate> = timeline
+        .iter()
+        .filter_map(|&(_, ts)| Local.timestamp_opt(ts, 0).single())
+        .map(|dt| dt.date_naive())
+        .collect();
+
+    let mut streak = Streak::default();
+    let mut run = 0;
+    let mut prev: Option<NaiveDate> = None;
+    for &d in &days {
+        run = match prev {
+            Some(p) if d - p == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        streak.longest = streak.longest.max(run);
+        prev = Some(d);
+    }
+
+    // A streak stays alive until a full day passes without practice
+    let today = Local::now().date_naive();
+    if prev.is_some_and(|last| today - last <= Duration::days(1)) {
+        streak.current = run;
+    }
+    Ok(streak)
+}
+
+/// Streak, solve times and interval history of the concept behind `problem_id`.
+pub fn get_problem_stats(conn: &Connection, problem_id: i64) -> Result<ProblemStats, String> {
+    let repo = Repository::new(conn);
+    if !repo.problem_exists(problem_id).map_err(|e| e.to_string())? {
+        return Err(format!("Problem {} does not exist", problem_id));
+    }
+    let (parent_id, _) = repo
+        .resolve_parent_id(problem_id)
+        .map_err(|e| e.to_string())?;
+    let outcomes = repo
+        .get_concept_outcomes(parent_id)
+        .map_err(|e| e.to_string())?;
+    let streak = outcomes
+        .iter()
+        .rev()
+        .take_while(|&&(_, clean)| clean)
+        .count() as i64;
+    let (best_minutes, worst_minutes, average_minutes) = repo
+        .get_concept_solve_times(parent_id)
+        .map_err(|e| e.to_string())?;
+    Ok(ProblemStats {
+        problem_id: parent_id,
+        attempts: outcomes.len() as i64,
+        streak,
+        best_minutes,
+        worst_minutes,
+        average_minutes,
+        intervals: repo
+            .get_interval_history(parent_id)
+            .map_err(|e| e.to_string())?,
+    })
+}
+
+// --- Review Quality ---
+
+/// Which SM-2 branch each review in the range took, per Monday-based week and per skill,
+/// with the week's average ease factor alongside. Defaults to the last
+/// `REPORT_DEFAULT_RANGE_DAYS` days.
+pub fn get_review_quality_stats(
+    conn: &Connection,
+    range: Option<&DateRange>,
+) -> Result<ReviewQualityStats, String> {
+    let repo = Repository::new(conn);
+    let (from, to) = resolve_range(range)?;
+    let start_ts = local_midnight(from);
+    let end_ts = local_midnight(to + Duration::days(1)) - 1;
+    let week_of =
+        |date: NaiveDate| date - Duration::days(date.weekday().num_days_from_monday() as i64);
+
+    let mut weeks: BTreeMap<NaiveDate, (ReviewBranchCounts, f64, usize)> = BTreeMap::new();
+    let mut week = week_of(from);
+    while week <= to {
+        weeks.insert(week, Default::default());
+        week += Duration::days(7);
+    }
+
+    let reviews = repo
+        .get_reviews_between(start_ts, end_ts)
+        .map_err(|e| e.to_string())?;
+    let mut total = ReviewBranchCounts::default();
+    let mut branches: HashMap<i64, ReviewBranch> = HashMap::new();
+    for r in &reviews {
+        let expected = r
+            .expected_minutes_override
+            .unwrap_or_else(|| pedagogy::expected_time(r.difficulty));
+        let branch = pedagogy::review_branch(!r.clean, r.time_minutes / expected);
+        total.add(branch);
+        if let Some(w) = weeks.get_mut(&week_of(plan::local_date(r.timestamp))) {
+            w.0.add(branch);
+        }
+        branches.insert(r.attempt_id, branch);
+    }
+
+    for (ts, ease) in repo
+        .get_ease_factors_between(start_ts, end_ts)
+        .map_err(|e| e.to_string())?
+    {
+        if let Some(w) = weeks.get_mut(&week_of(plan::local_date(ts))) {
+            w.1 += ease;
+            w.2 += 1;
+        }
+    }
+
+    let names = repo.get_skill_names().map_err(|e| e.to_string())?;
+    let mut by_skill: BTreeMap<i64, ReviewBranchCounts> = BTreeMap::new();
+    for (attempt_id, skill_id) in repo
+        .get_attempt_skills_between(start_ts, end_ts)
+        .map_err(|e| e.to_string())?
+    {
+        if let Some(&branch) = branches.get(&attempt_id) {
+            by_skill.entry(skill_id).or_default().add(branch);
+        }
+    }
+    let mut skills: Vec<SkillReviewQuality> = by_skill
+        .into_iter()
+        .map(|(skill_id, counts)| SkillReviewQuality {
+            skill_id,
+            skill: names.get(&skill_id).cloned().unwrap_or_default(),
+            counts,
+        })
+        .collect();
+    let reviewed = |c: &ReviewBranchCounts| c.fail + c.struggle + c.normal + c.speed;
+    skills.sort_by(|a, b| {
+        reviewed(&b.counts)
+            .cmp(&reviewed(&a.counts))
+            .then_with(|| a.skill.cmp(&b.skill))
+    });
+
+    Ok(ReviewQualityStats {
+        from: from.to_string(),
+        to: to.to_string(),
+        total,
+        weeks: weeks
+            .into_iter()
+            .map(
+                |(week_start, (counts, ease_sum, ease_n))| ReviewQualityWeek {
+                    week_start: week_start.to_string(),
+                    counts,
+                    mean_ease: (ease_n > 0).then(|| ease_sum / ease_n as f64),
+                },
+            )
+            .collect(),
+        skills,
+    })
+}
+
+/// Returns the (start, end) indices into the timeline for the requested session.
+fn find_session(timeline: &[(i64, i64)], session_id: Option<i64>) -> Option<(usize, usize)> {
+    let start = match session_id {
+        Some(id) => timeline.iter().position(|&(aid, _)| aid == id)?,
+        None => {
+            let mut i = timeline.len().checked_sub(1)?;
+            while i > 0 && timeline[i].1 - timeline[i - 1].1 <= SESSION_GAP_SECONDS {
+                i -= 1;
+            }
+            i
+        }
+    };
+    let mut end = start;
+    while end + 1 < timeline.len() && timeline[end + 1].1 - timeline[end].1 <= SESSION_GAP_SECONDS {
+        end += 1;
+    }
+    Some((start, end))
+}
+
+/// Reconstructs every skill's state as of `ts` by rolling back logged changes made after it.
+fn skill_states_at(conn: &Connection, ts: i64) -> Result<Vec<SkillMasteryState>, String> {
+    let repo = Repository::new(conn);
+    let mut states = repo.get_all_skill_states().map_err(|e| e.to_string())?;
+    let changes = repo
+        .get_mastery_changes_since(ts + 1)
+        .map_err(|e| e.to_string())?;
+
+    // Earliest change after `ts` holds the value at `ts`; every attempt after it added one
+    let mut rollback: BTreeMap<i64, (f64, i32)> = BTreeMap::new();
+    for c in &changes {
+        let entry = rollback.entry(c.skill_id).or_insert((c.old_mastery, 0));
+        if c.attempt_id.is_some() {
+            entry.1 += 1;
+        }
+    }
+    for s in states.iter_mut() {
+        if let Some(&(mastery, later_attempts)) = rollback.get(&s.skill_id) {
+            s.mastery = mastery;
+            s.attempts = (s.attempts - later_attempts).max(0);
+        }
+    }
+    Ok(states)
+}
+
+/// Parses an optional date range, defaulting to the last `REPORT_DEFAULT_RANGE_DAYS` days.
+pub fn resolve_range(range: Option<&DateRange>) -> Result<(NaiveDate, NaiveDate), String> {
+    let parse = |s: &str| {
+        NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date '{}': {}", s, e))
+    };
+    let to = match range.and_then(|r| r.to.as_deref()) {
+        Some(s) => parse(s)?,
+        None => Local::now().date_naive(),
+    };
+    let from = match range.and_then(|r| r.from.as_deref()) {
+        Some(s) => parse(s)?,
+        None => to - Duration::days(REPORT_DEFAULT_RANGE_DAYS - 1),
+    };
+    if from > to {
+        return Err(format!("Range start {} is after end {}", from, to));
+    }
+    Ok((from, to))
+}
+
+pub fn local_midnight(date: NaiveDate) -> i64 {
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|dt| Local.from_local_datetime(&dt).earliest())
+        .map(|dt| dt.timestamp())
+        .unwrap_or_else(|| date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp())
+}
+
+fn end_of_tomorrow() -> i64 {
+    let tomorrow = Local::now().date_naive() + Duration::days(1);
+    tomorrow
+        .and_hms_opt(23, 59, 59)
+        .and_then(|dt| Local.from_local_datetime(&dt).single())
+        .map(|dt| dt.timestamp())
+        .unwrap_or_else(|| Utc::now().timestamp() + 2 * DAY_SECONDS)
+}
@@ -1,376 +1,3298 @@
 // src/repository.rs
 
 use crate::constants::*;
-use crate::models::{Difficulty, ProblemRepetitionState, ProblemView, SkillMasteryState};
-use log::debug;
+use crate::models::{
+    AttemptDraft, AttemptLog, AttemptRecord, AuditEntry, CommandUsage, Difficulty, DifficultyFit,
+    IntervalPoint, JsonAlternative, LastAttempt, MasteryChange, MistakeStat, MistakeType,
+    Pagination, PlanDay, ProblemListItem, ProblemRepetitionState, ProblemSort, ProblemStatus,
+    ProblemView, QueryStat, QueueKind, Retrospective, ReviewAttempt, ReviewOrder, SearchHit,
+    ServeOutcome, ServedProblem, SkillActivity, SkillCooccurrence, SkillMasteryState, SkillProblem,
+    SkipReason, SortDirection, SortSpec, StatusCounts, StoredTimer, StudyPlan, SuspendedProblem,
+    SyncRow, TagCount, TimedOutcome,
+};
+use log::{info, warn};
 use rusqlite::{params, Connection, OptionalExtension, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::str::FromStr;
+use std::sync::{Mutex, PoisonError};
 
-/// Fetches the current mastery state for a specific skill.
-pub fn get_skill_state(conn: &Connection, skill_id: i64) -> Result<SkillMasteryState> {
-    conn.query_row(
-        "SELECT mastery, attempts FROM skill_state WHERE skill_id = ?",
-        [skill_id],
-        |row| {
-            Ok(SkillMasteryState {
-                skill_id,
-                mastery: row.get(0)?,
-                attempts: row.get(1)?,
-            })
-        },
-    )
-    .or_else(|_| {
-        Ok(SkillMasteryState {
-            skill_id,
-            mastery: 0.0,
-            attempts: 0,
-        })
-    })
+/// Selection queries whose plan has already been logged this session.
+static EXPLAINED_QUERIES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+/// Typed access to the database. Borrows a connection (or transaction), so one is cheap
+/// to create wherever a connection is at hand. Every statement goes through the
+/// connection's statement cache, so repeated calls skip parsing the SQL again.
+#[derive(Clone, Copy)]
+pub struct Repository<'c> {
+    conn: &'c Connection,
 }
 
-/// Checks if a problem ID is an alternative.
-/// Returns (parent_id, is_alternative).
-/// If it's a normal problem, returns (id, false).
-pub fn resolve_parent_id(conn: &Connection, problem_id: i64) -> Result<(i64, bool)> {
-    let parent_id: Option<i64> = conn
-        .query_row(
-            "SELECT parent_id FROM alternatives WHERE id = ?",
-            [problem_id],
-            |row| row.get(0),
-        )
-        .optional()?;
+impl<'c> Repository<'c> {
+    pub fn new(conn: &'c Connection) -> Self {
+        Repository { conn }
+    }
+
+    /// Fetches the current mastery state for a specific skill.
+    pub fn get_skill_state(&self, skill_id: i64) -> Result<SkillMasteryState> {
+        self.conn
+            .prepare_cached("SELECT mastery, attempts FROM skill_state WHERE skill_id = ?")?
+            .query_row([skill_id], |row| {
+                Ok(SkillMasteryState {
+                    skill_id,
+                    mastery: row.get(0)?,
+                    attempts: row.get(1)?,
+                })
+            })
+            .or_else(|_| {
+                Ok(SkillMasteryState {
+                    skill_id,
+                    mastery: 0.0,
+                    attempts: 0,
+                })
+            })
+    }
+
+    /// Checks if a problem ID is an alternative.
+    /// Returns (parent_id, is_alternative).
+    /// If it's a normal problem, returns (id, false).
+    pub fn resolve_parent_id(&self, problem_id: i64) -> Result<(i64, bool)> {
+        let parent_id: Option<i64> = self
+            .conn
+            .prepare_cached("SELECT parent_id FROM alternatives WHERE id = ?")?
+            .query_row([problem_id], |row| row.get(0))
+            .optional()?;
+
+        match parent_id {
+            Some(pid) => Ok((pid, true)),
+            None => Ok((problem_id, false)),
+        }
+    }
+
+    /// Tries to find a random alternative for a given parent problem ID.
+    pub fn get_random_alternative(&self, parent_id: i64) -> Result<Option<ProblemView>> {
+        let result = self
+            .conn
+            .prepare_cached(
+                "SELECT id, title, difficulty, url 
+             FROM alternatives 
+             WHERE parent_id = ? 
+             ORDER BY selection_random() 
+             LIMIT 1",
+            )?
+            .query_row([parent_id], |row| {
+                Ok(ProblemView {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    difficulty: Difficulty::from_str(&row.get::<_, String>(2)?)
+                        .unwrap_or(Difficulty::Medium),
+                    url: row.get(3)?,
+                    queue: QueueKind::AlternativeFor { parent_id },
+                    queue_display: Some(QueueKind::AlternativeFor { parent_id }.display()),
+                    skills: Vec::new(),
+                    skill_ids: Vec::new(),
+                    attempt_count: 0,
+                    last_attempt_ts: None,
+                    next_review_ts: None,
+                    retrospectives: Vec::new(),
+                })
+            })
+            .optional()?;
+
+        if let Some(mut p) = result {
+            // Alternatives might not have direct skill mappings in your DB design,
+            // fallback to parent skills if needed, or if alternatives share skills:
+            self.fill_problem_details(&mut p, parent_id)?;
+            return Ok(Some(p));
+        }
+        Ok(None)
+    }
+
+    /// Updates the mastery state for a skill.
+    pub fn update_skill_state(&self, state: &SkillMasteryState) -> Result<()> {
+        self.conn
+            .prepare_cached("UPDATE skill_state SET mastery = ?, attempts = ? WHERE skill_id = ?")?
+            .execute(params![state.mastery, state.attempts, state.skill_id])?;
+        Ok(())
+    }
+
+    pub fn skill_exists(&self, skill_id: i64) -> Result<bool> {
+        self.conn
+            .prepare_cached("SELECT EXISTS(SELECT 1 FROM skills WHERE id = ?)")?
+            .query_row([skill_id], |row| row.get(0))
+    }
+
+    /// Fetches repetition state (SM-2) for a problem.
+    pub fn get_problem_repetition_state(&self, problem_id: i64) -> Result<ProblemRepetitionState> {
+        self.conn
+            .prepare_cached(
+                "SELECT ease_factor, interval_days FROM problem_state WHERE problem_id = ?",
+            )?
+            .query_row([problem_id], |row| {
+                Ok(ProblemRepetitionState {
+                    problem_id,
+                    ease_factor: row.get(0)?,
+                    interval_days: row.get(1)?,
+                    next_review_ts: 0, // Not needed for logic calc, overwritten on save
+                })
+            })
+            .optional()?
+            .map_or(
+                Ok(ProblemRepetitionState {
+                    problem_id,
+                    ease_factor: EASE_FACTOR_DEFAULT,
+                    interval_days: 0.0,
+                    next_review_ts: 0,
+                }),
+                Ok,
+            )
+    }
+
+    /// Saves the calculated repetition state.
+    pub fn save_problem_repetition_state(&self, state: &ProblemRepetitionState) -> Result<()> {
+        self.conn.prepare_cached("INSERT OR REPLACE INTO problem_state (problem_id, ease_factor, interval_days, next_review_ts) VALUES (?, ?, ?, ?)")?.execute(params![state.problem_id, state.ease_factor, state.interval_days, state.next_review_ts])?;
+        Ok(())
+    }
+
+    /// Fills the card fields of a `ProblemView`: skills and history are read from the concept
+    /// (`parent_id`), since alternatives share their parent's skills and schedule.
+    fn fill_problem_details(&self, p: &mut ProblemView, parent_id: i64) -> Result<()> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT s.id, s.name
+             FROM skills s
+             JOIN problem_skills ps ON s.id = ps.skill_id
+             WHERE ps.problem_id = ?
+             ORDER BY s.id",
+        )?;
+        let skills = stmt
+            .query_map([parent_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<(i64, String)>, _>>()?;
+        (p.skill_ids, p.skills) = skills.into_iter().unzip();
+
+        (p.attempt_count, p.last_attempt_ts) = self
+            .conn
+            .prepare_cached(
+                "SELECT COUNT(*), MAX(timestamp) FROM attempts
+             WHERE problem_id = ?1
+                OR problem_id IN (SELECT id FROM alternatives WHERE parent_id = ?1)",
+            )?
+            .query_row([parent_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        p.next_review_ts = self
+            .conn
+            .prepare_cached("SELECT next_review_ts FROM problem_state WHERE problem_id = ?")?
+            .query_row([parent_id], |row| row.get(0))
+            .optional()?;
+        Ok(())
+    }
+
+    /// Records a raw attempt log. Returns the new attempt ID.
+    pub fn log_attempt(&self, log: &AttemptLog, timestamp: i64, time_clamped: bool) -> Result<i64> {
+        let explanation = log
+            .explanation
+            .as_deref()
+            .map(str::trim)
+            .filter(|e| !e.is_empty());
+        self.conn.prepare_cached("INSERT INTO attempts (problem_id, time_minutes, solved, read_solution, timestamp, explanation, time_clamped, pause_count, paused_minutes) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)")?.execute(params![log.problem_id, log.time_minutes, log.solved, log.read_solution, timestamp, explanation, time_clamped, log.pause_count, log.paused_minutes])?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn get_attempt_key_result(&self, key: &str) -> Result<Option<String>> {
+        self.conn
+            .prepare_cached("SELECT result FROM attempt_keys WHERE key = ?")?
+            .query_row([key], |row| row.get(0))
+            .optional()
+    }
+
+    pub fn save_attempt_key(&self, key: &str, result: &str, timestamp: i64) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT INTO attempt_keys (key, result, created_at) VALUES (?1, ?2, ?3)",
+            )?
+            .execute(params![key, result, timestamp])?;
+        Ok(())
+    }
+
+    /// Whether the ID is a seeded/custom problem or an alternative.
+    pub fn problem_exists(&self, problem_id: i64) -> Result<bool> {
+        self.conn
+            .prepare_cached(
+                "SELECT EXISTS(SELECT 1 FROM problems WHERE id = ?1)
+                 OR EXISTS(SELECT 1 FROM alternatives WHERE id = ?1)",
+            )?
+            .query_row([problem_id], |row| row.get(0))
+    }
+
+    /// Most recent explanation written for a concept (the parent or any of its alternatives).
+    pub fn get_latest_explanation(&self, parent_id: i64) -> Result<Option<String>> {
+        self.conn.prepare_cached("SELECT explanation FROM attempts
+             WHERE (problem_id = ?1 OR problem_id IN (SELECT id FROM alternatives WHERE parent_id = ?1))
+             AND explanation IS NOT NULL
+             ORDER BY timestamp DESC, id DESC
+             LIMIT 1")?.query_row([parent_id],
+            |row| row.get(0),)
+        .optional()
+    }
+
+    // --- Mistake Taxonomy ---
+
+    pub fn get_mistake_types(&self) -> Result<Vec<MistakeType>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT id, name FROM mistake_types ORDER BY name")?;
+        let types = stmt
+            .query_map([], |row| {
+                Ok(MistakeType {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(types)
+    }
+
+    /// Adds a mistake type to the taxonomy (no-op if it already exists).
+    pub fn add_mistake_type(&self, name: &str) -> Result<()> {
+        self.conn
+            .prepare_cached("INSERT OR IGNORE INTO mistake_types (name) VALUES (?)")?
+            .execute([name])?;
+        Ok(())
+    }
+
+    /// Links mistake tags to an attempt. Unknown tags extend the taxonomy.
+    pub fn tag_attempt_mistakes(&self, attempt_id: i64, mistakes: &[String]) -> Result<()> {
+        let mut stmt = self.conn.prepare_cached(
+            "INSERT OR IGNORE INTO attempt_mistakes (attempt_id, mistake_type_id)
+             SELECT ?, id FROM mistake_types WHERE name = ?",
+        )?;
+        for m in mistakes {
+            let name = m.trim();
+            if name.is_empty() {
+                continue;
+            }
+            self.add_mistake_type(name)?;
+            stmt.execute(params![attempt_id, name])?;
+        }
+        Ok(())
+    }
+
+    /// Counts mistake tags per skill, most frequent first.
+    pub fn get_mistake_stats(&self) -> Result<Vec<MistakeStat>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT s.name, mt.name, count(*) AS n
+             FROM attempt_mistakes am
+             JOIN mistake_types mt ON am.mistake_type_id = mt.id
+             JOIN attempt_skills ask ON ask.attempt_id = am.attempt_id
+             JOIN skills s ON ask.skill_id = s.id
+             GROUP BY s.id, mt.id
+             ORDER BY n DESC, s.name, mt.name",
+        )?;
+        let stats = stmt
+            .query_map([], |row| {
+                Ok(MistakeStat {
+                    skill: row.get(0)?,
+                    mistake: row.get(1)?,
+                    count: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(stats)
+    }
+
+    /// Every pair of skills that share a problem or a failed attempt, most failures first.
+    pub fn get_skill_cooccurrence(&self) -> Result<Vec<SkillCooccurrence>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT sa.name, sb.name, SUM(pair.problems) AS problems, SUM(pair.failures) AS failures
+             FROM (
+                SELECT x.skill_id AS a, y.skill_id AS b, 1 AS problems, 0 AS failures
+                FROM problem_skills x
+                JOIN problem_skills y ON y.problem_id = x.problem_id AND x.skill_id < y.skill_id
+                UNION ALL
+                SELECT x.skill_id, y.skill_id, 0, 1
+                FROM attempts t
+                JOIN attempt_skills x ON x.attempt_id = t.id
+                JOIN attempt_skills y ON y.attempt_id = t.id AND x.skill_id < y.skill_id
+                WHERE t.solved = 0 OR t.read_solution = 1
+             ) pair
+             JOIN skills sa ON sa.id = pair.a
+             JOIN skills sb ON sb.id = pair.b
+             GROUP BY pair.a, pair.b
+             ORDER BY failures DESC, problems DESC, sa.name, sb.name",
+        )?;
+        let pairs = stmt
+            .query_map([], |row| {
+                Ok(SkillCooccurrence {
+                    skill_a: row.get(0)?,
+                    skill_b: row.get(1)?,
+                    problems: row.get(2)?,
+                    failures: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(pairs)
+    }
+
+    // --- Retrospectives ---
+
+    pub fn add_retrospective(
+        &self,
+        attempt_id: i64,
+        problem_id: i64,
+        prompt: &str,
+        created_at: i64,
+    ) -> Result<i64> {
+        self.conn.prepare_cached("INSERT INTO retrospectives (attempt_id, problem_id, prompt, created_at) VALUES (?, ?, ?, ?)")?.execute(params![attempt_id, problem_id, prompt, created_at],)?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Retrospectives stored against a (parent) problem, newest first.
+    pub fn get_retrospectives(&self, problem_id: i64) -> Result<Vec<Retrospective>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, attempt_id, prompt, response, created_at
+             FROM retrospectives
+             WHERE problem_id = ?
+             ORDER BY created_at DESC, id DESC",
+        )?;
+        let retros = stmt
+            .query_map([problem_id], |row| {
+                Ok(Retrospective {
+                    id: row.get(0)?,
+                    attempt_id: row.get(1)?,
+                    prompt: row.get(2)?,
+                    response: row.get(3)?,
+                    created_at: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(retros)
+    }
+
+    /// Stores the user's answer to a retrospective prompt. Returns false if it doesn't exist.
+    pub fn answer_retrospective(&self, retrospective_id: i64, response: &str) -> Result<bool> {
+        let changed = self
+            .conn
+            .prepare_cached("UPDATE retrospectives SET response = ? WHERE id = ?")?
+            .execute(params![response, retrospective_id])?;
+        Ok(changed > 0)
+    }
+
+    // --- Attempt Drafts ---
+
+    pub fn save_attempt_draft(&self, draft: &AttemptDraft) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT OR REPLACE INTO attempt_drafts
+                    (problem_id, elapsed_minutes, notes, code, updated_at)
+                 VALUES (?, ?, ?, ?, ?)",
+            )?
+            .execute(params![
+                draft.problem_id,
+                draft.elapsed_minutes,
+                draft.notes,
+                draft.code,
+                draft.updated_at
+            ])?;
+        Ok(())
+    }
+
+    pub fn get_attempt_draft(&self, problem_id: i64) -> Result<Option<AttemptDraft>> {
+        self.conn
+            .prepare_cached(
+                "SELECT problem_id, elapsed_minutes, notes, code, updated_at
+                 FROM attempt_drafts WHERE problem_id = ?",
+            )?
+            .query_row([problem_id], |row| {
+                Ok(AttemptDraft {
+                    problem_id: row.get(0)?,
+                    elapsed_minutes: row.get(1)?,
+                    notes: row.get(2)?,
+                    code: row.get(3)?,
+                    updated_at: row.get(4)?,
+                })
+            })
+            .optional()
+    }
+
+    /// Removes and returns the problem's draft.
+    pub fn take_attempt_draft(&self, problem_id: i64) -> Result<Option<AttemptDraft>> {
+        let draft = self.get_attempt_draft(problem_id)?;
+        if draft.is_some() {
+            self.conn
+                .prepare_cached("DELETE FROM attempt_drafts WHERE problem_id = ?")?
+                .execute([problem_id])?;
+        }
+        Ok(draft)
+    }
+
+    // --- Attempt Timers ---
+
+    pub fn get_attempt_timer(&self, problem_id: i64) -> Result<Option<StoredTimer>> {
+        self.conn
+            .prepare_cached(
+                "SELECT started_at, paused_at, pause_count, paused_seconds
+                 FROM attempt_timers WHERE problem_id = ?",
+            )?
+            .query_row([problem_id], |row| {
+                Ok(StoredTimer {
+                    started_at: row.get(0)?,
+                    paused_at: row.get(1)?,
+                    pause_count: row.get(2)?,
+                    paused_seconds: row.get(3)?,
+                })
+            })
+            .optional()
+    }
+
+    pub fn save_attempt_timer(&self, problem_id: i64, timer: &StoredTimer) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT OR REPLACE INTO attempt_timers
+                    (problem_id, started_at, paused_at, pause_count, paused_seconds)
+                 VALUES (?, ?, ?, ?, ?)",
+            )?
+            .execute(params![
+                problem_id,
+                timer.started_at,
+                timer.paused_at,
+                timer.pause_count,
+                timer.paused_seconds
+            ])?;
+        Ok(())
+    }
+
+    /// Problems whose timer is running, not paused.
+    pub fn get_running_attempt_timers(&self) -> Result<Vec<i64>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT problem_id FROM attempt_timers WHERE paused_at IS NULL")?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
+    }
+
+    pub fn delete_attempt_timer(&self, problem_id: i64) -> Result<()> {
+        self.conn
+            .prepare_cached("DELETE FROM attempt_timers WHERE problem_id = ?")?
+            .execute([problem_id])?;
+        Ok(())
+    }
+
+    // --- Attempt History ---
+
+    /// Newest timestamp among attempts, mastery changes and interval changes.
+    pub fn get_latest_event_ts(&self) -> Result<Option<i64>> {
+        self.conn
+            .prepare_cached(
+                "SELECT MAX(ts) FROM (
+                    SELECT MAX(timestamp) AS ts FROM attempts
+                    UNION ALL SELECT MAX(timestamp) FROM skill_mastery_log
+                    UNION ALL SELECT MAX(timestamp) FROM problem_interval_log
+                 )",
+            )?
+            .query_row([], |row| row.get(0))
+    }
+
+    /// Attempt IDs and timestamps in chronological order (used for session detection).
+    pub fn get_attempt_timeline(&self) -> Result<Vec<(i64, i64)>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT id, timestamp FROM attempts ORDER BY timestamp, id")?;
+        let timeline = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(timeline)
+    }
+
+    /// Attempts with `start_ts <= timestamp <= end_ts`, oldest first.
+    pub fn get_attempts_between(&self, start_ts: i64, end_ts: i64) -> Result<Vec<AttemptRecord>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT a.problem_id, a.time_minutes, a.solved, a.read_solution, a.timestamp,
+                EXISTS (
+                    SELECT 1 FROM attempts b
+                    WHERE (b.timestamp < a.timestamp OR (b.timestamp = a.timestamp AND b.id < a.id))
+                    AND COALESCE((SELECT parent_id FROM alternatives WHERE id = b.problem_id), b.problem_id)
+                      = COALESCE((SELECT parent_id FROM alternatives WHERE id = a.problem_id), a.problem_id)
+                ),
+                COALESCE((SELECT parent_id FROM alternatives WHERE id = a.problem_id), a.problem_id),
+                COALESCE(p.difficulty, alt.difficulty, 'Medium')
+             FROM attempts a
+             LEFT JOIN problems p ON p.id = a.problem_id
+             LEFT JOIN alternatives alt ON alt.id = a.problem_id
+             WHERE a.timestamp BETWEEN ? AND ?
+             ORDER BY a.timestamp, a.id",
+        )?;
+        let attempts = stmt
+            .query_map([start_ts, end_ts], |row| {
+                Ok(AttemptRecord {
+                    problem_id: row.get(0)?,
+                    time_minutes: row.get(1)?,
+                    solved: row.get(2)?,
+                    read_solution: row.get(3)?,
+                    timestamp: row.get(4)?,
+                    is_review: row.get(5)?,
+                    concept_id: row.get(6)?,
+                    difficulty: Difficulty::from_str(&row.get::<_, String>(7)?)
+                        .unwrap_or(Difficulty::Medium),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(attempts)
+    }
+
+    /// Reviews (attempts after a concept's first) with `start_ts <= timestamp <= end_ts`,
+    /// oldest first, with the overrides `update_repetition_logic` would have used.
+    pub fn get_reviews_between(&self, start_ts: i64, end_ts: i64) -> Result<Vec<ReviewAttempt>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT a.id, a.timestamp, a.time_minutes, a.solved = 1 AND a.read_solution = 0,
+                COALESCE(o.difficulty, p.difficulty, alt.difficulty, 'Medium'),
+                o.expected_minutes
+             FROM attempts a
+             LEFT JOIN problems p ON p.id = a.problem_id
+             LEFT JOIN alternatives alt ON alt.id = a.problem_id
+             LEFT JOIN problem_overrides o ON o.problem_id = a.problem_id
+             WHERE a.timestamp BETWEEN ? AND ?
+             AND EXISTS (
+                SELECT 1 FROM attempts b
+                WHERE (b.timestamp < a.timestamp OR (b.timestamp = a.timestamp AND b.id < a.id))
+                AND COALESCE((SELECT parent_id FROM alternatives WHERE id = b.problem_id), b.problem_id)
+                  = COALESCE(alt.parent_id, a.problem_id)
+             )
+             ORDER BY a.timestamp, a.id",
+        )?;
+        let reviews = stmt
+            .query_map([start_ts, end_ts], |row| {
+                Ok(ReviewAttempt {
+                    attempt_id: row.get(0)?,
+                    timestamp: row.get(1)?,
+                    time_minutes: row.get(2)?,
+                    clean: row.get(3)?,
+                    difficulty: Difficulty::from_str(&row.get::<_, String>(4)?)
+                        .unwrap_or(Difficulty::Medium),
+                    expected_minutes_override: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(reviews)
+    }
+
+    /// (attempt_id, skill_id) for every skill credited by attempts in a time range.
+    pub fn get_attempt_skills_between(
+        &self,
+        start_ts: i64,
+        end_ts: i64,
+    ) -> Result<Vec<(i64, i64)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT x.attempt_id, x.skill_id
+             FROM attempt_skills x
+             JOIN attempts a ON a.id = x.attempt_id
+             WHERE a.timestamp BETWEEN ? AND ?",
+        )?;
+        let rows = stmt
+            .query_map([start_ts, end_ts], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    // --- Interval History ---
+
+    pub fn log_interval(&self, state: &ProblemRepetitionState, timestamp: i64) -> Result<()> {
+        self.conn.prepare_cached("INSERT INTO problem_interval_log (problem_id, interval_days, ease_factor, timestamp)
+             VALUES (?, ?, ?, ?)")?.execute(params![
+                state.problem_id,
+                state.interval_days,
+                state.ease_factor,
+                timestamp
+            ],)?;
+        Ok(())
+    }
+
+    /// Oldest first.
+    pub fn get_interval_history(&self, parent_id: i64) -> Result<Vec<IntervalPoint>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT timestamp, interval_days, ease_factor FROM problem_interval_log
+             WHERE problem_id = ?
+             ORDER BY timestamp, id",
+        )?;
+        let points = stmt
+            .query_map([parent_id], |row| {
+                Ok(IntervalPoint {
+                    timestamp: row.get(0)?,
+                    interval_days: row.get(1)?,
+                    ease_factor: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(points)
+    }
+
+    /// (timestamp, ease_factor) of every interval change in a time range, oldest first.
+    pub fn get_ease_factors_between(&self, start_ts: i64, end_ts: i64) -> Result<Vec<(i64, f64)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT timestamp, ease_factor FROM problem_interval_log
+             WHERE timestamp BETWEEN ? AND ?
+             ORDER BY timestamp, id",
+        )?;
+        let rows = stmt
+            .query_map([start_ts, end_ts], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// `(best, worst, average)` minutes over the concept's clean solves; all `None` without any.
+    pub fn get_concept_solve_times(
+        &self,
+        parent_id: i64,
+    ) -> Result<(Option<f64>, Option<f64>, Option<f64>)> {
+        self.conn
+            .prepare_cached(
+                "SELECT MIN(time_minutes), MAX(time_minutes), AVG(time_minutes) FROM attempts
+             WHERE (problem_id = ?1
+                OR problem_id IN (SELECT id FROM alternatives WHERE parent_id = ?1))
+             AND solved = 1 AND read_solution = 0",
+            )?
+            .query_row([parent_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+    }
+
+    /// The latest attempt of each listed problem or its alternatives, in one query. Problems
+    /// never attempted are left out.
+    pub fn get_last_attempts(&self, problem_ids: &[i64]) -> Result<Vec<LastAttempt>> {
+        if problem_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let values = problem_ids
+            .iter()
+            .map(|_| "(?)")
+            .collect::<Vec<_>>()
+            .join(",");
+        let sql = format!(
+            "WITH ids(id) AS (VALUES {})
+             SELECT id, problem_id, timestamp, time_minutes, solved, read_solution FROM (
+                SELECT ids.id, a.problem_id, a.timestamp, a.time_minutes, a.solved, a.read_solution,
+                    ROW_NUMBER() OVER (PARTITION BY ids.id ORDER BY a.timestamp DESC, a.id DESC) AS rn
+                FROM ids
+                JOIN attempts a ON a.problem_id = ids.id
+                    OR a.problem_id IN (SELECT id FROM alternatives WHERE parent_id = ids.id)
+             )
+             WHERE rn = 1
+             ORDER BY id",
+            values
+        );
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        let attempts = stmt
+            .query_map(rusqlite::params_from_iter(problem_ids), |row| {
+                Ok(LastAttempt {
+                    problem_id: row.get(0)?,
+                    attempted_id: row.get(1)?,
+                    timestamp: row.get(2)?,
+                    time_minutes: row.get(3)?,
+                    solved: row.get(4)?,
+                    read_solution: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(attempts)
+    }
+
+    // --- Mastery History ---
+
+    pub fn log_mastery_change(
+        &self,
+        skill_id: i64,
+        attempt_id: Option<i64>,
+        old_mastery: f64,
+        new_mastery: f64,
+        reason: &str,
+        timestamp: i64,
+    ) -> Result<()> {
+        self.conn.prepare_cached("INSERT INTO skill_mastery_log (skill_id, attempt_id, old_mastery, new_mastery, reason, timestamp)
+             VALUES (?, ?, ?, ?, ?, ?)")?.execute(params![skill_id, attempt_id, old_mastery, new_mastery, reason, timestamp],)?;
+        Ok(())
+    }
+
+    pub fn get_skill_activity(&self) -> Result<Vec<SkillActivity>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT s.id,
+                (SELECT MAX(a.timestamp) FROM attempt_skills x
+                 JOIN attempts a ON a.id = x.attempt_id WHERE x.skill_id = s.id),
+                (SELECT MAX(timestamp) FROM skill_mastery_log
+                 WHERE skill_id = s.id AND reason = 'decay')
+             FROM skills s",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(SkillActivity {
+                    skill_id: row.get(0)?,
+                    last_attempt_ts: row.get(1)?,
+                    last_decay_ts: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    pub fn log_skill_unlock(
+        &self,
+        skill_id: i64,
+        attempt_id: Option<i64>,
+        timestamp: i64,
+    ) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT INTO skill_unlocks (skill_id, attempt_id, unlocked_at) VALUES (?, ?, ?)",
+            )?
+            .execute(params![skill_id, attempt_id, timestamp])?;
+        Ok(())
+    }
+
+    /// Mastery changes with `timestamp >= since_ts`, oldest first.
+    pub fn get_mastery_changes_since(&self, since_ts: i64) -> Result<Vec<MasteryChange>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT skill_id, attempt_id, old_mastery, new_mastery, timestamp
+             FROM skill_mastery_log
+             WHERE timestamp >= ?
+             ORDER BY timestamp, id",
+        )?;
+        let changes = stmt
+            .query_map([since_ts], |row| {
+                Ok(MasteryChange {
+                    skill_id: row.get(0)?,
+                    attempt_id: row.get(1)?,
+                    old_mastery: row.get(2)?,
+                    new_mastery: row.get(3)?,
+                    timestamp: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(changes)
+    }
+
+    /// Attempts on a concept (the parent or any alternative) as `(timestamp, clean solve)`,
+    /// oldest first.
+    pub fn get_concept_outcomes(&self, parent_id: i64) -> Result<Vec<(i64, bool)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT timestamp, solved = 1 AND read_solution = 0 FROM attempts
+             WHERE problem_id = ?1
+                OR problem_id IN (SELECT id FROM alternatives WHERE parent_id = ?1)
+             ORDER BY timestamp, id",
+        )?;
+        let outcomes = stmt
+            .query_map([parent_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(outcomes)
+    }
+
+    /// The concept's `limit` most recent attempts, newest first.
+    pub fn get_recent_concept_attempts(
+        &self,
+        parent_id: i64,
+        limit: usize,
+    ) -> Result<Vec<TimedOutcome>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT a.time_minutes, a.solved = 1 AND a.read_solution = 0,
+                    COALESCE(o.difficulty, p.difficulty, alt.difficulty, 'Medium'),
+                    o.expected_minutes
+             FROM attempts a
+             LEFT JOIN problems p ON p.id = a.problem_id
+             LEFT JOIN alternatives alt ON alt.id = a.problem_id
+             LEFT JOIN problem_overrides o ON o.problem_id = a.problem_id
+             WHERE a.problem_id = ?1
+                OR a.problem_id IN (SELECT id FROM alternatives WHERE parent_id = ?1)
+             ORDER BY a.timestamp DESC, a.id DESC
+             LIMIT ?2",
+        )?;
+        let attempts = stmt
+            .query_map(params![parent_id, limit as i64], |row| {
+                Ok(TimedOutcome {
+                    time_minutes: row.get(0)?,
+                    clean: row.get(1)?,
+                    difficulty: Difficulty::from_str(&row.get::<_, String>(2)?)
+                        .unwrap_or(Difficulty::Medium),
+                    expected_minutes_override: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(attempts)
+    }
+
+    /// Every (skill, difficulty, clean solve) credited by an attempt, for ability estimation.
+    /// Difficulty is the user's override if set, else the problem's or alternative's own.
+    pub fn get_skill_outcomes(&self) -> Result<Vec<(i64, Difficulty, bool)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT x.skill_id,
+                    COALESCE(o.difficulty, p.difficulty, alt.difficulty, 'Medium'),
+                    a.solved = 1 AND a.read_solution = 0
+             FROM attempt_skills x
+             JOIN attempts a ON a.id = x.attempt_id
+             LEFT JOIN problems p ON p.id = a.problem_id
+             LEFT JOIN alternatives alt ON alt.id = a.problem_id
+             LEFT JOIN problem_overrides o ON o.problem_id = a.problem_id",
+        )?;
+        let outcomes = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    Difficulty::from_str(&row.get::<_, String>(1)?).unwrap_or(Difficulty::Medium),
+                    row.get(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(outcomes)
+    }
+
+    /// Failed attempts (unsolved or solution read) per skill in a time range, most first.
+    pub fn get_failures_by_skill(&self, start_ts: i64, end_ts: i64) -> Result<Vec<(String, i64)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT s.name, count(*) AS n
+             FROM attempts a
+             JOIN attempt_skills ask ON ask.attempt_id = a.id
+             JOIN skills s ON s.id = ask.skill_id
+             WHERE a.timestamp BETWEEN ? AND ?
+             AND (a.solved = 0 OR a.read_solution = 1)
+             GROUP BY s.id
+             ORDER BY n DESC, s.name",
+        )?;
+        let failures = stmt
+            .query_map([start_ts, end_ts], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(failures)
+    }
+
+    /// (difficulty, clean solves, failures) for attempts in a time range, Easy to Hard.
+    pub fn get_difficulty_breakdown(
+        &self,
+        start_ts: i64,
+        end_ts: i64,
+    ) -> Result<Vec<(String, i64, i64)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT COALESCE(p.difficulty, alt.difficulty, 'Medium') AS d,
+                SUM(a.solved = 1 AND a.read_solution = 0),
+                SUM(a.solved = 0 OR a.read_solution = 1)
+             FROM attempts a
+             LEFT JOIN problems p ON p.id = a.problem_id
+             LEFT JOIN alternatives alt ON alt.id = a.problem_id
+             WHERE a.timestamp BETWEEN ? AND ?
+             GROUP BY d
+             ORDER BY CASE d WHEN 'Easy' THEN 1 WHEN 'Medium' THEN 2 WHEN 'Hard' THEN 3 ELSE 4 END",
+        )?;
+        let rows = stmt
+            .query_map([start_ts, end_ts], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Distinct concepts solved without reading the solution, per difficulty.
+    pub fn get_solved_by_difficulty(&self) -> Result<Vec<(String, i64)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT COALESCE(p.difficulty, alt.difficulty, 'Medium') AS d,
+                COUNT(DISTINCT COALESCE(alt.parent_id, a.problem_id))
+             FROM attempts a
+             LEFT JOIN problems p ON p.id = a.problem_id
+             LEFT JOIN alternatives alt ON alt.id = a.problem_id
+             WHERE a.solved = 1 AND a.read_solution = 0
+             GROUP BY d
+             ORDER BY CASE d WHEN 'Easy' THEN 1 WHEN 'Medium' THEN 2 WHEN 'Hard' THEN 3 ELSE 4 END",
+        )?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Every title we know, as (concept_id, title, slug or URL). Problems carry their slug,
+    /// alternatives their URL, and map to their parent.
+    pub fn get_problem_lookup(&self) -> Result<Vec<(i64, String, Option<String>)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, title, slug FROM problems
+             UNION ALL
+             SELECT parent_id, title, url FROM alternatives",
+        )?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    pub fn has_problem_state(&self, problem_id: i64) -> Result<bool> {
+        self.conn
+            .prepare_cached("SELECT EXISTS (SELECT 1 FROM problem_state WHERE problem_id = ?)")?
+            .query_row([problem_id], |row| row.get(0))
+    }
+
+    pub fn get_track_id(&self, name: &str) -> Result<Option<i64>> {
+        self.conn
+            .prepare_cached("SELECT id FROM tracks WHERE name = ?")?
+            .query_row([name], |row| row.get(0))
+            .optional()
+    }
+
+    pub fn get_track_name(&self, track_id: i64) -> Result<Option<String>> {
+        self.conn
+            .prepare_cached("SELECT name FROM tracks WHERE id = ?")?
+            .query_row([track_id], |row| row.get(0))
+            .optional()
+    }
+
+    pub fn create_track(&self, name: &str) -> Result<i64> {
+        self.conn
+            .prepare_cached("INSERT INTO tracks (name) VALUES (?)")?
+            .execute([name])?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn add_track_problem(&self, track_id: i64, problem_id: i64) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT OR IGNORE INTO track_problems (track_id, problem_id) VALUES (?, ?)",
+            )?
+            .execute(params![track_id, problem_id])?;
+        Ok(())
+    }
+
+    pub fn get_skill_id(&self, name: &str) -> Result<Option<i64>> {
+        self.conn
+            .prepare_cached("SELECT id FROM skills WHERE name = ?")?
+            .query_row([name], |row| row.get(0))
+            .optional()
+    }
+
+    /// Looks a skill up by name, creating it (with fresh state) if needed. Returns (id, created).
+    pub fn get_or_create_skill(&self, name: &str) -> Result<(i64, bool)> {
+        if let Some(id) = self
+            .conn
+            .prepare_cached("SELECT id FROM skills WHERE name = ?")?
+            .query_row([name], |row| row.get(0))
+            .optional()?
+        {
+            return Ok((id, false));
+        }
+        self.conn
+            .prepare_cached("INSERT INTO skills (name) VALUES (?)")?
+            .execute([name])?;
+        let id = self.conn.last_insert_rowid();
+        self.conn
+            .prepare_cached("INSERT OR IGNORE INTO skill_state (skill_id) VALUES (?)")?
+            .execute([id])?;
+        Ok((id, true))
+    }
+
+    /// Inserts a user-supplied problem. IDs start at `CUSTOM_PROBLEM_ID_BASE` so they can never
+    /// collide with seeded problems or alternatives.
+    pub fn insert_custom_problem(
+        &self,
+        slug: &str,
+        title: &str,
+        difficulty: Difficulty,
+        url: Option<&str>,
+    ) -> Result<i64> {
+        let id: i64 = self
+            .conn
+            .prepare_cached("SELECT MAX(COALESCE(MAX(id), 0) + 1, ?) FROM problems")?
+            .query_row([CUSTOM_PROBLEM_ID_BASE], |row| row.get(0))?;
+        self.conn
+            .prepare_cached(
+                "INSERT INTO problems (id, slug, title, difficulty, url) VALUES (?, ?, ?, ?, ?)",
+            )?
+            .execute(params![id, slug, title, difficulty.as_str(), url])?;
+        Ok(id)
+    }
+
+    pub fn add_problem_skill(&self, problem_id: i64, skill_id: i64, weight: f64) -> Result<()> {
+        self.conn.prepare_cached("INSERT OR IGNORE INTO problem_skills (problem_id, skill_id, weight) VALUES (?, ?, ?)")?.execute(params![problem_id, skill_id, weight],)?;
+        Ok(())
+    }
+
+    // --- Settings ---
+
+    pub fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        self.conn
+            .prepare_cached("SELECT value FROM settings WHERE key = ?")?
+            .query_row([key], |row| row.get(0))
+            .optional()
+    }
+
+    /// Typed read with a fallback for missing or unparsable values.
+    pub fn get_setting_or<T: FromStr>(&self, key: &str, default: T) -> Result<T> {
+        Ok(self
+            .get_setting(key)?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default))
+    }
+
+    pub fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        self.conn
+            .prepare_cached("INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)")?
+            .execute(params![key, value])?;
+        Ok(())
+    }
+
+    pub fn get_all_settings(&self) -> Result<HashMap<String, String>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT key, value FROM settings")?;
+        let settings = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(settings)
+    }
+
+    // --- Notes ---
+
+    pub fn get_note(&self, problem_id: i64) -> Result<Option<String>> {
+        self.conn
+            .prepare_cached("SELECT body FROM notes WHERE problem_id = ?")?
+            .query_row([problem_id], |row| row.get(0))
+            .optional()
+    }
+
+    /// Saves the note for a concept; an empty body removes it.
+    pub fn save_note(&self, problem_id: i64, body: &str, timestamp: i64) -> Result<()> {
+        if body.is_empty() {
+            self.conn
+                .prepare_cached("DELETE FROM notes WHERE problem_id = ?")?
+                .execute([problem_id])?;
+        } else {
+            self.conn
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO notes (problem_id, body, updated_at) VALUES (?, ?, ?)",
+                )?
+                .execute(params![problem_id, body, timestamp])?;
+        }
+        Ok(())
+    }
+
+    // --- Tags ---
+
+    pub fn add_problem_tag(&self, problem_id: i64, tag: &str, timestamp: i64) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT OR IGNORE INTO problem_tags (problem_id, tag, created_at) VALUES (?, ?, ?)",
+            )?
+            .execute(params![problem_id, tag, timestamp])?;
+        Ok(())
+    }
+
+    pub fn remove_problem_tag(&self, problem_id: i64, tag: &str) -> Result<()> {
+        self.conn
+            .prepare_cached("DELETE FROM problem_tags WHERE problem_id = ? AND tag = ?")?
+            .execute(params![problem_id, tag])?;
+        Ok(())
+    }
+
+    pub fn get_problem_tags(&self, problem_id: i64) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT tag FROM problem_tags WHERE problem_id = ? ORDER BY tag")?;
+        let tags = stmt
+            .query_map([problem_id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(tags)
+    }
+
+    /// Every tag in use with how many concepts carry it, most used first.
+    pub fn get_all_tags(&self) -> Result<Vec<TagCount>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT MIN(tag), COUNT(*) FROM problem_tags
+             GROUP BY tag
+             ORDER BY COUNT(*) DESC, MIN(tag)",
+        )?;
+        let tags = stmt
+            .query_map([], |row| {
+                Ok(TagCount {
+                    tag: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(tags)
+    }
+
+    /// Renames `old` to `new` everywhere, merging into `new` where a concept already has it.
+    /// Returns how many concepts were affected.
+    pub fn rename_tag(&self, old: &str, new: &str) -> Result<usize> {
+        // Rows whose concept already carries `new` conflict and are left in place...
+        let renamed = self
+            .conn
+            .prepare_cached("UPDATE OR IGNORE problem_tags SET tag = ?2 WHERE tag = ?1")?
+            .execute(params![old, new])?;
+        // ...and are then merged away. A change of case only never conflicts.
+        let merged = if old.eq_ignore_ascii_case(new) {
+            0
+        } else {
+            self.conn
+                .prepare_cached("DELETE FROM problem_tags WHERE tag = ?")?
+                .execute([old])?
+        };
+        Ok(renamed + merged)
+    }
+
+    pub fn delete_tag(&self, tag: &str) -> Result<usize> {
+        self.conn
+            .prepare_cached("DELETE FROM problem_tags WHERE tag = ?")?
+            .execute([tag])
+    }
+
+    // --- Starred Problems ---
+
+    pub fn star_problem(&self, problem_id: i64, timestamp: i64) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT OR IGNORE INTO starred_problems (problem_id, starred_at) VALUES (?, ?)",
+            )?
+            .execute(params![problem_id, timestamp])?;
+        Ok(())
+    }
+
+    pub fn unstar_problem(&self, problem_id: i64) -> Result<()> {
+        self.conn
+            .prepare_cached("DELETE FROM starred_problems WHERE problem_id = ?")?
+            .execute([problem_id])?;
+        Ok(())
+    }
+
+    /// Up to `limit` starred concepts, least recently attempted first (never attempted before
+    /// anything else), so practicing them in order cycles through the whole list.
+    pub fn find_starred_problems(&self, limit: usize) -> Result<Vec<ProblemView>> {
+        self.query_problem_views(
+            "SELECT p.id, p.title, p.difficulty, p.url
+             FROM starred_problems s
+             JOIN problems p ON p.id = s.problem_id
+             WHERE p.deleted_at IS NULL
+             ORDER BY (
+                SELECT MAX(a.timestamp) FROM attempts a
+                WHERE a.problem_id = s.problem_id
+                   OR a.problem_id IN (SELECT id FROM alternatives WHERE parent_id = s.problem_id)
+             ) ASC, s.starred_at ASC
+             LIMIT ?",
+            vec![Box::new(i64::try_from(limit).unwrap_or(i64::MAX))],
+            QueueKind::Starred,
+        )
+    }
+
+    // --- Study Plan ---
+
+    /// Replaces the stored plan. Callers wrap this in a transaction.
+    pub fn save_study_plan(&self, plan: &StudyPlan, created_at: i64) -> Result<()> {
+        self.clear_study_plan()?;
+        let warnings = serde_json::to_string(&plan.warnings).unwrap_or_else(|_| "[]".to_string());
+        self.conn.prepare_cached("INSERT INTO study_plan (id, deadline, new_problems, feasible, warnings, created_at)
+             VALUES (1, ?, ?, ?, ?, ?)")?.execute(params![
+                plan.deadline,
+                plan.new_problems,
+                plan.feasible,
+                warnings,
+                created_at
+            ],)?;
+        let mut stmt = self
+            .conn
+            .prepare_cached("INSERT INTO study_plan_skills (skill_id) VALUES (?)")?;
+        for skill_id in &plan.skill_ids {
+            stmt.execute([skill_id])?;
+        }
+        let mut stmt = self.conn.prepare_cached(
+            "INSERT INTO study_plan_days (day, new_problems, reviews) VALUES (?, ?, ?)",
+        )?;
+        for day in &plan.days {
+            stmt.execute(params![day.date, day.new_problems, day.reviews])?;
+        }
+        Ok(())
+    }
+
+    pub fn get_study_plan(&self) -> Result<Option<StudyPlan>> {
+        let Some((deadline, new_problems, feasible, warnings)) = self
+            .conn
+            .prepare_cached(
+                "SELECT deadline, new_problems, feasible, warnings FROM study_plan WHERE id = 1",
+            )?
+            .query_row([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })
+            .optional()?
+        else {
+            return Ok(None);
+        };
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT skill_id FROM study_plan_skills ORDER BY skill_id")?;
+        let skill_ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<i64>, _>>()?;
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT day, new_problems, reviews FROM study_plan_days ORDER BY day",
+        )?;
+        let days = stmt
+            .query_map([], |row| {
+                Ok(PlanDay {
+                    date: row.get(0)?,
+                    new_problems: row.get(1)?,
+                    reviews: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Some(StudyPlan {
+            deadline,
+            skill_ids,
+            new_problems,
+            feasible,
+            warnings: serde_json::from_str(&warnings).unwrap_or_default(),
+            days,
+        }))
+    }
+
+    pub fn clear_study_plan(&self) -> Result<()> {
+        self.conn.execute_batch(
+            "DELETE FROM study_plan;
+             DELETE FROM study_plan_skills;
+             DELETE FROM study_plan_days;",
+        )
+    }
+
+    /// New problems planned for `day`, if the plan covers it.
+    pub fn get_plan_day_quota(&self, day: &str) -> Result<Option<i64>> {
+        self.conn
+            .prepare_cached("SELECT new_problems FROM study_plan_days WHERE day = ?")?
+            .query_row([day], |row| row.get(0))
+            .optional()
+    }
+
+    /// Concepts whose first attempt (of the problem or any alternative) is at or after `since`.
+    pub fn count_concepts_started_since(&self, since: i64) -> Result<i64> {
+        self.conn
+            .prepare_cached(
+                "SELECT count(*) FROM (
+                SELECT MIN(a.timestamp) AS first_ts
+                FROM attempts a
+                LEFT JOIN alternatives alt ON alt.id = a.problem_id
+                GROUP BY COALESCE(alt.parent_id, a.problem_id)
+             ) WHERE first_ts >= ?",
+            )?
+            .query_row([since], |row| row.get(0))
+    }
+
+    /// `(next_review_ts, interval_days, ease_factor)` of every tracked, unsuspended problem.
+    pub fn get_review_schedule(&self) -> Result<Vec<(i64, f64, f64)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT next_review_ts, interval_days, ease_factor FROM problem_state
+             WHERE problem_id NOT IN (SELECT problem_id FROM suspended_problems)
+             AND problem_id NOT IN (SELECT id FROM problems WHERE deleted_at IS NOT NULL)",
+        )?;
+        let schedule = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(schedule)
+    }
+
+    // --- Suspended Problems ---
+    // Suspended concepts keep their schedule but are never served as due reviews.
+
+    pub fn suspend_problem(&self, problem_id: i64, reason: &str, now: i64) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT OR IGNORE INTO suspended_problems (problem_id, reason, suspended_at)
+             VALUES (?, ?, ?)",
+            )?
+            .execute(params![problem_id, reason, now])?;
+        Ok(())
+    }
+
+    /// Up to `limit` concepts of the skill suspended for `reason`, longest suspended first.
+    pub fn find_suspended_for_skill(
+        &self,
+        skill_id: i64,
+        reason: &str,
+        limit: usize,
+    ) -> Result<Vec<i64>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT s.problem_id FROM suspended_problems s
+             JOIN problem_skills ps ON ps.problem_id = s.problem_id
+             WHERE ps.skill_id = ? AND s.reason = ?
+             ORDER BY s.suspended_at ASC, s.problem_id
+             LIMIT ?",
+        )?;
+        let ids = stmt
+            .query_map(params![skill_id, reason, limit as i64], |row| row.get(0))?
+            .collect::<Result<Vec<i64>, _>>()?;
+        Ok(ids)
+    }
+
+    pub fn get_suspension_reason(&self, problem_id: i64) -> Result<Option<String>> {
+        self.conn
+            .prepare_cached("SELECT reason FROM suspended_problems WHERE problem_id = ?")?
+            .query_row([problem_id], |row| row.get(0))
+            .optional()
+    }
+
+    /// Returns whether the problem was suspended.
+    pub fn unsuspend_problem(&self, problem_id: i64) -> Result<bool> {
+        Ok(self
+            .conn
+            .prepare_cached("DELETE FROM suspended_problems WHERE problem_id = ?")?
+            .execute([problem_id])?
+            > 0)
+    }
+
+    /// Suspended problems, most recently suspended first; all reasons when `reason` is `None`.
+    pub fn get_suspended_problems(&self, reason: Option<&str>) -> Result<Vec<SuspendedProblem>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT s.problem_id, p.title, s.reason, s.suspended_at
+             FROM suspended_problems s
+             JOIN problems p ON p.id = s.problem_id
+             WHERE (?1 IS NULL OR s.reason = ?1) AND p.deleted_at IS NULL
+             ORDER BY s.suspended_at DESC, s.problem_id",
+        )?;
+        let problems = stmt
+            .query_map([reason], |row| {
+                Ok(SuspendedProblem {
+                    problem_id: row.get(0)?,
+                    title: row.get(1)?,
+                    reason: row.get(2)?,
+                    suspended_at: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(problems)
+    }
+
+    /// Overdue, unsuspended problems with the weighted mean mastery of their skills, weakest
+    /// first; among equals, the shorter interval (the less settled item) comes first.
+    pub fn find_overdue_by_mastery(&self, ts: i64) -> Result<Vec<(i64, String, f64)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT ps.problem_id, p.title,
+                COALESCE(SUM(ss.mastery * psk.weight) / SUM(psk.weight), 0.0) AS mastery
+             FROM problem_state ps
+             JOIN problems p ON p.id = ps.problem_id
+             LEFT JOIN problem_skills psk ON psk.problem_id = ps.problem_id
+             LEFT JOIN skill_state ss ON ss.skill_id = psk.skill_id
+             WHERE ps.next_review_ts <= ?
+             AND ps.problem_id NOT IN (SELECT problem_id FROM suspended_problems)
+             AND p.deleted_at IS NULL
+             GROUP BY ps.problem_id
+             ORDER BY mastery ASC, ps.interval_days ASC, ps.next_review_ts ASC",
+        )?;
+        let rows = stmt
+            .query_map([ts], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    // --- Exam Cram ---
+
+    /// `(until, started_at)` of the active cram, if any.
+    pub fn get_exam_cram(&self) -> Result<Option<(String, i64)>> {
+        self.conn
+            .prepare_cached("SELECT until, started_at FROM exam_cram WHERE id = 1")?
+            .query_row([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .optional()
+    }
+
+    /// Records the cram and snapshots every repetition state. Callers wrap this in a
+    /// transaction.
+    pub fn start_exam_cram(&self, until: &str, started_at: i64) -> Result<()> {
+        self.conn
+            .prepare_cached("INSERT INTO exam_cram (id, until, started_at) VALUES (1, ?, ?)")?
+            .execute(params![until, started_at])?;
+        self.conn
+            .prepare_cached("DELETE FROM exam_cram_snapshot")?
+            .execute([])?;
+        self.conn.prepare_cached("INSERT INTO exam_cram_snapshot (problem_id, ease_factor, interval_days, next_review_ts)
+             SELECT problem_id, ease_factor, interval_days, next_review_ts FROM problem_state")?.execute([],)?;
+        Ok(())
+    }
+
+    /// Puts snapshotted repetition states back and ends the cram. Problems first tracked
+    /// during the cram keep their state. Callers wrap this in a transaction.
+    pub fn end_exam_cram(&self) -> Result<()> {
+        self.conn.prepare_cached("INSERT OR REPLACE INTO problem_state (problem_id, ease_factor, interval_days, next_review_ts)
+             SELECT problem_id, ease_factor, interval_days, next_review_ts FROM exam_cram_snapshot")?.execute([],)?;
+        self.conn
+            .prepare_cached("DELETE FROM exam_cram_snapshot")?
+            .execute([])?;
+        self.conn
+            .prepare_cached("DELETE FROM exam_cram")?
+            .execute([])?;
+        Ok(())
+    }
+
+    // --- Problem Browser ---
+
+    /// A track's problems, optionally only those with `status`; all of them when `page` is
+    /// `None`.
+    pub fn list_problems(
+        &self,
+        track_id: i64,
+        status: Option<ProblemStatus>,
+        sort: SortSpec<ProblemSort>,
+        page: Option<Pagination>,
+        now: i64,
+    ) -> Result<Vec<ProblemListItem>> {
+        let field = match sort.field {
+            ProblemSort::Id => "id",
+            ProblemSort::Title => "title COLLATE NOCASE",
+            ProblemSort::Difficulty => {
+                "CASE difficulty WHEN 'Easy' THEN 1 WHEN 'Medium' THEN 2 ELSE 3 END"
+            }
+            ProblemSort::NextReview => "next_review_ts",
+            ProblemSort::LastAttempt => "last_attempt_ts",
+        };
+        let (limit, offset) = limit_offset(page);
+        let sql = format!(
+            "{}
+             SELECT id, title, url, difficulty, status, next_review_ts, last_attempt_ts
+             FROM listed
+             WHERE ?3 IS NULL OR status = ?3
+             ORDER BY {}
+             LIMIT ?4 OFFSET ?5",
+            track_problems_with_status_sql(),
+            order_by(field, sort.direction, "id")
+        );
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        let problems = stmt
+            .query_map(
+                params![track_id, now, status.map(|s| s.as_str()), limit, offset],
+                problem_list_item,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(problems)
+    }
+
+    /// Problems mapped to the skill, optionally only those with `status`, most weighted
+    /// first.
+    pub fn get_problems_for_skill(
+        &self,
+        skill_id: i64,
+        status: Option<ProblemStatus>,
+        now: i64,
+    ) -> Result<Vec<SkillProblem>> {
+        let sql = format!(
+            "{}
+             SELECT l.id, l.title, l.url, l.difficulty, l.status, l.next_review_ts,
+                l.last_attempt_ts, k.weight
+             FROM listed l
+             JOIN problem_skills k ON k.problem_id = l.id AND k.skill_id = ?1
+             WHERE ?3 IS NULL OR l.status = ?3
+             ORDER BY k.weight DESC, l.id",
+            problems_with_status_sql(
+                "problem_skills k JOIN problems p ON p.id = k.problem_id",
+                "k.skill_id = ?1",
+            )
+        );
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        let problems = stmt
+            .query_map(params![skill_id, now, status.map(|s| s.as_str())], |row| {
+                Ok(SkillProblem {
+                    problem: problem_list_item(row)?,
+                    weight: row.get(7)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(problems)
+    }
+
+    /// Problems only in track `a`, only in track `b`, and in both.
+    pub fn count_track_overlap(&self, a: i64, b: i64) -> Result<(i64, i64, i64)> {
+        self.conn
+            .prepare_cached(
+                "SELECT
+                COALESCE(SUM(in_a AND NOT in_b), 0),
+                COALESCE(SUM(in_b AND NOT in_a), 0),
+                COALESCE(SUM(in_a AND in_b), 0)
+             FROM (
+                SELECT problem_id, MAX(track_id = ?1) AS in_a, MAX(track_id = ?2) AS in_b
+                FROM track_problems
+                WHERE track_id IN (?1, ?2)
+                AND problem_id NOT IN (SELECT id FROM problems WHERE deleted_at IS NOT NULL)
+                GROUP BY problem_id
+             )",
+            )?
+            .query_row(params![a, b], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+    }
+
+    /// (skill_id, name, problems in `a`, problems in `b`) for every skill either track covers.
+    pub fn get_track_skill_coverage(&self, a: i64, b: i64) -> Result<Vec<(i64, String, i64, i64)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT s.id, s.name,
+                COUNT(DISTINCT CASE WHEN tp.track_id = ?1 THEN tp.problem_id END),
+                COUNT(DISTINCT CASE WHEN tp.track_id = ?2 THEN tp.problem_id END)
+             FROM track_problems tp
+             JOIN problem_skills ps ON ps.problem_id = tp.problem_id
+             JOIN skills s ON s.id = ps.skill_id
+             WHERE tp.track_id IN (?1, ?2)
+             AND tp.problem_id NOT IN (SELECT id FROM problems WHERE deleted_at IS NOT NULL)
+             GROUP BY s.id
+             ORDER BY s.id",
+        )?;
+        let coverage = stmt
+            .query_map(params![a, b], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(coverage)
+    }
+
+    pub fn is_catalog_problem(&self, problem_id: i64) -> Result<bool> {
+        self.conn
+            .prepare_cached("SELECT EXISTS(SELECT 1 FROM problems WHERE id = ?)")?
+            .query_row([problem_id], |row| row.get(0))
+    }
+
+    /// Moves everything keyed by `duplicate_id` onto `keep_id` and deletes the duplicate.
+    /// Where both have a row in a one-per-problem table, `keep_id`'s is kept. Notes are left to
+    /// the caller. Returns how many attempts moved.
+    pub fn merge_problem_into(&self, keep_id: i64, duplicate_id: i64) -> Result<usize> {
+        let attempts = self
+            .conn
+            .prepare_cached("UPDATE attempts SET problem_id = ?1 WHERE problem_id = ?2")?
+            .execute(params![keep_id, duplicate_id])?;
+        // Explanation hits open the problem they were written for
+        self.conn.prepare_cached("UPDATE search_index SET problem_id = ?1 WHERE kind = 'explanation' AND problem_id = ?2")?.execute(params![keep_id, duplicate_id],)?;
+        for table in ["retrospectives", "problem_interval_log"] {
+            self.conn
+                .prepare_cached(&format!(
+                    "UPDATE {} SET problem_id = ?1 WHERE problem_id = ?2",
+                    table
+                ))?
+                .execute(params![keep_id, duplicate_id])?;
+        }
+        for table in [
+            "problem_state",
+            "track_problems",
+            "problem_skills",
+            "problem_tags",
+            "starred_problems",
+            "problem_overrides",
+            "suspended_problems",
+            "exam_cram_snapshot",
+            "notion_sync",
+            "reservations",
+        ] {
+            self.conn
+                .prepare_cached(&format!(
+                    "UPDATE OR IGNORE {} SET problem_id = ?1 WHERE problem_id = ?2",
+                    table
+                ))?
+                .execute(params![keep_id, duplicate_id])?;
+            self.conn
+                .prepare_cached(&format!("DELETE FROM {} WHERE problem_id = ?", table))?
+                .execute([duplicate_id])?;
+        }
+        self.conn
+            .prepare_cached("UPDATE alternatives SET parent_id = ?1 WHERE parent_id = ?2")?
+            .execute(params![keep_id, duplicate_id])?;
+        self.conn
+            .prepare_cached("DELETE FROM problems WHERE id = ?")?
+            .execute([duplicate_id])?;
+        Ok(attempts)
+    }
+
+    /// Hides a problem from selection and listings; its attempts stay. Returns whether it was
+    /// live before.
+    pub fn soft_delete_problem(&self, problem_id: i64, now: i64) -> Result<bool> {
+        let changed = self
+            .conn
+            .prepare_cached(
+                "UPDATE problems SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+            )?
+            .execute(params![now, problem_id])?;
+        Ok(changed > 0)
+    }
+
+    /// Permanently removes soft-deleted problems together with their attempts, including those
+    /// logged against their alternatives. Everything else keyed by the problem cascades.
+    /// Returns how many problems were removed.
+    pub fn purge_deleted_problems(&self) -> Result<usize> {
+        let deleted = "SELECT id FROM problems WHERE deleted_at IS NOT NULL";
+        let related = format!(
+            "SELECT id FROM ({0}) UNION SELECT id FROM alternatives WHERE parent_id IN ({0})",
+            deleted
+        );
+        // Attempts, skill mappings and overrides may point at an alternative, so they carry
+        // no foreign key and are removed by hand
+        for table in ["attempts", "problem_skills", "problem_overrides"] {
+            self.conn
+                .prepare_cached(&format!(
+                    "DELETE FROM {} WHERE problem_id IN ({})",
+                    table, related
+                ))?
+                .execute([])?;
+        }
+        self.conn
+            .prepare_cached("DELETE FROM problems WHERE deleted_at IS NOT NULL")?
+            .execute([])
+    }
+
+    pub fn count_problems_by_status(&self, track_id: i64, now: i64) -> Result<StatusCounts> {
+        let sql = format!(
+            "{} SELECT status, COUNT(*) FROM listed GROUP BY status",
+            track_problems_with_status_sql()
+        );
+        let mut stmt = self.conn.prepare_cached(&sql)?;
+        let mut counts = StatusCounts::default();
+        let rows = stmt.query_map(params![track_id, now], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+        for row in rows {
+            let (status, count) = row?;
+            match ProblemStatus::from_str(&status) {
+                Ok(ProblemStatus::New) => counts.new = count,
+                Ok(ProblemStatus::Learning) => counts.learning = count,
+                Ok(ProblemStatus::Review) => counts.review = count,
+                Ok(ProblemStatus::Mastered) => counts.mastered = count,
+                Ok(ProblemStatus::Suspended) => counts.suspended = count,
+                Err(_) => {}
+            }
+        }
+        Ok(counts)
+    }
+
+    // --- Translations ---
+
+    /// Stores `text` as the `locale` translation of a problem or skill, keeping an existing
+    /// one unless `replace`. Locales are matched case-insensitively, with `_` and `-` alike,
+    /// so `pt_BR` and `pt-br` are one locale.
+    pub fn save_translation(
+        &self,
+        kind: &str,
+        ref_id: i64,
+        locale: &str,
+        text: &str,
+        replace: bool,
+    ) -> Result<()> {
+        if text.trim().is_empty() {
+            return Ok(());
+        }
+        let sql = if replace {
+            "INSERT OR REPLACE INTO translations (kind, ref_id, locale, text)
+             VALUES (?, ?, lower(replace(trim(?), '_', '-')), ?)"
+        } else {
+            "INSERT OR IGNORE INTO translations (kind, ref_id, locale, text)
+             VALUES (?, ?, lower(replace(trim(?), '_', '-')), ?)"
+        };
+        self.conn
+            .prepare_cached(sql)?
+            .execute(params![kind, ref_id, locale, text.trim()])?;
+        Ok(())
+    }
+
+    /// Whether the translations table exists. A database opened read-only skips schema
+    /// setup, so it may predate the table.
+    pub fn has_translations(&self) -> Result<bool> {
+        self.conn
+            .prepare_cached(
+                "SELECT EXISTS (SELECT 1 FROM sqlite_master
+                                WHERE type = 'table' AND name = 'translations')",
+            )?
+            .query_row([], |row| row.get(0))
+    }
+
+    /// (kind, ref_id, text) translations into `locale` or its bare `language`, with the
+    /// exact locale's rows last so they win when both exist.
+    pub fn get_translations(
+        &self,
+        locale: &str,
+        language: &str,
+    ) -> Result<Vec<(String, i64, String)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT kind, ref_id, text FROM translations
+             WHERE locale IN (lower(replace(?1, '_', '-')), lower(?2))
+             ORDER BY locale = lower(replace(?1, '_', '-'))",
+        )?;
+        let rows = stmt
+            .query_map([locale, language], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    // --- Track Bundles ---
+
+    /// (id, title, difficulty, url) of a track's problems, by id.
+    pub fn get_bundle_problems(&self, track_id: i64) -> Result<Vec<(i64, String, String, String)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT p.id, p.title, COALESCE(p.difficulty, 'Medium'), COALESCE(p.url, '')
+             FROM track_problems tp
+             JOIN problems p ON p.id = tp.problem_id
+             WHERE tp.track_id = ? AND p.deleted_at IS NULL
+             ORDER BY p.id",
+        )?;
+        let rows = stmt
+            .query_map([track_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// (problem_id, skill, weight) of a track's problems, heaviest skill first.
+    pub fn get_bundle_skills(&self, track_id: i64) -> Result<Vec<(i64, String, f64)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT ps.problem_id, s.name, ps.weight
+             FROM track_problems tp
+             JOIN problem_skills ps ON ps.problem_id = tp.problem_id
+             JOIN skills s ON s.id = ps.skill_id
+             WHERE tp.track_id = ?
+             ORDER BY ps.problem_id, ps.weight DESC, s.name",
+        )?;
+        let rows = stmt
+            .query_map([track_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// (problem_id, locale, text) title translations of a track's problems.
+    pub fn get_bundle_problem_titles(&self, track_id: i64) -> Result<Vec<(i64, String, String)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT t.ref_id, t.locale, t.text
+             FROM track_problems tp
+             JOIN translations t ON t.kind = ?1 AND t.ref_id = tp.problem_id
+             WHERE tp.track_id = ?2
+             ORDER BY t.ref_id, t.locale",
+        )?;
+        let rows = stmt
+            .query_map(params![TRANSLATION_KIND_PROBLEM, track_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// (alternative_id, locale, text) title translations of a track's alternatives.
+    pub fn get_bundle_alternative_titles(
+        &self,
+        track_id: i64,
+    ) -> Result<Vec<(i64, String, String)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT t.ref_id, t.locale, t.text
+             FROM track_problems tp
+             JOIN alternatives a ON a.parent_id = tp.problem_id
+             JOIN translations t ON t.kind = ?1 AND t.ref_id = a.id
+             WHERE tp.track_id = ?2
+             ORDER BY t.ref_id, t.locale",
+        )?;
+        let rows = stmt
+            .query_map(params![TRANSLATION_KIND_ALTERNATIVE, track_id], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
 
-    match parent_id {
-        Some(pid) => Ok((pid, true)),
-        None => Ok((problem_id, false)),
+    /// (parent_id, alternative) of a track's problems.
+    pub fn get_bundle_alternatives(&self, track_id: i64) -> Result<Vec<(i64, JsonAlternative)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT a.parent_id, a.id, a.title, COALESCE(a.difficulty, 'Medium'),
+                COALESCE(a.url, '')
+             FROM track_problems tp
+             JOIN alternatives a ON a.parent_id = tp.problem_id
+             WHERE tp.track_id = ?
+             ORDER BY a.id",
+        )?;
+        let rows = stmt
+            .query_map([track_id], |row| {
+                Ok((
+                    row.get(0)?,
+                    JsonAlternative {
+                        id: row.get(1)?,
+                        title: row.get(2)?,
+                        difficulty: row.get(3)?,
+                        url: row.get(4)?,
+                        titles: BTreeMap::new(),
+                    },
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
     }
-}
 
-/// Tries to find a random alternative for a given parent problem ID.
-pub fn get_random_alternative(conn: &Connection, parent_id: i64) -> Result<Option<ProblemView>> {
-    let result = conn
-        .query_row(
-            "SELECT id, title, difficulty, url 
-         FROM alternatives 
-         WHERE parent_id = ? 
-         ORDER BY RANDOM() 
-         LIMIT 1",
-            [parent_id],
-            |row| {
-                Ok(ProblemView {
-                    id: row.get(0)?,
+    pub fn get_bundle_notes(&self, track_id: i64) -> Result<Vec<(i64, String)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT n.problem_id, n.body
+             FROM track_problems tp
+             JOIN notes n ON n.problem_id = tp.problem_id
+             WHERE tp.track_id = ?",
+        )?;
+        let rows = stmt
+            .query_map([track_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    // --- Notion Sync ---
+
+    pub fn get_sync_rows(&self, track_id: i64) -> Result<Vec<SyncRow>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT p.id, p.title, p.url, COALESCE(p.difficulty, 'Medium'),
+                ps.interval_days, ps.next_review_ts, n.body, ns.synced_status, ns.synced_note,
+                sp.problem_id IS NOT NULL
+             FROM track_problems tp
+             JOIN problems p ON p.id = tp.problem_id
+             LEFT JOIN problem_state ps ON ps.problem_id = p.id
+             LEFT JOIN suspended_problems sp ON sp.problem_id = p.id
+             LEFT JOIN notes n ON n.problem_id = p.id
+             LEFT JOIN notion_sync ns ON ns.problem_id = p.id
+             WHERE tp.track_id = ? AND p.deleted_at IS NULL
+             ORDER BY p.id",
+        )?;
+        let rows = stmt
+            .query_map([track_id], |row| {
+                let interval: Option<f64> = row.get(4)?;
+                let next_review: Option<i64> = row.get(5)?;
+                Ok(SyncRow {
+                    problem_id: row.get(0)?,
                     title: row.get(1)?,
-                    difficulty: row.get(2)?,
-                    url: row.get(3)?,
-                    track_name: "🔀 Concept Variation".to_string(),
-                    skills: Vec::new(),
+                    url: row.get(2)?,
+                    difficulty: row.get(3)?,
+                    repetition: interval.zip(next_review),
+                    suspended: row.get(9)?,
+                    note: row.get(6)?,
+                    synced_status: row.get(7)?,
+                    synced_note: row.get(8)?,
                 })
-            },
-        )
-        .optional()?;
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
 
-    if let Some(mut p) = result {
-        // Alternatives might not have direct skill mappings in your DB design,
-        // fallback to parent skills if needed, or if alternatives share skills:
-        p.skills = get_skill_names_for_problem(conn, parent_id).unwrap_or_default();
-        return Ok(Some(p));
+    pub fn save_sync_state(
+        &self,
+        problem_id: i64,
+        page_id: &str,
+        status: &str,
+        note: Option<&str>,
+        timestamp: i64,
+    ) -> Result<()> {
+        self.conn.prepare_cached("INSERT OR REPLACE INTO notion_sync (problem_id, page_id, synced_status, synced_note, synced_at)
+             VALUES (?, ?, ?, ?, ?)")?.execute(params![problem_id, page_id, status, note, timestamp],)?;
+        Ok(())
     }
-    Ok(None)
-}
 
-/// Updates the mastery state for a skill.
-pub fn update_skill_state(conn: &Connection, state: &SkillMasteryState) -> Result<()> {
-    conn.execute(
-        "UPDATE skill_state SET mastery = ?, attempts = ? WHERE skill_id = ?",
-        params![state.mastery, state.attempts, state.skill_id],
-    )?;
-    Ok(())
-}
+    /// The problem's URL (falling back to its parent's for alternatives) and slug, if any.
+    pub fn get_problem_link(&self, problem_id: i64) -> Result<(Option<String>, Option<String>)> {
+        self.conn
+            .prepare_cached(
+                "SELECT COALESCE(alt.url, p.url), p.slug
+             FROM (SELECT ? AS id) q
+             LEFT JOIN alternatives alt ON alt.id = q.id
+             LEFT JOIN problems p ON p.id = COALESCE(alt.parent_id, q.id)",
+            )?
+            .query_row([problem_id], |row| Ok((row.get(0)?, row.get(1)?)))
+    }
+
+    /// Finds the problem (or alternative) a LeetCode slug refers to. Seeded problems win over
+    /// alternatives that link to the same page.
+    pub fn find_problem_by_slug(&self, slug: &str) -> Result<Option<i64>> {
+        let url_pattern = format!("%/problems/{}/%", slug);
+        self.conn
+            .prepare_cached(
+                "SELECT id FROM (
+                SELECT id, 0 AS rank FROM problems WHERE slug = ?1 OR url LIKE ?2
+                UNION ALL
+                SELECT id, 1 AS rank FROM alternatives WHERE url LIKE ?2
+             ) ORDER BY rank LIMIT 1",
+            )?
+            .query_row(params![slug, url_pattern], |row| row.get(0))
+            .optional()
+    }
+
+    // --- Attempt Journal ---
+
+    pub fn mark_journal_applied(&self, entry_id: &str, timestamp: i64) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT OR IGNORE INTO journal_applied (entry_id, applied_at) VALUES (?1, ?2)",
+            )?
+            .execute(params![entry_id, timestamp])?;
+        Ok(())
+    }
+
+    pub fn is_journal_applied(&self, entry_id: &str) -> Result<bool> {
+        self.conn
+            .prepare_cached("SELECT EXISTS(SELECT 1 FROM journal_applied WHERE entry_id = ?1)")?
+            .query_row([entry_id], |row| row.get(0))
+    }
+
+    pub fn prune_journal_applied(&self, before_ts: i64) -> Result<()> {
+        self.conn
+            .prepare_cached("DELETE FROM journal_applied WHERE applied_at < ?1")?
+            .execute([before_ts])?;
+        Ok(())
+    }
+
+    // --- Serve History ---
+
+    /// Logs a serve. Serving the latest unattempted problem again within its reservation is
+    /// the same serve, so nothing is added.
+    pub fn log_serve(
+        &self,
+        problem_id: i64,
+        concept_id: i64,
+        queue: &QueueKind,
+        served_at: i64,
+    ) -> Result<()> {
+        let is_alternative = matches!(queue, QueueKind::AlternativeFor { .. });
+        let latest: Option<(i64, bool, i64, Option<i64>)> = self
+            .conn
+            .prepare_cached(
+                "SELECT problem_id, is_alternative, served_at, attempt_id
+                 FROM served_log ORDER BY id DESC LIMIT 1",
+            )?
+            .query_row([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .optional()?;
+        if latest.is_some_and(|(id, alt, at, attempt)| {
+            id == problem_id
+                && alt == is_alternative
+                && attempt.is_none()
+                && at + RESERVATION_TTL_SECONDS >= served_at
+        }) {
+            return Ok(());
+        }
+        let queue = serde_json::to_string(queue).unwrap_or_default();
+        self.conn
+            .prepare_cached(
+                "INSERT INTO served_log (problem_id, is_alternative, concept_id, queue, served_at)
+                 VALUES (?, ?, ?, ?, ?)",
+            )?
+            .execute(params![
+                problem_id,
+                is_alternative,
+                concept_id,
+                queue,
+                served_at
+            ])?;
+        Ok(())
+    }
 
-/// Fetches repetition state (SM-2) for a problem.
-pub fn get_problem_repetition_state(
-    conn: &Connection,
-    problem_id: i64,
-) -> Result<ProblemRepetitionState> {
-    conn.query_row(
-        "SELECT ease_factor, interval_days FROM problem_state WHERE problem_id = ?",
-        [problem_id],
-        |row| {
-            Ok(ProblemRepetitionState {
+    /// Credits an attempt made at `attempted_at` to the latest unattempted serve of the
+    /// problem or its concept, as long as that serve's reservation still held.
+    pub fn mark_serve_attempted(
+        &self,
+        problem_id: i64,
+        concept_id: i64,
+        attempt_id: i64,
+        attempted_at: i64,
+    ) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "UPDATE served_log SET attempt_id = ?3
+                 WHERE id = (
+                    SELECT id FROM served_log
+                    WHERE (problem_id = ?1 OR concept_id = ?2) AND attempt_id IS NULL
+                      AND served_at BETWEEN ?4 - ?5 AND ?4
+                    ORDER BY id DESC LIMIT 1
+                 )",
+            )?
+            .execute(params![
                 problem_id,
-                ease_factor: row.get(0)?,
-                interval_days: row.get(1)?,
-                next_review_ts: 0, // Not needed for logic calc, overwritten on save
+                concept_id,
+                attempt_id,
+                attempted_at,
+                RESERVATION_TTL_SECONDS
+            ])?;
+        Ok(())
+    }
+
+    /// Serves as of `now`, newest first.
+    pub fn get_served_log(&self, page: Pagination, now: i64) -> Result<Vec<ServedProblem>> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT s.id, s.problem_id, COALESCE(p.title, a.title, ''), s.queue, s.served_at,
+                {}, s.attempt_id
+             FROM served_log s
+             LEFT JOIN problems p ON p.id = s.problem_id AND NOT s.is_alternative
+             LEFT JOIN alternatives a ON a.id = s.problem_id AND s.is_alternative
+             ORDER BY s.id DESC
+             LIMIT ?2 OFFSET ?3",
+            serve_outcome_sql()
+        ))?;
+        let serves = stmt
+            .query_map(
+                params![now, page.limit() as i64, page.offset() as i64],
+                |row| {
+                    let queue: String = row.get(3)?;
+                    let outcome: String = row.get(5)?;
+                    Ok(ServedProblem {
+                        id: row.get(0)?,
+                        problem_id: row.get(1)?,
+                        title: row.get(2)?,
+                        queue: serde_json::from_str(&queue).unwrap_or(QueueKind::Discovery),
+                        served_at: row.get(4)?,
+                        outcome: ServeOutcome::from_str(&outcome).unwrap_or(ServeOutcome::Pending),
+                        attempt_id: row.get(6)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(serves)
+    }
+
+    /// How many serves ended each way, as of `now`.
+    pub fn count_serves_by_outcome(&self, now: i64) -> Result<Vec<(ServeOutcome, i64)>> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {} AS outcome, COUNT(*) FROM served_log s GROUP BY outcome",
+            serve_outcome_sql()
+        ))?;
+        let counts = stmt
+            .query_map([now], |row| Ok((row.get::<_, String>(0)?, row.get(1)?)))?
+            .filter_map(|row| match row {
+                Ok((outcome, n)) => ServeOutcome::from_str(&outcome).ok().map(|o| Ok((o, n))),
+                Err(e) => Some(Err(e)),
             })
-        },
-    )
-    .optional()?
-    .map_or(
-        Ok(ProblemRepetitionState {
-            problem_id,
-            ease_factor: EASE_FACTOR_DEFAULT,
-            interval_days: 0.0,
-            next_review_ts: 0,
-        }),
-        Ok,
-    )
-}
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(counts)
+    }
 
-/// Saves the calculated repetition state.
-pub fn save_problem_repetition_state(
-    conn: &Connection,
-    state: &ProblemRepetitionState,
-) -> Result<()> {
-    conn.execute(
-        "INSERT OR REPLACE INTO problem_state (problem_id, ease_factor, interval_days, next_review_ts) VALUES (?, ?, ?, ?)",
-        params![state.problem_id, state.ease_factor, state.interval_days, state.next_review_ts]
-    )?;
-    Ok(())
-}
+    pub fn prune_served_log(&self, before_ts: i64) -> Result<()> {
+        self.conn
+            .prepare_cached("DELETE FROM served_log WHERE served_at < ?1")?
+            .execute([before_ts])?;
+        Ok(())
+    }
 
-pub fn get_skill_names_for_problem(conn: &Connection, problem_id: i64) -> Result<Vec<String>> {
-    let mut stmt = conn.prepare(
-        "SELECT s.name 
-         FROM skills s
-         JOIN problem_skills ps ON s.id = ps.skill_id
-         WHERE ps.problem_id = ?",
-    )?;
+    // --- Skips ---
 
-    let skills = stmt
-        .query_map([problem_id], |row| row.get(0))?
-        .collect::<Result<Vec<String>, _>>()?;
+    pub fn log_skip(&self, problem_id: i64, reason: SkipReason, skipped_at: i64) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT INTO problem_skips (problem_id, reason, skipped_at) VALUES (?, ?, ?)",
+            )?
+            .execute(params![problem_id, reason.as_str(), skipped_at])?;
+        Ok(())
+    }
 
-    Ok(skills)
-}
+    /// Concepts skipped after `since_ts`: the skipped problems, and the parents of skipped
+    /// alternatives.
+    pub fn get_skipped_concepts_since(&self, since_ts: i64) -> Result<HashSet<i64>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT problem_id FROM problem_skips WHERE skipped_at > ?1
+             UNION
+             SELECT a.parent_id FROM problem_skips s
+             JOIN alternatives a ON a.id = s.problem_id
+             WHERE s.skipped_at > ?1",
+        )?;
+        let ids = stmt
+            .query_map([since_ts], |row| row.get(0))?
+            .collect::<Result<HashSet<i64>, _>>()?;
+        Ok(ids)
+    }
 
-/// Records a raw attempt log.
-pub fn log_attempt(
-    conn: &Connection,
-    problem_id: i64,
-    time_minutes: f64,
-    solved: bool,
-    read_solution: bool,
-    timestamp: i64,
-) -> Result<()> {
-    conn.execute(
-        "INSERT INTO attempts (problem_id, time_minutes, solved, read_solution, timestamp) VALUES (?, ?, ?, ?, ?)",
-        params![problem_id, time_minutes, solved, read_solution, timestamp]
-    )?;
-    Ok(())
-}
+    /// Shrinks a tracked concept's interval by `factor`, down to `INTERVAL_MIN`. Its next
+    /// review stays where it is.
+    pub fn scale_interval(&self, problem_id: i64, factor: f64) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "UPDATE problem_state SET interval_days = MAX(?2, interval_days * ?3)
+                 WHERE problem_id = ?1",
+            )?
+            .execute(params![problem_id, INTERVAL_MIN, factor])?;
+        Ok(())
+    }
 
-/// Helper to get difficulty and associated skills for a problem.
-pub fn get_problem_metadata(conn: &Connection, problem_id: i64) -> Result<(Difficulty, Vec<i64>)> {
-    let diff_str: String = conn.query_row(
-        "SELECT difficulty FROM problems WHERE id = ?",
-        [problem_id],
-        |r| r.get(0),
-    )?;
+    // --- Search ---
 
-    let difficulty = Difficulty::from_str(&diff_str).unwrap_or(Difficulty::Medium);
+    /// Runs an FTS5 `MATCH` over the search index, best matches first. `kind` restricts hits
+    /// to one source (e.g. "note").
+    pub fn search_index(
+        &self,
+        fts_query: &str,
+        kind: Option<&str>,
+        page: Pagination,
+    ) -> Result<Vec<SearchHit>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT s.kind, s.problem_id,
+                    CASE WHEN s.kind = 'alternative' THEN alt.title
+                         ELSE COALESCE(p.title, alt.title, '') END,
+                    snippet(search_index, -1, '<mark>', '</mark>', '…', ?2)
+             FROM search_index s
+             LEFT JOIN problems p ON p.id = s.problem_id
+             LEFT JOIN alternatives alt ON alt.id = s.problem_id
+             WHERE search_index MATCH ?1
+             AND (?3 IS NULL OR s.kind = ?3)
+             AND p.deleted_at IS NULL
+             ORDER BY rank
+             LIMIT ?4 OFFSET ?5",
+        )?;
+        let hits = stmt
+            .query_map(
+                params![
+                    fts_query,
+                    SEARCH_SNIPPET_TOKENS,
+                    kind,
+                    page.limit() as i64,
+                    page.offset() as i64
+                ],
+                |row| {
+                    Ok(SearchHit {
+                        kind: row.get(0)?,
+                        problem_id: row.get(1)?,
+                        title: row.get(2)?,
+                        snippet: row.get(3)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(hits)
+    }
 
-    let mut stmt = conn.prepare("SELECT skill_id FROM problem_skills WHERE problem_id = ?")?;
-    let skills = stmt
-        .query_map([problem_id], |row| row.get(0))?
-        .collect::<Result<Vec<i64>, _>>()?;
+    pub fn count_search_hits(&self, fts_query: &str, kind: Option<&str>) -> Result<i64> {
+        self.conn
+            .prepare_cached(
+                "SELECT COUNT(*) FROM search_index
+             WHERE search_index MATCH ?1
+             AND (?2 IS NULL OR kind = ?2)
+             AND problem_id NOT IN (SELECT id FROM problems WHERE deleted_at IS NOT NULL)",
+            )?
+            .query_row(params![fts_query, kind], |row| row.get(0))
+    }
 
-    Ok((difficulty, skills))
-}
+    // --- Audit Log ---
 
-pub fn get_attempt_count(conn: &Connection, problem_id: i64) -> Result<i64> {
-    conn.query_row(
-        "SELECT count(*) FROM attempts WHERE problem_id = ?",
-        [problem_id],
-        |r| r.get(0),
-    )
-}
+    /// The cause the audit triggers currently record, if one is set.
+    pub fn get_audit_cause(&self) -> Result<Option<(String, Option<i64>)>> {
+        self.conn
+            .prepare_cached("SELECT cause, attempt_id FROM audit_context WHERE id = 1")?
+            .query_row([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .optional()
+    }
+
+    /// Sets (or with `None`, clears) the cause recorded for later state changes.
+    pub fn set_audit_cause(&self, cause: Option<(&str, Option<i64>)>) -> Result<()> {
+        match cause {
+            Some((cause, attempt_id)) => self
+                .conn
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO audit_context (id, cause, attempt_id) VALUES (1, ?, ?)",
+                )?
+                .execute(params![cause, attempt_id])?,
+            None => self
+                .conn
+                .prepare_cached("DELETE FROM audit_context")?
+                .execute([])?,
+        };
+        Ok(())
+    }
+
+    /// Audit entries, newest first, optionally only those for `entity` (and `entity_id`).
+    pub fn get_audit_log(
+        &self,
+        entity: Option<&str>,
+        entity_id: Option<i64>,
+        page: Pagination,
+    ) -> Result<Vec<AuditEntry>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT id, timestamp, entity, entity_id, old_value, new_value, cause, attempt_id
+             FROM audit_log
+             WHERE (?1 IS NULL OR entity = ?1) AND (?2 IS NULL OR entity_id = ?2)
+             ORDER BY id DESC
+             LIMIT ?3 OFFSET ?4",
+        )?;
+        let json = |value: Option<String>| value.and_then(|v| serde_json::from_str(&v).ok());
+        let entries = stmt
+            .query_map(
+                params![entity, entity_id, page.limit() as i64, page.offset() as i64],
+                |row| {
+                    Ok(AuditEntry {
+                        id: row.get(0)?,
+                        timestamp: row.get(1)?,
+                        entity: row.get(2)?,
+                        entity_id: row.get(3)?,
+                        old_value: json(row.get(4)?),
+                        new_value: json(row.get(5)?),
+                        cause: row.get(6)?,
+                        attempt_id: row.get(7)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    /// Timestamp of the oldest audit entry, i.e. how far back state can be reconstructed.
+    pub fn get_audit_log_start(&self) -> Result<Option<i64>> {
+        self.conn
+            .prepare_cached("SELECT MIN(timestamp) FROM audit_log")?
+            .query_row([], |row| row.get(0))
+    }
+
+    /// (entity, entity_id, value) for every row changed after `ts`, where value is what the
+    /// row held at `ts`: the old value of its first later change (`None` if it did not exist).
+    pub fn get_audit_values_at(&self, ts: i64) -> Result<Vec<(String, i64, Option<String>)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT entity, entity_id, old_value FROM audit_log
+             WHERE id IN (
+                SELECT MIN(id) FROM audit_log WHERE timestamp > ? GROUP BY entity, entity_id
+             )
+             ORDER BY id",
+        )?;
+        let values = stmt
+            .query_map([ts], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(values)
+    }
+
+    pub fn count_audit_log(&self, entity: Option<&str>, entity_id: Option<i64>) -> Result<i64> {
+        self.conn
+            .prepare_cached(
+                "SELECT COUNT(*) FROM audit_log
+             WHERE (?1 IS NULL OR entity = ?1) AND (?2 IS NULL OR entity_id = ?2)",
+            )?
+            .query_row(params![entity, entity_id], |row| row.get(0))
+    }
+
+    // --- Usage Telemetry ---
+
+    pub fn log_usage_event(
+        &self,
+        command: &str,
+        duration_ms: i64,
+        ok: bool,
+        timestamp: i64,
+    ) -> Result<()> {
+        self.conn.prepare_cached("INSERT INTO usage_events (command, duration_ms, ok, timestamp) VALUES (?1, ?2, ?3, ?4)")?.execute(params![command, duration_ms, ok, timestamp],)?;
+        Ok(())
+    }
+
+    /// Per-command totals since `since_ts`, most used first. `per_day` is left for the caller.
+    pub fn get_usage_by_command(&self, since_ts: i64) -> Result<Vec<CommandUsage>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT command, COUNT(*), SUM(ok = 0), AVG(duration_ms), MAX(duration_ms)
+             FROM usage_events
+             WHERE timestamp >= ?1
+             GROUP BY command
+             ORDER BY COUNT(*) DESC, command",
+        )?;
+        let rows = stmt.query_map([since_ts], |row| {
+            Ok(CommandUsage {
+                command: row.get(0)?,
+                calls: row.get(1)?,
+                errors: row.get(2)?,
+                avg_ms: row.get(3)?,
+                max_ms: row.get(4)?,
+                per_day: 0.0,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Adds one flush of statement timings to the running totals for `sql`.
+    pub fn record_query_stat(
+        &self,
+        sql: &str,
+        calls: i64,
+        total_us: i64,
+        max_us: i64,
+        now: i64,
+    ) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT INTO query_stats (sql, calls, total_us, max_us, last_seen)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(sql) DO UPDATE SET
+                calls = calls + excluded.calls,
+                total_us = total_us + excluded.total_us,
+                max_us = MAX(max_us, excluded.max_us),
+                last_seen = excluded.last_seen",
+            )?
+            .execute(params![sql, calls, total_us, max_us, now])?;
+        Ok(())
+    }
+
+    /// The `limit` statements with the highest mean latency.
+    pub fn get_slow_queries(&self, limit: usize) -> Result<Vec<QueryStat>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT sql, calls, total_us * 1.0 / calls / 1000.0, max_us / 1000.0
+             FROM query_stats
+             ORDER BY total_us * 1.0 / calls DESC
+             LIMIT ?",
+        )?;
+        let stats = stmt
+            .query_map([limit as i64], |row| {
+                Ok(QueryStat {
+                    sql: row.get(0)?,
+                    calls: row.get(1)?,
+                    avg_ms: row.get(2)?,
+                    max_ms: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(stats)
+    }
+
+    pub fn prune_query_stats(&self, before_ts: i64) -> Result<()> {
+        self.conn
+            .prepare_cached("DELETE FROM query_stats WHERE last_seen < ?1")?
+            .execute([before_ts])?;
+        Ok(())
+    }
+
+    pub fn prune_usage_events(&self, before_ts: i64) -> Result<()> {
+        self.conn
+            .prepare_cached("DELETE FROM usage_events WHERE timestamp < ?1")?
+            .execute([before_ts])?;
+        Ok(())
+    }
+
+    // --- Learned Difficulty Multipliers ---
+
+    /// (difficulty, attempts, failures, total minutes) over every attempt, Easy to Hard. The
+    /// user's difficulty override wins over the catalog, as when the attempt was credited.
+    pub fn get_difficulty_fit_inputs(&self) -> Result<Vec<(Difficulty, i64, i64, f64)>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT COALESCE(o.difficulty, p.difficulty, alt.difficulty, 'Medium') AS d,
+                count(*),
+                SUM(a.solved = 0 OR a.read_solution = 1),
+                SUM(a.time_minutes)
+             FROM attempts a
+             LEFT JOIN problems p ON p.id = a.problem_id
+             LEFT JOIN alternatives alt ON alt.id = a.problem_id
+             LEFT JOIN problem_overrides o ON o.problem_id = a.problem_id
+             GROUP BY d",
+        )?;
+        let mut rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    Difficulty::from_str(&row.get::<_, String>(0)?).unwrap_or(Difficulty::Medium),
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.sort_by_key(|r| r.0);
+        Ok(rows)
+    }
+
+    pub fn save_difficulty_fits(&self, fits: &[DifficultyFit], now: i64) -> Result<()> {
+        let mut stmt = self.conn.prepare_cached(
+            "INSERT OR REPLACE INTO difficulty_fits
+                (difficulty, default_multiplier, multiplier, attempts, fail_rate, mean_minutes,
+                 fitted_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )?;
+        for fit in fits {
+            stmt.execute(params![
+                fit.difficulty.as_str(),
+                fit.default_multiplier,
+                fit.multiplier,
+                fit.attempts,
+                fit.fail_rate,
+                fit.mean_minutes,
+                now
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// The stored fits, Easy to Hard, and when they were made.
+    pub fn get_difficulty_fits(&self) -> Result<(Vec<DifficultyFit>, Option<i64>)> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT difficulty, default_multiplier, multiplier, attempts, fail_rate,
+                    mean_minutes, fitted_at
+             FROM difficulty_fits",
+        )?;
+        let mut fitted_at = None;
+        let mut fits = stmt
+            .query_map([], |row| {
+                Ok((
+                    DifficultyFit {
+                        difficulty: Difficulty::from_str(&row.get::<_, String>(0)?)
+                            .unwrap_or(Difficulty::Medium),
+                        default_multiplier: row.get(1)?,
+                        multiplier: row.get(2)?,
+                        attempts: row.get(3)?,
+                        fail_rate: row.get(4)?,
+                        mean_minutes: row.get(5)?,
+                    },
+                    row.get::<_, i64>(6)?,
+                ))
+            })?
+            .map(|row| {
+                row.map(|(fit, ts)| {
+                    fitted_at = fitted_at.max(Some(ts));
+                    fit
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        fits.sort_by_key(|f| f.difficulty);
+        Ok((fits, fitted_at))
+    }
+
+    pub fn get_fitted_multiplier(&self, difficulty: Difficulty) -> Result<Option<f64>> {
+        self.conn
+            .prepare_cached("SELECT multiplier FROM difficulty_fits WHERE difficulty = ?")?
+            .query_row([difficulty.as_str()], |row| row.get(0))
+            .optional()
+    }
+
+    pub fn delete_problem_repetition_state(&self, problem_id: i64) -> Result<()> {
+        self.conn
+            .prepare_cached("DELETE FROM problem_state WHERE problem_id = ?")?
+            .execute([problem_id])?;
+        Ok(())
+    }
+
+    pub fn get_all_intervals(&self) -> Result<Vec<f64>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT interval_days FROM problem_state")?;
+        let intervals = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<f64>, _>>()?;
+        Ok(intervals)
+    }
+
+    /// Unsuspended tracked problems due at or before `ts`, most overdue first.
+    pub fn find_overdue_problem_ids(&self, ts: i64) -> Result<Vec<i64>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT problem_id FROM problem_state
+             WHERE next_review_ts <= ?
+             AND problem_id NOT IN (SELECT problem_id FROM suspended_problems)
+             AND problem_id NOT IN (SELECT id FROM problems WHERE deleted_at IS NOT NULL)
+             ORDER BY next_review_ts ASC, problem_id ASC",
+        )?;
+        let ids = stmt
+            .query_map([ts], |row| row.get(0))?
+            .collect::<Result<Vec<i64>, _>>()?;
+        Ok(ids)
+    }
+
+    pub fn set_next_review_ts(&self, problem_id: i64, ts: i64) -> Result<()> {
+        self.conn
+            .prepare_cached("UPDATE problem_state SET next_review_ts = ? WHERE problem_id = ?")?
+            .execute(params![ts, problem_id])?;
+        Ok(())
+    }
+
+    pub fn count_reviews_due_by(&self, ts: i64) -> Result<i64> {
+        self.conn
+            .prepare_cached(
+                "SELECT count(*) FROM problem_state
+             WHERE next_review_ts <= ?
+             AND problem_id NOT IN (SELECT problem_id FROM suspended_problems)
+             AND problem_id NOT IN (SELECT id FROM problems WHERE deleted_at IS NOT NULL)",
+            )?
+            .query_row([ts], |r| r.get(0))
+    }
+
+    /// Tracked problems other than `exclude_problem_id` due in `[from_ts, to_ts)`.
+    pub fn count_reviews_between(
+        &self,
+        from_ts: i64,
+        to_ts: i64,
+        exclude_problem_id: i64,
+    ) -> Result<i64> {
+        self.conn
+            .prepare_cached(
+                "SELECT count(*) FROM problem_state
+             WHERE next_review_ts >= ? AND next_review_ts < ? AND problem_id != ?
+             AND problem_id NOT IN (SELECT problem_id FROM suspended_problems)
+             AND problem_id NOT IN (SELECT id FROM problems WHERE deleted_at IS NOT NULL)",
+            )?
+            .query_row(params![from_ts, to_ts, exclude_problem_id], |r| r.get(0))
+    }
+
+    /// Helper to get difficulty and associated skills for a problem, as `(skill_id, weight)`
+    /// with the primary skill first.
+    pub fn get_problem_metadata(&self, problem_id: i64) -> Result<(Difficulty, Vec<(i64, f64)>)> {
+        let diff_str: String = self
+            .conn
+            .prepare_cached("SELECT difficulty FROM problems WHERE id = ?")?
+            .query_row([problem_id], |r| r.get(0))?;
+
+        let difficulty = Difficulty::from_str(&diff_str).unwrap_or(Difficulty::Medium);
+
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT skill_id, weight FROM problem_skills WHERE problem_id = ?
+             ORDER BY weight DESC, skill_id",
+        )?;
+        let skills = stmt
+            .query_map([problem_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<(i64, f64)>, _>>()?;
+
+        Ok((difficulty, skills))
+    }
+
+    /// The user's difficulty for a problem, if they overrode the catalog's.
+    pub fn get_difficulty_override(&self, problem_id: i64) -> Result<Option<Difficulty>> {
+        let difficulty: Option<String> = self
+            .conn
+            .prepare_cached("SELECT difficulty FROM problem_overrides WHERE problem_id = ?")?
+            .query_row([problem_id], |row| row.get(0))
+            .optional()?
+            .flatten();
+        Ok(difficulty.and_then(|d| Difficulty::from_str(&d).ok()))
+    }
+
+    /// Sets or, with `None`, clears the difficulty override for a problem.
+    pub fn set_difficulty_override(
+        &self,
+        problem_id: i64,
+        difficulty: Option<Difficulty>,
+    ) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT INTO problem_overrides (problem_id, difficulty) VALUES (?1, ?2)
+             ON CONFLICT(problem_id) DO UPDATE SET difficulty = excluded.difficulty",
+            )?
+            .execute(params![problem_id, difficulty.map(|d| d.to_string())])?;
+        Ok(())
+    }
+
+    /// The user's expected solve time for a problem, if they set one.
+    pub fn get_expected_minutes_override(&self, problem_id: i64) -> Result<Option<f64>> {
+        Ok(self
+            .conn
+            .prepare_cached("SELECT expected_minutes FROM problem_overrides WHERE problem_id = ?")?
+            .query_row([problem_id], |row| row.get(0))
+            .optional()?
+            .flatten())
+    }
+
+    /// Sets or, with `None`, clears the expected solve time for a problem.
+    pub fn set_expected_minutes_override(
+        &self,
+        problem_id: i64,
+        minutes: Option<f64>,
+    ) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT INTO problem_overrides (problem_id, expected_minutes) VALUES (?1, ?2)
+             ON CONFLICT(problem_id) DO UPDATE SET expected_minutes = excluded.expected_minutes",
+            )?
+            .execute(params![problem_id, minutes])?;
+        Ok(())
+    }
+
+    pub fn get_attempt_count(&self, problem_id: i64) -> Result<i64> {
+        self.conn
+            .prepare_cached("SELECT count(*) FROM attempts WHERE problem_id = ?")?
+            .query_row([problem_id], |r| r.get(0))
+    }
+
+    // --- Queries for "Get Next Problem" ---
+
+    /// Concepts reserved until after `now_ts`. Expired rows are dropped on the way.
+    pub fn get_active_reservations(&self, now_ts: i64) -> Result<HashSet<i64>> {
+        // Read-only databases just keep their stale rows; they are filtered out below
+        let _ = self
+            .conn
+            .prepare_cached("DELETE FROM reservations WHERE expires_at <= ?")?
+            .execute([now_ts]);
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT problem_id FROM reservations WHERE expires_at > ?")?;
+        let ids = stmt
+            .query_map([now_ts], |row| row.get(0))?
+            .collect::<Result<HashSet<i64>, _>>()?;
+        Ok(ids)
+    }
+
+    pub fn reserve_problem(&self, problem_id: i64, expires_at: i64) -> Result<()> {
+        self.conn
+            .prepare_cached(
+                "INSERT OR REPLACE INTO reservations (problem_id, expires_at) VALUES (?1, ?2)",
+            )?
+            .execute(params![problem_id, expires_at])?;
+        Ok(())
+    }
 
-// --- Queries for "Get Next Problem" ---
+    pub fn clear_reservation(&self, problem_id: i64) -> Result<()> {
+        self.conn
+            .prepare_cached("DELETE FROM reservations WHERE problem_id = ?")?
+            .execute([problem_id])?;
+        Ok(())
+    }
+
+    /// Up to `limit` due reviews in `order` (most overdue first for `ReviewOrder::Due`).
+    pub fn find_due_reviews(
+        &self,
+        now_ts: i64,
+        limit: usize,
+        order: ReviewOrder,
+    ) -> Result<Vec<ProblemView>> {
+        // Skills are compared by each problem's primary skill
+        let order_by = match order {
+            ReviewOrder::Due => "next_review_ts",
+            ReviewOrder::Blocked => {
+                "MIN(next_review_ts) OVER (PARTITION BY skill_id), skill_id, next_review_ts"
+            }
+            ReviewOrder::Interleaved => {
+                "ROW_NUMBER() OVER (PARTITION BY skill_id ORDER BY next_review_ts), next_review_ts"
+            }
+        };
+        let sql = format!(
+            "WITH due AS (
+                SELECT p.id, p.title, p.difficulty, p.url, ps.next_review_ts,
+                    (SELECT skill_id FROM problem_skills WHERE problem_id = p.id
+                     ORDER BY weight DESC, skill_id LIMIT 1) AS skill_id
+                FROM problem_state ps
+                JOIN problems p ON ps.problem_id = p.id
+                WHERE ps.next_review_ts <= ?
+                AND ps.problem_id NOT IN (SELECT problem_id FROM suspended_problems)
+                AND p.deleted_at IS NULL
+             )
+             SELECT id, title, difficulty, url FROM due
+             ORDER BY {}
+             LIMIT ?",
+            order_by
+        );
+        let params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(now_ts), Box::new(limit as i64)];
+        self.log_query_plan_once("find_due_reviews", &sql, &params);
+        self.query_problem_views(&sql, params, QueueKind::Review)
+    }
 
-pub fn find_due_review(conn: &Connection, now_ts: i64) -> Result<Option<ProblemView>> {
-    let result = conn
-        .query_row(
+    /// Up to `limit` tracked problems next due at or after `ts`, soonest first.
+    pub fn find_reviews_scheduled_after(&self, ts: i64, limit: usize) -> Result<Vec<ProblemView>> {
+        self.query_problem_views(
             "SELECT p.id, p.title, p.difficulty, p.url
-         FROM problem_state ps
-         JOIN problems p ON ps.problem_id = p.id
-         WHERE ps.next_review_ts <= ?
-         ORDER BY ps.next_review_ts ASC
-         LIMIT 1",
-            [now_ts],
-            |row| {
-                let id: i64 = row.get(0)?;
+             FROM problem_state ps
+             JOIN problems p ON ps.problem_id = p.id
+             WHERE ps.next_review_ts >= ?
+             AND ps.problem_id NOT IN (SELECT problem_id FROM suspended_problems)
+             AND p.deleted_at IS NULL
+             ORDER BY ps.next_review_ts ASC
+             LIMIT ?",
+            vec![Box::new(ts), Box::new(limit as i64)],
+            QueueKind::Review,
+        )
+    }
+
+    /// In debug builds, logs the query plan of a big selection query the first time it runs
+    /// in a session, so a missing index shows up in development rather than as a slow queue
+    /// for users with long histories.
+    fn log_query_plan_once(
+        &self,
+        name: &'static str,
+        sql: &str,
+        params: &[Box<dyn rusqlite::ToSql>],
+    ) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        let first = EXPLAINED_QUERIES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(name);
+        if !first {
+            return;
+        }
+        let plan = self
+            .conn
+            .prepare_cached(&format!("EXPLAIN QUERY PLAN {}", sql))
+            .and_then(|mut stmt| {
+                stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+                    row.get::<_, String>(3)
+                })?
+                .collect::<Result<Vec<_>>>()
+            });
+        match plan {
+            Ok(steps) => info!("Query plan for {}: {}", name, steps.join("; ")),
+            Err(e) => warn!("Could not explain {}: {}", name, e),
+        }
+    }
+
+    /// Runs a `SELECT id, title, difficulty, url` over `problems` and builds full views.
+    fn query_problem_views(
+        &self,
+        sql: &str,
+        params: Vec<Box<dyn rusqlite::ToSql>>,
+        queue: QueueKind,
+    ) -> Result<Vec<ProblemView>> {
+        let mut stmt = self.conn.prepare_cached(sql)?;
+        let mut problems = stmt
+            .query_map(rusqlite::params_from_iter(params.iter()), |row| {
                 Ok(ProblemView {
-                    id,
+                    id: row.get(0)?,
                     title: row.get(1)?,
-                    difficulty: row.get(2)?,
+                    difficulty: Difficulty::from_str(&row.get::<_, String>(2)?)
+                        .unwrap_or(Difficulty::Medium),
                     url: row.get(3)?,
-                    track_name: "🧠 Spaced Review".to_string(), // Updated Label
-                    skills: Vec::new(),                         // Placeholder, filled below
+                    queue: queue.clone(),
+                    queue_display: Some(queue.display()),
+                    skills: Vec::new(), // Placeholder, filled below
+                    skill_ids: Vec::new(),
+                    attempt_count: 0,
+                    last_attempt_ts: None,
+                    next_review_ts: None,
+                    retrospectives: Vec::new(),
                 })
-            },
-        )
-        .optional()?;
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        for p in &mut problems {
+            let id = p.id;
+            self.fill_problem_details(p, id)?;
+        }
+        Ok(problems)
+    }
+
+    /// All skill states, including skills that have never been practiced.
+    pub fn get_all_skill_states(&self) -> Result<Vec<SkillMasteryState>> {
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT s.id, COALESCE(ss.mastery, 0.0), COALESCE(ss.attempts, 0)
+             FROM skills s
+             LEFT JOIN skill_state ss ON ss.skill_id = s.id
+             ORDER BY s.id",
+        )?;
+        let states = stmt
+            .query_map([], |row| {
+                Ok(SkillMasteryState {
+                    skill_id: row.get(0)?,
+                    mastery: row.get(1)?,
+                    attempts: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(states)
+    }
 
-    if let Some(mut p) = result {
-        p.skills = get_skill_names_for_problem(conn, p.id).unwrap_or_default();
-        debug!("[DB] Found due review: {}", p.title);
-        return Ok(Some(p));
+    /// Prerequisite edges as (skill_id, prereq_id).
+    pub fn get_skill_prereqs(&self) -> Result<Vec<(i64, i64)>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT skill_id, prereq_id FROM skill_prereqs")?;
+        let edges = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(edges)
     }
-    Ok(None)
-}
 
-pub fn get_unlocked_skills(conn: &Connection) -> Result<Vec<i64>> {
-    // A skill is unlocked if all its prerequisites are met.
-    // Prereq met = (Mastery >= Unlock_Threshold) OR (Mastery >= Consolidation AND Attempts >= Consolidation)
-    let mut stmt = conn.prepare("SELECT id FROM skills")?;
-    let all_skills = stmt.query_map([], |row| row.get::<_, i64>(0))?;
-
-    let mut unlocked = Vec::new();
-
-    // Note: We are doing N+1 query here for logic clarity, but since N=18 (skills), it's negligible.
-    // Can be optimized into a single complex query if scaling is needed.
-    for s in all_skills {
-        let sid = s?;
-        let failed_prereqs: i64 = conn.query_row(
-            "SELECT count(*) FROM skill_prereqs sp
-             JOIN skill_state ss ON sp.prereq_id = ss.skill_id
-             WHERE sp.skill_id = ?
-             AND (ss.mastery < ? OR (ss.mastery < ? AND ss.attempts < ?))",
-            params![
-                sid,
-                MASTERY_UNLOCK_THRESHOLD,
-                MASTERY_CONSOLIDATION_THRESHOLD,
-                ATTEMPTS_CONSOLIDATION_THRESHOLD
+    pub fn get_skill_names(&self) -> Result<HashMap<i64, String>> {
+        let mut stmt = self.conn.prepare_cached("SELECT id, name FROM skills")?;
+        let names = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(names)
+    }
+
+    /// Up to `limit` untouched problems for the given skills, easiest first.
+    pub fn find_new_problems_for_skills(
+        &self,
+        track_id: i64,
+        skill_ids: &[i64],
+        limit: usize,
+    ) -> Result<Vec<ProblemView>> {
+        if skill_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders = skill_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+
+        // Note: We use GROUP BY because a problem might map to multiple unlocked skills.
+        // Problems whose main skill is unlocked come before ones only reached through a
+        // secondary skill.
+        let sql = format!(
+            "SELECT p.id, p.title, p.difficulty, p.url
+             FROM problems p
+             JOIN track_problems tp ON p.id = tp.problem_id
+             JOIN problem_skills ps ON p.id = ps.problem_id
+             WHERE tp.track_id = ?
+             AND ps.skill_id IN ({})
+             AND p.deleted_at IS NULL
+             -- Exclude if the problem itself is tracked
+             AND p.id NOT IN (SELECT problem_id FROM problem_state)
+             -- Exclude if the problem is an alternative to a tracked parent
+             AND p.id NOT IN (
+                SELECT id FROM alternatives 
+                WHERE parent_id IN (SELECT problem_id FROM problem_state)
+             )
+             -- Exclude if the problem IS the parent of a tracked alternative
+             AND p.id NOT IN (
+                SELECT parent_id FROM alternatives
+                WHERE id IN (SELECT problem_id FROM attempts)
+             )
+             GROUP BY p.id
+             ORDER BY
+                MAX(ps.weight) DESC,
+                CASE p.difficulty
+                    WHEN 'Easy' THEN 1
+                    WHEN 'Medium' THEN 2
+                    WHEN 'Hard' THEN 3
+                    ELSE 4
+                END ASC,
+                selection_random()
+             LIMIT ?",
+            placeholders
+        );
+
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        params.push(Box::new(track_id));
+        for id in skill_ids {
+            params.push(Box::new(*id));
+        }
+        params.push(Box::new(limit as i64));
+
+        self.log_query_plan_once("find_new_problems_for_skills", &sql, &params);
+        self.query_problem_views(&sql, params, QueueKind::Discovery)
+    }
+
+    /// How many problems `find_new_problems_for_skills` could still offer for these skills.
+    pub fn count_new_problems_for_skills(&self, track_id: i64, skill_ids: &[i64]) -> Result<i64> {
+        if skill_ids.is_empty() {
+            return Ok(0);
+        }
+        let placeholders = skill_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+        let sql = format!(
+            "SELECT count(DISTINCT p.id)
+             FROM problems p
+             JOIN track_problems tp ON p.id = tp.problem_id
+             JOIN problem_skills ps ON p.id = ps.problem_id
+             WHERE tp.track_id = ?
+             AND ps.skill_id IN ({})
+             AND p.deleted_at IS NULL
+             AND p.id NOT IN (SELECT problem_id FROM problem_state)
+             AND p.id NOT IN (
+                SELECT id FROM alternatives
+                WHERE parent_id IN (SELECT problem_id FROM problem_state)
+             )
+             AND p.id NOT IN (
+                SELECT parent_id FROM alternatives
+                WHERE id IN (SELECT problem_id FROM attempts)
+             )",
+            placeholders
+        );
+        let mut params: Vec<i64> = vec![track_id];
+        params.extend_from_slice(skill_ids);
+        self.conn
+            .prepare_cached(&sql)?
+            .query_row(rusqlite::params_from_iter(params), |row| row.get(0))
+    }
+
+    /// Up to `limit` problems of `skill_id` whose concept has not been attempted since
+    /// `since`, easiest first.
+    pub fn find_exam_cram_problems(
+        &self,
+        track_id: i64,
+        skill_id: i64,
+        since: i64,
+        limit: usize,
+    ) -> Result<Vec<ProblemView>> {
+        self.query_problem_views(
+            "SELECT p.id, p.title, p.difficulty, p.url
+             FROM problems p
+             JOIN track_problems tp ON p.id = tp.problem_id
+             JOIN problem_skills ps ON p.id = ps.problem_id
+             WHERE tp.track_id = ?1 AND ps.skill_id = ?2 AND p.deleted_at IS NULL
+             AND NOT EXISTS (
+                SELECT 1 FROM attempts a
+                WHERE a.timestamp >= ?3
+                AND (a.problem_id = p.id
+                     OR a.problem_id IN (SELECT id FROM alternatives WHERE parent_id = p.id))
+             )
+             ORDER BY
+                CASE p.difficulty
+                    WHEN 'Easy' THEN 1
+                    WHEN 'Medium' THEN 2
+                    WHEN 'Hard' THEN 3
+                    ELSE 4
+                END ASC,
+                selection_random()
+             LIMIT ?4",
+            vec![
+                Box::new(track_id),
+                Box::new(skill_id),
+                Box::new(since),
+                Box::new(limit as i64),
             ],
-            |row| row.get(0),
-        )?;
+            QueueKind::Cram,
+        )
+    }
+
+    /// Up to `limit` problems for the given skills, weakest skill first.
+    pub fn find_cram_problems(
+        &self,
+        track_id: i64,
+        skill_ids: &[i64],
+        limit: usize,
+    ) -> Result<Vec<ProblemView>> {
+        if skill_ids.is_empty() {
+            return Ok(Vec::new());
+        }
 
-        if failed_prereqs == 0 {
-            unlocked.push(sid);
+        let placeholders = skill_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+
+        // A problem ranks by its lowest-mastery skill, so one weak skill is enough to
+        // bubble it up.
+        let sql = format!(
+            "SELECT p.id, p.title, p.difficulty, p.url
+             FROM problems p
+             JOIN track_problems tp ON p.id = tp.problem_id
+             JOIN problem_skills ps ON p.id = ps.problem_id
+             JOIN skill_state ss ON ps.skill_id = ss.skill_id
+             WHERE tp.track_id = ?
+             AND ps.skill_id IN ({})
+             AND p.deleted_at IS NULL
+             GROUP BY p.id
+             ORDER BY MIN(ss.mastery) ASC, selection_random()
+             LIMIT ?",
+            placeholders
+        );
+
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+        params.push(Box::new(track_id));
+        for id in skill_ids {
+            params.push(Box::new(*id));
+        }
+        params.push(Box::new(limit as i64));
+
+        self.query_problem_views(&sql, params, QueueKind::Cram)
+    }
+
+    /// The first problem (lowest ID) of each difficulty in `difficulties` that practices the
+    /// skill, in that order. Difficulties with no such problem are skipped.
+    pub fn find_assessment_problems(
+        &self,
+        track_id: i64,
+        skill_id: i64,
+        difficulties: &[&str],
+    ) -> Result<Vec<ProblemView>> {
+        let mut problems = Vec::new();
+        for difficulty in difficulties {
+            problems.extend(self.query_problem_views(
+                "SELECT p.id, p.title, p.difficulty, p.url
+                 FROM problems p
+                 JOIN track_problems tp ON p.id = tp.problem_id
+                 JOIN problem_skills ps ON p.id = ps.problem_id
+                 WHERE tp.track_id = ? AND ps.skill_id = ? AND p.difficulty = ?
+                 AND p.deleted_at IS NULL
+                 ORDER BY p.id
+                 LIMIT 1",
+                vec![
+                    Box::new(track_id),
+                    Box::new(skill_id),
+                    Box::new(difficulty.to_string()),
+                ],
+                QueueKind::Assessment,
+            )?);
         }
+        Ok(problems)
+    }
+
+    /// Up to `limit` due reviews carrying `tag`, most overdue first.
+    pub fn find_due_reviews_with_tag(
+        &self,
+        tag: &str,
+        now_ts: i64,
+        limit: usize,
+    ) -> Result<Vec<ProblemView>> {
+        self.query_problem_views(
+            "SELECT p.id, p.title, p.difficulty, p.url
+             FROM problem_tags t
+             JOIN problem_state ps ON ps.problem_id = t.problem_id
+             JOIN problems p ON p.id = t.problem_id
+             WHERE t.tag = ? AND ps.next_review_ts <= ?
+             AND ps.problem_id NOT IN (SELECT problem_id FROM suspended_problems)
+             AND p.deleted_at IS NULL
+             ORDER BY ps.next_review_ts ASC
+             LIMIT ?",
+            vec![
+                Box::new(tag.to_string()),
+                Box::new(now_ts),
+                Box::new(limit as i64),
+            ],
+            QueueKind::Review,
+        )
+    }
+
+    /// Up to `limit` never-attempted problems carrying `tag`, easiest first. Tags are an
+    /// explicit choice, so locked skills and other tracks are not filtered out.
+    pub fn find_new_problems_with_tag(&self, tag: &str, limit: usize) -> Result<Vec<ProblemView>> {
+        self.query_problem_views(
+            "SELECT p.id, p.title, p.difficulty, p.url
+             FROM problem_tags t
+             JOIN problems p ON p.id = t.problem_id
+             WHERE t.tag = ? AND p.deleted_at IS NULL
+             AND p.id NOT IN (SELECT problem_id FROM problem_state)
+             ORDER BY
+                CASE p.difficulty
+                    WHEN 'Easy' THEN 1
+                    WHEN 'Medium' THEN 2
+                    WHEN 'Hard' THEN 3
+                    ELSE 4
+                END ASC,
+                selection_random()
+             LIMIT ?",
+            vec![Box::new(tag.to_string()), Box::new(limit as i64)],
+            QueueKind::Discovery,
+        )
+    }
+
+    /// Up to `limit` tracked problems carrying `tag` that are not due yet, soonest first.
+    pub fn find_upcoming_reviews_with_tag(
+        &self,
+        tag: &str,
+        now_ts: i64,
+        limit: usize,
+    ) -> Result<Vec<ProblemView>> {
+        self.query_problem_views(
+            "SELECT p.id, p.title, p.difficulty, p.url
+             FROM problem_tags t
+             JOIN problem_state ps ON ps.problem_id = t.problem_id
+             JOIN problems p ON p.id = t.problem_id
+             WHERE t.tag = ? AND ps.next_review_ts > ? AND p.deleted_at IS NULL
+             ORDER BY ps.next_review_ts ASC
+             LIMIT ?",
+            vec![
+                Box::new(tag.to_string()),
+                Box::new(now_ts),
+                Box::new(limit as i64),
+            ],
+            QueueKind::Review,
+        )
     }
-    Ok(unlocked)
 }
 
-pub fn find_new_problem_for_skills(
-    conn: &Connection,
-    track_id: i64,
-    skill_ids: &[i64],
-) -> Result<Option<ProblemView>> {
-    if skill_ids.is_empty() {
-        return Ok(None);
-    }
-
-    let placeholders = skill_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
-
-    // Note: We use GROUP BY because a problem might map to multiple unlocked skills.
-    let sql = format!(
-        "SELECT p.id, p.title, p.difficulty, p.url
-         FROM problems p
-         JOIN track_problems tp ON p.id = tp.problem_id
-         JOIN problem_skills ps ON p.id = ps.problem_id
-         WHERE tp.track_id = ?
-         AND ps.skill_id IN ({})
-         -- Exclude if the problem itself is tracked
-         AND p.id NOT IN (SELECT problem_id FROM problem_state)
-         -- Exclude if the problem is an alternative to a tracked parent
-         AND p.id NOT IN (
-            SELECT id FROM alternatives 
-            WHERE parent_id IN (SELECT problem_id FROM problem_state)
-         )
-         -- Exclude if the problem IS the parent of a tracked alternative
-         AND p.id NOT IN (
-            SELECT parent_id FROM alternatives
-            WHERE id IN (SELECT problem_id FROM attempts)
-         )
-         GROUP BY p.id
-         ORDER BY
-            CASE p.difficulty
-                WHEN 'Easy' THEN 1
-                WHEN 'Medium' THEN 2
-                WHEN 'Hard' THEN 3
-                ELSE 4
-            END ASC,
-            RANDOM()
-         LIMIT 1",
-        placeholders
-    );
-
-    let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-    params.push(Box::new(track_id));
-    for id in skill_ids {
-        params.push(Box::new(*id));
-    }
-
-    let result = conn
-        .query_row(&sql, rusqlite::params_from_iter(params.iter()), |row| {
-            Ok(ProblemView {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                difficulty: row.get(2)?,
-                url: row.get(3)?,
-                track_name: "✨ New Discovery".to_string(),
-                skills: Vec::new(),
-            })
-        })
-        .optional()?;
+/// The storage the scheduling core in `pedagogy` needs, so it can run against something
+/// other than SQLite (see `mock_repository::MockRepository`).
+pub trait ProblemRepository {
+    fn get_setting(&self, key: &str) -> Result<Option<String>>;
 
-    if let Some(mut p) = result {
-        p.skills = get_skill_names_for_problem(conn, p.id).unwrap_or_default();
-        return Ok(Some(p));
+    /// Typed read with a fallback for missing or unparsable values.
+    fn get_setting_or<T: FromStr>(&self, key: &str, default: T) -> Result<T> {
+        Ok(self
+            .get_setting(key)?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default))
     }
-    Ok(None)
+
+    fn get_skill_state(&self, skill_id: i64) -> Result<SkillMasteryState>;
+    fn update_skill_state(&self, state: &SkillMasteryState) -> Result<()>;
+    fn get_all_skill_states(&self) -> Result<Vec<SkillMasteryState>>;
+    fn get_skill_prereqs(&self) -> Result<Vec<(i64, i64)>>;
+    fn log_mastery_change(
+        &self,
+        skill_id: i64,
+        attempt_id: Option<i64>,
+        old_mastery: f64,
+        new_mastery: f64,
+        reason: &str,
+        timestamp: i64,
+    ) -> Result<()>;
+    fn get_attempt_count(&self, problem_id: i64) -> Result<i64>;
+    fn get_recent_concept_attempts(
+        &self,
+        parent_id: i64,
+        limit: usize,
+    ) -> Result<Vec<TimedOutcome>>;
+    fn get_suspension_reason(&self, problem_id: i64) -> Result<Option<String>>;
+    fn suspend_problem(&self, problem_id: i64, reason: &str, now: i64) -> Result<()>;
+    fn unsuspend_problem(&self, problem_id: i64) -> Result<bool>;
+    fn find_suspended_for_skill(
+        &self,
+        skill_id: i64,
+        reason: &str,
+        limit: usize,
+    ) -> Result<Vec<i64>>;
+    fn set_next_review_ts(&self, problem_id: i64, ts: i64) -> Result<()>;
+    fn get_problem_repetition_state(&self, problem_id: i64) -> Result<ProblemRepetitionState>;
+    fn save_problem_repetition_state(&self, state: &ProblemRepetitionState) -> Result<()>;
+    fn log_interval(&self, state: &ProblemRepetitionState, timestamp: i64) -> Result<()>;
+    fn get_concept_outcomes(&self, parent_id: i64) -> Result<Vec<(i64, bool)>>;
+    fn get_fitted_multiplier(&self, difficulty: Difficulty) -> Result<Option<f64>>;
+    fn count_reviews_between(
+        &self,
+        from_ts: i64,
+        to_ts: i64,
+        exclude_problem_id: i64,
+    ) -> Result<i64>;
 }
 
-pub fn find_cram_problem(
-    conn: &Connection,
-    track_id: i64,
-    skill_ids: &[i64],
-) -> Result<Option<ProblemView>> {
-    if skill_ids.is_empty() {
-        return Ok(None);
-    }
-
-    let placeholders = skill_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
-
-    // Note: We do NOT group by here intentionally.
-    // If a problem uses a low-mastery skill, we want that specific row to bubble up
-    // via the ORDER BY mastery clause.
-    let sql = format!(
-        "SELECT p.id, p.title, p.difficulty, p.url
-         FROM problems p
-         JOIN track_problems tp ON p.id = tp.problem_id
-         JOIN problem_skills ps ON p.id = ps.problem_id
-         JOIN skill_state ss ON ps.skill_id = ss.skill_id
-         WHERE tp.track_id = ?
-         AND ps.skill_id IN ({}) 
-         ORDER BY ss.mastery ASC, RANDOM()
-         LIMIT 1",
-        placeholders
-    );
-
-    let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-    params.push(Box::new(track_id));
-    for id in skill_ids {
-        params.push(Box::new(*id));
-    }
-
-    let result = conn
-        .query_row(&sql, rusqlite::params_from_iter(params.iter()), |row| {
-            Ok(ProblemView {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                difficulty: row.get(2)?,
-                url: row.get(3)?,
-                track_name: "🔥 Cram Mode".to_string(),
-                skills: Vec::new(),
-            })
-        })
-        .optional()?;
+impl ProblemRepository for Repository<'_> {
+    fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        Repository::get_setting(self, key)
+    }
+
+    fn get_skill_state(&self, skill_id: i64) -> Result<SkillMasteryState> {
+        Repository::get_skill_state(self, skill_id)
+    }
+
+    fn update_skill_state(&self, state: &SkillMasteryState) -> Result<()> {
+        Repository::update_skill_state(self, state)
+    }
+
+    fn get_all_skill_states(&self) -> Result<Vec<SkillMasteryState>> {
+        Repository::get_all_skill_states(self)
+    }
+
+    fn get_skill_prereqs(&self) -> Result<Vec<(i64, i64)>> {
+        Repository::get_skill_prereqs(self)
+    }
+
+    fn log_mastery_change(
+        &self,
+        skill_id: i64,
+        attempt_id: Option<i64>,
+        old_mastery: f64,
+        new_mastery: f64,
+        reason: &str,
+        timestamp: i64,
+    ) -> Result<()> {
+        Repository::log_mastery_change(
+            self,
+            skill_id,
+            attempt_id,
+            old_mastery,
+            new_mastery,
+            reason,
+            timestamp,
+        )
+    }
+
+    fn get_attempt_count(&self, problem_id: i64) -> Result<i64> {
+        Repository::get_attempt_count(self, problem_id)
+    }
+
+    fn get_recent_concept_attempts(
+        &self,
+        parent_id: i64,
+        limit: usize,
+    ) -> Result<Vec<TimedOutcome>> {
+        Repository::get_recent_concept_attempts(self, parent_id, limit)
+    }
+
+    fn get_suspension_reason(&self, problem_id: i64) -> Result<Option<String>> {
+        Repository::get_suspension_reason(self, problem_id)
+    }
+
+    fn suspend_problem(&self, problem_id: i64, reason: &str, now: i64) -> Result<()> {
+        Repository::suspend_problem(self, problem_id, reason, now)
+    }
+
+    fn unsuspend_problem(&self, problem_id: i64) -> Result<bool> {
+        Repository::unsuspend_problem(self, problem_id)
+    }
+
+    fn find_suspended_for_skill(
+        &self,
+        skill_id: i64,
+        reason: &str,
+        limit: usize,
+    ) -> Result<Vec<i64>> {
+        Repository::find_suspended_for_skill(self, skill_id, reason, limit)
+    }
+
+    fn set_next_review_ts(&self, problem_id: i64, ts: i64) -> Result<()> {
+        Repository::set_next_review_ts(self, problem_id, ts)
+    }
+
+    fn get_problem_repetition_state(&self, problem_id: i64) -> Result<ProblemRepetitionState> {
+        Repository::get_problem_repetition_state(self, problem_id)
+    }
+
+    fn save_problem_repetition_state(&self, state: &ProblemRepetitionState) -> Result<()> {
+        Repository::save_problem_repetition_state(self, state)
+    }
+
+    fn log_interval(&self, state: &ProblemRepetitionState, timestamp: i64) -> Result<()> {
+        Repository::log_interval(self, state, timestamp)
+    }
+
+    fn get_concept_outcomes(&self, parent_id: i64) -> Result<Vec<(i64, bool)>> {
+        Repository::get_concept_outcomes(self, parent_id)
+    }
+
+    fn get_fitted_multiplier(&self, difficulty: Difficulty) -> Result<Option<f64>> {
+        Repository::get_fitted_multiplier(self, difficulty)
+    }
+
+    fn count_reviews_between(
+        &self,
+        from_ts: i64,
+        to_ts: i64,
+        exclude_problem_id: i64,
+    ) -> Result<i64> {
+        Repository::count_reviews_between(self, from_ts, to_ts, exclude_problem_id)
+    }
+}
 
-    if let Some(mut p) = result {
-        p.skills = get_skill_names_for_problem(conn, p.id).unwrap_or_default();
-        return Ok(Some(p));
+// --- Paging ---
+
+/// ORDER BY terms for sorting on `field`: missing values last in either direction, then
+/// `tiebreak` so pages never overlap.
+fn order_by(field: &str, direction: SortDirection, tiebreak: &str) -> String {
+    let direction = match direction {
+        SortDirection::Asc => "ASC",
+        SortDirection::Desc => "DESC",
+    };
+    format!("{0} IS NULL, {0} {1}, {2}", field, direction, tiebreak)
+}
+
+/// LIMIT and OFFSET values for `page`; `None` takes every row (a negative LIMIT has no
+/// upper bound in SQLite).
+fn limit_offset(page: Option<Pagination>) -> (i64, i64) {
+    match page {
+        Some(page) => (page.limit() as i64, page.offset() as i64),
+        None => (-1, 0),
     }
-    Ok(None)
+}
+
+// --- Problem Browser ---
+
+/// A `listed` CTE of problems with their status, as `ProblemStatus::derive` computes it.
+/// `source` joins in the problems to list as `p`, `filter` picks them by ?1; ?2 is now.
+fn problems_with_status_sql(source: &str, filter: &str) -> String {
+    format!(
+        "WITH listed AS (
+            SELECT p.id, p.title, p.url, COALESCE(p.difficulty, 'Medium') AS difficulty,
+                st.next_review_ts,
+                (SELECT MAX(a.timestamp) FROM attempts a
+                 WHERE a.problem_id = p.id
+                    OR a.problem_id IN (SELECT id FROM alternatives WHERE parent_id = p.id))
+                    AS last_attempt_ts,
+                CASE
+                    WHEN sp.problem_id IS NOT NULL THEN 'Suspended'
+                    WHEN st.problem_id IS NULL THEN 'New'
+                    WHEN st.next_review_ts <= ?2 THEN 'Review'
+                    WHEN st.interval_days >= {} THEN 'Mastered'
+                    ELSE 'Learning'
+                END AS status
+            FROM {}
+            LEFT JOIN problem_state st ON st.problem_id = p.id
+            LEFT JOIN suspended_problems sp ON sp.problem_id = p.id
+            WHERE {} AND p.deleted_at IS NULL
+        )",
+        STATUS_MASTERED_INTERVAL, source, filter
+    )
+}
+
+fn track_problems_with_status_sql() -> String {
+    problems_with_status_sql(
+        "track_problems tp JOIN problems p ON p.id = tp.problem_id",
+        "tp.track_id = ?1",
+    )
+}
+
+fn problem_list_item(row: &rusqlite::Row) -> Result<ProblemListItem> {
+    Ok(ProblemListItem {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        url: row.get(2)?,
+        difficulty: Difficulty::from_str(&row.get::<_, String>(3)?).unwrap_or(Difficulty::Medium),
+        status: ProblemStatus::from_str(&row.get::<_, String>(4)?).unwrap_or(ProblemStatus::New),
+        next_review_ts: row.get(5)?,
+        last_attempt_ts: row.get(6)?,
+    })
+}
+
+/// A serve's `ServeOutcome` as of `?1`: abandoned once anything else was served after it or
+/// its reservation lapsed, as long as no attempt was credited to it.
+fn serve_outcome_sql() -> String {
+    format!(
+        "CASE WHEN s.attempt_id IS NOT NULL THEN 'attempted'
+            WHEN s.served_at + {} < ?1 OR EXISTS (
+                SELECT 1 FROM served_log l WHERE l.id > s.id AND l.concept_id != s.concept_id
+            ) THEN 'abandoned'
+            ELSE 'pending' END",
+        RESERVATION_TTL_SECONDS
+    )
 }
@@ -0,0 +1,72 @@
+// src/retention.rs
+
+use crate::constants::*;
+use crate::repository::ProblemRepository;
+
+/// Recall probability `elapsed_days` after a review, for a memory of `stability` days.
+pub fn recall_probability(elapsed_days: f64, stability: f64) -> f64 {
+    (-elapsed_days / stability).exp()
+}
+
+/// MAP stability of a forgetting curve `R(t) = exp(-t / S)` from `(elapsed days, recalled)`
+/// reviews, under a log-normal prior centred on `prior_stability`. With little history
+/// the prior dominates; the grid keeps this robust to all-success or all-fail histories.
+pub fn estimate_stability(reviews: &[(f64, bool)], prior_stability: f64) -> f64 {
+    let prior_log = prior_stability.max(f64::MIN_POSITIVE).ln();
+    let (lo, hi) = ((INTERVAL_MIN / 10.0).ln(), (INTERVAL_MAX * 10.0).ln());
+    let log_posterior = |log_s: f64| {
+        let s = log_s.exp();
+        let prior = -(log_s - prior_log).powi(2) / (2.0 * RETENTION_PRIOR_SIGMA.powi(2));
+        let likelihood: f64 = reviews
+            .iter()
+            .map(|&(t, recalled)| {
+                let r = recall_probability(t, s);
+                if recalled {
+                    r.ln()
+                } else {
+                    (1.0 - r).max(f64::MIN_POSITIVE).ln()
+                }
+            })
+            .sum();
+        prior + likelihood
+    };
+    (0..=RETENTION_GRID_STEPS)
+        .map(|i| lo + (hi - lo) * i as f64 / RETENTION_GRID_STEPS as f64)
+        .max_by(|a, b| log_posterior(*a).total_cmp(&log_posterior(*b)))
+        .map_or(prior_stability, f64::exp)
+}
+
+/// Days until recall drops to `target` for a memory of `stability` days.
+pub fn interval_for(stability: f64, target: f64) -> f64 {
+    stability * (1.0 / target).ln()
+}
+
+/// The user's target retention, if they set one, kept within the supported range.
+pub fn target_retention(repo: &impl ProblemRepository) -> Result<Option<f64>, String> {
+    Ok(repo
+        .get_setting(SETTING_TARGET_RETENTION)
+        .map_err(|e| e.to_string())?
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|v| v.is_finite())
+        .map(|v| v.clamp(RETENTION_TARGET_MIN, RETENTION_TARGET_MAX)))
+}
+
+/// The interval that hits `target` for a concept, from its own review history. The SM-2
+/// interval, read as aiming for `RETENTION_SM2_BASELINE`, is the prior for its stability.
+pub fn target_interval(
+    repo: &impl ProblemRepository,
+    parent_id: i64,
+    sm2_interval: f64,
+    target: f64,
+) -> Result<f64, String> {
+    let history = repo
+        .get_concept_outcomes(parent_id)
+        .map_err(|e| e.to_string())?;
+    let reviews: Vec<(f64, bool)> = history
+        .windows(2)
+        .map(|w| ((w[1].0 - w[0].0) as f64 / DAY_SECONDS as f64, w[1].1))
+        .filter(|&(elapsed, _)| elapsed >= RETENTION_MIN_ELAPSED_DAYS)
+        .collect();
+    let prior = sm2_interval / (1.0 / RETENTION_SM2_BASELINE).ln();
+    Ok(interval_for(estimate_stability(&reviews, prior), target))
+}
@@ -0,0 +1,131 @@
+// src/scheduler.rs
+
+use crate::attempt_timer;
+use crate::clock;
+use crate::constants::*;
+use crate::difficulty_model;
+use crate::events::{self, Snapshot};
+use crate::exam_cram;
+use crate::models::AppState;
+use crate::pedagogy;
+use crate::repository::Repository;
+use crate::study_window;
+use crate::triage;
+use chrono::Utc;
+use log::{info, warn};
+use rusqlite::Connection;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// Spawns the background loop: every `SCHEDULER_INTERVAL_SECONDS` it applies mastery
+/// decay, refits the difficulty multipliers once a day, ends an expired exam cram, emits
+/// change events and, once a day in the user's usual study window, notifies about waiting
+/// reviews. After a resume from sleep or a clock change it ticks right away and drops the
+/// cached next problem; a forward jump also pauses running attempt timers where it began.
+pub fn start(app: AppHandle) {
+    thread::spawn(move || {
+        let mut last: Option<Snapshot> = None;
+        loop {
+            match tick(&app, last.as_ref()) {
+                Ok(snapshot) => last = Some(snapshot),
+                Err(e) => warn!("Scheduler tick failed: {}", e),
+            }
+            if let Some((drift, before)) = wait_for_next_tick() {
+                let state = app.state::<AppState>();
+                state.next_problem.clear();
+                // The sleep is not time spent on the problem
+                if drift > 0 {
+                    match state.with_db(|conn| attempt_timer::pause_all(conn, before)) {
+                        Ok(0) => {}
+                        Ok(n) => info!("Paused {} attempt timers across the jump", n),
+                        Err(e) => warn!("Could not pause attempt timers: {}", e),
+                    }
+                }
+            }
+        }
+    });
+    info!("Background scheduler started");
+}
+
+/// Waits up to `SCHEDULER_INTERVAL_SECONDS`, returning early if the wall clock jumped
+/// against the monotonic clock. The monotonic clock pauses while the system sleeps (on
+/// Linux and macOS) and ignores clock changes, so either shows up as a jump. Returns the
+/// jump in seconds and the last wall time seen before it.
+fn wait_for_next_tick() -> Option<(i64, i64)> {
+    let started = Instant::now();
+    let started_wall = Utc::now().timestamp();
+    let mut before = started_wall;
+    while started.elapsed() < Duration::from_secs(SCHEDULER_INTERVAL_SECONDS) {
+        thread::sleep(Duration::from_secs(SCHEDULER_WAKE_CHECK_SECONDS));
+        let wall = Utc::now().timestamp();
+        let drift = wall - started_wall - started.elapsed().as_secs() as i64;
+        if drift.abs() > SCHEDULER_CLOCK_JUMP_SECONDS {
+            info!(
+                "Wall clock moved {}s against the monotonic clock (resume or clock change); re-evaluating",
+                drift
+            );
+            return Some((drift, before));
+        }
+        before = wall;
+    }
+    None
+}
+
+fn tick(app: &AppHandle, last: Option<&Snapshot>) -> Result<Snapshot, String> {
+    app.state::<AppState>()
+        .with_db(|conn| tick_with(app, conn, last))
+}
+
+fn tick_with(
+    app: &AppHandle,
+    conn: &Connection,
+    last: Option<&Snapshot>,
+) -> Result<Snapshot, String> {
+    let repo = Repository::new(conn);
+    let now = clock::now(conn)?;
+    let decayed = pedagogy::apply_decay(conn, now)?;
+    if decayed > 0 {
+        info!("Scheduler: decayed {} skills", decayed);
+    }
+    difficulty_model::refit_if_stale(conn, now)?;
+    if exam_cram::expire_exam_cram(conn, now)? {
+        app.state::<AppState>().next_problem.clear();
+    }
+    if repo
+        .get_setting_or(SETTING_TRIAGE_AUTO, false)
+        .map_err(|e| e.to_string())?
+    {
+        triage::triage_backlog(conn, now)?;
+    }
+
+    // Compare against the previous tick so time-driven changes (reviews falling due,
+    // streaks lapsing at midnight) reach the UI too
+    let snapshot = Snapshot::capture(conn)?;
+    let Some(last) = last else {
+        return Ok(snapshot);
+    };
+    events::emit_diff(app, conn, last, &snapshot);
+
+    let notify = repo
+        .get_setting_or(SETTING_NOTIFICATIONS_ENABLED, true)
+        .map_err(|e| e.to_string())?;
+    if notify && snapshot.reviews_due > 0 && study_window::notification_due(conn, now)? {
+        let body = match snapshot.reviews_due {
+            1 => "1 review is due.".to_string(),
+            n => format!("{} reviews are due.", n),
+        };
+        study_window::mark_notified(conn, now)?;
+        if let Err(e) = app
+            .notification()
+            .builder()
+            .title("Time to review")
+            .body(body)
+            .show()
+        {
+            warn!("Failed to show notification: {}", e);
+        }
+    }
+    Ok(snapshot)
+}
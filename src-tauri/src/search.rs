@@ -0,0 +1,59 @@
+// src/search.rs
+
+use crate::models::{Page, Pagination, SearchHit};
+use crate::repository::Repository;
+use rusqlite::Connection;
+
+/// Turns free text into an FTS5 query: every word must match, as a prefix, so partial
+/// words still find results. Quoting each word keeps FTS5 operators and punctuation in the
+/// input (e.g. `two-sum`, `"`, `OR`) from being parsed as query syntax.
+/// `None` when the input has no searchable words.
+pub fn fts_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| format!("\"{}\"*", w))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// Problem titles, alternatives, notes and explanations matching `query`, best first.
+pub fn search_everything(
+    conn: &Connection,
+    query: &str,
+    page: Pagination,
+) -> Result<Page<SearchHit>, String> {
+    search(conn, query, None, page)
+}
+
+/// Notes matching `query`, with the problem they belong to and a highlighted excerpt.
+pub fn search_notes(
+    conn: &Connection,
+    query: &str,
+    page: Pagination,
+) -> Result<Page<SearchHit>, String> {
+    search(conn, query, Some("note"), page)
+}
+
+fn search(
+    conn: &Connection,
+    query: &str,
+    kind: Option<&str>,
+    page: Pagination,
+) -> Result<Page<SearchHit>, String> {
+    let repo = Repository::new(conn);
+    let Some(fts) = fts_query(query) else {
+        return Ok(Page::new(Vec::new(), page, 0));
+    };
+    let total = repo
+        .count_search_hits(&fts, kind)
+        .map_err(|e| e.to_string())?;
+    let hits = repo
+        .search_index(&fts, kind, page)
+        .map_err(|e| e.to_string())?;
+    Ok(Page::new(hits, page, total))
+}
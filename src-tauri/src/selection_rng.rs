@@ -0,0 +1,49 @@
+// src/selection_rng.rs
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rusqlite::functions::FunctionFlags;
+use rusqlite::{Connection, Result};
+use std::sync::{Mutex, PoisonError};
+
+/// The app's random source behind problem selection, so a seed makes `get_next_problem`
+/// reproducible. Seeded from entropy until `set_seed` is called.
+static SELECTION_RNG: Mutex<Option<StdRng>> = Mutex::new(None);
+
+/// Reseeds selection: the same seed over the same database serves the same problems.
+/// `None` goes back to entropy.
+pub fn set_seed(seed: Option<u64>) {
+    let rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    *SELECTION_RNG.lock().unwrap_or_else(PoisonError::into_inner) = Some(rng);
+}
+
+/// Runs `op` with a generator seeded from the connection's `selection_random()`, so draws
+/// made in Rust follow the same source as the tie-breaks made in queries.
+pub fn with_rng<T>(conn: &Connection, op: impl FnOnce(&mut StdRng) -> T) -> Result<T> {
+    let seed: i64 = conn.query_row("SELECT selection_random()", [], |row| row.get(0))?;
+    Ok(op(&mut StdRng::seed_from_u64(seed as u64)))
+}
+
+/// Registers `selection_random()`, which selection queries use instead of SQLite's
+/// `RANDOM()` to break ties. It draws from the app's shared source.
+pub fn register(conn: &Connection) -> Result<()> {
+    conn.create_scalar_function("selection_random", 0, FunctionFlags::SQLITE_UTF8, |_| {
+        let mut rng = SELECTION_RNG.lock().unwrap_or_else(PoisonError::into_inner);
+        Ok(rng.get_or_insert_with(StdRng::from_entropy).gen::<i64>())
+    })
+}
+
+/// Replaces the connection's `selection_random()` with a source of its own seeded from
+/// `seed`, so selection on it is reproducible whatever the app selects meanwhile.
+pub fn register_seeded(conn: &Connection, seed: u64) -> Result<()> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    conn.create_scalar_function(
+        "selection_random",
+        0,
+        FunctionFlags::SQLITE_UTF8,
+        move |_| Ok(rng.gen::<i64>()),
+    )
+}
@@ -0,0 +1,51 @@
+// src/serve_log.rs
+
+use crate::constants::*;
+use crate::models::{Page, Pagination, ProblemView, ServeHistory, ServeOutcome};
+use crate::pedagogy;
+use crate::repository::Repository;
+use log::warn;
+use rusqlite::Connection;
+
+/// Logs a served problem, dropping serves past `SERVED_LOG_RETENTION_DAYS`. Best effort,
+/// like the reservation: a read-only database can still serve problems.
+pub fn record(conn: &Connection, problem: &ProblemView, now: i64) {
+    let repo = Repository::new(conn);
+    let logged = repo
+        .log_serve(
+            problem.id,
+            pedagogy::concept_id(problem),
+            &problem.queue,
+            now,
+        )
+        .and_then(|_| repo.prune_served_log(now - SERVED_LOG_RETENTION_DAYS * DAY_SECONDS));
+    if let Err(e) = logged {
+        warn!("Could not log serve of problem {}: {}", problem.id, e);
+    }
+}
+
+/// Served problems, newest first, with how many were attempted or abandoned overall.
+pub fn get_history(conn: &Connection, page: Pagination, now: i64) -> Result<ServeHistory, String> {
+    let repo = Repository::new(conn);
+    let serves = repo.get_served_log(page, now).map_err(|e| e.to_string())?;
+    let counts = repo
+        .count_serves_by_outcome(now)
+        .map_err(|e| e.to_string())?;
+    let count = |outcome| {
+        counts
+            .iter()
+            .find(|(o, _)| *o == outcome)
+            .map_or(0, |(_, n)| *n)
+    };
+    let (attempted, abandoned, pending) = (
+        count(ServeOutcome::Attempted),
+        count(ServeOutcome::Abandoned),
+        count(ServeOutcome::Pending),
+    );
+    Ok(ServeHistory {
+        serves: Page::new(serves, page, attempted + abandoned + pending),
+        attempted,
+        abandoned,
+        pending,
+    })
+}
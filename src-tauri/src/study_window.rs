@@ -0,0 +1,79 @@
+// src/study_window.rs
+
+use crate::constants::*;
+use crate::models::NotificationSchedule;
+use crate::plan;
+use crate::repository::Repository;
+use chrono::{Local, TimeZone, Timelike};
+use rusqlite::Connection;
+
+/// When today's "reviews waiting" notification goes out: the `notification_hour` setting
+/// if set, else the start of the `STUDY_WINDOW_HOURS` window holding the most attempts over
+/// the last `STUDY_WINDOW_HISTORY_DAYS`, else `NOTIFICATION_DEFAULT_HOUR`.
+pub fn get_schedule(conn: &Connection, now: i64) -> Result<NotificationSchedule, String> {
+    let repo = Repository::new(conn);
+    let override_hour = repo
+        .get_setting(SETTING_NOTIFICATION_HOUR)
+        .map_err(|e| e.to_string())?
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .filter(|h| *h < 24);
+
+    let since = now - STUDY_WINDOW_HISTORY_DAYS * DAY_SECONDS;
+    let mut per_hour = [0usize; 24];
+    for (_, ts) in repo.get_attempt_timeline().map_err(|e| e.to_string())? {
+        if ts < since {
+            continue;
+        }
+        if let Some(dt) = Local.timestamp_opt(ts, 0).single() {
+            per_hour[dt.hour() as usize] += 1;
+        }
+    }
+    let attempts_considered: usize = per_hour.iter().sum();
+    let learned_hour = (attempts_considered >= STUDY_WINDOW_MIN_ATTEMPTS).then(|| {
+        // Windows may wrap past midnight. Ties go to the latest start, so a single busy
+        // hour starts its window instead of ending it
+        (0..24u32)
+            .max_by_key(|&start| {
+                (0..STUDY_WINDOW_HOURS)
+                    .map(|h| per_hour[((start + h) % 24) as usize])
+                    .sum::<usize>()
+            })
+            .unwrap_or(NOTIFICATION_DEFAULT_HOUR)
+    });
+
+    let today = plan::local_date(now).to_string();
+    Ok(NotificationSchedule {
+        hour: override_hour
+            .or(learned_hour)
+            .unwrap_or(NOTIFICATION_DEFAULT_HOUR),
+        learned_hour,
+        override_hour,
+        attempts_considered,
+        notified_today: repo
+            .get_setting(SETTING_REVIEW_NOTIFIED_ON)
+            .map_err(|e| e.to_string())?
+            .is_some_and(|d| d == today),
+    })
+}
+
+/// Whether today's notification is due: the scheduled hour has come and none went out
+/// today yet.
+pub fn notification_due(conn: &Connection, now: i64) -> Result<bool, String> {
+    let schedule = get_schedule(conn, now)?;
+    let hour = Local
+        .timestamp_opt(now, 0)
+        .single()
+        .map(|dt| dt.hour())
+        .unwrap_or(0);
+    Ok(!schedule.notified_today && hour >= schedule.hour)
+}
+
+/// Records that today's notification went out.
+pub fn mark_notified(conn: &Connection, now: i64) -> Result<(), String> {
+    Repository::new(conn)
+        .set_setting(
+            SETTING_REVIEW_NOTIFIED_ON,
+            &plan::local_date(now).to_string(),
+        )
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,322 @@
+// src/sync.rs
+
+use crate::constants::*;
+use crate::imports;
+use crate::models::{AppState, AttemptLog, ProblemStatus, SyncReport, SyncRow};
+use crate::pedagogy;
+use crate::repository::Repository;
+use chrono::Utc;
+use log::{info, warn};
+use rusqlite::Connection;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+// --- Notion ---
+
+/// Pushes status and notes for every problem in the active track to a Notion database,
+/// optionally pulling back statuses that were changed by hand in Notion first.
+///
+/// The database needs these properties: `Name` (title), `Status` (select), `Difficulty`
+/// (select), `URL` (url), `LeetGraph ID` (number) and `Notes` (text).
+pub fn sync_notion(conn: &Connection, pull: bool) -> Result<SyncReport, String> {
+    let repo = Repository::new(conn);
+    let setting = |key: &str| {
+        repo.get_setting(key)
+            .map_err(|e| e.to_string())?
+            .filter(|v| !v.trim().is_empty())
+            .ok_or_else(|| format!("Notion sync needs the '{}' setting", key))
+    };
+    let client = NotionClient {
+        token: setting(SETTING_NOTION_TOKEN)?,
+        database_id: setting(SETTING_NOTION_DATABASE)?,
+    };
+    let local_wins = repo
+        .get_setting(SETTING_NOTION_CONFLICT_POLICY)
+        .map_err(|e| e.to_string())?
+        .is_some_and(|p| p == "local");
+
+    // Always read the database so pages created by an earlier install are reused
+    let remote = client.query_pages()?;
+    let now = Utc::now().timestamp();
+    let track_id = 1; // Active track, as in get_next_problem
+    let rows = repo.get_sync_rows(track_id).map_err(|e| e.to_string())?;
+
+    let mut report = SyncReport::default();
+    for row in rows {
+        let mut local = ProblemStatus::derive(row.repetition, row.suspended, now);
+        let page = remote.get(&row.problem_id);
+        let synced = row.synced_status.as_deref().and_then(|s| s.parse().ok());
+
+        if let (true, Some(&(_, Some(remote_status)))) = (pull, page.as_ref()) {
+            // Only a side that moved away from the last agreed status counts as a change
+            let remote_changed = synced.is_some_and(|s| s != *remote_status);
+            let local_changed = synced.is_some_and(|s| s != local);
+            if remote_changed && *remote_status != local {
+                if local_changed {
+                    let winner = if local_wins { "local" } else { "Notion" };
+                    report.conflicts.push(format!(
+                        "{}: local {} vs Notion {} ({} kept)",
+                        row.title,
+                        local.as_str(),
+                        remote_status.as_str(),
+                        winner
+                    ));
+                }
+                if !local_changed || !local_wins {
+                    pedagogy::apply_manual_status(conn, row.problem_id, *remote_status, now)?;
+                    local = *remote_status;
+                    report.pulled += 1;
+                }
+            }
+        }
+
+        let up_to_date = page.filter(|(_, remote_status)| {
+            *remote_status == Some(local) && row.synced_note == row.note
+        });
+        if let Some((page_id, _)) = up_to_date {
+            // Nothing to push, but a pulled status becomes the new agreed baseline
+            if synced != Some(local) {
+                repo.save_sync_state(
+                    row.problem_id,
+                    page_id,
+                    local.as_str(),
+                    row.note.as_deref(),
+                    now,
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            continue;
+        }
+
+        let properties = page_properties(&row, local);
+        let page_id = match page.map(|(id, _)| id.clone()) {
+            Some(id) => {
+                client.update_page(&id, properties)?;
+                report.updated += 1;
+                id
+            }
+            None => {
+                let id = client.create_page(properties)?;
+                report.created += 1;
+                id
+            }
+        };
+        repo.save_sync_state(
+            row.problem_id,
+            &page_id,
+            local.as_str(),
+            row.note.as_deref(),
+            now,
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    info!(
+        "Notion sync: {} created, {} updated, {} pulled, {} conflicts",
+        report.created,
+        report.updated,
+        report.pulled,
+        report.conflicts.len()
+    );
+    Ok(report)
+}
+
+// --- LeetCode ---
+
+/// Strict mode: a clean solve only counts if LeetCode shows a recent accepted submission.
+/// Unverified claims in `logs` are downgraded to an assisted solve; returns the warnings
+/// for each log. Settings are read under the database lock, but the requests run after it
+/// is released, so a slow LeetCode never holds up other commands or a transaction. If
+/// LeetCode cannot be reached the claim stands, with a warning, so being offline never
+/// costs progress.
+pub fn verify_claimed_solves(
+    state: &AppState,
+    logs: &mut [AttemptLog],
+) -> Result<Vec<Vec<String>>, String> {
+    let lookups = state.with_db(|conn| {
+        let strict = Repository::new(conn)
+            .get_setting_or(SETTING_STRICT_MODE, false)
+            .map_err(|e| e.to_string())?;
+        Ok(logs
+            .iter()
+            .map(|log| {
+                (strict && log.solved && !log.read_solution)
+                    .then(|| submission_lookup(conn, log.problem_id))
+            })
+            .collect::<Vec<_>>())
+    })?;
+
+    let mut warnings = vec![Vec::new(); logs.len()];
+    for ((log, lookup), warnings) in logs.iter_mut().zip(lookups).zip(&mut warnings) {
+        let Some(lookup) = lookup else {
+            continue;
+        };
+        let verified = lookup.and_then(|target| match target {
+            Some((slug, username)) => has_recent_accepted_submission(&slug, &username).map(Some),
+            None => Ok(None),
+        });
+        match verified {
+            Ok(Some(true)) | Ok(None) => {}
+            Ok(Some(false)) => {
+                warn!("Strict mode: no accepted submission for {}", log.problem_id);
+                log.read_solution = true;
+                warnings.push(
+                    "No recent accepted LeetCode submission found; counted as an assisted solve"
+                        .to_string(),
+                );
+            }
+            Err(e) => warnings.push(format!("Could not verify solve on LeetCode: {}", e)),
+        }
+    }
+    Ok(warnings)
+}
+
+/// The LeetCode slug of this problem and the configured user to check it for. `None` if the
+/// problem does not live on LeetCode.
+fn submission_lookup(
+    conn: &Connection,
+    problem_id: i64,
+) -> Result<Option<(String, String)>, String> {
+    let repo = Repository::new(conn);
+    let (url, slug) = repo
+        .get_problem_link(problem_id)
+        .map_err(|e| e.to_string())?;
+    // Alternatives often point elsewhere (e.g. NeetCode); those can't be checked
+    let slug = match url {
+        Some(u) if u.contains("leetcode.com") => imports::slug_from_url(&u),
+        Some(_) => return Ok(None),
+        None => slug,
+    };
+    let Some(slug) = slug else {
+        return Ok(None);
+    };
+    let username = repo
+        .get_setting(SETTING_LEETCODE_USERNAME)
+        .map_err(|e| e.to_string())?
+        .filter(|u| !u.trim().is_empty())
+        .ok_or_else(|| {
+            format!(
+                "Strict mode needs the '{}' setting",
+                SETTING_LEETCODE_USERNAME
+            )
+        })?;
+    Ok(Some((slug, username.trim().to_string())))
+}
+
+/// Whether `username` has an accepted submission for `slug` within
+/// `STRICT_MODE_WINDOW_SECONDS`.
+fn has_recent_accepted_submission(slug: &str, username: &str) -> Result<bool, String> {
+    let body = json!({
+        "query": "query recentAcSubmissions($username: String!, $limit: Int!) {
+            recentAcSubmissionList(username: $username, limit: $limit) { titleSlug timestamp }
+        }",
+        "variables": { "username": username, "limit": LEETCODE_RECENT_AC_LIMIT },
+    });
+    let resp: Value = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(LEETCODE_TIMEOUT_SECONDS))
+        .build()
+        .post(LEETCODE_GRAPHQL_URL)
+        .set("Referer", "https://leetcode.com")
+        .send_json(body)
+        .map_err(|e| format!("LeetCode request failed: {}", e))?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+
+    let cutoff = Utc::now().timestamp() - STRICT_MODE_WINDOW_SECONDS;
+    let submissions = resp["data"]["recentAcSubmissionList"]
+        .as_array()
+        .ok_or("Unexpected response from LeetCode")?;
+    Ok(submissions.iter().any(|s| {
+        // LeetCode sends the timestamp as a string
+        let ts = s["timestamp"].as_str().and_then(|t| t.parse::<i64>().ok());
+        s["titleSlug"].as_str() == Some(slug) && ts.is_some_and(|t| t >= cutoff)
+    }))
+}
+
+struct NotionClient {
+    token: String,
+    database_id: String,
+}
+
+impl NotionClient {
+    /// Maps LeetGraph IDs to (page_id, status) for every page in the database.
+    fn query_pages(&self) -> Result<HashMap<i64, (String, Option<ProblemStatus>)>, String> {
+        let url = format!("{}/databases/{}/query", NOTION_API_URL, self.database_id);
+        let mut pages = HashMap::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut body = json!({ "page_size": 100 });
+            if let Some(c) = &cursor {
+                body["start_cursor"] = json!(c);
+            }
+            let resp = self.send("POST", &url, body)?;
+            for page in resp["results"].as_array().into_iter().flatten() {
+                let props = &page["properties"];
+                let Some(problem_id) = props["LeetGraph ID"]["number"].as_f64() else {
+                    continue;
+                };
+                let status = props["Status"]["select"]["name"]
+                    .as_str()
+                    .and_then(|s| s.parse().ok());
+                let page_id = page["id"].as_str().unwrap_or_default().to_string();
+                pages.insert(problem_id as i64, (page_id, status));
+            }
+            cursor = resp["next_cursor"].as_str().map(str::to_string);
+            if !resp["has_more"].as_bool().unwrap_or(false) || cursor.is_none() {
+                return Ok(pages);
+            }
+        }
+    }
+
+    fn create_page(&self, properties: Value) -> Result<String, String> {
+        let body = json!({
+            "parent": { "database_id": self.database_id },
+            "properties": properties,
+        });
+        let resp = self.send("POST", &format!("{}/pages", NOTION_API_URL), body)?;
+        resp["id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "Notion returned a page without an id".to_string())
+    }
+
+    fn update_page(&self, page_id: &str, properties: Value) -> Result<(), String> {
+        let url = format!("{}/pages/{}", NOTION_API_URL, page_id);
+        self.send("PATCH", &url, json!({ "properties": properties }))
+            .map(|_| ())
+    }
+
+    fn send(&self, method: &str, url: &str, body: Value) -> Result<Value, String> {
+        let result = ureq::request(method, url)
+            .set("Authorization", &format!("Bearer {}", self.token))
+            .set("Notion-Version", NOTION_API_VERSION)
+            .send_json(body);
+        match result {
+            Ok(resp) => resp.into_json().map_err(|e| e.to_string()),
+            Err(ureq::Error::Status(code, resp)) => {
+                let detail = resp.into_string().unwrap_or_default();
+                warn!("Notion API {} {} failed: {} {}", method, url, code, detail);
+                Err(format!("Notion API error {}: {}", code, detail))
+            }
+            Err(e) => Err(format!("Could not reach Notion: {}", e)),
+        }
+    }
+}
+
+fn page_properties(row: &SyncRow, status: ProblemStatus) -> Value {
+    // Notion caps each rich-text object, so long notes are sent as several chunks
+    let chars: Vec<char> = row.note.as_deref().unwrap_or_default().chars().collect();
+    let notes: Vec<Value> = chars
+        .chunks(NOTION_RICH_TEXT_LIMIT)
+        .map(|c| json!({ "text": { "content": c.iter().collect::<String>() } }))
+        .collect();
+    json!({
+        "Name": { "title": [{ "text": { "content": row.title } }] },
+        "Status": { "select": { "name": status.as_str() } },
+        "Difficulty": { "select": { "name": row.difficulty } },
+        "URL": { "url": row.url },
+        "LeetGraph ID": { "number": row.problem_id },
+        "Notes": { "rich_text": notes },
+    })
+}
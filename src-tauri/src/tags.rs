@@ -0,0 +1,95 @@
+// src/tags.rs
+
+use crate::constants::*;
+use crate::models::TagCount;
+use crate::repository::Repository;
+use chrono::Utc;
+use rusqlite::Connection;
+
+/// Trims a user-entered tag and checks it is usable.
+pub fn normalize_tag(tag: &str) -> Result<&str, String> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err("Tag cannot be empty".to_string());
+    }
+    if tag.chars().count() > TAG_MAX_LEN {
+        return Err(format!(
+            "Tag cannot be longer than {} characters",
+            TAG_MAX_LEN
+        ));
+    }
+    Ok(tag)
+}
+
+/// Tags the concept behind `problem_id` (alternatives share their parent's tags).
+pub fn add_tag(conn: &Connection, problem_id: i64, tag: &str) -> Result<(), String> {
+    let repo = Repository::new(conn);
+    let tag = normalize_tag(tag)?;
+    if !repo.problem_exists(problem_id).map_err(|e| e.to_string())? {
+        return Err(format!("Problem {} does not exist", problem_id));
+    }
+    let (parent_id, _) = repo
+        .resolve_parent_id(problem_id)
+        .map_err(|e| e.to_string())?;
+    repo.add_problem_tag(parent_id, tag, Utc::now().timestamp())
+        .map_err(|e| e.to_string())
+}
+
+pub fn remove_tag(conn: &Connection, problem_id: i64, tag: &str) -> Result<(), String> {
+    let repo = Repository::new(conn);
+    let (parent_id, _) = repo
+        .resolve_parent_id(problem_id)
+        .map_err(|e| e.to_string())?;
+    repo.remove_problem_tag(parent_id, tag.trim())
+        .map_err(|e| e.to_string())
+}
+
+pub fn get_tags(conn: &Connection, problem_id: i64) -> Result<Vec<String>, String> {
+    let repo = Repository::new(conn);
+    let (parent_id, _) = repo
+        .resolve_parent_id(problem_id)
+        .map_err(|e| e.to_string())?;
+    repo.get_problem_tags(parent_id).map_err(|e| e.to_string())
+}
+
+/// Stars the concept behind `problem_id` for favorites practice.
+pub fn star(conn: &Connection, problem_id: i64) -> Result<(), String> {
+    let repo = Repository::new(conn);
+    if !repo.problem_exists(problem_id).map_err(|e| e.to_string())? {
+        return Err(format!("Problem {} does not exist", problem_id));
+    }
+    let (parent_id, _) = repo
+        .resolve_parent_id(problem_id)
+        .map_err(|e| e.to_string())?;
+    repo.star_problem(parent_id, Utc::now().timestamp())
+        .map_err(|e| e.to_string())
+}
+
+pub fn unstar(conn: &Connection, problem_id: i64) -> Result<(), String> {
+    let repo = Repository::new(conn);
+    let (parent_id, _) = repo
+        .resolve_parent_id(problem_id)
+        .map_err(|e| e.to_string())?;
+    repo.unstar_problem(parent_id).map_err(|e| e.to_string())
+}
+
+pub fn list_tags(conn: &Connection) -> Result<Vec<TagCount>, String> {
+    Repository::new(conn)
+        .get_all_tags()
+        .map_err(|e| e.to_string())
+}
+
+/// Renames a tag on every concept; returns how many were affected.
+pub fn rename_tag(conn: &Connection, old: &str, new: &str) -> Result<usize, String> {
+    let new = normalize_tag(new)?;
+    Repository::new(conn)
+        .rename_tag(old.trim(), new)
+        .map_err(|e| e.to_string())
+}
+
+/// Removes a tag from every concept; returns how many were affected.
+pub fn delete_tag(conn: &Connection, tag: &str) -> Result<usize, String> {
+    Repository::new(conn)
+        .delete_tag(tag.trim())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,107 @@
+// src/telemetry.rs
+
+use crate::constants::*;
+use crate::models::{AppState, UsageStats};
+use crate::repository::Repository;
+use chrono::Utc;
+use log::warn;
+use rusqlite::Connection;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Statement timings since the last flush: sql -> (calls, total µs, max µs). The profiler
+/// hook is a plain function, so the tallies cannot live in `AppState`.
+static PENDING_QUERIES: Mutex<BTreeMap<String, (i64, i64, i64)>> = Mutex::new(BTreeMap::new());
+
+/// Runs a command, recording its latency and outcome when telemetry is enabled, and
+/// flushing statement timings regardless.
+/// Recording failures are logged and never affect the command's result.
+pub fn timed<T>(
+    state: &AppState,
+    command: &str,
+    op: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    let started = Instant::now();
+    let result = op();
+    if !state.read_only {
+        let duration_ms = started.elapsed().as_millis() as i64;
+        let recorded = state.with_db(|conn| record(conn, command, duration_ms, result.is_ok()));
+        if let Err(e) = recorded {
+            warn!("Telemetry not recorded for {}: {}", command, e);
+        }
+    }
+    result
+}
+
+/// Writes out the statement timings gathered since the last call, then logs one usage
+/// event if the user opted in. The timings are diagnostics for `get_db_info`, so they are
+/// kept either way.
+pub fn record(conn: &Connection, command: &str, duration_ms: i64, ok: bool) -> Result<(), String> {
+    let repo = Repository::new(conn);
+    let now = Utc::now().timestamp();
+    let queries = std::mem::take(
+        &mut *PENDING_QUERIES
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner),
+    );
+    for (sql, (calls, total_us, max_us)) in queries {
+        repo.record_query_stat(&sql, calls, total_us, max_us, now)
+            .map_err(|e| e.to_string())?;
+    }
+    let enabled = repo
+        .get_setting_or(SETTING_TELEMETRY_ENABLED, false)
+        .map_err(|e| e.to_string())?;
+    if enabled {
+        repo.log_usage_event(command, duration_ms, ok, now)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Profiler hook for the app's connection: tallies each statement's latency in memory
+/// until `record` writes it out. Whitespace is collapsed so one query is one row.
+pub fn record_query(sql: &str, duration: Duration) {
+    // Flushing the tallies is profiled too; don't count the bookkeeping
+    if sql.contains("query_stats") {
+        return;
+    }
+    let sql = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    let micros = duration.as_micros() as i64;
+    let mut pending = PENDING_QUERIES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let (calls, total_us, max_us) = pending.entry(sql).or_insert((0, 0, 0));
+    *calls += 1;
+    *total_us += micros;
+    *max_us = (*max_us).max(micros);
+}
+
+/// Startup bookkeeping: counts the launch and drops events past the retention window.
+pub fn on_app_start(conn: &Connection) -> Result<(), String> {
+    let repo = Repository::new(conn);
+    record(conn, TELEMETRY_APP_START, 0, true)?;
+    let cutoff = Utc::now().timestamp() - TELEMETRY_RETENTION_DAYS * DAY_SECONDS;
+    repo.prune_query_stats(cutoff).map_err(|e| e.to_string())?;
+    repo.prune_usage_events(cutoff).map_err(|e| e.to_string())
+}
+
+pub fn get_usage_stats(conn: &Connection, days: i64) -> Result<UsageStats, String> {
+    let repo = Repository::new(conn);
+    let days = days.max(1);
+    let enabled = repo
+        .get_setting_or(SETTING_TELEMETRY_ENABLED, false)
+        .map_err(|e| e.to_string())?;
+    let since = Utc::now().timestamp() - days * DAY_SECONDS;
+    let mut commands = repo
+        .get_usage_by_command(since)
+        .map_err(|e| e.to_string())?;
+    for usage in &mut commands {
+        usage.per_day = usage.calls as f64 / days as f64;
+    }
+    Ok(UsageStats {
+        enabled,
+        days,
+        commands,
+    })
+}
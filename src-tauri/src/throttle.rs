@@ -0,0 +1,60 @@
+// src/throttle.rs
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Per-command rate limit: a command called again within its interval is refused.
+#[derive(Default)]
+pub struct CommandThrottle {
+    last_call: Mutex<HashMap<&'static str, Instant>>,
+}
+
+impl CommandThrottle {
+    pub fn check(&self, command: &'static str, min_interval: Duration) -> Result<(), String> {
+        let mut last_call = self
+            .last_call
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        if let Some(last) = last_call.get(command) {
+            if now.duration_since(*last) < min_interval {
+                return Err(format!("{} was called too quickly, ignoring", command));
+            }
+        }
+        last_call.insert(command, now);
+        Ok(())
+    }
+}
+
+/// Debounced result: calls within `window` of the last computation get its cached value.
+pub struct Debounce<T> {
+    window: Duration,
+    last: Mutex<Option<(Instant, T)>>,
+}
+
+impl<T: Clone> Debounce<T> {
+    pub fn new(window: Duration) -> Self {
+        Debounce {
+            window,
+            last: Mutex::new(None),
+        }
+    }
+
+    pub fn get_or_compute(&self, compute: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+        let mut last = self.last.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((at, value)) = last.as_ref() {
+            if at.elapsed() < self.window {
+                return Ok(value.clone());
+            }
+        }
+        let value = compute()?;
+        *last = Some((Instant::now(), value.clone()));
+        Ok(value)
+    }
+
+    /// Drops the cached value, e.g. after a change that affects it.
+    pub fn clear(&self) {
+        *self.last.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
@@ -0,0 +1,155 @@
+// src/track_bundle.rs
+
+use crate::constants::*;
+use crate::imports;
+use crate::models::{JsonProblem, JsonSecondaryCategory, TrackBundle, TrackImport};
+use crate::repository::Repository;
+use log::info;
+use rusqlite::Connection;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+/// Writes a track to `path` as a `.lgtrack` bundle: every problem with its skills and
+/// alternatives and title translations, and the user's notes when `include_notes` is set.
+/// Each problem's heaviest skill becomes its category, as in the bundled pack.
+pub fn export_track(
+    conn: &Connection,
+    track_id: i64,
+    path: &str,
+    include_notes: bool,
+) -> Result<TrackBundle, String> {
+    let repo = Repository::new(conn);
+    let track = repo
+        .get_track_name(track_id)
+        .map_err(|e| e.to_string())?
+        .ok_or("Track not found")?;
+
+    let mut skills: HashMap<i64, Vec<(String, f64)>> = HashMap::new();
+    for (problem_id, skill, weight) in repo
+        .get_bundle_skills(track_id)
+        .map_err(|e| e.to_string())?
+    {
+        skills.entry(problem_id).or_default().push((skill, weight));
+    }
+    let mut alternatives: HashMap<i64, Vec<_>> = HashMap::new();
+    for (parent_id, alt) in repo
+        .get_bundle_alternatives(track_id)
+        .map_err(|e| e.to_string())?
+    {
+        alternatives.entry(parent_id).or_default().push(alt);
+    }
+    let mut alt_titles: HashMap<i64, BTreeMap<String, String>> = HashMap::new();
+    for (alt_id, locale, text) in repo
+        .get_bundle_alternative_titles(track_id)
+        .map_err(|e| e.to_string())?
+    {
+        alt_titles.entry(alt_id).or_default().insert(locale, text);
+    }
+    for alt in alternatives.values_mut().flatten() {
+        alt.titles = alt_titles.remove(&alt.id).unwrap_or_default();
+    }
+    let mut titles: HashMap<i64, BTreeMap<String, String>> = HashMap::new();
+    for (problem_id, locale, text) in repo
+        .get_bundle_problem_titles(track_id)
+        .map_err(|e| e.to_string())?
+    {
+        titles.entry(problem_id).or_default().insert(locale, text);
+    }
+    let problems = repo
+        .get_bundle_problems(track_id)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(id, title, difficulty, url)| {
+            let mut skills = skills.remove(&id).unwrap_or_default().into_iter();
+            let category = skills.next().map(|(name, _)| name).unwrap_or_default();
+            JsonProblem {
+                id,
+                title,
+                difficulty,
+                category,
+                secondary_categories: skills
+                    .map(|(category, weight)| JsonSecondaryCategory { category, weight })
+                    .collect(),
+                url,
+                alternatives: alternatives.remove(&id).unwrap_or_default(),
+                titles: titles.remove(&id).unwrap_or_default(),
+            }
+        })
+        .collect();
+    let notes = if include_notes {
+        repo.get_bundle_notes(track_id)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .collect()
+    } else {
+        BTreeMap::new()
+    };
+
+    let bundle = TrackBundle {
+        format: TRACK_BUNDLE_FORMAT,
+        track,
+        problems,
+        notes,
+    };
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(bundle)
+}
+
+/// Loads a `.lgtrack` bundle into the track it was exported from, creating the track and
+/// any missing skills. Problems already known by slug are shared rather than duplicated,
+/// and custom problems get ids of their own. Bundled notes only fill in problems without
+/// a note, so importing never overwrites the user's own. An export of the bundled track
+/// comes back as a track of its own, named with `IMPORTED_TRACK_SUFFIX`. All or nothing.
+pub fn import_track(conn: &Connection, path: &str, now: i64) -> Result<TrackImport, String> {
+    let data = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let bundle: TrackBundle =
+        serde_json::from_str(&data).map_err(|e| format!("Invalid track bundle: {}", e))?;
+    if bundle.format > TRACK_BUNDLE_FORMAT {
+        return Err("This track bundle was made by a newer version of the app".to_string());
+    }
+    let mut track = bundle.track.trim().to_string();
+    if track.is_empty() {
+        return Err("The track bundle has no track name".to_string());
+    }
+    if track == BUNDLED_TRACK_NAME {
+        track.push_str(IMPORTED_TRACK_SUFFIX);
+    }
+    let track = track.as_str();
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let mut created_skills = Vec::new();
+    let ids = imports::store_pack(&tx, track, &bundle.problems, &mut created_skills)?;
+    let repo = Repository::new(&tx);
+    let mut notes = 0;
+    for (p, &id) in bundle.problems.iter().zip(&ids) {
+        let Some(body) = bundle.notes.get(&p.id).map(|b| b.trim()) else {
+            continue;
+        };
+        if body.is_empty() || repo.get_note(id).map_err(|e| e.to_string())?.is_some() {
+            continue;
+        }
+        repo.save_note(id, body, now).map_err(|e| e.to_string())?;
+        notes += 1;
+    }
+    let track_id = repo
+        .get_track_id(track)
+        .map_err(|e| e.to_string())?
+        .ok_or("Track was not created")?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    info!(
+        "Imported track '{}' from {}: {} problems, {} notes",
+        track,
+        path,
+        ids.len(),
+        notes
+    );
+    Ok(TrackImport {
+        track_id,
+        track: track.to_string(),
+        problems: ids.len(),
+        created_skills,
+        notes,
+    })
+}
@@ -0,0 +1,92 @@
+// src/triage.rs
+
+use crate::constants::*;
+use crate::models::{SuspendedProblem, TriageReport, TriagedProblem};
+use crate::repository::Repository;
+use log::info;
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+
+/// When more reviews are overdue than the `triage_overdue_threshold` setting allows, keeps
+/// the `daily_reviews` weakest of them due and suspends the rest, most mastered first to go.
+/// Suspended problems keep their schedule and return with `unsuspend_problem`.
+pub fn triage_backlog(conn: &Connection, now: i64) -> Result<TriageReport, String> {
+    let repo = Repository::new(conn);
+    let threshold = repo
+        .get_setting_or(SETTING_TRIAGE_THRESHOLD, TRIAGE_THRESHOLD_DEFAULT)
+        .map_err(|e| e.to_string())?
+        .max(0);
+    let keep = repo
+        .get_setting_or(SETTING_DAILY_REVIEWS, DAILY_REVIEWS_DEFAULT)
+        .map_err(|e| e.to_string())?
+        .max(0) as usize;
+
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+        .map_err(|e| e.to_string())?;
+    let mut kept: Vec<TriagedProblem> = Repository::new(&tx)
+        .find_overdue_by_mastery(now)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(problem_id, title, mastery)| TriagedProblem {
+            problem_id,
+            title,
+            mastery,
+        })
+        .collect();
+    let overdue = kept.len() as i64;
+    let suspended = if overdue > threshold {
+        kept.split_off(keep.min(kept.len()))
+    } else {
+        Vec::new()
+    };
+    for p in &suspended {
+        Repository::new(&tx)
+            .suspend_problem(p.problem_id, SUSPEND_REASON_TRIAGE, now)
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    if !suspended.is_empty() {
+        info!(
+            "Triage: {} overdue (threshold {}), kept {}, suspended {}",
+            overdue,
+            threshold,
+            kept.len(),
+            suspended.len()
+        );
+    }
+    Ok(TriageReport {
+        overdue,
+        threshold,
+        kept,
+        suspended,
+    })
+}
+
+/// Puts a suspended concept back into the due queue on its old schedule.
+/// Accepts the suspended ID itself (as listed by `get_suspended_problems`) or any
+/// problem of the concept.
+pub fn unsuspend_problem(conn: &Connection, problem_id: i64) -> Result<(), String> {
+    let repo = Repository::new(conn);
+    let (parent_id, _) = repo
+        .resolve_parent_id(problem_id)
+        .map_err(|e| e.to_string())?;
+    for id in [problem_id, parent_id] {
+        if repo.unsuspend_problem(id).map_err(|e| e.to_string())? {
+            info!("Unsuspended Problem {}", id);
+            return Ok(());
+        }
+    }
+    Err(format!("Problem {} is not suspended", problem_id))
+}
+
+pub fn get_retired_problems(conn: &Connection) -> Result<Vec<SuspendedProblem>, String> {
+    Repository::new(conn)
+        .get_suspended_problems(Some(SUSPEND_REASON_RETIRED))
+        .map_err(|e| e.to_string())
+}
+
+pub fn get_suspended_problems(conn: &Connection) -> Result<Vec<SuspendedProblem>, String> {
+    Repository::new(conn)
+        .get_suspended_problems(None)
+        .map_err(|e| e.to_string())
+}